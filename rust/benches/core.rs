@@ -0,0 +1,80 @@
+// instructions per second of the core, without a frontend: cargo bench,
+// or cargo bench -- NAME for the workloads whose name contains NAME
+use chip8::builtin::BUILTIN_ROMS;
+use chip8::settings::UnknownOpcodePolicy;
+use chip8::{Chip8State, RunState};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const INSTRUCTIONS_PER_FRAME: usize = 1000;
+// each workload runs this long after a warm up of a tenth of it
+const RUN_TIME: Duration = Duration::from_secs(1);
+
+// 200: LD V0, 1; 202: ADD V1, V0; 204: XOR V2, V1; 206: SHR V3, V2
+// 208: SE V1, 0; 20A: JP 202; 20C: JP 202
+const ALU_LOOP: &[u8] = &[
+    0x60, 0x01, 0x81, 0x04, 0x82, 0x13, 0x83, 0x26, 0x31, 0x00, 0x12, 0x02, 0x12, 0x02,
+];
+
+// 200: LD I, 20C; 202: RND V0, 3F; 204: RND V1, 1F; 206: DRW V0, V1, 8
+// 208: ADD V2, 1; 20A: JP 202; 20C: sprite
+const DRAW_LOOP: &[u8] = &[
+    0xA2, 0x0C, 0xC0, 0x3F, 0xC1, 0x1F, 0xD0, 0x18, 0x72, 0x01, 0x12, 0x02, 0xFF, 0x81, 0xBD, 0xA5,
+    0xA5, 0xBD, 0x81, 0xFF,
+];
+
+// 200: LD I, 300; 202: LD V5, 2A; 204: LD [I], VF; 206: LD VF, [I]
+// 208: LD B, V5; 20A: CALL 20E; 20C: JP 202; 20E: RET
+const MEMORY_LOOP: &[u8] = &[
+    0xA3, 0x00, 0x65, 0x2A, 0xFF, 0x55, 0xFF, 0x65, 0xF5, 0x33, 0x22, 0x0E, 0x12, 0x02, 0x00, 0xEE,
+];
+
+fn machine(rom: &[u8], cached: bool) -> Chip8State {
+    let mut state = Chip8State::with_seed(1);
+    state.set_decode_cache(cached);
+    state.settings.unknown_opcode = UnknownOpcodePolicy::Skip;
+    state.load(rom).unwrap();
+    state
+}
+
+// runs the rom for about duration, reloading it whenever it stops
+fn measure(rom: &[u8], cached: bool, duration: Duration) -> f64 {
+    let mut state = machine(rom, cached);
+    let mut executed = 0;
+    let start = Instant::now();
+    while start.elapsed() < duration {
+        state.run_frame(INSTRUCTIONS_PER_FRAME).unwrap();
+        if state.run_state() != RunState::Running {
+            executed += state.emulated_time().cycles;
+            state = machine(rom, cached);
+        }
+    }
+    executed += state.emulated_time().cycles;
+    black_box(&state.display);
+    executed as f64 / start.elapsed().as_secs_f64() / 1e6
+}
+
+fn main() {
+    // cargo bench passes --bench, anything else picks workloads
+    let filter: Vec<String> = std::env::args()
+        .skip(1)
+        .filter(|arg| !arg.starts_with("--"))
+        .collect();
+    let mut workloads = vec![
+        ("alu", ALU_LOOP),
+        ("draw", DRAW_LOOP),
+        ("memory", MEMORY_LOOP),
+    ];
+    workloads.extend(BUILTIN_ROMS.iter().map(|rom| (rom.name, rom.bytes)));
+
+    for (name, rom) in workloads {
+        if !filter.is_empty() && !filter.iter().any(|part| name.contains(part.as_str())) {
+            continue;
+        }
+        let [plain, cached] = [false, true].map(|cached| {
+            measure(rom, cached, RUN_TIME / 10);
+            measure(rom, cached, RUN_TIME)
+        });
+        println!("{name:<10} {plain:>8.2} MIPS {cached:>8.2} MIPS with the decode cache");
+    }
+}
@@ -0,0 +1,42 @@
+/* the chip8 core as a c library, which
+ *     cargo build --release --no-default-features --features ffi
+ * builds into target/release. see src/ffi.rs:
+ *
+ *     chip8_machine *m = chip8_new(seed);
+ *     memcpy(chip8_rom_buffer(m, len), rom, len);
+ *     chip8_load(m);
+ *     every 60th of a second:
+ *         chip8_set_key(m, key, down);
+ *         chip8_run_frame(m, 10);
+ *         draw chip8_width(m) x chip8_height(m) bytes of chip8_framebuffer(m)
+ *     chip8_free(m);
+ *
+ * int results are 0, or -1 on an error or a null machine */
+#ifndef CHIP8_H
+#define CHIP8_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+typedef struct chip8_machine chip8_machine;
+
+chip8_machine *chip8_new(uint32_t seed);
+void chip8_free(chip8_machine *machine);
+
+uint8_t *chip8_rom_buffer(chip8_machine *machine, size_t len);
+int32_t chip8_load(chip8_machine *machine);
+
+int32_t chip8_run_frame(chip8_machine *machine, uint32_t instructions);
+int32_t chip8_step(chip8_machine *machine);
+void chip8_tick_timers(chip8_machine *machine);
+
+void chip8_set_key(chip8_machine *machine, uint8_t key, bool down);
+bool chip8_beeping(chip8_machine *machine);
+
+uint32_t chip8_width(chip8_machine *machine);
+uint32_t chip8_height(chip8_machine *machine);
+/* width * height bytes, 1 lit and 0 dark, valid until the next call */
+const uint8_t *chip8_framebuffer(chip8_machine *machine);
+
+#endif
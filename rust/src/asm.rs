@@ -0,0 +1,242 @@
+// a two pass assembler for the cowgod syntax disasm prints, so a listing
+// assembles back into the same rom:
+//
+//     start:  LD I, sprite      ; labels end in a colon
+//             DRW V0, V1, 5
+//             JP start
+//     sprite: DB 0xF0, 0x90, 0x90, 0x90, 0xF0
+//
+// numbers are decimal, 0x hex or 0b binary, and a label goes wherever a
+// number does. errors are "line N: what"
+use std::collections::HashMap;
+
+struct Statement<'a> {
+    line: usize,
+    mnemonic: String,
+    operands: Vec<&'a str>,
+}
+
+pub fn assemble(source: &str, origin: u16) -> Result<Vec<u8>, String> {
+    let mut labels = HashMap::new();
+    let mut statements = Vec::new();
+    let mut address = origin as usize;
+    for (n, text) in source.lines().enumerate() {
+        let line = n + 1;
+        let mut text = text.split(';').next().unwrap_or("").trim();
+        while let Some((label, rest)) = text.split_once(':') {
+            let label = label.trim();
+            if !is_label(label) {
+                return Err(format!("line {line}: bad label {label}"));
+            }
+            if labels.insert(label, address as u16).is_some() {
+                return Err(format!("line {line}: {label} is defined twice"));
+            }
+            text = rest.trim();
+        }
+        if text.is_empty() {
+            continue;
+        }
+        let (mnemonic, operands) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let operands: Vec<&str> = if operands.trim().is_empty() {
+            Vec::new()
+        } else {
+            operands.split(',').map(str::trim).collect()
+        };
+        let statement = Statement {
+            line,
+            mnemonic: mnemonic.to_ascii_uppercase(),
+            operands,
+        };
+        address += size(&statement);
+        statements.push(statement);
+    }
+
+    let mut rom = Vec::new();
+    for statement in &statements {
+        encode(statement, &labels, &mut rom)
+            .map_err(|err| format!("line {}: {err}", statement.line))?;
+    }
+    Ok(rom)
+}
+
+fn is_label(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn size(statement: &Statement) -> usize {
+    match statement.mnemonic.as_str() {
+        "DB" => statement.operands.len(),
+        "DW" => 2 * statement.operands.len(),
+        "LD" if is_long(&statement.operands) => 4,
+        _ => 2,
+    }
+}
+
+// LD I, long NNNN
+fn is_long(operands: &[&str]) -> bool {
+    matches!(operands, [i, value] if i.eq_ignore_ascii_case("I")
+        && value.len() > 5 && value[..5].eq_ignore_ascii_case("long "))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Operand {
+    V(u16),
+    I,
+    IndirectI,
+    Dt,
+    St,
+    K,
+    F,
+    B,
+    // the rpl flags
+    R,
+    Value(u16),
+}
+
+fn operand(text: &str, labels: &HashMap<&str, u16>) -> Result<Operand, String> {
+    let upper = text.to_ascii_uppercase();
+    Ok(match upper.as_str() {
+        "I" => Operand::I,
+        "[I]" => Operand::IndirectI,
+        "DT" => Operand::Dt,
+        "ST" => Operand::St,
+        "K" => Operand::K,
+        "F" => Operand::F,
+        "B" => Operand::B,
+        "R" => Operand::R,
+        _ if upper.len() == 2 && upper.starts_with('V') => {
+            match u16::from_str_radix(&upper[1..], 16) {
+                Ok(x) => Operand::V(x),
+                Err(_) => Operand::Value(value(text, labels)?),
+            }
+        }
+        _ => Operand::Value(value(text, labels)?),
+    })
+}
+
+fn value(text: &str, labels: &HashMap<&str, u16>) -> Result<u16, String> {
+    if let Some(address) = labels.get(text) {
+        return Ok(*address);
+    }
+    let lower = text.to_ascii_lowercase();
+    let parsed = if let Some(hex) = lower.strip_prefix("0x") {
+        u16::from_str_radix(hex, 16)
+    } else if let Some(binary) = lower.strip_prefix("0b") {
+        u16::from_str_radix(binary, 2)
+    } else {
+        lower.parse()
+    };
+    parsed.map_err(|_| {
+        if is_label(text) {
+            format!("unknown label {text}")
+        } else {
+            format!("bad number {text}")
+        }
+    })
+}
+
+fn fits(value: u16, max: u16, what: &str) -> Result<u16, String> {
+    if value > max {
+        return Err(format!("{value:#X} doesn't fit in {what}"));
+    }
+    Ok(value)
+}
+
+fn encode(
+    statement: &Statement,
+    labels: &HashMap<&str, u16>,
+    rom: &mut Vec<u8>,
+) -> Result<(), String> {
+    let mnemonic = statement.mnemonic.as_str();
+    match mnemonic {
+        "DB" => {
+            for text in &statement.operands {
+                rom.push(fits(value(text, labels)?, 0xFF, "a byte")? as u8);
+            }
+            return Ok(());
+        }
+        "DW" => {
+            for text in &statement.operands {
+                rom.extend_from_slice(&value(text, labels)?.to_be_bytes());
+            }
+            return Ok(());
+        }
+        "LD" if is_long(&statement.operands) => {
+            let address = value(statement.operands[1][5..].trim(), labels)?;
+            rom.extend_from_slice(&[0xF0, 0x00]);
+            rom.extend_from_slice(&address.to_be_bytes());
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let operands = statement
+        .operands
+        .iter()
+        .map(|text| operand(text, labels))
+        .collect::<Result<Vec<_>, _>>()?;
+    let nnn = |value: u16| fits(value, 0xFFF, "12 bits");
+    let nn = |value: u16| fits(value, 0xFF, "a byte");
+    let n = |value: u16| fits(value, 0xF, "4 bits");
+    let xy = |x: u16, y: u16| x << 8 | y << 4;
+    use Operand::*;
+    let opcode = match (mnemonic, operands.as_slice()) {
+        ("CLS", []) => 0x00E0,
+        ("RET", []) => 0x00EE,
+        ("SCD", [Value(v)]) => 0x00C0 | n(*v)?,
+        ("SCU", [Value(v)]) => 0x00D0 | n(*v)?,
+        ("SCR", []) => 0x00FB,
+        ("SCL", []) => 0x00FC,
+        ("LOW", []) => 0x00FE,
+        ("HIGH", []) => 0x00FF,
+        ("PLANE", [Value(v)]) => 0xF001 | n(*v)? << 8,
+        ("SYS", [Value(v)]) => nnn(*v)?,
+        ("JP", [Value(v)]) => 0x1000 | nnn(*v)?,
+        ("JP", [V(0), Value(v)]) => 0xB000 | nnn(*v)?,
+        ("CALL", [Value(v)]) => 0x2000 | nnn(*v)?,
+        ("SE", [V(x), Value(v)]) => 0x3000 | x << 8 | nn(*v)?,
+        ("SNE", [V(x), Value(v)]) => 0x4000 | x << 8 | nn(*v)?,
+        ("SE", [V(x), V(y)]) => 0x5000 | xy(*x, *y),
+        ("SNE", [V(x), V(y)]) => 0x9000 | xy(*x, *y),
+        ("LD", [V(x), Value(v)]) => 0x6000 | x << 8 | nn(*v)?,
+        ("ADD", [V(x), Value(v)]) => 0x7000 | x << 8 | nn(*v)?,
+        ("LD", [V(x), V(y)]) => 0x8000 | xy(*x, *y),
+        ("OR", [V(x), V(y)]) => 0x8001 | xy(*x, *y),
+        ("AND", [V(x), V(y)]) => 0x8002 | xy(*x, *y),
+        ("XOR", [V(x), V(y)]) => 0x8003 | xy(*x, *y),
+        ("ADD", [V(x), V(y)]) => 0x8004 | xy(*x, *y),
+        ("SUB", [V(x), V(y)]) => 0x8005 | xy(*x, *y),
+        ("SUBN", [V(x), V(y)]) => 0x8007 | xy(*x, *y),
+        // shifting a register into itself is the same with and without the
+        // shift quirk
+        ("SHR", [V(x)]) => 0x8006 | xy(*x, *x),
+        ("SHR", [V(x), V(y)]) => 0x8006 | xy(*x, *y),
+        ("SHL", [V(x)]) => 0x800E | xy(*x, *x),
+        ("SHL", [V(x), V(y)]) => 0x800E | xy(*x, *y),
+        ("LD", [I, Value(v)]) => 0xA000 | nnn(*v)?,
+        ("RND", [V(x), Value(v)]) => 0xC000 | x << 8 | nn(*v)?,
+        ("DRW", [V(x), V(y), Value(v)]) => 0xD000 | xy(*x, *y) | n(*v)?,
+        ("SKP", [V(x)]) => 0xE09E | x << 8,
+        ("SKNP", [V(x)]) => 0xE0A1 | x << 8,
+        ("LD", [V(x), Dt]) => 0xF007 | x << 8,
+        ("LD", [V(x), K]) => 0xF00A | x << 8,
+        ("LD", [Dt, V(x)]) => 0xF015 | x << 8,
+        ("LD", [St, V(x)]) => 0xF018 | x << 8,
+        ("ADD", [I, V(x)]) => 0xF01E | x << 8,
+        ("LD", [F, V(x)]) => 0xF029 | x << 8,
+        ("LD", [B, V(x)]) => 0xF033 | x << 8,
+        ("LD", [IndirectI, V(x)]) => 0xF055 | x << 8,
+        ("LD", [V(x), IndirectI]) => 0xF065 | x << 8,
+        ("LD", [R, V(x)]) => 0xF075 | x << 8,
+        ("LD", [V(x), R]) => 0xF085 | x << 8,
+        _ => {
+            return Err(format!(
+                "can't assemble {mnemonic} {}",
+                statement.operands.join(", ")
+            ));
+        }
+    };
+    rom.extend_from_slice(&opcode.to_be_bytes());
+    Ok(())
+}
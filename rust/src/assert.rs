@@ -0,0 +1,103 @@
+// assert_state! for rom tests: checks registers, memory and pixels of a
+// Chip8State and reports every mismatch at once along with the registers
+//
+//     assert_state!(state, v[3] == 0x10, pc == 0x224, pixel(10, 4) == on);
+//
+// checks are v[N], memory[ADDR], pixel(X, Y) against on or off, run_state
+// and any public field of Chip8State like pc, i, delay_timer or sound_timer
+use crate::chip8::{Chip8State, RunState};
+use std::fmt::Write;
+
+// how the report shows a value
+pub trait StateValue: PartialEq {
+    fn show(&self) -> String;
+}
+
+impl StateValue for u8 {
+    fn show(&self) -> String {
+        format!("{self:#04X}")
+    }
+}
+
+impl StateValue for u16 {
+    fn show(&self) -> String {
+        format!("{self:#05X}")
+    }
+}
+
+impl StateValue for bool {
+    fn show(&self) -> String {
+        (if *self { "on" } else { "off" }).to_owned()
+    }
+}
+
+impl StateValue for RunState {
+    fn show(&self) -> String {
+        format!("{self:?}")
+    }
+}
+
+pub fn check<T: StateValue>(failures: &mut Vec<String>, name: String, got: T, want: T) {
+    if got != want {
+        failures.push(format!(
+            "{name}: expected {}, got {}",
+            want.show(),
+            got.show()
+        ));
+    }
+}
+
+pub fn report(state: &Chip8State, failures: &[String]) -> String {
+    let mut report = "state assertion failed:\n".to_owned();
+    for failure in failures {
+        let _ = writeln!(report, "  {failure}");
+    }
+    let _ = write!(report, "  pc={:03X} i={:03X}", state.pc, state.i);
+    for (n, v) in state.v.iter().enumerate() {
+        let _ = write!(report, " v{n:X}={v:02X}");
+    }
+    report
+}
+
+#[macro_export]
+macro_rules! assert_state {
+    ($state:expr, $($checks:tt)+) => {{
+        let state: &$crate::Chip8State = &$state;
+        let mut failures = ::std::vec::Vec::new();
+        $crate::assert_state!(@check state, failures; $($checks)+);
+        if !failures.is_empty() {
+            panic!("{}", $crate::assert::report(state, &failures));
+        }
+    }};
+
+    (@check $state:ident, $failures:ident;) => {};
+    (@check $state:ident, $failures:ident; v[$reg:expr] == $want:expr $(, $($rest:tt)*)?) => {
+        $crate::assert::check(&mut $failures, format!("v[{:X}]", $reg), $state.v[$reg as usize], $want);
+        $crate::assert_state!(@check $state, $failures; $($($rest)*)?);
+    };
+    (@check $state:ident, $failures:ident; memory[$addr:expr] == $want:expr $(, $($rest:tt)*)?) => {
+        $crate::assert::check(
+            &mut $failures,
+            format!("memory[{:03X}]", $addr),
+            $state.memory[$addr as usize],
+            $want,
+        );
+        $crate::assert_state!(@check $state, $failures; $($($rest)*)?);
+    };
+    (@check $state:ident, $failures:ident; pixel($x:expr, $y:expr) == on $(, $($rest:tt)*)?) => {
+        $crate::assert::check(&mut $failures, format!("pixel({}, {})", $x, $y), $state.display.get($x, $y), true);
+        $crate::assert_state!(@check $state, $failures; $($($rest)*)?);
+    };
+    (@check $state:ident, $failures:ident; pixel($x:expr, $y:expr) == off $(, $($rest:tt)*)?) => {
+        $crate::assert::check(&mut $failures, format!("pixel({}, {})", $x, $y), $state.display.get($x, $y), false);
+        $crate::assert_state!(@check $state, $failures; $($($rest)*)?);
+    };
+    (@check $state:ident, $failures:ident; run_state == $want:expr $(, $($rest:tt)*)?) => {
+        $crate::assert::check(&mut $failures, "run_state".to_owned(), $state.run_state(), $want);
+        $crate::assert_state!(@check $state, $failures; $($($rest)*)?);
+    };
+    (@check $state:ident, $failures:ident; $field:ident == $want:expr $(, $($rest:tt)*)?) => {
+        $crate::assert::check(&mut $failures, stringify!($field).to_owned(), $state.$field, $want);
+        $crate::assert_state!(@check $state, $failures; $($($rest)*)?);
+    };
+}
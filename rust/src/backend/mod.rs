@@ -0,0 +1,456 @@
+// platform backends the binary drives; everything chip8 specific stays in the core
+#[cfg(feature = "raylib")]
+pub mod raylib;
+#[cfg(feature = "sdl2")]
+pub mod sdl2;
+pub mod ui;
+
+use self::ui::{DebugHud, KeyHistory, Menu};
+use crate::buzzer::Buzzer;
+use crate::chip8::SpriteBox;
+#[cfg(feature = "debugger")]
+use crate::debugger::DebuggerView;
+use crate::display::{Display, Geometry};
+use crate::heatmap::Heatmap;
+use crate::megachip::Sample;
+use crate::memory_view::MemoryView;
+use crate::palette::Palette;
+use std::path::PathBuf;
+
+// physical key for each chip8 key 0x0..=0xF (1234/qwer/asdf/zxcv on a qwerty keyboard)
+pub const KEY_LAYOUT: [char; 16] = [
+    'x', '1', '2', '3', 'q', 'w', 'e', 'a', 's', 'd', 'z', 'c', '4', 'r', 'f', 'v',
+];
+
+// chip8 keys as they sit on the original 4x4 hex keypad
+pub const KEYPAD_ROWS: [[u8; 4]; 4] = [
+    [0x1, 0x2, 0x3, 0xC],
+    [0x4, 0x5, 0x6, 0xD],
+    [0x7, 0x8, 0x9, 0xE],
+    [0xA, 0x0, 0xB, 0xF],
+];
+
+// emulator controls, as opposed to keys forwarded to the chip8 keypad
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hotkey {
+    ToggleKeypadOverlay,
+    // clickable keys beside the display, see Renderer::set_virtual_keypad
+    ToggleVirtualKeypad,
+    ToggleHeatmap,
+    // dark pixels fading out, see Renderer::set_ghosting
+    ToggleGhosting,
+    // lines between the pixels, then also the last sprite's outline, then
+    // off, see ui::pixel_grid
+    CyclePixelGrid,
+    // registers, timers, the next instruction and the speed, see ui::debug_hud
+    ToggleDebugHud,
+    // hex view around pc, then around I, then off, see ui::memory_view
+    CycleMemoryView,
+    // registers, code, memory and stack with buttons to click, see
+    // ui::debugger_panel. needs the debugger feature
+    ToggleDebugger,
+    ToggleCrt,
+    ToggleFullscreen,
+    TogglePause,
+    // one frame while paused
+    StepFrame,
+    // one instruction while paused
+    StepInstruction,
+    // full speed while paused until a DXYN or FX18 ran
+    RunToDraw,
+    RunToSound,
+    // reset the machine and start the current rom over
+    Reset,
+    SpeedUp,
+    SpeedDown,
+    // runs faster for as long as it is held, see Input::is_held
+    FastForward,
+    // writes the last seconds of play to a gif, see clip::ClipRecorder
+    ExportClip,
+    // writes the screen to a png, see screenshot
+    Screenshot,
+    // starts a gif of everything from now on, pressing again saves it, see
+    // recording::GifRecorder
+    ToggleRecording,
+    // prints the --profile report so far
+    PrintProfile,
+    // numbered per rom state files, 1 to slots::SLOTS
+    SaveState(u8),
+    LoadState(u8),
+    // the rom browser: back to it from a game, moving and launching in it
+    Menu,
+    MenuUp,
+    MenuDown,
+    MenuSelect,
+}
+
+impl Hotkey {
+    // names for key bindings, see Keymap
+    pub const NAMES: &[(&str, Hotkey)] = &[
+        ("keypad-overlay", Hotkey::ToggleKeypadOverlay),
+        ("virtual-keypad", Hotkey::ToggleVirtualKeypad),
+        ("heatmap", Hotkey::ToggleHeatmap),
+        ("ghosting", Hotkey::ToggleGhosting),
+        ("pixel-grid", Hotkey::CyclePixelGrid),
+        ("hud", Hotkey::ToggleDebugHud),
+        ("memory", Hotkey::CycleMemoryView),
+        ("debugger", Hotkey::ToggleDebugger),
+        ("crt", Hotkey::ToggleCrt),
+        ("fullscreen", Hotkey::ToggleFullscreen),
+        ("pause", Hotkey::TogglePause),
+        ("step-frame", Hotkey::StepFrame),
+        ("step", Hotkey::StepInstruction),
+        ("next-draw", Hotkey::RunToDraw),
+        ("next-sound", Hotkey::RunToSound),
+        ("reset", Hotkey::Reset),
+        ("speed-up", Hotkey::SpeedUp),
+        ("speed-down", Hotkey::SpeedDown),
+        ("fast-forward", Hotkey::FastForward),
+        ("export-clip", Hotkey::ExportClip),
+        ("screenshot", Hotkey::Screenshot),
+        ("record", Hotkey::ToggleRecording),
+        ("profile", Hotkey::PrintProfile),
+        ("save-1", Hotkey::SaveState(1)),
+        ("save-2", Hotkey::SaveState(2)),
+        ("save-3", Hotkey::SaveState(3)),
+        ("save-4", Hotkey::SaveState(4)),
+        ("load-1", Hotkey::LoadState(1)),
+        ("load-2", Hotkey::LoadState(2)),
+        ("load-3", Hotkey::LoadState(3)),
+        ("load-4", Hotkey::LoadState(4)),
+        ("menu", Hotkey::Menu),
+        ("menu-up", Hotkey::MenuUp),
+        ("menu-down", Hotkey::MenuDown),
+        ("menu-select", Hotkey::MenuSelect),
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Hotkey::NAMES
+            .iter()
+            .find(|(hotkey, _)| *hotkey == name)
+            .map(|(_, hotkey)| *hotkey)
+    }
+
+    pub fn name(&self) -> &'static str {
+        Hotkey::NAMES
+            .iter()
+            .find(|(_, hotkey)| hotkey == self)
+            .map_or("?", |(name, _)| name)
+    }
+}
+
+// default bindings, by the key names backends resolve to their own key codes:
+// single characters, F1-F12, Backspace, Tab, Space, Return, Escape and the
+// arrows Up, Down, Left and Right, any of them after Shift+ or Ctrl+, see
+// split_modifiers
+pub const HOTKEYS: &[(&str, Hotkey)] = &[
+    ("F1", Hotkey::ToggleKeypadOverlay),
+    ("F2", Hotkey::ToggleHeatmap),
+    ("H", Hotkey::ToggleGhosting),
+    ("F3", Hotkey::ToggleCrt),
+    ("F5", Hotkey::ToggleDebugHud),
+    ("F6", Hotkey::CycleMemoryView),
+    ("F7", Hotkey::ToggleDebugger),
+    ("F8", Hotkey::ToggleVirtualKeypad),
+    ("G", Hotkey::CyclePixelGrid),
+    ("F11", Hotkey::ToggleFullscreen),
+    ("P", Hotkey::TogglePause),
+    ("N", Hotkey::StepFrame),
+    ("M", Hotkey::StepInstruction),
+    ("Shift+N", Hotkey::RunToDraw),
+    ("Shift+M", Hotkey::RunToSound),
+    ("Backspace", Hotkey::Reset),
+    ("=", Hotkey::SpeedUp),
+    ("-", Hotkey::SpeedDown),
+    ("Tab", Hotkey::FastForward),
+    ("F12", Hotkey::ExportClip),
+    ("F9", Hotkey::Screenshot),
+    ("F10", Hotkey::ToggleRecording),
+    ("F4", Hotkey::PrintProfile),
+    ("Shift+F1", Hotkey::SaveState(1)),
+    ("Shift+F2", Hotkey::SaveState(2)),
+    ("Shift+F3", Hotkey::SaveState(3)),
+    ("Shift+F4", Hotkey::SaveState(4)),
+    ("Ctrl+F1", Hotkey::LoadState(1)),
+    ("Ctrl+F2", Hotkey::LoadState(2)),
+    ("Ctrl+F3", Hotkey::LoadState(3)),
+    ("Ctrl+F4", Hotkey::LoadState(4)),
+    ("Escape", Hotkey::Menu),
+    ("Up", Hotkey::MenuUp),
+    ("Down", Hotkey::MenuDown),
+    ("Return", Hotkey::MenuSelect),
+];
+
+// shift and ctrl combined with a key, either side of the keyboard
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+}
+
+// "Shift+Ctrl+F1" into the modifiers and "F1", a hotkey fires only with
+// exactly its modifiers held so F1 and Shift+F1 can do different things
+pub fn split_modifiers(name: &str) -> (Modifiers, &str) {
+    let mut modifiers = Modifiers::default();
+    let mut key = name;
+    while let Some((modifier, rest)) = key.split_once('+').filter(|(_, rest)| !rest.is_empty()) {
+        match modifier {
+            "Shift" => modifiers.shift = true,
+            "Ctrl" => modifiers.ctrl = true,
+            _ => break,
+        }
+        key = rest;
+    }
+    (modifiers, key)
+}
+
+// gamepad button names, xbox style for the face buttons
+pub const GAMEPAD_BUTTONS: &[&str] = &[
+    "up", "down", "left", "right", "a", "b", "x", "y", "lb", "rb", "select", "start",
+];
+
+// default chip8 key of every button, the d-pad on the 2/4/6/8 cross most
+// games move with and 5, their usual action key, on a
+pub const GAMEPAD_LAYOUT: &[(&str, u8)] = &[
+    ("up", 0x2),
+    ("down", 0x8),
+    ("left", 0x4),
+    ("right", 0x6),
+    ("a", 0x5),
+    ("b", 0x0),
+    ("x", 0x1),
+    ("y", 0x3),
+    ("lb", 0x7),
+    ("rb", 0x9),
+    ("select", 0xE),
+    ("start", 0xF),
+];
+
+// of the beep, unsigned 8 bit mono
+pub const SAMPLE_RATE: u32 = 44100;
+
+// where the display sits in the window
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    // window pixels per display pixel
+    pub scale: f32,
+}
+
+// the largest whole multiple of the display that fits the window, centered
+// with bars on the other axis. windows smaller than the display get a
+// fractional scale so the picture is shrunk instead of cut off
+pub fn letterbox(window: (i32, i32), display: (usize, usize)) -> Viewport {
+    let (window_width, window_height) = (window.0.max(1) as f32, window.1.max(1) as f32);
+    let (width, height) = (display.0.max(1) as f32, display.1.max(1) as f32);
+
+    let fit = (window_width / width).min(window_height / height);
+    let scale = if fit >= 1.0 { fit.floor() } else { fit };
+    Viewport {
+        x: ((window_width - width * scale) / 2.0).floor(),
+        y: ((window_height - height * scale) / 2.0).floor(),
+        width: width * scale,
+        height: height * scale,
+        scale,
+    }
+}
+
+// how many framebuffer pixels a window's logical pixel covers, 2 on a retina
+// mac where the window is sized in points and its framebuffer in pixels
+pub fn dpi_scale(window: (i32, i32), framebuffer: (i32, i32)) -> f32 {
+    if window.0 <= 0 || framebuffer.0 <= 0 {
+        return 1.0;
+    }
+    framebuffer.0 as f32 / window.0 as f32
+}
+
+// letterbox for a window in logical pixels over a framebuffer dpi times
+// denser. the scale is picked in framebuffer pixels so every chip8 pixel
+// covers whole ones at any dpi, the viewport is in logical pixels again
+pub fn letterbox_dpi(window: (i32, i32), dpi: f32, display: (usize, usize)) -> Viewport {
+    let dpi = if dpi.is_finite() && dpi > 0.0 {
+        dpi
+    } else {
+        1.0
+    };
+    let framebuffer = (
+        (window.0 as f32 * dpi).round() as i32,
+        (window.1 as f32 * dpi).round() as i32,
+    );
+    let viewport = letterbox(framebuffer, display);
+    Viewport {
+        x: viewport.x / dpi,
+        y: viewport.y / dpi,
+        width: viewport.width / dpi,
+        height: viewport.height / dpi,
+        scale: viewport.scale / dpi,
+    }
+}
+
+pub trait Renderer {
+    // the display changed size, before the first draw and on every resolution
+    // switch, so buffers sized by the display can be reallocated once here
+    fn set_geometry(&mut self, _geometry: Geometry) {}
+
+    // scales whatever geometry the display has to the window, keeping its
+    // aspect ratio, see letterbox
+    fn draw(&mut self, display: &Display);
+
+    // colors of the display, overlays keep their own
+    fn set_palette(&mut self, palette: &Palette);
+
+    // argb rows the size of the display to show in place of its pixels, the
+    // megachip screen, None goes back to the palette. backends without true
+    // color ignore it and show the display in the palette
+    fn set_colors(&mut self, _colors: Option<&[u32]>) {}
+
+    // covers the monitor, draw letterboxes the display into it like any window
+    fn set_fullscreen(&mut self, on: bool);
+
+    // retro post processing (scanlines and, where supported, curvature and glow)
+    fn set_crt(&mut self, on: bool);
+
+    // the window title, "CHIP-8" until set
+    fn set_title(&mut self, title: &str);
+
+    // short message about the machine, e.g. "program finished", None clears it
+    fn set_status(&mut self, status: Option<&str>);
+
+    // draws ui::keypad_overlay over the display, pressed keys highlighted and
+    // with a ticker of the last presses if there is a history. None hides it
+    fn set_keypad_overlay(&mut self, keypad: Option<&[bool; 16]>, history: Option<&KeyHistory>);
+
+    // ui::virtual_keypad beside or below the display, clicks and touches on
+    // it press keys through Input::poll
+    fn set_virtual_keypad(&mut self, _on: bool) {}
+
+    // ui::pixel_grid over the display, with the outline of sprite if there is
+    // one. backends without it ignore it
+    fn set_pixel_grid(&mut self, _on: bool, _sprite: Option<&SpriteBox>) {}
+
+    // memory activity as a HEATMAP_SIDE square grid in a corner, None hides it
+    fn set_heatmap_overlay(&mut self, heatmap: Option<&Heatmap>);
+
+    // dark pixels fade out over a few frames keeping persistence percent of
+    // their brightness each, see phosphor. None shows the display as it is
+    fn set_ghosting(&mut self, _persistence: Option<u8>) {}
+
+    // ui::menu over the whole window instead of the display, None hides it
+    fn set_menu(&mut self, menu: Option<&Menu>);
+
+    // ui::debug_hud in the top left corner, None hides it. backends without
+    // it ignore it
+    fn set_debug_hud(&mut self, _hud: Option<&DebugHud>) {}
+
+    // ui::memory_view in the top right corner, None hides it. backends
+    // without it ignore it
+    fn set_memory_view(&mut self, _view: Option<&MemoryView>) {}
+
+    // ui::debugger_panel down the right side, its buttons clicked turn into
+    // hotkeys. None hides it, backends without it ignore it
+    #[cfg(feature = "debugger")]
+    fn set_debugger(&mut self, _view: Option<&DebuggerView>) {}
+}
+
+pub trait Input {
+    // returns false once the user asked to quit, hotkeys pressed since the
+    // last poll are appended to hotkeys
+    fn poll(&mut self, keypad: &mut [bool; 16], hotkeys: &mut Vec<Hotkey>) -> bool;
+
+    // whether the key of a hotkey is down right now, as of the last poll
+    fn is_held(&self, hotkey: Hotkey) -> bool;
+
+    // more keyboard keys and the chip8 key each one presses, on top of the
+    // keymap, e.g. keymap::auto_keys
+    fn set_extra_keys(&mut self, mapping: &[(String, u8)]);
+
+    // GAMEPAD_BUTTONS and the chip8 key each one presses, on top of the
+    // keyboard. backends without gamepad support ignore it
+    fn set_gamepad(&mut self, _mapping: &[(String, u8)]) {}
+
+    // whether the window has the keyboard focus, backends that can't tell
+    // always have it
+    fn is_focused(&self) -> bool {
+        true
+    }
+
+    // a file dragged onto the window since the last call, the last one if
+    // several were dropped at once. backends without file drops never have one
+    fn dropped_file(&mut self) -> Option<PathBuf> {
+        None
+    }
+}
+
+pub trait Audio {
+    // called every frame, on while the sound timer runs
+    fn set_beep(&mut self, on: bool);
+
+    // the tone of the beep from now on
+    fn set_buzzer(&mut self, buzzer: &Buzzer);
+
+    // starts a megachip sample, from the top even if it was playing. None
+    // stops it. backends that can't play samples ignore it
+    fn play_sample(&mut self, _sample: Option<&Sample>) {}
+}
+
+pub trait Backend: Renderer + Input + Audio {}
+
+impl<T: Renderer + Input + Audio> Backend for T {}
+
+// 3x5 pixel glyphs for backends without text rendering, one byte per row
+// with the pixels in the low three bits
+pub fn glyph(c: char) -> Option<[u8; 5]> {
+    let rows = match c.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b100, 0b100],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        '?' => [0b110, 0b001, 0b010, 0b000, 0b010],
+        '\'' => [0b010, 0b010, 0b000, 0b000, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        ' ' => [0; 5],
+        _ => return None,
+    };
+    Some(rows)
+}
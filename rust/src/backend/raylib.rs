@@ -0,0 +1,688 @@
+use super::{
+    Audio, Hotkey, Input, Modifiers, Renderer, SAMPLE_RATE, letterbox_dpi, split_modifiers,
+    ui::{self, DebugHud, KeyHistory, Menu},
+};
+use crate::buzzer::Buzzer;
+use crate::chip8::SpriteBox;
+#[cfg(feature = "debugger")]
+use crate::debugger::DebuggerView;
+use crate::display::{Display, Geometry};
+use crate::heatmap::{HEATMAP_SIDE, Heatmap};
+use crate::keymap::Keymap;
+use crate::megachip::Sample;
+use crate::memory_view::MemoryView;
+use crate::palette::{Palette, Rgb};
+use crate::phosphor::Phosphor;
+use ::raylib::prelude::*;
+use std::path::PathBuf;
+use std::time::Instant;
+
+pub struct RaylibBackend<'a> {
+    rl: RaylibHandle,
+    thread: RaylibThread,
+    keys: Vec<KeyboardKey>,
+    // key names for the keypad overlay
+    labels: [char; 16],
+    hotkeys: Vec<(KeyboardKey, Modifiers, Hotkey)>,
+    gamepad: Vec<(GamepadButton, usize)>,
+    extra_keys: Vec<(KeyboardKey, usize)>,
+    audio: Option<&'a RaylibAudio>,
+    buzzer: Buzzer,
+    // a second of the tone, replayed while the beep lasts
+    beep: Option<Sound<'a>>,
+    // when the beep started, or stopped at that volume and is fading out
+    envelope: Option<(Instant, Option<f32>)>,
+    // the megachip sample playing and whether it loops
+    sample: Option<(Sound<'a>, bool)>,
+    status: Option<String>,
+    keypad_overlay: Option<[bool; 16]>,
+    // see Renderer::set_virtual_keypad, with the keys last polled
+    virtual_keypad: Option<[bool; 16]>,
+    key_history: Option<KeyHistory>,
+    menu: Option<Menu>,
+    heatmap_overlay: Option<Heatmap>,
+    // see Renderer::set_pixel_grid, None when it's off
+    pixel_grid: Option<Option<SpriteBox>>,
+    debug_hud: Option<DebugHud>,
+    memory_view: Option<MemoryView>,
+    #[cfg(feature = "debugger")]
+    debugger: Option<DebuggerView>,
+    palette: Palette,
+    // the display as an rgba texture, rebuilt when the geometry changes
+    texture: Option<Texture2D>,
+    pixels: Vec<u8>,
+    // see Renderer::set_colors
+    colors: Option<Vec<u32>>,
+    // Display::generation of the texture contents
+    uploaded: Option<u64>,
+    // see Renderer::set_ghosting
+    ghosting: Option<Phosphor>,
+    // loaded the first time the crt look is switched on
+    crt_shader: Option<Shader>,
+    crt: bool,
+    fullscreen: bool,
+}
+
+const CRT_SHADER: &str = include_str!("shaders/crt.fs");
+
+impl<'a> RaylibBackend<'a> {
+    pub fn new(
+        width: i32,
+        height: i32,
+        audio: Option<&'a RaylibAudio>,
+        keymap: &Keymap,
+    ) -> Result<Self, String> {
+        let unknown = |name: &str| format!("unknown key {name}");
+        let keys = keymap
+            .keypad
+            .iter()
+            .map(|name| key_from_name(name).ok_or_else(|| unknown(name)))
+            .collect::<Result<_, _>>()?;
+        let hotkeys = keymap
+            .hotkeys
+            .iter()
+            .map(|(name, hotkey)| {
+                let (modifiers, key) = split_modifiers(name);
+                Ok((
+                    key_from_name(key).ok_or_else(|| unknown(name))?,
+                    modifiers,
+                    *hotkey,
+                ))
+            })
+            .collect::<Result<_, String>>()?;
+
+        // the window is sized in logical pixels, scaled by the monitor where
+        // the system doesn't, and draws into a framebuffer at the monitor's
+        // density. the builder ors its own flags onto these
+        unsafe { ::raylib::ffi::SetConfigFlags(ConfigFlags::FLAG_WINDOW_HIGHDPI as u32) };
+        let (mut rl, thread) = ::raylib::init()
+            .size(width, height)
+            .resizable()
+            .title("CHIP-8")
+            .build();
+        // escape is a hotkey, not a way to quit
+        rl.set_exit_key(None);
+
+        let buzzer = Buzzer::default();
+        let beep = audio.and_then(|audio| beep_sound(audio, &buzzer));
+
+        Ok(RaylibBackend {
+            rl,
+            thread,
+            keys,
+            labels: keymap.legend(),
+            hotkeys,
+            gamepad: Vec::new(),
+            extra_keys: Vec::new(),
+            audio,
+            buzzer,
+            beep,
+            envelope: None,
+            sample: None,
+            status: None,
+            keypad_overlay: None,
+            virtual_keypad: None,
+            key_history: None,
+            menu: None,
+            heatmap_overlay: None,
+            pixel_grid: None,
+            debug_hud: None,
+            memory_view: None,
+            #[cfg(feature = "debugger")]
+            debugger: None,
+            palette: Palette::default(),
+            texture: None,
+            pixels: Vec::new(),
+            colors: None,
+            uploaded: None,
+            ghosting: None,
+            crt_shader: None,
+            crt: false,
+            fullscreen: false,
+        })
+    }
+
+    fn upload(&mut self, display: &Display) {
+        if self.texture.is_none() {
+            let (width, height) = (display.width() as i32, display.height() as i32);
+            let image = Image::gen_image_color(width, height, Color::BLACK);
+            self.texture = self.rl.load_texture_from_image(&self.thread, &image).ok();
+            // crisp pixels however far a high dpi framebuffer stretches them
+            if let Some(texture) = &self.texture {
+                texture.set_texture_filter(&self.thread, TextureFilter::TEXTURE_FILTER_POINT);
+            }
+            self.uploaded = None;
+        }
+
+        // a fading ghost changes the texture without the display changing
+        let fading = self
+            .ghosting
+            .as_mut()
+            .is_some_and(|phosphor| phosphor.update(display));
+        if self.uploaded == Some(display.generation()) && !fading {
+            return;
+        }
+        self.uploaded = Some(display.generation());
+
+        self.pixels.clear();
+        match (&self.colors, &self.ghosting) {
+            (Some(colors), _) if colors.len() == display.width() * display.height() => {
+                for argb in colors {
+                    let [_, r, g, b] = argb.to_be_bytes();
+                    self.pixels.extend_from_slice(&[r, g, b, 255]);
+                }
+            }
+            (_, Some(phosphor)) => {
+                for Rgb(r, g, b) in phosphor.colors(&self.palette) {
+                    self.pixels.extend_from_slice(&[r, g, b, 255]);
+                }
+            }
+            _ => {
+                for (x, y, _) in display.iter_pixels() {
+                    let Rgb(r, g, b) = self.palette.colors[display.pixel(x, y) as usize];
+                    self.pixels.extend_from_slice(&[r, g, b, 255]);
+                }
+            }
+        }
+
+        if let Some(texture) = &mut self.texture {
+            let _ = texture.update_texture(&self.pixels);
+        }
+    }
+}
+
+impl Renderer for RaylibBackend<'_> {
+    fn draw(&mut self, display: &Display) {
+        self.upload(display);
+
+        let mut d = self.rl.begin_drawing(&self.thread);
+
+        // the letterbox bars
+        d.clear_background(Color::BLACK);
+
+        // one scaled quad instead of a rectangle per pixel
+        if let Some(texture) = &self.texture {
+            let source = Rectangle::new(0.0, 0.0, display.width() as f32, display.height() as f32);
+            let mut window = (d.get_screen_width(), d.get_screen_height());
+            if self.virtual_keypad.is_some() {
+                let (area, _) = ui::virtual_keypad_split(window);
+                window = (area.w, area.h);
+            }
+            let viewport = letterbox_dpi(
+                window,
+                d.get_window_scale_dpi().x,
+                (display.width(), display.height()),
+            );
+            let screen = Rectangle::new(viewport.x, viewport.y, viewport.width, viewport.height);
+            let origin = Vector2::new(0.0, 0.0);
+            match &mut self.crt_shader {
+                Some(shader) if self.crt => {
+                    let resolution = shader.get_shader_location("resolution");
+                    shader.set_shader_value(
+                        resolution,
+                        Vector2::new(display.width() as f32, display.height() as f32),
+                    );
+                    let mut s = d.begin_shader_mode(shader);
+                    s.draw_texture_pro(texture, source, screen, origin, 0.0, Color::WHITE);
+                }
+                _ => d.draw_texture_pro(texture, source, screen, origin, 0.0, Color::WHITE),
+            }
+            if let Some(sprite) = &self.pixel_grid {
+                let size = (display.width(), display.height());
+                for (rect, gray) in ui::pixel_grid(&viewport, size, sprite.as_ref()) {
+                    d.draw_rectangle(
+                        rect.x,
+                        rect.y,
+                        rect.w,
+                        rect.h,
+                        Color::new(gray, gray, gray, 255),
+                    );
+                }
+            }
+        }
+
+        if let Some(status) = &self.status {
+            let y = d.get_screen_height() - 30;
+            d.draw_text(status, 10, y, 20, Color::GRAY);
+        }
+
+        if let Some(keypad) = &self.keypad_overlay {
+            let history = self.key_history.as_ref().map(KeyHistory::presses);
+            let width = d.get_screen_width();
+            for (rect, gray) in ui::keypad_overlay(width, keypad, &self.labels, history) {
+                d.draw_rectangle(
+                    rect.x,
+                    rect.y,
+                    rect.w,
+                    rect.h,
+                    Color::new(gray, gray, gray, 255),
+                );
+            }
+        }
+
+        if let Some(keypad) = &self.virtual_keypad {
+            let window = (d.get_screen_width(), d.get_screen_height());
+            for (rect, gray) in ui::virtual_keypad(window, keypad) {
+                d.draw_rectangle(
+                    rect.x,
+                    rect.y,
+                    rect.w,
+                    rect.h,
+                    Color::new(gray, gray, gray, 255),
+                );
+            }
+        }
+
+        if let Some(heatmap) = &self.heatmap_overlay {
+            draw_heatmap_overlay(&mut d, heatmap);
+        }
+
+        if let Some(hud) = &self.debug_hud {
+            for (rect, gray) in ui::debug_hud(hud) {
+                d.draw_rectangle(
+                    rect.x,
+                    rect.y,
+                    rect.w,
+                    rect.h,
+                    Color::new(gray, gray, gray, 255),
+                );
+            }
+        }
+
+        if let Some(view) = &self.memory_view {
+            for (rect, gray) in ui::memory_view(d.get_screen_width(), view) {
+                d.draw_rectangle(
+                    rect.x,
+                    rect.y,
+                    rect.w,
+                    rect.h,
+                    Color::new(gray, gray, gray, 255),
+                );
+            }
+        }
+
+        #[cfg(feature = "debugger")]
+        if let Some(view) = &self.debugger {
+            for (rect, gray) in ui::debugger_panel(d.get_screen_width(), view) {
+                d.draw_rectangle(
+                    rect.x,
+                    rect.y,
+                    rect.w,
+                    rect.h,
+                    Color::new(gray, gray, gray, 255),
+                );
+            }
+        }
+
+        if let Some(menu) = &self.menu {
+            let window = (d.get_screen_width(), d.get_screen_height());
+            for (rect, gray) in ui::menu(window, menu) {
+                d.draw_rectangle(
+                    rect.x,
+                    rect.y,
+                    rect.w,
+                    rect.h,
+                    Color::new(gray, gray, gray, 255),
+                );
+            }
+        }
+    }
+
+    fn set_geometry(&mut self, _geometry: Geometry) {
+        // upload makes a new one the size of the next display
+        self.texture = None;
+    }
+
+    fn set_colors(&mut self, colors: Option<&[u32]>) {
+        if self.colors.as_deref() != colors {
+            match (&mut self.colors, colors) {
+                (Some(old), Some(new)) if old.len() == new.len() => old.copy_from_slice(new),
+                (old, new) => *old = new.map(<[u32]>::to_vec),
+            }
+            self.uploaded = None;
+        }
+    }
+
+    fn set_palette(&mut self, palette: &Palette) {
+        if self.palette != *palette {
+            self.palette = *palette;
+            self.uploaded = None;
+        }
+    }
+
+    fn set_fullscreen(&mut self, on: bool) {
+        // borderless instead of exclusive, which would switch the monitor
+        // to the window size rather than the window to the monitor size
+        if self.fullscreen != on {
+            self.fullscreen = on;
+            self.rl.toggle_borderless_windowed();
+        }
+    }
+
+    fn set_crt(&mut self, on: bool) {
+        if on && self.crt_shader.is_none() {
+            let shader = self
+                .rl
+                .load_shader_from_memory(&self.thread, None, Some(CRT_SHADER));
+            self.crt_shader = Some(shader);
+        }
+        self.crt = on;
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.rl.set_window_title(&self.thread, title);
+    }
+
+    fn set_status(&mut self, status: Option<&str>) {
+        self.status = status.map(str::to_owned);
+    }
+
+    fn set_menu(&mut self, menu: Option<&Menu>) {
+        self.menu = menu.cloned();
+    }
+
+    fn set_keypad_overlay(&mut self, keypad: Option<&[bool; 16]>, history: Option<&KeyHistory>) {
+        self.keypad_overlay = keypad.copied();
+        self.key_history = history.cloned();
+    }
+
+    fn set_virtual_keypad(&mut self, on: bool) {
+        if on != self.virtual_keypad.is_some() {
+            self.virtual_keypad = on.then_some([false; 16]);
+        }
+    }
+
+    fn set_debug_hud(&mut self, hud: Option<&DebugHud>) {
+        self.debug_hud = hud.copied();
+    }
+
+    fn set_memory_view(&mut self, view: Option<&MemoryView>) {
+        match (&mut self.memory_view, view) {
+            (Some(shown), Some(view)) => shown.clone_from(view),
+            (shown, view) => *shown = view.cloned(),
+        }
+    }
+
+    #[cfg(feature = "debugger")]
+    fn set_debugger(&mut self, view: Option<&DebuggerView>) {
+        match (&mut self.debugger, view) {
+            (Some(shown), Some(view)) => shown.clone_from(view),
+            (shown, view) => *shown = view.cloned(),
+        }
+    }
+
+    fn set_heatmap_overlay(&mut self, heatmap: Option<&Heatmap>) {
+        match (&mut self.heatmap_overlay, heatmap) {
+            (Some(overlay), Some(heatmap)) => overlay.clone_from(heatmap),
+            (overlay, heatmap) => *overlay = heatmap.cloned(),
+        }
+    }
+
+    fn set_ghosting(&mut self, persistence: Option<u8>) {
+        if self.ghosting.as_ref().map(Phosphor::persistence) != persistence {
+            self.ghosting = persistence.map(Phosphor::new);
+            self.uploaded = None;
+        }
+    }
+
+    fn set_pixel_grid(&mut self, on: bool, sprite: Option<&SpriteBox>) {
+        self.pixel_grid = on.then(|| sprite.copied());
+    }
+}
+
+// 2x2 pixels per byte in the bottom left corner
+fn draw_heatmap_overlay(d: &mut RaylibDrawHandle, heatmap: &Heatmap) {
+    const CELL: i32 = 2;
+    let side = HEATMAP_SIDE as i32 * CELL;
+    let left = 10;
+    let top = d.get_screen_height() - side - 40;
+
+    d.draw_rectangle(left, top, side, side, Color::new(0, 0, 0, 200));
+    d.draw_rectangle_lines(left - 1, top - 1, side + 2, side + 2, Color::GRAY);
+    for addr in 0..HEATMAP_SIDE * HEATMAP_SIDE {
+        if let Some((r, g, b)) = heatmap.color(addr) {
+            let x = left + (addr % HEATMAP_SIDE) as i32 * CELL;
+            let y = top + (addr / HEATMAP_SIDE) as i32 * CELL;
+            d.draw_rectangle(x, y, CELL, CELL, Color::new(r, g, b, 255));
+        }
+    }
+}
+
+// "F1".."F12" or a single letter or digit
+fn key_from_name(name: &str) -> Option<KeyboardKey> {
+    if let Some(n) = name.strip_prefix('F').and_then(|n| n.parse::<i32>().ok())
+        && (1..=12).contains(&n)
+    {
+        return key_from_i32(KeyboardKey::KEY_F1 as i32 + n - 1);
+    }
+    match name {
+        "Backspace" => return Some(KeyboardKey::KEY_BACKSPACE),
+        "Tab" => return Some(KeyboardKey::KEY_TAB),
+        "Return" => return Some(KeyboardKey::KEY_ENTER),
+        "Escape" => return Some(KeyboardKey::KEY_ESCAPE),
+        "Space" => return Some(KeyboardKey::KEY_SPACE),
+        "Up" => return Some(KeyboardKey::KEY_UP),
+        "Down" => return Some(KeyboardKey::KEY_DOWN),
+        "Left" => return Some(KeyboardKey::KEY_LEFT),
+        "Right" => return Some(KeyboardKey::KEY_RIGHT),
+        _ => {}
+    }
+
+    // printable keys use their ascii code, letters the upper case one
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_graphic() => key_from_i32(c.to_ascii_uppercase() as i32),
+        _ => None,
+    }
+}
+
+// the names of GAMEPAD_BUTTONS
+fn button_from_name(name: &str) -> Option<GamepadButton> {
+    use GamepadButton::*;
+    Some(match name {
+        "up" => GAMEPAD_BUTTON_LEFT_FACE_UP,
+        "down" => GAMEPAD_BUTTON_LEFT_FACE_DOWN,
+        "left" => GAMEPAD_BUTTON_LEFT_FACE_LEFT,
+        "right" => GAMEPAD_BUTTON_LEFT_FACE_RIGHT,
+        "a" => GAMEPAD_BUTTON_RIGHT_FACE_DOWN,
+        "b" => GAMEPAD_BUTTON_RIGHT_FACE_RIGHT,
+        "x" => GAMEPAD_BUTTON_RIGHT_FACE_LEFT,
+        "y" => GAMEPAD_BUTTON_RIGHT_FACE_UP,
+        "lb" => GAMEPAD_BUTTON_LEFT_TRIGGER_1,
+        "rb" => GAMEPAD_BUTTON_RIGHT_TRIGGER_1,
+        "select" => GAMEPAD_BUTTON_MIDDLE_LEFT,
+        "start" => GAMEPAD_BUTTON_MIDDLE_RIGHT,
+        _ => return None,
+    })
+}
+
+impl Input for RaylibBackend<'_> {
+    fn poll(&mut self, keypad: &mut [bool; 16], hotkeys: &mut Vec<Hotkey>) -> bool {
+        for (pressed, key) in keypad.iter_mut().zip(&self.keys) {
+            *pressed = self.rl.is_key_down(*key);
+        }
+
+        for (key, chip8_key) in &self.extra_keys {
+            keypad[*chip8_key] |= self.rl.is_key_down(*key);
+        }
+
+        // the first gamepad, pressing keys on top of the keyboard
+        if self.rl.is_gamepad_available(0) {
+            for (button, key) in &self.gamepad {
+                keypad[*key] |= self.rl.is_gamepad_button_down(0, *button);
+            }
+        }
+
+        // raylib reports the first touch as the mouse
+        if let Some(shown) = &mut self.virtual_keypad {
+            if self.rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+                let window = (self.rl.get_screen_width(), self.rl.get_screen_height());
+                let (x, y) = (self.rl.get_mouse_x(), self.rl.get_mouse_y());
+                if let Some(key) = ui::virtual_key_at(window, x, y) {
+                    keypad[key as usize] = true;
+                }
+            }
+            *shown = *keypad;
+        }
+
+        let held = |left, right| self.rl.is_key_down(left) || self.rl.is_key_down(right);
+        let modifiers = Modifiers {
+            shift: held(KeyboardKey::KEY_LEFT_SHIFT, KeyboardKey::KEY_RIGHT_SHIFT),
+            ctrl: held(
+                KeyboardKey::KEY_LEFT_CONTROL,
+                KeyboardKey::KEY_RIGHT_CONTROL,
+            ),
+        };
+        for (key, bound, hotkey) in &self.hotkeys {
+            if *bound == modifiers && self.rl.is_key_pressed(*key) {
+                hotkeys.push(*hotkey);
+            }
+        }
+
+        #[cfg(feature = "debugger")]
+        if let Some(view) = &self.debugger
+            && self
+                .rl
+                .is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT)
+        {
+            let (x, y) = (self.rl.get_mouse_x(), self.rl.get_mouse_y());
+            let width = self.rl.get_screen_width();
+            hotkeys.extend(ui::debugger_button_at(width, view.paused, x, y));
+        }
+
+        !self.rl.window_should_close()
+    }
+
+    fn set_extra_keys(&mut self, mapping: &[(String, u8)]) {
+        self.extra_keys = mapping
+            .iter()
+            .filter_map(|(name, key)| Some((key_from_name(name)?, *key as usize)))
+            .collect();
+    }
+
+    fn set_gamepad(&mut self, mapping: &[(String, u8)]) {
+        self.gamepad = mapping
+            .iter()
+            .filter_map(|(name, key)| Some((button_from_name(name)?, *key as usize)))
+            .collect();
+    }
+
+    fn dropped_file(&mut self) -> Option<PathBuf> {
+        if !self.rl.is_file_dropped() {
+            return None;
+        }
+        let files = self.rl.load_dropped_files();
+        files.paths().last().map(PathBuf::from)
+    }
+
+    fn is_held(&self, hotkey: Hotkey) -> bool {
+        self.hotkeys
+            .iter()
+            .any(|(key, _, bound)| *bound == hotkey && self.rl.is_key_down(*key))
+    }
+
+    fn is_focused(&self) -> bool {
+        self.rl.is_window_focused()
+    }
+}
+
+impl Audio for RaylibBackend<'_> {
+    // the sound loops at full volume, the envelope ramps its volume once a frame
+    fn set_beep(&mut self, on: bool) {
+        if let Some((sample, true)) = &self.sample
+            && !sample.is_playing()
+        {
+            sample.play();
+        }
+        let Some(beep) = &self.beep else {
+            return;
+        };
+
+        match (on, self.envelope) {
+            (true, Some((_, None))) => {
+                if !beep.is_playing() {
+                    beep.play();
+                }
+            }
+            (true, _) => {
+                beep.play();
+                self.envelope = Some((Instant::now(), None));
+            }
+            (false, Some((start, None))) => {
+                let level = self.buzzer.envelope(start.elapsed(), None);
+                self.envelope = Some((Instant::now(), Some(level)));
+            }
+            (false, _) => {}
+        }
+        if let Some((since, stopped)) = self.envelope {
+            if stopped.is_some() && since.elapsed() >= self.buzzer.release {
+                beep.stop();
+                self.envelope = None;
+            } else {
+                beep.set_volume(self.buzzer.envelope(since.elapsed(), stopped));
+            }
+        }
+    }
+
+    fn set_buzzer(&mut self, buzzer: &Buzzer) {
+        if let Some(beep) = &self.beep {
+            beep.stop();
+        }
+        self.buzzer = *buzzer;
+        self.envelope = None;
+        self.beep = self.audio.and_then(|audio| beep_sound(audio, buzzer));
+    }
+
+    fn play_sample(&mut self, sample: Option<&Sample>) {
+        if let Some((old, _)) = self.sample.take() {
+            old.stop();
+        }
+        let (Some(audio), Some(sample)) = (self.audio, sample) else {
+            return;
+        };
+        let sound = audio
+            .new_wave_from_memory(".wav", &wav(sample.rate, &sample.data))
+            .ok()
+            .and_then(|wave| audio.new_sound_from_wave(&wave).ok());
+        if let Some(sound) = sound {
+            sound.play();
+            self.sample = Some((sound, sample.looped));
+        }
+    }
+}
+
+fn beep_sound<'a>(audio: &'a RaylibAudio, buzzer: &Buzzer) -> Option<Sound<'a>> {
+    let wave = audio.new_wave_from_memory(".wav", &beep_wav(buzzer)).ok()?;
+    audio.new_sound_from_wave(&wave).ok()
+}
+
+// one second of the tone as an in-memory 8 bit mono wav file
+fn beep_wav(buzzer: &Buzzer) -> Vec<u8> {
+    let period = buzzer.period(SAMPLE_RATE);
+    let samples: Vec<u8> = period
+        .iter()
+        .copied()
+        .cycle()
+        .take(SAMPLE_RATE as usize)
+        .collect();
+    wav(SAMPLE_RATE, &samples)
+}
+
+// an in-memory 8 bit mono wav file of samples at rate
+fn wav(rate: u32, samples: &[u8]) -> Vec<u8> {
+    let mut wav = Vec::new();
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + samples.len() as u32).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // pcm
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&rate.to_le_bytes());
+    wav.extend_from_slice(&rate.to_le_bytes()); // byte rate
+    wav.extend_from_slice(&1u16.to_le_bytes()); // block align
+    wav.extend_from_slice(&8u16.to_le_bytes()); // bits per sample
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&(samples.len() as u32).to_le_bytes());
+    wav.extend_from_slice(samples);
+    wav
+}
@@ -0,0 +1,726 @@
+// minimal hand written bindings to the parts of SDL2 the backend needs,
+// so the feature only requires the system SDL2 library and no extra crates
+use super::{
+    Audio, Hotkey, Input, Modifiers, Renderer, SAMPLE_RATE, dpi_scale, letterbox, split_modifiers,
+    ui::{self, KeyHistory, Menu, UiLayer},
+};
+use crate::buzzer::Buzzer;
+use crate::chip8::SpriteBox;
+use crate::display::Display;
+use crate::heatmap::{HEATMAP_SIDE, Heatmap};
+use crate::keymap::Keymap;
+use crate::palette::{Palette, Rgb};
+use crate::phosphor::Phosphor;
+use std::ffi::{CStr, CString, c_char, c_int, c_void};
+use std::ptr;
+
+const SDL_INIT_AUDIO: u32 = 0x0000_0010;
+const SDL_INIT_VIDEO: u32 = 0x0000_0020;
+const SDL_WINDOWPOS_CENTERED: c_int = 0x2FFF_0000;
+const SDL_WINDOW_SHOWN: u32 = 0x0000_0004;
+const SDL_WINDOW_RESIZABLE: u32 = 0x0000_0020;
+const SDL_WINDOW_FULLSCREEN_DESKTOP: u32 = 0x0000_1001;
+const SDL_WINDOW_INPUT_FOCUS: u32 = 0x0000_0200;
+const SDL_WINDOW_ALLOW_HIGHDPI: u32 = 0x0000_2000;
+const SDL_RENDERER_ACCELERATED: u32 = 0x0000_0002;
+const SDL_QUIT: u32 = 0x100;
+const SDL_KEYDOWN: u32 = 0x300;
+const SDL_BUTTON_LMASK: u32 = 0x0001;
+const KMOD_LSHIFT: u16 = 0x0001;
+const KMOD_RSHIFT: u16 = 0x0002;
+const KMOD_LCTRL: u16 = 0x0040;
+const KMOD_RCTRL: u16 = 0x0080;
+const AUDIO_U8: u16 = 0x0008;
+const SDL_BLENDMODE_NONE: c_int = 0;
+const SDL_BLENDMODE_BLEND: c_int = 1;
+// periods of the tone queued at a time while the beep lasts
+const BEEP_PERIODS: usize = 20;
+
+#[repr(C)]
+struct SdlRect {
+    x: c_int,
+    y: c_int,
+    w: c_int,
+    h: c_int,
+}
+
+#[repr(C)]
+struct SdlAudioSpec {
+    freq: c_int,
+    format: u16,
+    channels: u8,
+    silence: u8,
+    samples: u16,
+    padding: u16,
+    size: u32,
+    callback: Option<extern "C" fn(*mut c_void, *mut u8, c_int)>,
+    userdata: *mut c_void,
+}
+
+// SDL_Event is a 56 byte union, only the type tag and for key events the
+// repeat flag (byte 9) and scancode (bytes 12..16) are read
+#[repr(C, align(8))]
+struct SdlEvent {
+    kind: u32,
+    padding: [u8; 52],
+}
+
+#[link(name = "SDL2")]
+unsafe extern "C" {
+    fn SDL_Init(flags: u32) -> c_int;
+    fn SDL_Quit();
+    fn SDL_GetError() -> *const c_char;
+    fn SDL_CreateWindow(
+        title: *const c_char,
+        x: c_int,
+        y: c_int,
+        w: c_int,
+        h: c_int,
+        flags: u32,
+    ) -> *mut c_void;
+    fn SDL_DestroyWindow(window: *mut c_void);
+    fn SDL_SetWindowTitle(window: *mut c_void, title: *const c_char);
+    fn SDL_SetWindowFullscreen(window: *mut c_void, flags: u32) -> c_int;
+    fn SDL_GetWindowSize(window: *mut c_void, w: *mut c_int, h: *mut c_int);
+    fn SDL_GetWindowFlags(window: *mut c_void) -> u32;
+    fn SDL_GetDisplayDPI(index: c_int, ddpi: *mut f32, hdpi: *mut f32, vdpi: *mut f32) -> c_int;
+    fn SDL_SetHint(name: *const c_char, value: *const c_char) -> c_int;
+    fn SDL_CreateRenderer(window: *mut c_void, index: c_int, flags: u32) -> *mut c_void;
+    fn SDL_DestroyRenderer(renderer: *mut c_void);
+    fn SDL_GetRendererOutputSize(renderer: *mut c_void, w: *mut c_int, h: *mut c_int) -> c_int;
+    fn SDL_RenderSetScale(renderer: *mut c_void, x: f32, y: f32) -> c_int;
+    fn SDL_SetRenderDrawColor(renderer: *mut c_void, r: u8, g: u8, b: u8, a: u8) -> c_int;
+    fn SDL_RenderClear(renderer: *mut c_void) -> c_int;
+    fn SDL_SetRenderDrawBlendMode(renderer: *mut c_void, mode: c_int) -> c_int;
+    fn SDL_RenderFillRect(renderer: *mut c_void, rect: *const SdlRect) -> c_int;
+    fn SDL_RenderPresent(renderer: *mut c_void);
+    fn SDL_PollEvent(event: *mut SdlEvent) -> c_int;
+    fn SDL_GetKeyboardState(numkeys: *mut c_int) -> *const u8;
+    fn SDL_GetMouseState(x: *mut c_int, y: *mut c_int) -> u32;
+    fn SDL_GetScancodeFromName(name: *const c_char) -> c_int;
+    fn SDL_OpenAudioDevice(
+        device: *const c_char,
+        iscapture: c_int,
+        desired: *const SdlAudioSpec,
+        obtained: *mut SdlAudioSpec,
+        allowed_changes: c_int,
+    ) -> u32;
+    fn SDL_CloseAudioDevice(dev: u32);
+    fn SDL_GetNumAudioDevices(iscapture: c_int) -> c_int;
+    fn SDL_GetNumRenderDrivers() -> c_int;
+    fn SDL_PauseAudioDevice(dev: u32, pause_on: c_int);
+    fn SDL_QueueAudio(dev: u32, data: *const c_void, len: u32) -> c_int;
+    fn SDL_GetQueuedAudioSize(dev: u32) -> u32;
+    fn SDL_ClearQueuedAudio(dev: u32);
+}
+
+fn sdl_error() -> String {
+    unsafe {
+        CStr::from_ptr(SDL_GetError())
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+// the monitor's scale for windows sized in pixels, from its dpi over the 96
+// of an unscaled one, in the quarter steps desktops offer
+fn monitor_scale() -> f32 {
+    if cfg!(target_os = "macos") {
+        return 1.0;
+    }
+    let mut dpi = 0.0;
+    if unsafe { SDL_GetDisplayDPI(0, ptr::null_mut(), &mut dpi, ptr::null_mut()) } != 0 {
+        return 1.0;
+    }
+    ((dpi / 96.0 * 4.0).round() / 4.0).clamp(1.0, 4.0)
+}
+
+// for doctor: number of playback devices
+pub fn probe_audio() -> Result<usize, String> {
+    unsafe {
+        if SDL_Init(SDL_INIT_AUDIO) != 0 {
+            return Err(sdl_error());
+        }
+        let devices = SDL_GetNumAudioDevices(0);
+        SDL_Quit();
+        Ok(devices.max(0) as usize)
+    }
+}
+
+// for doctor: number of render drivers the video subsystem offers
+pub fn probe_renderer() -> Result<usize, String> {
+    unsafe {
+        if SDL_Init(SDL_INIT_VIDEO) != 0 {
+            return Err(sdl_error());
+        }
+        let drivers = SDL_GetNumRenderDrivers();
+        SDL_Quit();
+        Ok(drivers.max(0) as usize)
+    }
+}
+
+// display generation, keypad overlay, virtual keypad, pixel grid and
+// framebuffer size on screen
+type Presented = (
+    u64,
+    Option<[bool; 16]>,
+    Option<u64>,
+    Option<[bool; 16]>,
+    Option<Option<SpriteBox>>,
+    (c_int, c_int),
+);
+
+pub struct Sdl2Backend {
+    window: *mut c_void,
+    renderer: *mut c_void,
+    scancodes: Vec<usize>,
+    // (scancode, chip8 key) of Input::set_extra_keys
+    extra_keys: Vec<(usize, usize)>,
+    // first letter of every key name, for the keypad overlay
+    labels: [char; 16],
+    hotkeys: Vec<(i32, Modifiers, Hotkey)>,
+    audio_device: u32,
+    // the tone, its attack queued ahead of it and its release in place of
+    // what's left when it stops
+    wave: Vec<u8>,
+    attack: Vec<u8>,
+    release: Vec<u8>,
+    beeping: bool,
+    // the window title without the status
+    title: String,
+    status: Option<String>,
+    keypad_overlay: Option<[bool; 16]>,
+    // see Renderer::set_virtual_keypad, with the keys last polled
+    virtual_keypad: Option<[bool; 16]>,
+    key_history: Option<KeyHistory>,
+    menu: Option<Menu>,
+    heatmap_overlay: Option<Heatmap>,
+    // see Renderer::set_ghosting
+    ghosting: Option<Phosphor>,
+    // see Renderer::set_pixel_grid, None when it's off
+    pixel_grid: Option<Option<SpriteBox>>,
+    palette: Palette,
+    crt: bool,
+    // draw is skipped while nothing changed
+    presented: Option<Presented>,
+}
+
+impl Sdl2Backend {
+    pub fn new(width: i32, height: i32, keymap: &Keymap) -> Result<Self, String> {
+        unsafe {
+            // names resolve without SDL_Init, SDL_SCANCODE_UNKNOWN is 0
+            let scancode = |name: &str| {
+                let code = CString::new(name)
+                    .map(|name| SDL_GetScancodeFromName(name.as_ptr()))
+                    .unwrap_or(0);
+                if code == 0 {
+                    Err(format!("unknown key {name}"))
+                } else {
+                    Ok(code)
+                }
+            };
+            let scancodes = keymap
+                .keypad
+                .iter()
+                .map(|name| Ok(scancode(name)? as usize))
+                .collect::<Result<_, String>>()?;
+            let hotkeys = keymap
+                .hotkeys
+                .iter()
+                .map(|(name, hotkey)| {
+                    let (modifiers, key) = split_modifiers(name);
+                    Ok((scancode(key)?, modifiers, *hotkey))
+                })
+                .collect::<Result<_, String>>()?;
+
+            // windows would otherwise scale the whole window up, blurry
+            SDL_SetHint(
+                c"SDL_WINDOWS_DPI_AWARENESS".as_ptr(),
+                c"permonitorv2".as_ptr(),
+            );
+
+            let flags = if cfg!(feature = "audio") {
+                SDL_INIT_VIDEO | SDL_INIT_AUDIO
+            } else {
+                SDL_INIT_VIDEO
+            };
+            if SDL_Init(flags) != 0 {
+                return Err(sdl_error());
+            }
+
+            // width and height are logical pixels. macos sizes windows in
+            // points and hands out a denser framebuffer by itself, elsewhere
+            // the window is sized in pixels and scaled by the monitor here
+            let scale = monitor_scale();
+            let window = SDL_CreateWindow(
+                c"CHIP-8".as_ptr(),
+                SDL_WINDOWPOS_CENTERED,
+                SDL_WINDOWPOS_CENTERED,
+                (width as f32 * scale) as c_int,
+                (height as f32 * scale) as c_int,
+                SDL_WINDOW_SHOWN | SDL_WINDOW_RESIZABLE | SDL_WINDOW_ALLOW_HIGHDPI,
+            );
+            if window.is_null() {
+                return Err(sdl_error());
+            }
+
+            let renderer = SDL_CreateRenderer(window, -1, SDL_RENDERER_ACCELERATED);
+            if renderer.is_null() {
+                return Err(sdl_error());
+            }
+
+            let desired = SdlAudioSpec {
+                freq: SAMPLE_RATE as c_int,
+                format: AUDIO_U8,
+                channels: 1,
+                silence: 0,
+                samples: 1024,
+                padding: 0,
+                size: 0,
+                callback: None,
+                userdata: ptr::null_mut(),
+            };
+            // a missing audio device is not fatal, the beep is just silent
+            let audio_device = if cfg!(feature = "audio") {
+                SDL_OpenAudioDevice(ptr::null(), 0, &desired, ptr::null_mut(), 0)
+            } else {
+                0
+            };
+            if audio_device != 0 {
+                SDL_PauseAudioDevice(audio_device, 0);
+            }
+
+            let buzzer = Buzzer::default();
+
+            Ok(Sdl2Backend {
+                window,
+                renderer,
+                scancodes,
+                extra_keys: Vec::new(),
+                labels: keymap.legend(),
+                hotkeys,
+                audio_device,
+                wave: buzzer.period(SAMPLE_RATE).repeat(BEEP_PERIODS),
+                attack: buzzer.attack(SAMPLE_RATE),
+                release: buzzer.release(SAMPLE_RATE),
+                beeping: false,
+                title: "CHIP-8".to_owned(),
+                status: None,
+                keypad_overlay: None,
+                virtual_keypad: None,
+                key_history: None,
+                menu: None,
+                heatmap_overlay: None,
+                ghosting: None,
+                pixel_grid: None,
+                palette: Palette::default(),
+                crt: false,
+                presented: None,
+            })
+        }
+    }
+}
+
+impl Drop for Sdl2Backend {
+    fn drop(&mut self) {
+        unsafe {
+            if self.audio_device != 0 {
+                SDL_CloseAudioDevice(self.audio_device);
+            }
+            SDL_DestroyRenderer(self.renderer);
+            SDL_DestroyWindow(self.window);
+            SDL_Quit();
+        }
+    }
+}
+
+impl Renderer for Sdl2Backend {
+    fn draw(&mut self, display: &Display) {
+        // the display is drawn in framebuffer pixels, the overlays in the
+        // window's logical ones scaled up to them
+        let (mut width, mut height) = (0, 0);
+        let (mut pixels_wide, mut pixels_high) = (0, 0);
+        unsafe {
+            SDL_GetWindowSize(self.window, &mut width, &mut height);
+            SDL_GetRendererOutputSize(self.renderer, &mut pixels_wide, &mut pixels_high);
+        }
+        let dpi = dpi_scale((width, height), (pixels_wide, pixels_high));
+
+        let presented = Some((
+            display.generation(),
+            self.keypad_overlay,
+            self.key_history.as_ref().map(KeyHistory::total),
+            self.virtual_keypad,
+            self.pixel_grid,
+            (pixels_wide, pixels_high),
+        ));
+        // the heatmap fades every frame, so it always redraws, ghosts redraw
+        // until they faded
+        let fading = self
+            .ghosting
+            .as_mut()
+            .is_some_and(|phosphor| phosphor.update(display));
+        if self.presented == presented && self.heatmap_overlay.is_none() && !fading {
+            // nothing paces the loop without a present, don't spin
+            std::thread::sleep(std::time::Duration::from_millis(1));
+            return;
+        }
+        self.presented = presented;
+
+        let mut framebuffer = (pixels_wide, pixels_high);
+        if self.virtual_keypad.is_some() {
+            let (area, _) = ui::virtual_keypad_split((width, height));
+            framebuffer = (
+                (area.w as f32 * dpi) as c_int,
+                (area.h as f32 * dpi) as c_int,
+            );
+        }
+        let viewport = letterbox(framebuffer, (display.width(), display.height()));
+        let screen = SdlRect {
+            x: viewport.x as c_int,
+            y: viewport.y as c_int,
+            w: viewport.width as c_int,
+            h: viewport.height as c_int,
+        };
+        // edges instead of a fixed size, so fractional scales leave no gaps
+        let edge = |n: usize, origin: c_int| origin + (n as f32 * viewport.scale) as c_int;
+
+        unsafe {
+            SDL_RenderSetScale(self.renderer, 1.0, 1.0);
+            // the letterbox bars
+            SDL_SetRenderDrawColor(self.renderer, 0, 0, 0, 255);
+            SDL_RenderClear(self.renderer);
+            let Rgb(r, g, b) = self.palette.background();
+            SDL_SetRenderDrawColor(self.renderer, r, g, b, 255);
+            SDL_RenderFillRect(self.renderer, &screen);
+
+            let rect = |x: usize, y: usize| {
+                let (left, top) = (edge(x, screen.x), edge(y, screen.y));
+                SdlRect {
+                    x: left,
+                    y: top,
+                    w: edge(x + 1, screen.x) - left,
+                    h: edge(y + 1, screen.y) - top,
+                }
+            };
+            match &self.ghosting {
+                // a color per pixel, the faded ones in between
+                Some(phosphor) => {
+                    let colors = phosphor.colors(&self.palette);
+                    for ((x, y, _), Rgb(r, g, b)) in display.iter_pixels().zip(colors) {
+                        if Rgb(r, g, b) != self.palette.background() {
+                            SDL_SetRenderDrawColor(self.renderer, r, g, b, 255);
+                            SDL_RenderFillRect(self.renderer, &rect(x, y));
+                        }
+                    }
+                }
+                // the first plane's color and for xo-chip the other two
+                None => {
+                    for (x, y, _) in display.iter_pixels().filter(|&(_, _, on)| on) {
+                        let Rgb(r, g, b) = self.palette.colors[display.pixel(x, y) as usize];
+                        SDL_SetRenderDrawColor(self.renderer, r, g, b, 255);
+                        SDL_RenderFillRect(self.renderer, &rect(x, y));
+                    }
+                }
+            }
+
+            if self.crt {
+                self.draw_scanlines(&screen, viewport.scale as c_int);
+            }
+            if let Some(sprite) = &self.pixel_grid {
+                let size = (display.width(), display.height());
+                self.paint(&ui::pixel_grid(&viewport, size, sprite.as_ref()));
+            }
+            SDL_RenderSetScale(self.renderer, dpi, dpi);
+
+            if let Some(keypad) = &self.keypad_overlay {
+                let history = self.key_history.as_ref().map(KeyHistory::presses);
+                self.paint(&ui::keypad_overlay(width, keypad, &self.labels, history));
+            }
+
+            if let Some(keypad) = &self.virtual_keypad {
+                self.paint(&ui::virtual_keypad((width, height), keypad));
+            }
+
+            if let Some(heatmap) = &self.heatmap_overlay {
+                self.draw_heatmap_overlay(heatmap, height);
+            }
+
+            if let Some(menu) = &self.menu {
+                self.paint(&ui::menu((width, height), menu));
+            }
+
+            SDL_RenderPresent(self.renderer);
+        }
+    }
+
+    fn set_palette(&mut self, palette: &Palette) {
+        if self.palette != *palette {
+            self.palette = *palette;
+            self.presented = None;
+        }
+    }
+
+    fn set_fullscreen(&mut self, on: bool) {
+        // desktop fullscreen keeps the monitor's mode, no mode switch flicker
+        let flags = if on { SDL_WINDOW_FULLSCREEN_DESKTOP } else { 0 };
+        unsafe { SDL_SetWindowFullscreen(self.window, flags) };
+    }
+
+    fn set_crt(&mut self, on: bool) {
+        if self.crt != on {
+            self.crt = on;
+            self.presented = None;
+        }
+    }
+
+    fn set_title(&mut self, title: &str) {
+        if self.title != title {
+            self.title = title.to_owned();
+            self.update_title();
+        }
+    }
+
+    fn set_status(&mut self, status: Option<&str>) {
+        if self.status.as_deref() != status {
+            self.status = status.map(str::to_owned);
+            self.update_title();
+        }
+    }
+
+    fn set_menu(&mut self, menu: Option<&Menu>) {
+        if self.menu.as_ref() != menu {
+            self.menu = menu.cloned();
+            self.presented = None;
+        }
+    }
+
+    fn set_keypad_overlay(&mut self, keypad: Option<&[bool; 16]>, history: Option<&KeyHistory>) {
+        self.keypad_overlay = keypad.copied();
+        self.key_history = history.cloned();
+    }
+
+    fn set_virtual_keypad(&mut self, on: bool) {
+        if on != self.virtual_keypad.is_some() {
+            self.virtual_keypad = on.then_some([false; 16]);
+        }
+    }
+
+    fn set_heatmap_overlay(&mut self, heatmap: Option<&Heatmap>) {
+        match (&mut self.heatmap_overlay, heatmap) {
+            (Some(overlay), Some(heatmap)) => overlay.clone_from(heatmap),
+            (overlay, heatmap) => *overlay = heatmap.cloned(),
+        }
+    }
+
+    fn set_ghosting(&mut self, persistence: Option<u8>) {
+        if self.ghosting.as_ref().map(Phosphor::persistence) != persistence {
+            self.ghosting = persistence.map(Phosphor::new);
+            self.presented = None;
+        }
+    }
+
+    fn set_pixel_grid(&mut self, on: bool, sprite: Option<&SpriteBox>) {
+        self.pixel_grid = on.then(|| sprite.copied());
+    }
+}
+
+impl Sdl2Backend {
+    // title and status in one, sdl has no status line of its own
+    fn update_title(&self) {
+        let title = match &self.status {
+            Some(status) => format!("{} - {status}", self.title),
+            None => self.title.clone(),
+        };
+        let title = CString::new(title).unwrap_or_default();
+        unsafe { SDL_SetWindowTitle(self.window, title.as_ptr()) };
+    }
+
+    unsafe fn paint(&self, layer: &UiLayer) {
+        for (rect, gray) in layer {
+            let rect = SdlRect {
+                x: rect.x,
+                y: rect.y,
+                w: rect.w,
+                h: rect.h,
+            };
+            unsafe {
+                SDL_SetRenderDrawColor(self.renderer, *gray, *gray, *gray, 255);
+                SDL_RenderFillRect(self.renderer, &rect);
+            }
+        }
+    }
+
+    // no shaders here, the crt look is only darkened lines between pixel rows
+    unsafe fn draw_scanlines(&self, screen: &SdlRect, pixel_height: c_int) {
+        if pixel_height < 3 {
+            return;
+        }
+        unsafe {
+            SDL_SetRenderDrawBlendMode(self.renderer, SDL_BLENDMODE_BLEND);
+            SDL_SetRenderDrawColor(self.renderer, 0, 0, 0, 90);
+            for y in (pixel_height - 1..screen.h).step_by(pixel_height as usize) {
+                let line = SdlRect {
+                    x: screen.x,
+                    y: screen.y + y,
+                    w: screen.w,
+                    h: 1,
+                };
+                SDL_RenderFillRect(self.renderer, &line);
+            }
+            SDL_SetRenderDrawBlendMode(self.renderer, SDL_BLENDMODE_NONE);
+        }
+    }
+
+    // 2x2 pixels per byte in the bottom left corner
+    unsafe fn draw_heatmap_overlay(&self, heatmap: &Heatmap, window_height: c_int) {
+        const CELL: c_int = 2;
+        let side = HEATMAP_SIDE as c_int * CELL;
+        let background = SdlRect {
+            x: 8,
+            y: window_height - side - 8,
+            w: side,
+            h: side,
+        };
+
+        unsafe {
+            SDL_SetRenderDrawColor(self.renderer, 0, 0, 0, 255);
+            SDL_RenderFillRect(self.renderer, &background);
+            for addr in 0..HEATMAP_SIDE * HEATMAP_SIDE {
+                if let Some((r, g, b)) = heatmap.color(addr) {
+                    let cell = SdlRect {
+                        x: background.x + (addr % HEATMAP_SIDE) as c_int * CELL,
+                        y: background.y + (addr / HEATMAP_SIDE) as c_int * CELL,
+                        w: CELL,
+                        h: CELL,
+                    };
+                    SDL_SetRenderDrawColor(self.renderer, r, g, b, 255);
+                    SDL_RenderFillRect(self.renderer, &cell);
+                }
+            }
+        }
+    }
+}
+
+impl Input for Sdl2Backend {
+    fn poll(&mut self, keypad: &mut [bool; 16], hotkeys: &mut Vec<Hotkey>) -> bool {
+        unsafe {
+            let mut event = SdlEvent {
+                kind: 0,
+                padding: [0; 52],
+            };
+            while SDL_PollEvent(&mut event) != 0 {
+                match event.kind {
+                    SDL_QUIT => return false,
+                    SDL_KEYDOWN if event.padding[9] == 0 => {
+                        let scancode = i32::from_ne_bytes([
+                            event.padding[12],
+                            event.padding[13],
+                            event.padding[14],
+                            event.padding[15],
+                        ]);
+                        // keysym.mod
+                        let held = u16::from_ne_bytes([event.padding[20], event.padding[21]]);
+                        let modifiers = Modifiers {
+                            shift: held & (KMOD_LSHIFT | KMOD_RSHIFT) != 0,
+                            ctrl: held & (KMOD_LCTRL | KMOD_RCTRL) != 0,
+                        };
+                        hotkeys.extend(
+                            self.hotkeys
+                                .iter()
+                                .filter(|(code, bound, _)| *code == scancode && *bound == modifiers)
+                                .map(|(_, _, hotkey)| *hotkey),
+                        );
+                    }
+                    _ => {}
+                }
+            }
+
+            let mut numkeys = 0;
+            let state = SDL_GetKeyboardState(&mut numkeys);
+            let state = std::slice::from_raw_parts(state, numkeys as usize);
+            for (pressed, scancode) in keypad.iter_mut().zip(&self.scancodes) {
+                *pressed = state.get(*scancode).is_some_and(|s| *s != 0);
+            }
+            for (scancode, key) in &self.extra_keys {
+                keypad[*key] |= state.get(*scancode).is_some_and(|s| *s != 0);
+            }
+
+            // sdl turns touches into mouse events too
+            if let Some(shown) = &mut self.virtual_keypad {
+                let (mut x, mut y) = (0, 0);
+                if SDL_GetMouseState(&mut x, &mut y) & SDL_BUTTON_LMASK != 0 {
+                    let (mut width, mut height) = (0, 0);
+                    SDL_GetWindowSize(self.window, &mut width, &mut height);
+                    if let Some(key) = ui::virtual_key_at((width, height), x, y) {
+                        keypad[key as usize] = true;
+                    }
+                }
+                *shown = *keypad;
+            }
+        }
+
+        true
+    }
+
+    fn set_extra_keys(&mut self, mapping: &[(String, u8)]) {
+        self.extra_keys = mapping
+            .iter()
+            .filter_map(|(name, key)| {
+                let name = CString::new(name.as_str()).ok()?;
+                let scancode = unsafe { SDL_GetScancodeFromName(name.as_ptr()) };
+                (scancode != 0).then_some((scancode as usize, *key as usize))
+            })
+            .collect();
+    }
+
+    fn is_held(&self, hotkey: Hotkey) -> bool {
+        unsafe {
+            let mut numkeys = 0;
+            let state = SDL_GetKeyboardState(&mut numkeys);
+            let state = std::slice::from_raw_parts(state, numkeys as usize);
+            self.hotkeys.iter().any(|(code, _, bound)| {
+                *bound == hotkey && state.get(*code as usize).is_some_and(|s| *s != 0)
+            })
+        }
+    }
+
+    fn is_focused(&self) -> bool {
+        unsafe { SDL_GetWindowFlags(self.window) & SDL_WINDOW_INPUT_FOCUS != 0 }
+    }
+}
+
+impl Audio for Sdl2Backend {
+    fn set_beep(&mut self, on: bool) {
+        if self.audio_device == 0 {
+            return;
+        }
+
+        let queue = |samples: &[u8]| unsafe {
+            SDL_QueueAudio(
+                self.audio_device,
+                samples.as_ptr() as *const c_void,
+                samples.len() as u32,
+            );
+        };
+        if on {
+            if !self.beeping {
+                unsafe { SDL_ClearQueuedAudio(self.audio_device) };
+                queue(&self.attack);
+            }
+            if unsafe { SDL_GetQueuedAudioSize(self.audio_device) } < self.wave.len() as u32 {
+                queue(&self.wave);
+            }
+        } else if self.beeping {
+            unsafe { SDL_ClearQueuedAudio(self.audio_device) };
+            queue(&self.release);
+        }
+        self.beeping = on;
+    }
+
+    fn set_buzzer(&mut self, buzzer: &Buzzer) {
+        self.wave = buzzer.period(SAMPLE_RATE).repeat(BEEP_PERIODS);
+        self.attack = buzzer.attack(SAMPLE_RATE);
+        self.release = buzzer.release(SAMPLE_RATE);
+    }
+}
@@ -0,0 +1,42 @@
+#version 330
+
+// crt look for the display texture: barrel distortion, a soft glow
+// and dark scanlines between the rows of chip8 pixels
+
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+// size of the display in chip8 pixels
+uniform vec2 resolution;
+
+out vec4 finalColor;
+
+const float CURVATURE = 0.04;
+const float GLOW = 0.5;
+const float SCANLINE_DARKNESS = 0.3;
+
+void main()
+{
+    vec2 uv = fragTexCoord * 2.0 - 1.0;
+    uv *= 1.0 + CURVATURE * dot(uv, uv);
+    uv = uv * 0.5 + 0.5;
+    if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+        finalColor = vec4(0.0, 0.0, 0.0, 1.0);
+        return;
+    }
+
+    vec2 texel = 1.0 / resolution;
+    vec4 color = texture(texture0, uv);
+    vec4 glow = (texture(texture0, uv + vec2(texel.x, 0.0))
+        + texture(texture0, uv - vec2(texel.x, 0.0))
+        + texture(texture0, uv + vec2(0.0, texel.y))
+        + texture(texture0, uv - vec2(0.0, texel.y))) * 0.25;
+    color = max(color, glow * GLOW);
+
+    float line = sin(uv.y * resolution.y * 6.2831853) * 0.5 + 0.5;
+    color.rgb *= mix(1.0 - SCANLINE_DARKNESS, 1.0, line);
+
+    finalColor = color * colDiffuse * fragColor;
+}
@@ -0,0 +1,672 @@
+// overlays laid out once as gray filled rectangles, text included by way of
+// glyph, so every backend draws them the same with nothing but rectangles
+#[cfg(feature = "debugger")]
+use super::Hotkey;
+use super::{KEYPAD_ROWS, Viewport, glyph};
+use crate::chip8::{CpuSnapshot, Instruction, RunState, SpriteBox};
+#[cfg(feature = "debugger")]
+use crate::debugger::{
+    DEBUGGER_CODE_ROWS, DEBUGGER_MEMORY_COLUMNS, DEBUGGER_MEMORY_ROWS, DebuggerView,
+};
+use crate::disasm;
+use crate::memory_view::{MEMORY_VIEW_COLUMNS, MemoryView};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UiRect {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+// (rectangle, gray level) in drawing order
+pub type UiLayer = Vec<(UiRect, u8)>;
+
+// presses the key history ticker shows, as many as fit under the keypad
+pub const KEY_HISTORY_LEN: usize = 11;
+
+const CELL: i32 = 24;
+const MARGIN: i32 = 8;
+const TEXT_SCALE: i32 = 2;
+const KEY: u8 = 40;
+const PRESSED: u8 = 255;
+const BORDER: u8 = 128;
+
+// a full window list to pick from, e.g. the rom browser
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Menu {
+    pub title: String,
+    pub items: Vec<String>,
+    pub selected: usize,
+    // shown instead of the items when there are none
+    pub empty: String,
+}
+
+// what the debug hud shows, the rates as the frontend measured them
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DebugHud {
+    pub cpu: CpuSnapshot,
+    pub instructions_per_second: f64,
+    pub frames_per_second: f64,
+}
+
+// the chip8 keys in the order they went down, newest last
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyHistory {
+    presses: Vec<u8>,
+    held: [bool; 16],
+    // presses ever recorded, lets a renderer notice a change cheaply
+    total: u64,
+}
+
+impl KeyHistory {
+    pub fn new() -> Self {
+        KeyHistory::default()
+    }
+
+    // call with every keypad poll, keys held down count once
+    pub fn update(&mut self, keypad: &[bool; 16]) {
+        for (key, (pressed, held)) in keypad.iter().zip(&mut self.held).enumerate() {
+            if *pressed && !*held {
+                if self.presses.len() == KEY_HISTORY_LEN {
+                    self.presses.remove(0);
+                }
+                self.presses.push(key as u8);
+                self.total += 1;
+            }
+            *held = *pressed;
+        }
+    }
+
+    pub fn presses(&self) -> &[u8] {
+        &self.presses
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+}
+
+// 4x4 boxes in the top right corner, each with the chip8 key on top and the
+// bound physical key below it, and the ticker of recent presses underneath
+pub fn keypad_overlay(
+    window_width: i32,
+    keypad: &[bool; 16],
+    labels: &[char; 16],
+    history: Option<&[u8]>,
+) -> UiLayer {
+    let left = window_width - 4 * CELL - MARGIN;
+    let top = MARGIN;
+    let mut layer = UiLayer::new();
+
+    for (row, keys) in KEYPAD_ROWS.iter().enumerate() {
+        for (col, key) in keys.iter().enumerate() {
+            let key = *key as usize;
+            let cell = UiRect {
+                x: left + col as i32 * CELL,
+                y: top + row as i32 * CELL,
+                w: CELL - 2,
+                h: CELL - 2,
+            };
+            let (fg, bg) = if keypad[key] {
+                (0, PRESSED)
+            } else {
+                (PRESSED, KEY)
+            };
+
+            layer.push((cell, bg));
+            outline(&mut layer, cell, BORDER);
+            let hex = char::from_digit(key as u32, 16).unwrap_or(' ');
+            text(&mut layer, &hex.to_string(), cell.x + 3, cell.y + 3, fg);
+            text(
+                &mut layer,
+                &labels[key].to_string(),
+                cell.x + 12,
+                cell.y + 9,
+                fg,
+            );
+        }
+    }
+
+    if let Some(presses) = history {
+        let ticker = UiRect {
+            x: left,
+            y: top + 4 * CELL,
+            w: 4 * CELL - 2,
+            h: 5 * TEXT_SCALE + 6,
+        };
+        layer.push((ticker, KEY));
+        outline(&mut layer, ticker, BORDER);
+        let keys: String = presses.iter().map(|key| format!("{key:X}")).collect();
+        text(&mut layer, &keys, ticker.x + 3, ticker.y + 3, PRESSED);
+    }
+    layer
+}
+
+// the title and as many items as fit under it, scrolled to keep the
+// selected one in view and drawn inverted
+pub fn menu(window: (i32, i32), menu: &Menu) -> UiLayer {
+    const LINE: i32 = 5 * TEXT_SCALE + 6;
+    let (width, height) = window;
+    let mut layer = vec![(
+        UiRect {
+            x: 0,
+            y: 0,
+            w: width,
+            h: height,
+        },
+        0,
+    )];
+    let columns = ((width - 2 * MARGIN) / (4 * TEXT_SCALE)).max(0) as usize;
+    let fit = |text: &str| text.chars().take(columns).collect::<String>();
+
+    text(&mut layer, &fit(&menu.title), MARGIN, MARGIN, BORDER);
+    let top = MARGIN + 2 * LINE;
+    if menu.items.is_empty() {
+        text(&mut layer, &fit(&menu.empty), MARGIN, top, PRESSED);
+        return layer;
+    }
+
+    let visible = ((height - top) / LINE).max(1) as usize;
+    let first = menu
+        .selected
+        .saturating_sub(visible / 2)
+        .min(menu.items.len().saturating_sub(visible));
+    for (row, item) in menu.items.iter().enumerate().skip(first).take(visible) {
+        let y = top + (row - first) as i32 * LINE;
+        let gray = if row == menu.selected {
+            let bar = UiRect {
+                x: MARGIN - 3,
+                y: y - 3,
+                w: width - 2 * MARGIN + 6,
+                h: LINE,
+            };
+            layer.push((bar, PRESSED));
+            0
+        } else {
+            PRESSED
+        };
+        text(&mut layer, &fit(item), MARGIN, y, gray);
+    }
+    layer
+}
+
+// "CHIP-8 - Tetris by Fran Dachille - paused - 60 fps 700 ips". rom is the
+// database byline or the file name, rates (fps, ips) are left out until the
+// frontend measured them
+pub fn window_title(rom: Option<&str>, state: RunState, rates: Option<(f64, f64)>) -> String {
+    let mut title = "CHIP-8".to_owned();
+    if let Some(rom) = rom {
+        title.push_str(" - ");
+        title.push_str(rom);
+    }
+    title.push_str(" - ");
+    title.push_str(run_state_label(state));
+    if let Some((fps, ips)) = rates {
+        title.push_str(&format!(" - {fps:.0} fps {ips:.0} ips"));
+    }
+    title
+}
+
+pub fn run_state_label(state: RunState) -> &'static str {
+    match state {
+        RunState::Running => "running",
+        RunState::Halted => "finished",
+        RunState::WaitingForKey { .. } => "waiting for key",
+        RunState::Paused => "paused",
+    }
+}
+
+// the lines of debug_hud
+pub fn debug_hud_lines(hud: &DebugHud) -> Vec<String> {
+    let cpu = &hud.cpu;
+    let [high, low] = cpu.opcode.to_be_bytes();
+    let registers = |range: std::ops::Range<usize>| {
+        range
+            .map(|n| format!("V{n:X} {:02X}", cpu.v[n]))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    vec![
+        format!(
+            "PC {:03X}  {:04X} {}",
+            cpu.pc,
+            cpu.opcode,
+            disasm::mnemonic(&Instruction::new(high, low))
+        ),
+        format!("I {:03X}  STACK {}", cpu.i, cpu.stack_depth),
+        registers(0..4),
+        registers(4..8),
+        registers(8..12),
+        registers(12..16),
+        format!("DT {:02X}  ST {:02X}", cpu.delay_timer, cpu.sound_timer),
+        format!(
+            "IPS {:.0}  FPS {:.0}",
+            hud.instructions_per_second, hud.frames_per_second
+        ),
+    ]
+}
+
+// a dark box in the top left corner with debug_hud_lines
+pub fn debug_hud(hud: &DebugHud) -> UiLayer {
+    const LINE: i32 = 5 * TEXT_SCALE + 6;
+    let lines = debug_hud_lines(hud);
+    let columns = lines.iter().map(|line| line.len()).max().unwrap_or(0) as i32;
+    let panel = UiRect {
+        x: MARGIN,
+        y: MARGIN,
+        w: columns * 4 * TEXT_SCALE + 6,
+        h: lines.len() as i32 * LINE + 3,
+    };
+    let mut layer = vec![(panel, KEY)];
+    outline(&mut layer, panel, BORDER);
+    for (row, line) in lines.iter().enumerate() {
+        let y = panel.y + 4 + row as i32 * LINE;
+        text(&mut layer, line, panel.x + 4, y, PRESSED);
+    }
+    layer
+}
+
+// a hex view in the top right corner, bytes written lately on a lighter
+// background fading back with the heat and the centered address outlined
+pub fn memory_view(window_width: i32, view: &MemoryView) -> UiLayer {
+    const LINE: i32 = 5 * TEXT_SCALE + 6;
+    const CHAR: i32 = 4 * TEXT_SCALE;
+    // "200  A2 2A 60 ..."
+    const FIRST_BYTE: i32 = 5;
+    let rows = view.bytes.len().div_ceil(MEMORY_VIEW_COLUMNS) as i32;
+    let columns = FIRST_BYTE + MEMORY_VIEW_COLUMNS as i32 * 3 - 1;
+    let w = columns * CHAR + 6;
+    let panel = UiRect {
+        x: window_width - MARGIN - w,
+        y: MARGIN,
+        w,
+        h: (rows + 1) * LINE + 3,
+    };
+    let mut layer = vec![(panel, KEY)];
+    outline(&mut layer, panel, BORDER);
+    let title = format!("{} {:03X}", view.center.name(), view.addr);
+    text(&mut layer, &title, panel.x + 4, panel.y + 4, BORDER);
+
+    for (row, bytes) in view.bytes.chunks(MEMORY_VIEW_COLUMNS).enumerate() {
+        let addr = view.start as usize + row * MEMORY_VIEW_COLUMNS;
+        let y = panel.y + 4 + (row as i32 + 1) * LINE;
+        text(&mut layer, &format!("{addr:03X}"), panel.x + 4, y, BORDER);
+        for (column, byte) in bytes.iter().enumerate() {
+            let offset = row * MEMORY_VIEW_COLUMNS + column;
+            let x = panel.x + 4 + (FIRST_BYTE + column as i32 * 3) * CHAR;
+            let cell = UiRect {
+                x: x - TEXT_SCALE,
+                y: y - TEXT_SCALE,
+                w: 2 * CHAR + TEXT_SCALE,
+                h: LINE - 2,
+            };
+            let heat = view.written[offset];
+            if heat > 0 {
+                let gray = KEY + ((BORDER - KEY) as u32 * heat as u32 / 255) as u8;
+                layer.push((cell, gray));
+            }
+            if addr + column == view.addr as usize {
+                outline(&mut layer, cell, PRESSED);
+            }
+            text(&mut layer, &format!("{byte:02X}"), x, y, PRESSED);
+        }
+    }
+    layer
+}
+
+// lines of debugger_panel: the header, four of registers, the stack, a gap,
+// the code, another gap and the memory
+#[cfg(feature = "debugger")]
+const DEBUGGER_LINES: usize = 8 + DEBUGGER_CODE_ROWS + DEBUGGER_MEMORY_ROWS;
+#[cfg(feature = "debugger")]
+const DEBUGGER_COLUMNS: usize = 28;
+// return addresses the stack line has room for
+#[cfg(feature = "debugger")]
+const DEBUGGER_CALLS: usize = 5;
+
+// the lines of debugger_panel above its buttons, DEBUGGER_LINES of them
+#[cfg(feature = "debugger")]
+pub fn debugger_panel_lines(view: &DebuggerView) -> Vec<String> {
+    let cpu = &view.cpu;
+    let mut lines = vec![format!(
+        "PC {:03X}  I {:03X}  DT {:02X} ST {:02X}",
+        cpu.pc, cpu.i, cpu.delay_timer, cpu.sound_timer
+    )];
+    for row in 0..4 {
+        let registers: Vec<String> = (row * 4..row * 4 + 4)
+            .map(|n| format!("V{n:X} {:02X}", cpu.v[n]))
+            .collect();
+        lines.push(registers.join(" "));
+    }
+    let calls: Vec<String> = view
+        .stack
+        .iter()
+        .rev()
+        .take(DEBUGGER_CALLS)
+        .map(|addr| format!("{addr:03X}"))
+        .collect();
+    lines.push(match view.stack.len() {
+        0 => "STACK -".to_owned(),
+        len if len > DEBUGGER_CALLS => format!("STACK {} ...", calls.join(" ")),
+        _ => format!("STACK {}", calls.join(" ")),
+    });
+    lines.push(String::new());
+    for (addr, opcode) in &view.code {
+        let [high, low] = opcode.to_be_bytes();
+        let mnemonic = disasm::mnemonic(&Instruction::new(high, low));
+        let line = format!("{addr:03X}  {opcode:04X}  {mnemonic}");
+        lines.push(line.chars().take(DEBUGGER_COLUMNS).collect());
+    }
+    lines.push(String::new());
+    for (row, bytes) in view.memory.chunks(DEBUGGER_MEMORY_COLUMNS).enumerate() {
+        let addr = view.memory_start as usize + row * DEBUGGER_MEMORY_COLUMNS;
+        let bytes: Vec<String> = bytes.iter().map(|byte| format!("{byte:02X}")).collect();
+        lines.push(format!("{addr:03X}  {}", bytes.join(" ")));
+    }
+    lines
+}
+
+#[cfg(feature = "debugger")]
+fn debugger_rect(window_width: i32) -> UiRect {
+    const LINE: i32 = 5 * TEXT_SCALE + 6;
+    let w = DEBUGGER_COLUMNS as i32 * 4 * TEXT_SCALE + 8;
+    UiRect {
+        x: window_width - MARGIN - w,
+        y: MARGIN,
+        w,
+        h: (DEBUGGER_LINES as i32 + 1) * LINE + 12,
+    }
+}
+
+// the buttons along the bottom of debugger_panel, with the hotkey a click
+// on each one stands for and its label
+#[cfg(feature = "debugger")]
+pub fn debugger_buttons(window_width: i32, paused: bool) -> [(UiRect, Hotkey, &'static str); 3] {
+    const LINE: i32 = 5 * TEXT_SCALE + 6;
+    const GAP: i32 = 4;
+    let panel = debugger_rect(window_width);
+    let w = (panel.w - 8 - 2 * GAP) / 3;
+    let button = |n: i32| UiRect {
+        x: panel.x + 4 + n * (w + GAP),
+        y: panel.y + 4 + DEBUGGER_LINES as i32 * LINE + 2,
+        w,
+        h: LINE + 2,
+    };
+    [
+        (button(0), Hotkey::StepInstruction, "STEP"),
+        (
+            button(1),
+            Hotkey::TogglePause,
+            if paused { "RUN" } else { "PAUSE" },
+        ),
+        (button(2), Hotkey::Reset, "RESET"),
+    ]
+}
+
+// what a click at x, y in the window hits, if anything
+#[cfg(feature = "debugger")]
+pub fn debugger_button_at(window_width: i32, paused: bool, x: i32, y: i32) -> Option<Hotkey> {
+    debugger_buttons(window_width, paused)
+        .into_iter()
+        .find(|(rect, _, _)| {
+            (rect.x..rect.x + rect.w).contains(&x) && (rect.y..rect.y + rect.h).contains(&y)
+        })
+        .map(|(_, hotkey, _)| hotkey)
+}
+
+// a panel down the right side with debugger_panel_lines, the instruction
+// at pc inverted, the byte at I outlined and the buttons underneath
+#[cfg(feature = "debugger")]
+pub fn debugger_panel(window_width: i32, view: &DebuggerView) -> UiLayer {
+    const LINE: i32 = 5 * TEXT_SCALE + 6;
+    const CHAR: i32 = 4 * TEXT_SCALE;
+    const FIRST_CODE: usize = 7;
+    const FIRST_MEMORY: usize = FIRST_CODE + DEBUGGER_CODE_ROWS + 1;
+    let panel = debugger_rect(window_width);
+    let mut layer = vec![(panel, KEY)];
+    outline(&mut layer, panel, BORDER);
+
+    let pc_row = view.code.iter().position(|(addr, _)| *addr == view.cpu.pc);
+    for (row, line) in debugger_panel_lines(view).iter().enumerate() {
+        let y = panel.y + 4 + row as i32 * LINE;
+        let gray = if pc_row.is_some_and(|pc_row| row == FIRST_CODE + pc_row) {
+            let bar = UiRect {
+                x: panel.x + 2,
+                y: y - 3,
+                w: panel.w - 4,
+                h: LINE,
+            };
+            layer.push((bar, PRESSED));
+            0
+        } else if (FIRST_CODE..FIRST_MEMORY).contains(&row) {
+            BORDER
+        } else {
+            PRESSED
+        };
+        text(&mut layer, line, panel.x + 4, y, gray);
+    }
+
+    let at = (view.cpu.i as usize).checked_sub(view.memory_start as usize);
+    if let Some(at) = at.filter(|at| *at < view.memory.len()) {
+        let row = (FIRST_MEMORY + at / DEBUGGER_MEMORY_COLUMNS) as i32;
+        // "2A0  A2 2A 60 ..."
+        let column = 5 + (at % DEBUGGER_MEMORY_COLUMNS) as i32 * 3;
+        let cell = UiRect {
+            x: panel.x + 4 + column * CHAR - TEXT_SCALE,
+            y: panel.y + 4 + row * LINE - TEXT_SCALE,
+            w: 2 * CHAR + TEXT_SCALE,
+            h: LINE - 2,
+        };
+        outline(&mut layer, cell, PRESSED);
+    }
+
+    for (rect, _, label) in debugger_buttons(window_width, view.paused) {
+        layer.push((rect, BORDER));
+        outline(&mut layer, rect, PRESSED);
+        let x = rect.x + (rect.w - label.len() as i32 * CHAR) / 2;
+        text(&mut layer, label, x, rect.y + 4, PRESSED);
+    }
+    layer
+}
+
+// the window split for the virtual keypad: the rect the display letterboxes
+// into and the square of the keypad, beside the display in a landscape
+// window and below it in a portrait one
+pub fn virtual_keypad_split(window: (i32, i32)) -> (UiRect, UiRect) {
+    let (width, height) = (window.0.max(1), window.1.max(1));
+    if width >= height {
+        let side = height.min(width / 3);
+        let display = UiRect {
+            x: 0,
+            y: 0,
+            w: width - side,
+            h: height,
+        };
+        let pad = UiRect {
+            x: width - side,
+            y: (height - side) / 2,
+            w: side,
+            h: side,
+        };
+        (display, pad)
+    } else {
+        let side = width.min(height / 2);
+        let display = UiRect {
+            x: 0,
+            y: 0,
+            w: width,
+            h: height - side,
+        };
+        let pad = UiRect {
+            x: (width - side) / 2,
+            y: height - side,
+            w: side,
+            h: side,
+        };
+        (display, pad)
+    }
+}
+
+// the 16 buttons of the virtual keypad in KEYPAD_ROWS order, with the chip8
+// key each presses
+pub fn virtual_keys(window: (i32, i32)) -> Vec<(UiRect, u8)> {
+    let (_, pad) = virtual_keypad_split(window);
+    let cell = pad.w / 4;
+    let mut keys = Vec::new();
+    for (row, row_keys) in KEYPAD_ROWS.iter().enumerate() {
+        for (col, key) in row_keys.iter().enumerate() {
+            let rect = UiRect {
+                x: pad.x + col as i32 * cell + 2,
+                y: pad.y + row as i32 * cell + 2,
+                w: cell - 4,
+                h: cell - 4,
+            };
+            keys.push((rect, *key));
+        }
+    }
+    keys
+}
+
+// the chip8 key under a click or touch at x, y
+pub fn virtual_key_at(window: (i32, i32), x: i32, y: i32) -> Option<u8> {
+    virtual_keys(window)
+        .into_iter()
+        .find(|(rect, _)| {
+            (rect.x..rect.x + rect.w).contains(&x) && (rect.y..rect.y + rect.h).contains(&y)
+        })
+        .map(|(_, key)| key)
+}
+
+// the buttons with their hex digit as large as fits, pressed ones lit
+pub fn virtual_keypad(window: (i32, i32), keypad: &[bool; 16]) -> UiLayer {
+    let mut layer = UiLayer::new();
+    for (rect, key) in virtual_keys(window) {
+        let (fg, bg) = if keypad[key as usize] {
+            (0, PRESSED)
+        } else {
+            (PRESSED, KEY)
+        };
+        layer.push((rect, bg));
+        outline(&mut layer, rect, BORDER);
+        let scale = (rect.h / 10).max(1);
+        let hex = char::from_digit(key as u32, 16).unwrap_or(' ');
+        let (x, y) = (
+            rect.x + (rect.w - 3 * scale) / 2,
+            rect.y + (rect.h - 5 * scale) / 2,
+        );
+        scaled_text(&mut layer, &hex.to_string(), x, y, scale, fg);
+    }
+    layer
+}
+
+// window pixels a display pixel needs before the grid leaves it room
+pub const GRID_MIN_SCALE: f32 = 4.0;
+const GRID: u8 = 64;
+const SPRITE_BOX: u8 = 160;
+
+// lines between the display pixels in viewport, the display's width by
+// height, and an outline around the pixels of the last sprite, doubled if it
+// collided. the sprite's part past the edges isn't outlined
+pub fn pixel_grid(
+    viewport: &Viewport,
+    display: (usize, usize),
+    sprite: Option<&SpriteBox>,
+) -> UiLayer {
+    let mut layer = UiLayer::new();
+    // the same edges the display pixels have, fractional scales included
+    let edge = |n: usize, origin: f32| (origin + n as f32 * viewport.scale) as i32;
+    let (left, top) = (viewport.x as i32, viewport.y as i32);
+    let (right, bottom) = (edge(display.0, viewport.x), edge(display.1, viewport.y));
+    if viewport.scale >= GRID_MIN_SCALE {
+        for x in 1..display.0 {
+            let rect = UiRect {
+                x: edge(x, viewport.x),
+                y: top,
+                w: 1,
+                h: bottom - top,
+            };
+            layer.push((rect, GRID));
+        }
+        for y in 1..display.1 {
+            let rect = UiRect {
+                x: left,
+                y: edge(y, viewport.y),
+                w: right - left,
+                h: 1,
+            };
+            layer.push((rect, GRID));
+        }
+    }
+    if let Some(sprite) = sprite.filter(|sprite| sprite.x < display.0 && sprite.y < display.1) {
+        let (x0, y0) = (edge(sprite.x, viewport.x), edge(sprite.y, viewport.y));
+        let x1 = edge((sprite.x + sprite.width).min(display.0), viewport.x);
+        let y1 = edge((sprite.y + sprite.height).min(display.1), viewport.y);
+        let rect = UiRect {
+            x: x0,
+            y: y0,
+            w: (x1 - x0).max(1),
+            h: (y1 - y0).max(1),
+        };
+        outline(&mut layer, rect, SPRITE_BOX);
+        if sprite.collided && rect.w > 2 && rect.h > 2 {
+            let inner = UiRect {
+                x: rect.x + 1,
+                y: rect.y + 1,
+                w: rect.w - 2,
+                h: rect.h - 2,
+            };
+            outline(&mut layer, inner, PRESSED);
+        }
+    }
+    layer
+}
+
+fn outline(layer: &mut UiLayer, rect: UiRect, gray: u8) {
+    let UiRect { x, y, w, h } = rect;
+    for edge in [
+        UiRect { x, y, w, h: 1 },
+        UiRect {
+            x,
+            y: y + h - 1,
+            w,
+            h: 1,
+        },
+        UiRect { x, y, w: 1, h },
+        UiRect {
+            x: x + w - 1,
+            y,
+            w: 1,
+            h,
+        },
+    ] {
+        layer.push((edge, gray));
+    }
+}
+
+// glyphs at TEXT_SCALE with a glyph pixel between characters
+fn text(layer: &mut UiLayer, text: &str, x: i32, y: i32, gray: u8) {
+    scaled_text(layer, text, x, y, TEXT_SCALE, gray);
+}
+
+fn scaled_text(layer: &mut UiLayer, text: &str, x: i32, y: i32, scale: i32, gray: u8) {
+    for (n, c) in text.chars().enumerate() {
+        let left = x + n as i32 * 4 * scale;
+        let Some(rows) = glyph(c) else {
+            continue;
+        };
+        for (dy, bits) in rows.iter().enumerate() {
+            for dx in 0..3 {
+                if bits & (0b100 >> dx) != 0 {
+                    let pixel = UiRect {
+                        x: left + dx * scale,
+                        y: y + dy as i32 * scale,
+                        w: scale,
+                        h: scale,
+                    };
+                    layer.push((pixel, gray));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+// breakpoints on instruction addresses, each with what a hit does: pause the
+// cpu, log the registers and go on, or only count. log and count are printf
+// style instrumentation without touching the rom
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::trace::Registers;
+use core::fmt::Write;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BreakAction {
+    #[default]
+    Pause,
+    Log,
+    Count,
+}
+
+impl BreakAction {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "pause" => Some(BreakAction::Pause),
+            "log" => Some(BreakAction::Log),
+            "count" => Some(BreakAction::Count),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Breakpoint {
+    pub addr: u16,
+    pub action: BreakAction,
+    pub hits: u64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Breakpoints {
+    entries: Vec<Breakpoint>,
+    // messages of Log hits until take_log
+    log: Vec<String>,
+}
+
+impl Breakpoints {
+    pub fn new() -> Self {
+        Breakpoints::default()
+    }
+
+    // replaces the action of an existing breakpoint, keeping its hits
+    pub fn add(&mut self, addr: u16, action: BreakAction) {
+        match self.entries.iter_mut().find(|b| b.addr == addr) {
+            Some(breakpoint) => breakpoint.action = action,
+            None => self.entries.push(Breakpoint {
+                addr,
+                action,
+                hits: 0,
+            }),
+        }
+    }
+
+    pub fn remove(&mut self, addr: u16) {
+        self.entries.retain(|b| b.addr != addr);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, addr: u16) -> Option<&Breakpoint> {
+        self.entries.iter().find(|b| b.addr == addr)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Breakpoint> {
+        self.entries.iter()
+    }
+
+    pub fn take_log(&mut self) -> Vec<String> {
+        core::mem::take(&mut self.log)
+    }
+
+    // the action of the breakpoint at pc after counting the hit, None without one
+    pub fn hit(&mut self, pc: u16, registers: &Registers) -> Option<BreakAction> {
+        let breakpoint = self.entries.iter_mut().find(|b| b.addr == pc)?;
+        breakpoint.hits += 1;
+        if breakpoint.action == BreakAction::Log {
+            let mut message = format!("break {pc:03X} hit {}:", breakpoint.hits);
+            for (n, v) in registers.v.iter().enumerate() {
+                let _ = write!(message, " V{n:X}={v:02X}");
+            }
+            let _ = write!(message, " I={:03X}", registers.i);
+            self.log.push(message);
+        }
+        Some(breakpoint.action)
+    }
+}
+
+// "2A0" pauses, "2A0:log" and "2A0:count" don't
+pub fn parse(text: &str) -> Option<(u16, BreakAction)> {
+    let (addr, action) = match text.split_once(':') {
+        Some((addr, action)) => (addr, BreakAction::parse(action)?),
+        None => (text, BreakAction::Pause),
+    };
+    let addr = addr
+        .strip_prefix("0x")
+        .or_else(|| addr.strip_prefix("0X"))
+        .unwrap_or(addr);
+    Some((u16::from_str_radix(addr, 16).ok()?, action))
+}
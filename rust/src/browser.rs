@@ -0,0 +1,95 @@
+// the rom list shown when no rom was given on the command line, recently
+// loaded roms first and then the roms in the directory
+use crate::rom::is_rom_file;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_ROM_DIR: &str = "roms";
+
+pub struct RomBrowser {
+    dir: PathBuf,
+    // newest first, whatever of RecentRoms still exists
+    recent: Vec<PathBuf>,
+    // sorted by file name
+    roms: Vec<PathBuf>,
+    // into recent followed by roms
+    selected: usize,
+}
+
+impl RomBrowser {
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let mut browser = RomBrowser {
+            dir: dir.into(),
+            recent: Vec::new(),
+            roms: Vec::new(),
+            selected: 0,
+        };
+        browser.rescan()?;
+        Ok(browser)
+    }
+
+    // reads the directory again, the selection stays on the same rom if it's
+    // still there
+    pub fn rescan(&mut self) -> io::Result<()> {
+        let selected = self.selected().map(Path::to_path_buf);
+        let mut roms = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if is_rom_file(&path) && path.is_file() {
+                roms.push(path);
+            }
+        }
+        roms.sort_by_key(|path| path.file_name().map(|name| name.to_ascii_lowercase()));
+        self.roms = roms;
+        self.recent.retain(|rom| rom.is_file());
+        self.selected = selected
+            .and_then(|rom| self.entries().position(|entry| *entry == rom))
+            .unwrap_or(0);
+        Ok(())
+    }
+
+    // selects the newest one, the rom loaded last
+    pub fn set_recent(&mut self, recent: &[PathBuf]) {
+        self.recent = recent.iter().filter(|rom| rom.is_file()).cloned().collect();
+        self.selected = 0;
+    }
+
+    fn entries(&self) -> impl Iterator<Item = &PathBuf> {
+        self.recent.iter().chain(&self.roms)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let name = |rom: &PathBuf| {
+            rom.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        };
+        let recent = self
+            .recent
+            .iter()
+            .map(|rom| format!("recent: {}", name(rom)));
+        recent.chain(self.roms.iter().map(name)).collect()
+    }
+
+    pub fn selected_index(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected(&self) -> Option<&Path> {
+        self.entries().nth(self.selected).map(PathBuf::as_path)
+    }
+
+    // wraps around at both ends
+    pub fn move_selection(&mut self, by: isize) {
+        let len = (self.recent.len() + self.roms.len()) as isize;
+        if len > 0 {
+            self.selected = (self.selected as isize + by).rem_euclid(len) as usize;
+        }
+    }
+}
@@ -0,0 +1,31 @@
+// roms bundled in the binary, so --builtin NAME runs without a rom file
+// around. each one is assembled from the source next to it in builtin/ with
+// chip8 asm, and all of them were written for this emulator and are public
+// domain
+pub struct BuiltinRom {
+    pub name: &'static str,
+    pub about: &'static str,
+    pub bytes: &'static [u8],
+}
+
+pub const BUILTIN_ROMS: &[BuiltinRom] = &[
+    BuiltinRom {
+        name: "ibm",
+        about: "the letters IBM, the customary first rom",
+        bytes: include_bytes!("builtin/ibm.ch8"),
+    },
+    BuiltinRom {
+        name: "opcodes",
+        about: "a tick or a cross for each opcode it checks",
+        bytes: include_bytes!("builtin/opcodes.ch8"),
+    },
+    BuiltinRom {
+        name: "catch",
+        about: "catch the falling dots, 4 and 6 move the paddle",
+        bytes: include_bytes!("builtin/catch.ch8"),
+    },
+];
+
+pub fn builtin_rom(name: &str) -> Option<&'static BuiltinRom> {
+    BUILTIN_ROMS.iter().find(|rom| rom.name == name)
+}
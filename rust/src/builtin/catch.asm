@@ -0,0 +1,64 @@
+; catch the falling dots with the paddle, 4 and 6 move it. a beep for
+; every catch. written for this emulator and placed in the public domain
+;
+; V0, V1 the paddle, V2, V3 the dot
+        LD V0, 28
+        LD V1, 30
+        LD I, paddle
+        DRW V0, V1, 1
+drop:   RND V2, 0x3F
+        LD V3, 0
+        LD I, dot
+        DRW V2, V3, 1
+
+        ; three timer ticks a step
+loop:   LD V5, 3
+        LD DT, V5
+wait:   LD V5, DT
+        SE V5, 0
+        JP wait
+
+        LD V4, 4
+        SKNP V4
+        CALL left
+        LD V4, 6
+        SKNP V4
+        CALL right
+
+        ; a dot that lands on the paddle collides with it
+        LD I, dot
+        DRW V2, V3, 1
+        ADD V3, 1
+        DRW V2, V3, 1
+        SE VF, 0
+        JP caught
+        SE V3, 30
+        JP loop
+        ; missed, the paddle row is the last one the dot gets to
+        DRW V2, V3, 1
+        JP drop
+
+        ; drawing the dot again puts the paddle back together
+caught: DRW V2, V3, 1
+        LD V5, 4
+        LD ST, V5
+        JP drop
+
+left:   SNE V0, 0
+        RET
+        LD I, paddle
+        DRW V0, V1, 1
+        ADD V0, 0xFE
+        DRW V0, V1, 1
+        RET
+
+right:  SNE V0, 56
+        RET
+        LD I, paddle
+        DRW V0, V1, 1
+        ADD V0, 2
+        DRW V0, V1, 1
+        RET
+
+paddle: DB 0xFF
+dot:    DB 0x80
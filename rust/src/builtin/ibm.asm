@@ -0,0 +1,35 @@
+; the letters IBM in striped 16 by 15 blocks, each drawn as a left and a
+; right half. a stand in for the well known logo rom, written for this
+; emulator and placed in the public domain
+        LD V1, 8
+        LD V0, 4
+        LD I, i_left
+        DRW V0, V1, 15
+        LD V0, 12
+        LD I, i_right
+        DRW V0, V1, 15
+        LD V0, 24
+        LD I, b_left
+        DRW V0, V1, 15
+        LD V0, 32
+        LD I, b_right
+        DRW V0, V1, 15
+        LD V0, 44
+        LD I, m_left
+        DRW V0, V1, 15
+        LD V0, 52
+        LD I, m_right
+        DRW V0, V1, 15
+done:   JP done
+i_left: DB 0b11111111, 0b00000000, 0b11111111, 0b00000000, 0b00001111, 0b00000000, 0b00001111, 0b00000000
+        DB 0b00001111, 0b00000000, 0b00001111, 0b00000000, 0b11111111, 0b00000000, 0b11111111
+i_right: DB 0b11111111, 0b00000000, 0b11111111, 0b00000000, 0b11110000, 0b00000000, 0b11110000, 0b00000000
+        DB 0b11110000, 0b00000000, 0b11110000, 0b00000000, 0b11111111, 0b00000000, 0b11111111
+b_left: DB 0b11111111, 0b00000000, 0b11110000, 0b00000000, 0b11110000, 0b00000000, 0b11111111, 0b00000000
+        DB 0b11110000, 0b00000000, 0b11110000, 0b00000000, 0b11110000, 0b00000000, 0b11111111
+b_right: DB 0b11111100, 0b00000000, 0b00011111, 0b00000000, 0b00011111, 0b00000000, 0b11111100, 0b00000000
+        DB 0b00011111, 0b00000000, 0b00011111, 0b00000000, 0b00011111, 0b00000000, 0b11111100
+m_left: DB 0b11111000, 0b00000000, 0b11111100, 0b00000000, 0b11111110, 0b00000000, 0b11110111, 0b00000000
+        DB 0b11110011, 0b00000000, 0b11110001, 0b00000000, 0b11110000, 0b00000000, 0b11110000
+m_right: DB 0b00011111, 0b00000000, 0b00111111, 0b00000000, 0b01111111, 0b00000000, 0b11101111, 0b00000000
+        DB 0b11001111, 0b00000000, 0b10001111, 0b00000000, 0b00001111, 0b00000000, 0b00001111
@@ -0,0 +1,218 @@
+; one mark per check, a tick when it passed and a cross when it didn't,
+; eight a row from the top left:
+;
+;     00E0  3XNN  4XNN  5XY0  9XY0  7XNN  8XY1  8XY2
+;     8XY3  8XY4  8XY5  8XY7  8XY6  8XYE  2NNN  BNNN
+;     FX1E  FX33  FX55  FX07  FX29
+;
+; every check leaves VA at 1 when it passed. VB and VC place the next mark.
+; written for this emulator and placed in the public domain
+        LD VB, 0
+        LD VC, 0
+        ; a pixel drawn twice around a clear doesn't collide
+        LD V2, 0
+        LD I, dot
+        DRW V2, V2, 1
+        CLS
+        DRW V2, V2, 1
+        LD VA, 1
+        SE VF, 0
+        LD VA, 0
+        ; the pixel is still on when the clear worked
+        SNE VA, 1
+        DRW V2, V2, 1
+        CALL mark
+
+        LD VA, 1
+        LD V2, 5
+        SE V2, 5
+        LD VA, 0
+        CALL mark
+
+        LD VA, 1
+        SNE V2, 6
+        LD VA, 0
+        CALL mark
+
+        LD VA, 1
+        LD V3, 5
+        SE V2, V3
+        LD VA, 0
+        CALL mark
+
+        LD VA, 1
+        LD V3, 6
+        SNE V2, V3
+        LD VA, 0
+        CALL mark
+
+        LD VA, 1
+        ADD V2, 3
+        SE V2, 8
+        LD VA, 0
+        CALL mark
+
+        LD VA, 1
+        LD V2, 0x0C
+        LD V3, 0x03
+        OR V2, V3
+        SE V2, 0x0F
+        LD VA, 0
+        CALL mark
+
+        LD VA, 1
+        LD V2, 0x0E
+        AND V2, V3
+        SE V2, 0x02
+        LD VA, 0
+        CALL mark
+
+        LD VA, 1
+        LD V2, 0x0F
+        LD V3, 0x05
+        XOR V2, V3
+        SE V2, 0x0A
+        LD VA, 0
+        CALL mark
+
+        ; 255 + 2 carries
+        LD VA, 1
+        LD V2, 0xFF
+        LD V3, 2
+        ADD V2, V3
+        SE V2, 1
+        LD VA, 0
+        SE VF, 1
+        LD VA, 0
+        CALL mark
+
+        ; 5 - 7 borrows
+        LD VA, 1
+        LD V2, 5
+        LD V3, 7
+        SUB V2, V3
+        SE V2, 0xFE
+        LD VA, 0
+        SE VF, 0
+        LD VA, 0
+        CALL mark
+
+        LD VA, 1
+        LD V2, 5
+        SUBN V2, V3
+        SE V2, 2
+        LD VA, 0
+        SE VF, 1
+        LD VA, 0
+        CALL mark
+
+        ; VY equals VX, so both shift quirks agree
+        LD VA, 1
+        LD V2, 5
+        LD V3, 5
+        SHR V2, V3
+        SE V2, 2
+        LD VA, 0
+        SE VF, 1
+        LD VA, 0
+        CALL mark
+
+        LD VA, 1
+        LD V2, 0x81
+        LD V3, 0x81
+        SHL V2, V3
+        SE V2, 2
+        LD VA, 0
+        SE VF, 1
+        LD VA, 0
+        CALL mark
+
+        LD VA, 0
+        CALL set_va
+        CALL mark
+
+        ; V0 and V2 match, so BXNN lands in the same place
+        LD VA, 0
+        LD V0, 2
+        LD V2, 2
+        JP V0, jumped
+jumped: JP landed
+        LD VA, 1
+landed: CALL mark
+
+        LD VA, 1
+        LD I, data
+        LD V2, 1
+        ADD I, V2
+        LD V0, [I]
+        SE V0, 0x22
+        LD VA, 0
+        CALL mark
+
+        LD VA, 1
+        LD V2, 137
+        LD I, scratch
+        LD B, V2
+        LD V2, [I]
+        SE V0, 1
+        LD VA, 0
+        SE V1, 3
+        LD VA, 0
+        SE V2, 7
+        LD VA, 0
+        CALL mark
+
+        LD VA, 1
+        LD V0, 0xA5
+        LD V1, 0x5A
+        LD I, scratch
+        LD [I], V1
+        LD V0, 0
+        LD V1, 0
+        LD I, scratch
+        LD V1, [I]
+        SE V0, 0xA5
+        LD VA, 0
+        SE V1, 0x5A
+        LD VA, 0
+        CALL mark
+
+        LD VA, 1
+        LD V2, 10
+        LD DT, V2
+        LD V3, DT
+        SNE V3, 0
+        LD VA, 0
+        CALL mark
+
+        ; the font's 0 starts with a full row
+        LD VA, 1
+        LD V2, 0
+        LD F, V2
+        LD V0, [I]
+        SE V0, 0xF0
+        LD VA, 0
+        CALL mark
+
+done:   JP done
+
+set_va: LD VA, 1
+        RET
+
+; draws a tick or a cross at VB, VC and moves along
+mark:   LD I, cross
+        SNE VA, 1
+        LD I, tick
+        DRW VB, VC, 5
+        ADD VB, 8
+        SE VB, 64
+        RET
+        LD VB, 0
+        ADD VC, 7
+        RET
+
+tick:   DB 0x01, 0x02, 0x84, 0x48, 0x30
+cross:  DB 0x88, 0x50, 0x20, 0x50, 0x88
+dot:    DB 0x80
+data:   DB 0x11, 0x22
+scratch: DB 0, 0, 0
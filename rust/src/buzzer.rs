@@ -0,0 +1,207 @@
+// the tone of the beep: waveform, pitch, volume, the duty cycle of the square
+// and a fade in and out so starting and stopping don't click. --buzzer takes
+// the same settings comma separated, e.g. --buzzer wave=sine,volume=30, and
+// the [buzzer] section of the config file one a line:
+//
+//     [buzzer]
+//     wave = triangle
+//     frequency = 440
+//     volume = 50
+//     duty = 50
+//     attack = 5
+//     release = 20
+//
+// volume and duty are percent, attack and release milliseconds
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Waveform {
+    #[default]
+    Square,
+    Sine,
+    Triangle,
+}
+
+impl Waveform {
+    pub const NAMES: &[(&str, Waveform)] = &[
+        ("square", Waveform::Square),
+        ("sine", Waveform::Sine),
+        ("triangle", Waveform::Triangle),
+    ];
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Buzzer {
+    pub waveform: Waveform,
+    pub frequency: u32,
+    pub volume: u8,
+    // share of the square's period spent high
+    pub duty: u8,
+    pub attack: Duration,
+    pub release: Duration,
+}
+
+impl Default for Buzzer {
+    fn default() -> Self {
+        Buzzer {
+            waveform: Waveform::Square,
+            frequency: 440,
+            volume: 50,
+            duty: 50,
+            attack: Duration::from_millis(5),
+            release: Duration::from_millis(10),
+        }
+    }
+}
+
+impl Buzzer {
+    pub const SETTINGS: &[&str] = &["wave", "frequency", "volume", "duty", "attack", "release"];
+
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let (key, value) = (key.trim(), value.trim());
+        let number = |range: std::ops::RangeInclusive<u32>, what: &str| {
+            value
+                .parse()
+                .ok()
+                .filter(|number| range.contains(number))
+                .ok_or_else(|| {
+                    format!("{key} expects {what}, {} to {}", range.start(), range.end())
+                })
+        };
+        match key {
+            "wave" => {
+                let (_, waveform) = Waveform::NAMES
+                    .iter()
+                    .find(|(name, _)| *name == value)
+                    .ok_or_else(|| {
+                        let names: Vec<_> = Waveform::NAMES.iter().map(|(name, _)| *name).collect();
+                        format!("wave expects one of {}", names.join(", "))
+                    })?;
+                self.waveform = *waveform;
+            }
+            "frequency" => self.frequency = number(20..=20_000, "hertz")?,
+            "volume" => self.volume = number(0..=100, "percent")? as u8,
+            "duty" => self.duty = number(1..=99, "percent")? as u8,
+            "attack" => {
+                self.attack = Duration::from_millis(number(0..=1000, "milliseconds")? as u64)
+            }
+            "release" => {
+                self.release = Duration::from_millis(number(0..=1000, "milliseconds")? as u64)
+            }
+            _ => {
+                return Err(format!(
+                    "unknown buzzer setting {key}, expected one of {}",
+                    Buzzer::SETTINGS.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // "wave=sine,volume=30" as --buzzer takes it
+    pub fn set_list(&mut self, list: &str) -> Result<(), String> {
+        for setting in list.split(',').filter(|setting| !setting.trim().is_empty()) {
+            let (key, value) = setting
+                .split_once('=')
+                .ok_or_else(|| format!("expected setting=value, got {setting}"))?;
+            self.set(key, value)?;
+        }
+        Ok(())
+    }
+
+    // "setting = value" lines of the [buzzer] section, the way
+    // Keymap::load_config skips the other sections
+    pub fn load_config(&mut self, text: &str) -> Result<(), String> {
+        let mut in_section = false;
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                in_section = name.trim() == "buzzer";
+                continue;
+            }
+            if !in_section {
+                continue;
+            }
+            line.split_once('=')
+                .ok_or_else(|| "expected setting = value".to_owned())
+                .and_then(|(key, value)| self.set(key, value))
+                .map_err(|err| format!("line {}: {err}", n + 1))?;
+        }
+        Ok(())
+    }
+
+    // -1 to 1 at phase 0 to 1 of a period, before the volume
+    pub fn level(&self, phase: f32) -> f32 {
+        match self.waveform {
+            Waveform::Square if phase < self.duty as f32 / 100.0 => 1.0,
+            Waveform::Square => -1.0,
+            Waveform::Sine => (phase * TAU).sin(),
+            Waveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+        }
+    }
+
+    // whole periods of unsigned 8 bit samples, about duration long, scaled
+    // by envelope(0 to 1 through them)
+    fn samples(
+        &self,
+        sample_rate: u32,
+        duration: Duration,
+        envelope: impl Fn(f32) -> f32,
+    ) -> Vec<u8> {
+        let period = (sample_rate / self.frequency).max(1) as usize;
+        let periods = (duration.as_secs_f32() * self.frequency as f32)
+            .ceil()
+            .max(1.0) as usize;
+        let len = period * periods;
+        let amplitude = 127.0 * self.volume as f32 / 100.0;
+        (0..len)
+            .map(|n| {
+                let level = self.level((n % period) as f32 / period as f32);
+                let scale = envelope(n as f32 / len as f32);
+                (128.0 + level * amplitude * scale).round() as u8
+            })
+            .collect()
+    }
+
+    // one period of the tone at full volume
+    pub fn period(&self, sample_rate: u32) -> Vec<u8> {
+        self.samples(sample_rate, Duration::ZERO, |_| 1.0)
+    }
+
+    // the tone fading in over attack, queued ahead of the periods
+    pub fn attack(&self, sample_rate: u32) -> Vec<u8> {
+        if self.attack.is_zero() {
+            return Vec::new();
+        }
+        self.samples(sample_rate, self.attack, |t| t)
+    }
+
+    // the tone fading out over release, in place of the periods cut off
+    pub fn release(&self, sample_rate: u32) -> Vec<u8> {
+        if self.release.is_zero() {
+            return Vec::new();
+        }
+        self.samples(sample_rate, self.release, |t| 1.0 - t)
+    }
+
+    // the volume factor since the beep started or, with stopped, since it
+    // stopped at level. for backends that ramp a looping sound instead of
+    // queueing samples
+    pub fn envelope(&self, since: Duration, stopped: Option<f32>) -> f32 {
+        let ramp = |length: Duration| {
+            if length.is_zero() {
+                1.0
+            } else {
+                (since.as_secs_f32() / length.as_secs_f32()).min(1.0)
+            }
+        };
+        match stopped {
+            None => ramp(self.attack),
+            Some(level) => level * (1.0 - ramp(self.release)),
+        }
+    }
+}
@@ -0,0 +1,145 @@
+// octo cartridges: gifs with the program and the ide options hidden in the
+// low two bits of every pixel, four pixels to a byte with the high bits
+// first. the payload is a big endian length and then json:
+//
+//     {"options": {"tickrate": 20, "fillColor": "#FFCC00", ...}, "program": ": main ..."}
+//
+// the program is octo source, running a cartridge still takes the .ch8 octo
+// exports for it, see program_path
+use crate::gif;
+use crate::json::{self, Value};
+use crate::palette::{Palette, Rgb};
+use crate::settings::{MemoryIncrement, Quirks};
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OctoOptions {
+    // instructions per 60Hz frame
+    pub tickrate: Option<u32>,
+    // background, fill, fill2 and blend, in palette order
+    pub colors: [Option<Rgb>; 4],
+    pub vblank: Option<bool>,
+    // sprites clip at the edges, octo wraps them otherwise
+    pub clip: Option<bool>,
+    // 8XY6 and 8XYE shift VX, see Quirks::shift_vx
+    pub shift: Option<bool>,
+    // FX55 and FX65 leave I alone, octo moves it past the registers otherwise
+    pub load_store: Option<bool>,
+    pub jump: Option<bool>,
+    // 8XY1, 8XY2 and 8XY3 clear VF
+    pub logic: Option<bool>,
+    // quirks the cartridge turns on that this interpreter doesn't have
+    pub unsupported: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cartridge {
+    pub options: OctoOptions,
+    pub source: String,
+}
+
+const COLOR_OPTIONS: [&str; 4] = ["backgroundColor", "fillColor", "fillColor2", "blendColor"];
+const UNSUPPORTED_QUIRKS: [&str; 1] = ["vfOrderQuirks"];
+
+pub fn is_cartridge(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gif"))
+}
+
+// the rom octo exports next to the cartridge, game.gif runs game.ch8
+pub fn program_path(cartridge: &Path) -> PathBuf {
+    cartridge.with_extension("ch8")
+}
+
+pub fn parse(gif: &[u8]) -> Result<Cartridge, String> {
+    let images = gif::decode(gif)?;
+    let bits: Vec<u8> = images
+        .iter()
+        .flat_map(|image| &image.pixels)
+        .map(|pixel| pixel & 0x03)
+        .collect();
+    let bytes: Vec<u8> = bits
+        .chunks_exact(4)
+        .map(|quad| quad.iter().fold(0, |byte, bits| byte << 2 | bits))
+        .collect();
+    let Some((len, payload)) = bytes.split_first_chunk::<4>() else {
+        return Err("no cartridge data in this gif".to_owned());
+    };
+    let payload = payload
+        .get(..u32::from_be_bytes(*len) as usize)
+        .ok_or_else(|| "no cartridge data in this gif".to_owned())?;
+    let text = std::str::from_utf8(payload).map_err(|_| "cartridge data isn't text".to_owned())?;
+    let root = json::parse(text).map_err(|err| format!("cartridge json: {err}"))?;
+
+    let source = root
+        .get("program")
+        .and_then(Value::as_str)
+        .ok_or_else(|| "cartridge has no program".to_owned())?
+        .to_owned();
+    let options = root.get("options").map(options).unwrap_or_default();
+    Ok(Cartridge { options, source })
+}
+
+fn options(json: &Value) -> OctoOptions {
+    let flag = |name: &str| json.get(name).and_then(Value::as_bool);
+    OctoOptions {
+        tickrate: json
+            .get("tickrate")
+            .and_then(Value::as_f64)
+            .filter(|rate| *rate >= 1.0)
+            .map(|rate| rate as u32),
+        colors: COLOR_OPTIONS
+            .map(|name| json.get(name).and_then(Value::as_str).and_then(Rgb::parse)),
+        vblank: flag("vBlankQuirks"),
+        clip: flag("clipQuirks"),
+        shift: flag("shiftQuirks"),
+        load_store: flag("loadStoreQuirks"),
+        jump: flag("jumpQuirks"),
+        logic: flag("logicQuirks"),
+        unsupported: UNSUPPORTED_QUIRKS
+            .iter()
+            .filter(|name| flag(name) == Some(true))
+            .map(|name| name.to_string())
+            .collect(),
+    }
+}
+
+impl OctoOptions {
+    pub fn apply(
+        &self,
+        quirks: &mut Quirks,
+        palette: &mut Palette,
+        instructions_per_second: &mut u32,
+    ) {
+        if let Some(tickrate) = self.tickrate {
+            *instructions_per_second = tickrate.saturating_mul(60);
+        }
+        for (color, option) in palette.colors.iter_mut().zip(self.colors) {
+            if let Some(option) = option {
+                *color = option;
+            }
+        }
+        if let Some(vblank) = self.vblank {
+            quirks.display_wait = vblank;
+        }
+        if let Some(clip) = self.clip {
+            quirks.wrap_sprites = !clip;
+        }
+        if let Some(shift) = self.shift {
+            quirks.shift_vx = shift;
+        }
+        if let Some(load_store) = self.load_store {
+            quirks.memory = if load_store {
+                MemoryIncrement::Unchanged
+            } else {
+                MemoryIncrement::ByXPlusOne
+            };
+        }
+        if let Some(jump) = self.jump {
+            quirks.jump_vx = jump;
+        }
+        if let Some(logic) = self.logic {
+            quirks.vf_reset = logic;
+        }
+    }
+}
@@ -0,0 +1,1722 @@
+use crate::breakpoint::{BreakAction, Breakpoints};
+use crate::coverage::Coverage;
+use crate::decode::{Alu, MegaOp, Op, VIP_FRAME_CYCLES, decode};
+use crate::display::{Display, Geometry};
+use crate::error::Chip8Error;
+use crate::heatmap::{Access, Heatmap};
+use crate::megachip::{Blend, MEGACHIP_MEMORY_SIZE, MegaChip, Sample};
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+#[cfg(feature = "std")]
+use crate::profile::{OpClass, Profile};
+use crate::rng::Rng;
+use crate::rom::{is_hires, validate_rom};
+use crate::rpl::{RPL_FLAGS, RplFlags, RplStore};
+use crate::savestate::Snapshot;
+use crate::settings::{Settings, Timing, UnknownOpcodePolicy, WriteProtection};
+use crate::stats::RunStats;
+use crate::trace::Registers;
+#[cfg(feature = "std")]
+use crate::trace::{self, StateSnapshot, Tracer};
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+// granularity of memory change tracking, 4 KB is 64 pages
+pub const PAGE_SIZE: usize = 64;
+
+pub const STACK_SIZE: usize = 16;
+
+pub const MEMORY_SIZE: usize = 4096;
+// experimental xo-chip style address space, reached through F000 NNNN
+pub const EXTENDED_MEMORY_SIZE: usize = 0x10000;
+// programs are loaded here, below is reserved for the interpreter
+pub const PROGRAM_START: usize = 0x200;
+// the hex digit sprites FX29 points I at, 5 rows each, below PROGRAM_START
+// where every interpreter kept them
+pub const FONT_START: usize = 0x50;
+pub const FONT_SPRITE: usize = 5;
+pub const FONT: [u8; 16 * FONT_SPRITE] = [
+    0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
+    0x20, 0x60, 0x20, 0x20, 0x70, // 1
+    0xF0, 0x10, 0xF0, 0x80, 0xF0, // 2
+    0xF0, 0x10, 0xF0, 0x10, 0xF0, // 3
+    0x90, 0x90, 0xF0, 0x10, 0x10, // 4
+    0xF0, 0x80, 0xF0, 0x10, 0xF0, // 5
+    0xF0, 0x80, 0xF0, 0x90, 0xF0, // 6
+    0xF0, 0x10, 0x20, 0x40, 0x40, // 7
+    0xF0, 0x90, 0xF0, 0x90, 0xF0, // 8
+    0xF0, 0x90, 0xF0, 0x10, 0xF0, // 9
+    0xF0, 0x90, 0xF0, 0x90, 0x90, // A
+    0xE0, 0x90, 0xE0, 0x90, 0xE0, // B
+    0xF0, 0x80, 0x80, 0x80, 0xF0, // C
+    0xE0, 0x90, 0x90, 0x90, 0xE0, // D
+    0xF0, 0x80, 0xF0, 0x80, 0xF0, // E
+    0xF0, 0x80, 0xF0, 0x80, 0x80, // F
+];
+// the eti-660's interpreter takes more, its programs start here, see
+// Chip8State::set_program_start
+pub const ETI_660_PROGRAM_START: usize = 0x600;
+
+// instructions recent_instructions remembers, what a crash dump lists
+pub const HISTORY_LEN: usize = 32;
+
+// key 0 to F, true while held
+pub type Keypad = [bool; 16];
+
+pub const TIMER_PERIOD: Duration = Duration::from_nanos(1_000_000_000 / 60);
+// run_for drops time beyond this, so a stalled host doesn't fast forward the program
+const MAX_CATCH_UP: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RunState {
+    Running,
+    // the program jumped to itself and can never make progress again
+    Halted,
+    // FX0A is waiting for a key, the result goes into V[register]
+    WaitingForKey { register: u8 },
+    // stopped by the user, cpu and timers stand still until set_paused(false)
+    Paused,
+}
+
+// what a cycle did, so frontends and the headless runners can react to it
+// instead of polling the state after every instruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StepOutcome {
+    // an instruction ran and changed nothing a frontend shows
+    Executed,
+    DisplayUpdated,
+    // FX0A has the cpu waiting for a key, nothing else runs
+    WaitingForKey,
+    // the sound timer started or stopped
+    Beep(bool),
+    Halted(HaltReason),
+    // a pause breakpoint stopped the cpu before the instruction at this address
+    Breakpoint(u16),
+    // WriteProtection::Pause stopped the cpu after the instruction at pc
+    // wrote to addr
+    ProtectedWrite { pc: u16, addr: usize },
+    // paused by the user, nothing ran
+    Paused,
+}
+
+impl StepOutcome {
+    // the next cycle runs an instruction too
+    pub fn is_running(&self) -> bool {
+        matches!(
+            self,
+            StepOutcome::Executed | StepOutcome::DisplayUpdated | StepOutcome::Beep(_)
+        )
+    }
+}
+
+// the instructions Chip8State::run_to runs a paused machine up to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunTarget {
+    // a DXYN that draws, not one waiting for vblank
+    Draw,
+    // FX18, 0 included
+    Sound,
+}
+
+// what on_event observers hear about, as it happens, and what
+// set_event_queue collects for frontends that drain it once a frame.
+// alternative frontends redraw and start or stop audio on these instead of
+// polling every cycle
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Event {
+    DisplayUpdated,
+    // the sound timer started, or stopped by running out or FX18 with 0
+    Sound(bool),
+    // once per halt, a halted machine cycling on stays quiet
+    Halted(HaltReason),
+    // a pause breakpoint stopped the cpu before the instruction at this address
+    Breakpoint(u16),
+    // restore replaced the machine with a snapshot
+    StateLoaded,
+}
+
+// events an undrained queue holds on to, later ones are dropped
+pub const MAX_QUEUED_EVENTS: usize = 256;
+
+// how long the debuggers let run_to look for its instruction, ten seconds
+pub const RUN_TO_FRAMES: u64 = 600;
+
+pub type Observer = Box<dyn FnMut(Event) + Send>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HaltReason {
+    // the program jumped to itself, only a reset gets it going again
+    Finished,
+    // an unknown opcode under UnknownOpcodePolicy::Trap, see clear_trap
+    Trap { pc: u16, opcode: u16 },
+}
+
+// the pixels the last DXYN covered, before wrapping or clipping at the
+// edges, and whether it set VF
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpriteBox {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+    pub collided: bool,
+}
+
+// the registers, timers and next opcode, copied into every frame so overlays
+// read them without touching the machine
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CpuSnapshot {
+    pub pc: u16,
+    pub i: u16,
+    pub v: [u8; 16],
+    pub delay_timer: u8,
+    pub sound_timer: u8,
+    pub stack_depth: usize,
+    // the instruction at pc, 0 if pc is out of memory
+    pub opcode: u16,
+}
+
+// time as the program sees it, independent of host speed and pauses
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EmulatedTime {
+    // 60Hz timer ticks since the program started
+    pub frames: u64,
+    // instructions executed since the program started
+    pub cycles: u64,
+}
+
+impl EmulatedTime {
+    pub fn as_duration(&self) -> Duration {
+        Duration::from_nanos(TIMER_PERIOD.as_nanos() as u64 * self.frames)
+    }
+}
+
+pub struct Instruction(u16);
+
+impl Instruction {
+    pub fn new(first: u8, second: u8) -> Self {
+        Instruction(((first as u16) << 8) | (second as u16))
+    }
+
+    pub fn opcode(&self) -> u16 {
+        self.0
+    }
+
+    pub fn indicator(&self) -> u8 {
+        ((self.opcode() & 0xF000) >> 12) as u8
+    }
+
+    pub fn x(&self) -> u8 {
+        ((self.opcode() & 0x0F00) >> 8) as u8
+    }
+
+    pub fn y(&self) -> u8 {
+        ((self.opcode() & 0x00F0) >> 4) as u8
+    }
+
+    pub fn n(&self) -> u8 {
+        (self.opcode() & 0x000F) as u8
+    }
+
+    pub fn nn(&self) -> u8 {
+        (self.opcode() & 0x00FF) as u8
+    }
+
+    pub fn nnn(&self) -> u16 {
+        self.opcode() & 0x0FFF
+    }
+}
+
+pub struct Chip8State {
+    pub display: Display,
+    pub memory: Vec<u8>,
+    pub v: Vec<u8>,
+    pub pc: u16,
+    pub i: u16,
+    pub stack: Vec<u16>,
+    pub keypad: Keypad,
+    pub delay_timer: u8,
+    pub sound_timer: u8,
+    pub rng: Rng,
+    pub settings: Settings,
+    pub breakpoints: Breakpoints,
+    sprites_this_frame: usize,
+    // set when a deferred DXYN has to wait for the next frame
+    frame_done: bool,
+    // instructions step() ran since the last timer tick, under
+    // Timing::CosmacVip the machine cycles every cycle() spent, some of
+    // which may have overrun into this frame
+    frame_cycles: usize,
+    run_state: RunState,
+    // what set_paused(false) goes back to
+    resume_state: RunState,
+    // (pc, opcode) of an unknown opcode caught by UnknownOpcodePolicy::Trap
+    trap: Option<(u16, u16)>,
+    // one bit per memory page written since the last clear_dirty_pages,
+    // writes made directly through `memory` bypass it
+    dirty_pages: Vec<u64>,
+    // wall clock time run_for still owes to the cpu and the timers
+    instruction_debt: Duration,
+    timer_debt: Duration,
+    // FX0A: keys that were already down when the wait started, they count
+    // only after going up once, so a held or autorepeating key isn't taken
+    // again by the next FX0A
+    wait_held: u16,
+    // FX0A with the key-release quirk: the key that went down, done when it's up
+    wait_pressed: Option<u8>,
+    // one bit per key EX9E or EXA1 has looked at since the program started
+    polled_keys: u16,
+    // a pause breakpoint that stopped the cpu here, it lets the instruction
+    // run once resumed
+    paused_at_breakpoint: Option<u16>,
+    // see geometry_changes
+    geometry_changes: u64,
+    // what set_geometry configured, reset goes back to it from whatever the
+    // program switched to
+    base_geometry: Geometry,
+    // only tracked while enabled, it costs a store per access
+    heatmap: Option<Heatmap>,
+    // same, two clock reads per instruction, so only with std
+    #[cfg(feature = "std")]
+    profile: Option<Profile>,
+    // same, a store per access and a set insert per instruction
+    coverage: Option<Coverage>,
+    // --trace, a line per instruction
+    #[cfg(feature = "std")]
+    trace: Option<Tracer>,
+    // FX75/FX85 flags of the loaded rom, saved to rpl_store under rom_key
+    rpl: RplFlags,
+    rpl_store: RplStore,
+    rom_key: String,
+    // where load puts the rom and pc starts, PROGRAM_START unless set
+    program_start: usize,
+    // (pc, opcode) of the last HISTORY_LEN instructions, a ring indexed by
+    // executed. always kept since a crash doesn't announce itself, it's one
+    // store per instruction
+    history: [(u16, u16); HISTORY_LEN],
+    executed: usize,
+    // (pc, addr) of a write that paused the cpu under WriteProtection::Pause
+    protected_write: Option<(u16, usize)>,
+    time: EmulatedTime,
+    // see on_event, kept across resets
+    observers: Vec<Observer>,
+    // see set_event_queue, kept across resets too
+    queued: Option<Vec<Event>>,
+    // see set_decode_cache, (opcode, op) of the word at each address
+    decoded: Option<Vec<Option<(u16, Op)>>>,
+    // see set_megachip
+    megachip: Option<MegaChip>,
+    // see stats, the instructions and frames come from time
+    counts: RunStats,
+    // see last_sprite
+    last_sprite: Option<SpriteBox>,
+}
+
+// seeded from the clock, without std there is none, see with_seed
+#[cfg(feature = "std")]
+impl Default for Chip8State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Chip8State {
+    #[cfg(feature = "std")]
+    pub fn new() -> Self {
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        Self::with_seed(seed)
+    }
+
+    // same seed and same input give the same run
+    pub fn with_seed(seed: u32) -> Self {
+        let mut memory = vec![0; MEMORY_SIZE];
+        memory[FONT_START..FONT_START + FONT.len()].copy_from_slice(&FONT);
+        Chip8State {
+            display: Display::default(),
+            memory,
+            v: vec![0; 16],
+            pc: PROGRAM_START as u16,
+            i: 0,
+            stack: Vec::with_capacity(STACK_SIZE),
+            keypad: [false; 16],
+            delay_timer: 0,
+            sound_timer: 0,
+            rng: Rng::new(seed),
+            settings: Settings::default(),
+            breakpoints: Breakpoints::new(),
+            sprites_this_frame: 0,
+            frame_done: false,
+            frame_cycles: 0,
+            run_state: RunState::Running,
+            resume_state: RunState::Running,
+            trap: None,
+            dirty_pages: vec![0; MEMORY_SIZE.div_ceil(PAGE_SIZE * 64)],
+            instruction_debt: Duration::ZERO,
+            timer_debt: Duration::ZERO,
+            wait_held: 0,
+            wait_pressed: None,
+            polled_keys: 0,
+            paused_at_breakpoint: None,
+            geometry_changes: 0,
+            base_geometry: Geometry::LORES,
+            heatmap: None,
+            #[cfg(feature = "std")]
+            profile: None,
+            coverage: None,
+            #[cfg(feature = "std")]
+            trace: None,
+            rpl: [0; RPL_FLAGS],
+            rpl_store: RplStore::new(),
+            rom_key: String::new(),
+            program_start: PROGRAM_START,
+            history: [(0, 0); HISTORY_LEN],
+            executed: 0,
+            protected_write: None,
+            time: EmulatedTime::default(),
+            observers: Vec::new(),
+            queued: None,
+            decoded: None,
+            megachip: None,
+            counts: RunStats::default(),
+            last_sprite: None,
+        }
+    }
+
+    // back to power on for a new program, keeping the settings, the geometry
+    // of set_geometry, memory size, keypad and rng stream
+    pub fn reset(&mut self) {
+        let fresh = Chip8State {
+            display: Display::new(self.base_geometry),
+            ..Chip8State::with_seed(0)
+        };
+        let old = core::mem::replace(self, fresh);
+        self.rng = old.rng;
+        self.settings = old.settings;
+        self.breakpoints = old.breakpoints;
+        self.keypad = old.keypad;
+        self.heatmap = old.heatmap.map(|_| Heatmap::new());
+        #[cfg(feature = "std")]
+        {
+            self.profile = old.profile.map(|_| Profile::new());
+            self.trace = old.trace;
+        }
+        self.coverage = old.coverage.map(|_| Coverage::new());
+        self.rpl_store = old.rpl_store;
+        self.set_program_start(old.program_start);
+        self.observers = old.observers;
+        self.queued = old.queued;
+        self.decoded = old.decoded.map(|_| Vec::new());
+        self.megachip = old.megachip.map(|_| MegaChip::default());
+        self.base_geometry = old.base_geometry;
+        self.geometry_changes = old.geometry_changes;
+        if old.display.geometry() != self.base_geometry {
+            self.geometry_changes += 1;
+        }
+        self.resize_memory(old.memory.len());
+        self.mark_dirty(0, self.memory.len());
+    }
+
+    // experimental: 64 KB of memory with F000 NNNN loading a 16 bit address
+    // into I. programs still run from the low 4 KB since jumps stay 12 bit,
+    // the rest is room for data. clears memory, so call it before load
+    pub fn set_extended_memory(&mut self, enabled: bool) {
+        self.set_memory_size(if enabled {
+            EXTENDED_MEMORY_SIZE
+        } else {
+            MEMORY_SIZE
+        });
+    }
+
+    pub fn extended_memory(&self) -> bool {
+        self.memory.len() > MEMORY_SIZE
+    }
+
+    // PROGRAM_START to EXTENDED_MEMORY_SIZE bytes of memory, the vip came
+    // with 2 KB. past MEMORY_SIZE it is extended memory. clears memory, so
+    // call it before load
+    pub fn set_memory_size(&mut self, size: usize) {
+        self.resize_memory(size);
+        self.clear_memory();
+    }
+
+    pub fn memory_size(&self) -> usize {
+        self.memory.len()
+    }
+
+    // where load puts the rom and the program starts, PROGRAM_START or
+    // ETI_660_PROGRAM_START. moves pc there and is kept by reset, so call it
+    // before load
+    pub fn set_program_start(&mut self, addr: usize) {
+        self.program_start = addr;
+        self.pc = addr as u16;
+    }
+
+    pub fn program_start(&self) -> usize {
+        self.program_start
+    }
+
+    // megachip-8: 16 MB of memory and the megachip opcodes, which the rom
+    // turns on with 0011, see megachip. clears memory, so call it before load
+    pub fn set_megachip(&mut self, enabled: bool) {
+        if enabled == self.megachip.is_some() {
+            return;
+        }
+        if self.megachip.take().is_some_and(|megachip| megachip.active) {
+            self.switch_geometry(self.base_geometry);
+        }
+        self.megachip = enabled.then(MegaChip::default);
+        let size = if enabled {
+            MEGACHIP_MEMORY_SIZE
+        } else {
+            MEMORY_SIZE
+        };
+        self.resize_memory(size);
+        self.clear_memory();
+    }
+
+    // the megachip state while set_megachip is on, its colors, palette and
+    // the sample playing
+    pub fn megachip(&self) -> Option<&MegaChip> {
+        self.megachip.as_ref()
+    }
+
+    // I with the megachip high byte, what megachip sprites, palettes and
+    // samples are read from
+    fn long_i(&self) -> usize {
+        let high = self.megachip.as_ref().map_or(0, |megachip| megachip.i_high);
+        ((high as usize) << 16) | self.i as usize
+    }
+
+    // the megachip state while its 256x192 screen is on
+    fn active_megachip(&mut self) -> Option<&mut MegaChip> {
+        self.megachip.as_mut().filter(|megachip| megachip.active)
+    }
+
+    // zeroes all but the font
+    fn clear_memory(&mut self) {
+        self.memory.fill(0);
+        self.memory[FONT_START..FONT_START + FONT.len()].copy_from_slice(&FONT);
+        self.mark_dirty(0, self.memory.len());
+    }
+
+    fn resize_memory(&mut self, size: usize) {
+        self.memory.resize(size, 0);
+        self.dirty_pages.resize(size.div_ceil(PAGE_SIZE * 64), 0);
+        if let Some(decoded) = &mut self.decoded {
+            decoded.resize(size, None);
+        }
+    }
+
+    // the machine as savestate stores it
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            display: self.display.clone(),
+            memory: self.memory.clone(),
+            v: self.v.clone(),
+            pc: self.pc,
+            i: self.i,
+            stack: self.stack.clone(),
+            delay_timer: self.delay_timer,
+            sound_timer: self.sound_timer,
+            rng: self.rng,
+            run_state: self.run_state,
+            resume_state: self.resume_state,
+            wait_held: self.wait_held,
+            wait_pressed: self.wait_pressed,
+            polled_keys: self.polled_keys,
+            time: self.time,
+            megachip: self.megachip.clone(),
+        }
+    }
+
+    // continues from a snapshot at a frame boundary, settings and the keypad
+    // stay as they are
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        if snapshot.display.geometry() != self.display.geometry() {
+            self.geometry_changes += 1;
+        }
+        self.display = snapshot.display;
+        self.resize_memory(snapshot.memory.len());
+        self.memory = snapshot.memory;
+        self.mark_dirty(0, self.memory.len());
+        self.v = snapshot.v;
+        self.pc = snapshot.pc;
+        self.i = snapshot.i;
+        self.stack = snapshot.stack;
+        self.delay_timer = snapshot.delay_timer;
+        self.sound_timer = snapshot.sound_timer;
+        self.rng = snapshot.rng;
+        self.run_state = snapshot.run_state;
+        self.resume_state = snapshot.resume_state;
+        self.wait_held = snapshot.wait_held;
+        self.wait_pressed = snapshot.wait_pressed;
+        self.polled_keys = snapshot.polled_keys;
+        self.time = snapshot.time;
+        self.megachip = snapshot.megachip;
+        if self.is_observed() {
+            self.emit(Event::StateLoaded);
+        }
+        self.sprites_this_frame = 0;
+        self.frame_done = false;
+        self.frame_cycles = 0;
+        self.trap = None;
+        self.instruction_debt = Duration::ZERO;
+        self.timer_debt = Duration::ZERO;
+    }
+
+    // switches to another screen size, clearing the display. the machine
+    // starts over on it after a reset too
+    pub fn set_geometry(&mut self, geometry: Geometry) {
+        self.base_geometry = geometry;
+        self.display.resize(geometry);
+        self.geometry_changes += 1;
+    }
+
+    // the program's resolution switches, until the next reset
+    fn switch_geometry(&mut self, geometry: Geometry) {
+        if self.display.geometry() != geometry {
+            self.display.resize(geometry);
+            self.geometry_changes += 1;
+        }
+    }
+
+    // counts set_geometry calls and resolution switches of the program, a
+    // frontend reallocates whenever the count moves
+    pub fn geometry_changes(&self) -> u64 {
+        self.geometry_changes
+    }
+
+    pub fn load(&mut self, bytes: &[u8]) -> Result<(), Chip8Error> {
+        let start = self.program_start;
+        validate_rom(bytes, self.memory.len().saturating_sub(start))?;
+
+        self.memory[start..start + bytes.len()].copy_from_slice(bytes);
+        // the vip code at 0x260 can't run here, the program proper starts
+        // at 0x2C0 on a 64x64 screen that 0230 clears
+        if is_hires(bytes) && self.megachip.is_none() && start == PROGRAM_START {
+            self.memory[start + 1] = 0xC0;
+            self.switch_geometry(Geometry::HIRES);
+        }
+        self.mark_dirty(start, bytes.len());
+        self.rom_key = RplStore::key(bytes);
+        self.rpl = self.rpl_store.load(&self.rom_key);
+        Ok(())
+    }
+
+    // where FX75 keeps the flags of each rom, kept by reset. applies from
+    // the next load
+    pub fn set_rpl_store(&mut self, store: RplStore) {
+        self.rpl_store = store;
+    }
+
+    pub fn rpl_store_mut(&mut self) -> &mut RplStore {
+        &mut self.rpl_store
+    }
+
+    pub fn rpl_flags(&self) -> &RplFlags {
+        &self.rpl
+    }
+
+    pub fn read_memory(&self, addr: usize) -> Result<u8, Chip8Error> {
+        self.memory
+            .get(addr)
+            .copied()
+            .ok_or(Chip8Error::MemoryOutOfBounds { pc: self.pc, addr })
+    }
+
+    pub fn write_memory(&mut self, addr: usize, value: u8) -> Result<(), Chip8Error> {
+        let Some(byte) = self.memory.get_mut(addr) else {
+            return Err(Chip8Error::MemoryOutOfBounds { pc: self.pc, addr });
+        };
+        *byte = value;
+        self.mark_dirty(addr, 1);
+        if let Some(heatmap) = &mut self.heatmap {
+            heatmap.record(addr, Access::Write);
+        }
+        if let Some(coverage) = &mut self.coverage {
+            coverage.record_access(addr, Access::Write);
+        }
+        Ok(())
+    }
+
+    // write_memory for the cpu itself, minding settings.write_protection
+    fn store(&mut self, addr: usize, value: u8) -> Result<(), Chip8Error> {
+        if addr < self.program_start {
+            // the instruction running is the last one in the history
+            let pc = self.history[(self.executed + HISTORY_LEN - 1) % HISTORY_LEN].0;
+            match self.settings.write_protection {
+                WriteProtection::Off => {}
+                WriteProtection::Halt => return Err(Chip8Error::ProtectedWrite { pc, addr }),
+                WriteProtection::Pause => {
+                    self.protected_write.get_or_insert((pc, addr));
+                }
+            }
+        }
+        self.write_memory(addr, value)
+    }
+
+    // the write that paused the cpu under WriteProtection::Pause, once
+    pub fn take_protected_write(&mut self) -> Option<(u16, usize)> {
+        self.protected_write.take()
+    }
+
+    // read_memory for the cpu itself, feeding the heatmap
+    fn read(&mut self, addr: usize, access: Access) -> Result<u8, Chip8Error> {
+        let value = self.read_memory(addr)?;
+        self.record_access(addr, access);
+        Ok(value)
+    }
+
+    fn record_access(&mut self, addr: usize, access: Access) {
+        if let Some(heatmap) = &mut self.heatmap {
+            heatmap.record(addr, access);
+        }
+        if let Some(coverage) = &mut self.coverage {
+            coverage.record_access(addr, access);
+        }
+    }
+
+    // the opcode at pc and what it decodes to, from the decode cache when
+    // it has been there before
+    fn fetch(&mut self, pc: usize) -> Result<(u16, Op), Chip8Error> {
+        if let Some(&Some(entry)) = self.decoded.as_ref().and_then(|decoded| decoded.get(pc)) {
+            if self.heatmap.is_some() || self.coverage.is_some() {
+                self.record_access(pc, Access::Fetch);
+                self.record_access(pc + 1, Access::Fetch);
+            }
+            return Ok(entry);
+        }
+        let opcode = u16::from_be_bytes([
+            self.read(pc, Access::Fetch)?,
+            self.read(pc + 1, Access::Fetch)?,
+        ]);
+        let entry = (opcode, decode(opcode));
+        if let Some(slot) = self
+            .decoded
+            .as_mut()
+            .and_then(|decoded| decoded.get_mut(pc))
+        {
+            *slot = Some(entry);
+        }
+        Ok(entry)
+    }
+
+    // keeps every instruction decoded after its first run, which saves the
+    // fetch and decode of loops at high speeds. costs 6 bytes per byte of
+    // memory, so it's off by default. writes through write_memory, the cpu,
+    // load and restore drop the words they touch, writes made directly through
+    // `memory` don't, call invalidate_decode_cache after those
+    pub fn set_decode_cache(&mut self, enabled: bool) {
+        if enabled != self.decoded.is_some() {
+            self.decoded = enabled.then(|| vec![None; self.memory.len()]);
+        }
+    }
+
+    pub fn invalidate_decode_cache(&mut self) {
+        if let Some(decoded) = &mut self.decoded {
+            decoded.fill(None);
+        }
+    }
+
+    pub fn set_heatmap_enabled(&mut self, enabled: bool) {
+        if enabled != self.heatmap.is_some() {
+            self.heatmap = enabled.then(Heatmap::new);
+        }
+    }
+
+    pub fn heatmap(&self) -> Option<&Heatmap> {
+        self.heatmap.as_ref()
+    }
+
+    // starts over with an empty profile when enabled
+    #[cfg(feature = "std")]
+    pub fn set_profiling(&mut self, enabled: bool) {
+        self.profile = enabled.then(Profile::new);
+    }
+
+    #[cfg(feature = "std")]
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    // starts over with nothing covered when enabled
+    pub fn set_coverage(&mut self, enabled: bool) {
+        self.coverage = enabled.then(Coverage::new);
+    }
+
+    pub fn coverage(&self) -> Option<&Coverage> {
+        self.coverage.as_ref()
+    }
+
+    // the previous tracer is flushed and dropped
+    #[cfg(feature = "std")]
+    pub fn set_trace(&mut self, trace: Option<Tracer>) {
+        if let Some(mut old) = core::mem::replace(&mut self.trace, trace) {
+            old.flush();
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn is_tracing(&self) -> bool {
+        self.trace.is_some()
+    }
+
+    // counts a breakpoint hit at pc, true if it paused the cpu
+    fn break_here(&mut self) -> bool {
+        if self.paused_at_breakpoint.take() == Some(self.pc) {
+            return false;
+        }
+        let registers = self.registers();
+        if self.breakpoints.hit(self.pc, &registers) != Some(BreakAction::Pause) {
+            return false;
+        }
+        self.paused_at_breakpoint = Some(self.pc);
+        self.set_paused(true);
+        true
+    }
+
+    #[cfg(feature = "std")]
+    fn trace_frame(&mut self, delay_expired: bool, sound_expired: bool) {
+        let frame = self.time.frames;
+        let snapshot = StateSnapshot {
+            frame,
+            pc: self.pc,
+            registers: self.registers(),
+            stack: &self.stack,
+            delay_timer: self.delay_timer,
+            sound_timer: self.sound_timer,
+        };
+        let Some(trace) = &mut self.trace else {
+            return;
+        };
+        if delay_expired {
+            trace.timer_expired(frame, "delay");
+        }
+        if sound_expired {
+            trace.timer_expired(frame, "sound");
+        }
+        if frame.is_multiple_of(trace::SNAPSHOT_FRAMES) {
+            trace.snapshot(&snapshot);
+        }
+    }
+
+    fn registers(&self) -> Registers {
+        let mut v = [0; 16];
+        v.copy_from_slice(&self.v[..16]);
+        Registers { v, i: self.i }
+    }
+
+    fn mark_dirty(&mut self, addr: usize, len: usize) {
+        if len == 0 {
+            return;
+        }
+        // the word starting one byte earlier has changed too
+        if let Some(decoded) = &mut self.decoded {
+            let end = (addr + len).min(decoded.len());
+            if let Some(words) = decoded.get_mut(addr.saturating_sub(1)..end) {
+                words.fill(None);
+            }
+        }
+        for page in addr / PAGE_SIZE..=(addr + len - 1) / PAGE_SIZE {
+            if let Some(bits) = self.dirty_pages.get_mut(page / 64) {
+                *bits |= 1 << (page % 64);
+            }
+        }
+    }
+
+    // indices of pages touched since the last clear, page n covers
+    // memory[n * PAGE_SIZE..(n + 1) * PAGE_SIZE]
+    pub fn dirty_pages(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.memory.len() / PAGE_SIZE).filter(|page| self.is_page_dirty(*page))
+    }
+
+    pub fn is_page_dirty(&self, page: usize) -> bool {
+        self.dirty_pages
+            .get(page / 64)
+            .is_some_and(|bits| bits & (1 << (page % 64)) != 0)
+    }
+
+    // call after taking a snapshot
+    pub fn clear_dirty_pages(&mut self) {
+        self.dirty_pages.fill(0);
+    }
+
+    fn held_keys(&self) -> u16 {
+        (0..16)
+            .filter(|key| self.keypad[*key])
+            .map(|key| 1 << key)
+            .sum()
+    }
+
+    // the key FX0A is done with, if any: the first one that went down since
+    // the wait started, or with the key-release quirk the one that went down
+    // and is up again
+    fn poll_key_wait(&mut self) -> Option<u8> {
+        let held = self.held_keys();
+        self.wait_held &= held;
+        if let Some(key) = self.wait_pressed {
+            return (held & (1 << key) == 0).then(|| {
+                self.wait_pressed = None;
+                key
+            });
+        }
+
+        let pressed = held & !self.wait_held;
+        if pressed == 0 {
+            return None;
+        }
+        let key = pressed.trailing_zeros() as u8;
+        if self.settings.quirks.key_release {
+            self.wait_pressed = Some(key);
+            return None;
+        }
+        Some(key)
+    }
+
+    // the keys the program reads, bit n for chip8 key n
+    pub fn polled_keys(&self) -> u16 {
+        self.polled_keys
+    }
+
+    // where the last DXYN drew, None before one or on a megachip screen
+    pub fn last_sprite(&self) -> Option<SpriteBox> {
+        self.last_sprite
+    }
+
+    pub fn cpu_snapshot(&self) -> CpuSnapshot {
+        let pc = self.pc as usize;
+        let (high, low) = (self.read_memory(pc), self.read_memory(pc + 1));
+        CpuSnapshot {
+            pc: self.pc,
+            i: self.i,
+            v: self.registers().v,
+            delay_timer: self.delay_timer,
+            sound_timer: self.sound_timer,
+            stack_depth: self.stack.len(),
+            opcode: u16::from_be_bytes([high.unwrap_or(0), low.unwrap_or(0)]),
+        }
+    }
+
+    // sha1 of the loaded rom, see RplStore::key
+    pub fn rom_key(&self) -> &str {
+        &self.rom_key
+    }
+
+    // (pc, opcode) of up to HISTORY_LEN instructions, oldest first. the last
+    // one is the instruction that failed if cycle returned an error
+    pub fn recent_instructions(&self) -> Vec<(u16, u16)> {
+        let len = self.executed.min(HISTORY_LEN);
+        (self.executed - len..self.executed)
+            .map(|n| self.history[n % HISTORY_LEN])
+            .collect()
+    }
+
+    pub fn emulated_time(&self) -> EmulatedTime {
+        self.time
+    }
+
+    // since the program was loaded
+    pub fn stats(&self) -> RunStats {
+        RunStats {
+            instructions: self.time.cycles,
+            frames: self.time.frames,
+            ..self.counts
+        }
+    }
+
+    pub fn run_state(&self) -> RunState {
+        self.run_state
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        match (paused, self.run_state) {
+            (true, RunState::Paused) => {}
+            (true, state) => {
+                self.resume_state = state;
+                self.run_state = RunState::Paused;
+            }
+            (false, RunState::Paused) => self.run_state = self.resume_state,
+            (false, _) => {}
+        }
+    }
+
+    // runs one frame of a paused machine, instructions_per_second / 60
+    // instructions and a timer tick, and pauses again
+    pub fn step_frame(&mut self) -> Result<(), Chip8Error> {
+        if self.run_state != RunState::Paused {
+            return Ok(());
+        }
+        self.run_state = self.resume_state;
+        let result = self.frame();
+        self.set_paused(true);
+        result
+    }
+
+    // instructions between two timer ticks at settings.instructions_per_second
+    pub fn instructions_per_frame(&self) -> usize {
+        (self.settings.instructions_per_second / 60).max(1) as usize
+    }
+
+    // one instruction, with the timers ticking after every
+    // instructions_per_frame of them, so an embedder stepping by hand keeps
+    // the program's timing. a DXYN that waits for vblank ends the frame early
+    pub fn step(&mut self) -> Result<StepOutcome, Chip8Error> {
+        if self.run_state == RunState::Paused {
+            return Ok(StepOutcome::Paused);
+        }
+        let outcome = self.cycle();
+        let frame_over = match self.settings.timing {
+            // nothing runs to use the frame up, it goes by in one step
+            Timing::CosmacVip => {
+                self.frame_cycles >= VIP_FRAME_CYCLES as usize
+                    || matches!(
+                        outcome,
+                        Ok(StepOutcome::WaitingForKey | StepOutcome::Halted(_))
+                    )
+            }
+            _ => {
+                self.frame_cycles += 1;
+                self.frame_cycles >= self.instructions_per_frame()
+            }
+        };
+        if self.frame_done || frame_over {
+            self.tick_timers();
+        }
+        outcome
+    }
+
+    // one 60Hz frame at settings.instructions_per_second, run_frame with the
+    // budget worked out
+    pub fn frame(&mut self) -> Result<(), Chip8Error> {
+        self.run_frame(self.instructions_per_frame())
+    }
+
+    // steps until the screen changes, the next thing worth showing, or
+    // max_frames went by without a change. true if it changed
+    pub fn run_until_draw(&mut self, max_frames: u64) -> Result<bool, Chip8Error> {
+        let (generation, end) = (self.display.generation(), self.time.frames + max_frames);
+        while self.time.frames < end {
+            if self.step()? == StepOutcome::Paused {
+                return Ok(false);
+            }
+            if self.display.generation() != generation {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    // runs the next instruction of a paused machine and pauses again, a
+    // breakpoint on it doesn't stop it. nothing happens unless paused
+    pub fn step_instruction(&mut self) -> Result<StepOutcome, Chip8Error> {
+        if self.run_state != RunState::Paused {
+            return Ok(StepOutcome::Paused);
+        }
+        self.run_state = self.resume_state;
+        self.paused_at_breakpoint = Some(self.pc);
+        let outcome = self.cycle();
+        self.paused_at_breakpoint = None;
+        self.set_paused(true);
+        outcome
+    }
+
+    // runs a paused machine at full speed, timers and all, until target's
+    // instruction ran and pauses it again after that. a breakpoint on the
+    // first instruction doesn't stop it, another one, a halt, FX0A or
+    // max_frames going by does. true if the target ran
+    pub fn run_to(&mut self, target: RunTarget, max_frames: u64) -> Result<bool, Chip8Error> {
+        if self.run_state != RunState::Paused {
+            return Ok(false);
+        }
+        self.run_state = self.resume_state;
+        self.paused_at_breakpoint = Some(self.pc);
+        let end = self.time.frames + max_frames;
+        let (mut found, mut breakpoint) = (Ok(false), false);
+        while self.time.frames < end {
+            let (executed, draws) = (self.executed, self.counts.draws);
+            let outcome = match self.step() {
+                Ok(outcome) => outcome,
+                Err(err) => {
+                    found = Err(err);
+                    break;
+                }
+            };
+            let ran = self.executed != executed;
+            let opcode = self.history[(self.executed + HISTORY_LEN - 1) % HISTORY_LEN].1;
+            let hit = match target {
+                RunTarget::Draw => self.counts.draws != draws,
+                RunTarget::Sound => ran && opcode & 0xF0FF == 0xF018,
+            };
+            if hit {
+                found = Ok(true);
+                break;
+            }
+            if !outcome.is_running() {
+                breakpoint = matches!(outcome, StepOutcome::Breakpoint(_));
+                break;
+            }
+        }
+        // one that stopped it is stepped over by the next run, like any
+        if !breakpoint {
+            self.paused_at_breakpoint = None;
+        }
+        self.set_paused(true);
+        found
+    }
+
+    pub fn cycle(&mut self) -> Result<StepOutcome, Chip8Error> {
+        if !self.is_observed() {
+            return self.run_cycle();
+        }
+        let halted = self.trap.is_some() || self.run_state == RunState::Halted;
+        let (generation, beeping) = (self.display.generation(), self.sound_timer > 0);
+        let outcome = self.run_cycle()?;
+        if self.display.generation() != generation {
+            self.emit(Event::DisplayUpdated);
+        }
+        if (self.sound_timer > 0) != beeping {
+            self.emit(Event::Sound(!beeping));
+        }
+        match outcome {
+            StepOutcome::Halted(reason) if !halted => self.emit(Event::Halted(reason)),
+            StepOutcome::Breakpoint(pc) => self.emit(Event::Breakpoint(pc)),
+            _ => {}
+        }
+        Ok(outcome)
+    }
+
+    // calls observer with every Event from now on, next to any registered
+    // before. observers run on the thread driving the machine, in the middle
+    // of a cycle or timer tick, so they should be quick
+    pub fn on_event(&mut self, observer: impl FnMut(Event) + Send + 'static) {
+        self.observers.push(Box::new(observer));
+    }
+
+    pub fn clear_observers(&mut self) {
+        self.observers.clear();
+    }
+
+    // collects every Event from now on for take_events, consecutive display
+    // updates as one. off drops what was queued
+    pub fn set_event_queue(&mut self, enabled: bool) {
+        if enabled != self.queued.is_some() {
+            self.queued = enabled.then(Vec::new);
+        }
+    }
+
+    // the queued events in the order they happened, emptying the queue
+    pub fn take_events(&mut self) -> Vec<Event> {
+        self.queued
+            .as_mut()
+            .map(core::mem::take)
+            .unwrap_or_default()
+    }
+
+    fn is_observed(&self) -> bool {
+        !self.observers.is_empty() || self.queued.is_some()
+    }
+
+    fn emit(&mut self, event: Event) {
+        for observer in &mut self.observers {
+            observer(event);
+        }
+        if let Some(queued) = &mut self.queued {
+            let repeated = event == Event::DisplayUpdated && queued.last() == Some(&event);
+            if !repeated && queued.len() < MAX_QUEUED_EVENTS {
+                queued.push(event);
+            }
+        }
+    }
+
+    fn run_cycle(&mut self) -> Result<StepOutcome, Chip8Error> {
+        if let Some((pc, opcode)) = self.trap {
+            return Ok(StepOutcome::Halted(HaltReason::Trap { pc, opcode }));
+        }
+
+        match self.run_state {
+            RunState::Running => {}
+            RunState::Halted => return Ok(StepOutcome::Halted(HaltReason::Finished)),
+            RunState::Paused => return Ok(StepOutcome::Paused),
+            RunState::WaitingForKey { register } => {
+                let Some(key) = self.poll_key_wait() else {
+                    return Ok(StepOutcome::WaitingForKey);
+                };
+                self.v[register as usize] = key;
+                self.run_state = RunState::Running;
+                return Ok(StepOutcome::Executed);
+            }
+        }
+
+        if !self.breakpoints.is_empty() && self.break_here() {
+            return Ok(StepOutcome::Breakpoint(self.pc));
+        }
+        let (generation, beeping) = (self.display.generation(), self.sound_timer > 0);
+        #[cfg(feature = "std")]
+        if let Some(trace) = &mut self.trace {
+            trace.keypad(self.time.cycles, self.time.frames, &self.keypad);
+        }
+        #[cfg(feature = "std")]
+        let start = self.profile.is_some().then(Instant::now);
+        let pc = self.pc;
+        let (opcode, op) = self.fetch(pc as usize)?;
+        let inst = Instruction(opcode);
+        self.pc = self.pc.wrapping_add(2);
+        self.time.cycles += 1;
+        self.history[self.executed % HISTORY_LEN] = (pc, inst.opcode());
+        self.executed += 1;
+        if let Some(coverage) = &mut self.coverage {
+            coverage.record_opcode(inst.opcode());
+        }
+        #[cfg(feature = "std")]
+        let traced = self
+            .trace
+            .as_ref()
+            .is_some_and(|trace| trace.wants(pc))
+            .then(|| self.registers());
+
+        // set again by store if this instruction writes where it shouldn't
+        let earlier_write = self.protected_write.take();
+        #[cfg(not(feature = "std"))]
+        let result = self.execute(op);
+        #[cfg(feature = "std")]
+        let result = match start {
+            Some(start) => {
+                let (class, opcode) = (OpClass::of(&inst), inst.opcode());
+                let fetched = Instant::now();
+                let result = self.execute(op);
+                if let Some(profile) = &mut self.profile {
+                    profile.record_address(pc, opcode);
+                    profile.record(OpClass::Fetch, fetched - start);
+                    profile.record(class, fetched.elapsed());
+                }
+                result
+            }
+            None => self.execute(op),
+        };
+        if self.settings.timing == Timing::CosmacVip && result.is_ok() {
+            let skipped = self.pc != pc.wrapping_add(2);
+            self.frame_cycles += op.vip_cycles(skipped) as usize;
+        }
+        #[cfg(feature = "std")]
+        if let Some(before) = traced {
+            let after = self.registers();
+            if let Some(trace) = &mut self.trace {
+                trace.record(self.time.cycles, pc, &inst, &before, &after);
+            }
+        }
+
+        let wrote = self.protected_write;
+        if wrote.is_none() {
+            self.protected_write = earlier_write;
+        }
+
+        // leave pc on the failing instruction and report that address
+        let executed = || match self.run_state {
+            RunState::Halted => StepOutcome::Halted(HaltReason::Finished),
+            RunState::WaitingForKey { .. } => StepOutcome::WaitingForKey,
+            _ if self.display.generation() != generation => StepOutcome::DisplayUpdated,
+            _ if (self.sound_timer > 0) != beeping => StepOutcome::Beep(!beeping),
+            _ => StepOutcome::Executed,
+        };
+        match result {
+            Ok(()) => match wrote {
+                Some((pc, addr)) => {
+                    self.set_paused(true);
+                    Ok(StepOutcome::ProtectedWrite { pc, addr })
+                }
+                None => Ok(executed()),
+            },
+            Err(Chip8Error::InvalidOpcode { pc, opcode }) => match self.settings.unknown_opcode {
+                UnknownOpcodePolicy::Halt => {
+                    self.pc = pc;
+                    Err(Chip8Error::InvalidOpcode { pc, opcode })
+                }
+                UnknownOpcodePolicy::Skip => Ok(executed()),
+                UnknownOpcodePolicy::Trap => {
+                    self.pc = pc;
+                    self.trap = Some((pc, opcode));
+                    Ok(StepOutcome::Halted(HaltReason::Trap { pc, opcode }))
+                }
+            },
+            Err(Chip8Error::MemoryOutOfBounds { addr, .. }) => {
+                self.pc = pc;
+                Err(Chip8Error::MemoryOutOfBounds { pc, addr })
+            }
+            Err(err) => {
+                self.pc = pc;
+                Err(err)
+            }
+        }
+    }
+
+    // (pc, opcode) the cpu is parked on, see UnknownOpcodePolicy::Trap
+    pub fn trap(&self) -> Option<(u16, u16)> {
+        self.trap
+    }
+
+    // resumes a trapped cpu, retrying the instruction at pc
+    pub fn clear_trap(&mut self) {
+        self.trap = None;
+    }
+
+    // a fixed instruction budget per timer tick, independent of wall clock.
+    // under Timing::CosmacVip the budget is a vip frame instead
+    pub fn run_frame(&mut self, instructions: usize) -> Result<(), Chip8Error> {
+        if self.run_state == RunState::Paused {
+            return Ok(());
+        }
+        let vip = self.settings.timing == Timing::CosmacVip;
+        let mut ran = 0;
+        while if vip {
+            self.frame_cycles < VIP_FRAME_CYCLES as usize
+        } else {
+            ran < instructions
+        } {
+            let outcome = self.cycle()?;
+            ran += 1;
+            if self.frame_done || !outcome.is_running() {
+                break;
+            }
+        }
+        self.tick_timers();
+        Ok(())
+    }
+
+    // advances the machine by elapsed real time, running
+    // settings.instructions_per_second and ticking the timers at 60Hz, or
+    // whole vip frames under Timing::CosmacVip
+    pub fn run_for(&mut self, elapsed: Duration) -> Result<(), Chip8Error> {
+        // the time paused is not owed to anyone
+        if self.run_state == RunState::Paused {
+            return Ok(());
+        }
+        if self.settings.timing == Timing::CosmacVip {
+            self.timer_debt = (self.timer_debt + elapsed).min(MAX_CATCH_UP);
+            while self.timer_debt >= TIMER_PERIOD && self.run_state != RunState::Paused {
+                self.timer_debt -= TIMER_PERIOD;
+                self.run_frame(0)?;
+            }
+            return Ok(());
+        }
+        let instruction_time =
+            Duration::from_secs(1) / self.settings.instructions_per_second.max(1);
+        self.instruction_debt = (self.instruction_debt + elapsed).min(MAX_CATCH_UP);
+        self.timer_debt = (self.timer_debt + elapsed).min(MAX_CATCH_UP);
+
+        while self.instruction_debt >= instruction_time {
+            self.instruction_debt -= instruction_time;
+            let outcome = self.cycle()?;
+            // nothing to catch up on until the next tick or forever
+            if self.frame_done || matches!(outcome, StepOutcome::Halted(_)) {
+                self.instruction_debt = Duration::ZERO;
+            }
+        }
+
+        while self.timer_debt >= TIMER_PERIOD {
+            self.timer_debt -= TIMER_PERIOD;
+            self.tick_timers();
+        }
+        Ok(())
+    }
+
+    pub fn tick_timers(&mut self) {
+        self.time.frames += 1;
+        if let RunState::WaitingForKey { .. } = self.run_state {
+            self.counts.key_wait_frames += 1;
+        }
+        #[cfg(feature = "std")]
+        let expired = (self.delay_timer == 1, self.sound_timer == 1);
+        self.delay_timer = self.delay_timer.saturating_sub(1);
+        let silenced = self.sound_timer == 1;
+        self.sound_timer = self.sound_timer.saturating_sub(1);
+        if silenced && self.is_observed() {
+            self.emit(Event::Sound(false));
+        }
+        #[cfg(feature = "std")]
+        if self.trace.is_some() {
+            self.trace_frame(expired.0, expired.1);
+        }
+        self.sprites_this_frame = 0;
+        self.frame_done = false;
+        self.frame_cycles = match self.settings.timing {
+            // an instruction that overran the frame eats into this one
+            Timing::CosmacVip => self.frame_cycles.saturating_sub(VIP_FRAME_CYCLES as usize),
+            _ => 0,
+        };
+        if let Some(heatmap) = &mut self.heatmap {
+            heatmap.decay();
+        }
+    }
+
+    fn execute(&mut self, op: Op) -> Result<(), Chip8Error> {
+        // errors report the address of the failing instruction
+        let pc = self.pc.wrapping_sub(2);
+
+        match op {
+            // megachip shows the frame drawn so far instead
+            Op::Clear => match &mut self.megachip {
+                Some(megachip) if megachip.active => megachip.present(&mut self.display),
+                _ => self.display.clear_selected(),
+            },
+            Op::Return => {
+                self.pc = self.stack.pop().ok_or(Chip8Error::StackUnderflow { pc })?;
+            }
+            // schip scrolling, 00DN up is xo-chip
+            Op::ScrollDown(_) | Op::ScrollUp(_) | Op::ScrollRight | Op::ScrollLeft
+                if self.active_megachip().is_some() =>
+            {
+                let (dx, dy) = match op {
+                    Op::ScrollDown(n) => (0, n as isize),
+                    Op::ScrollUp(n) => (0, -(n as isize)),
+                    Op::ScrollRight => (4, 0),
+                    _ => (-4, 0),
+                };
+                if let Some(megachip) = self.active_megachip() {
+                    megachip.scroll(dx, dy);
+                }
+            }
+            Op::ScrollDown(n) => self.display.scroll_down(n as usize),
+            Op::ScrollUp(n) => self.display.scroll_up(n as usize),
+            Op::ScrollRight => self.display.scroll_right(4),
+            Op::ScrollLeft => self.display.scroll_left(4),
+            // schip low and high resolution
+            Op::Lores => self.switch_geometry(Geometry::LORES),
+            Op::Hires => self.switch_geometry(Geometry::SCHIP_HIRES),
+            Op::Planes(mask) => self.display.select_planes(mask),
+            Op::Sys => {}
+            // the hires interpreter's clear, 02NN is a megachip palette
+            Op::Mega(MegaOp::LoadPalette(0x30))
+                if self.megachip.is_none() && self.display.geometry() == Geometry::HIRES =>
+            {
+                self.display.clear()
+            }
+            Op::Mega(op) => self.execute_megachip(op)?,
+            Op::Jump(nnn) => {
+                if nnn == pc {
+                    self.run_state = RunState::Halted;
+                }
+                self.pc = nnn;
+            }
+            Op::Call(nnn) => {
+                if self.stack.len() >= STACK_SIZE {
+                    return Err(Chip8Error::StackOverflow { pc });
+                }
+                self.stack.push(self.pc);
+                self.pc = nnn;
+            }
+            Op::SkipIfEqual { x, nn } => {
+                if self.v[x as usize] == nn {
+                    self.skip_next();
+                }
+            }
+            Op::SkipIfNotEqual { x, nn } => {
+                if self.v[x as usize] != nn {
+                    self.skip_next();
+                }
+            }
+            Op::SkipIfEqualReg { x, y } => {
+                if self.v[x as usize] == self.v[y as usize] {
+                    self.skip_next();
+                }
+            }
+            Op::SkipIfNotEqualReg { x, y } => {
+                if self.v[x as usize] != self.v[y as usize] {
+                    self.skip_next();
+                }
+            }
+            Op::Load { x, nn } => self.v[x as usize] = nn,
+            // wraps and leaves VF alone
+            Op::Add { x, nn } => {
+                let x = x as usize;
+                self.v[x] = self.v[x].wrapping_add(nn);
+            }
+            Op::Alu { op, x, y } => {
+                let (x, y) = (x as usize, y as usize);
+                let quirks = self.settings.quirks;
+                let shifted = || {
+                    if quirks.shift_vx {
+                        self.v[x]
+                    } else {
+                        self.v[y]
+                    }
+                };
+                // the flag goes in after the result, VF as X ends up the flag
+                let (result, flag) = match op {
+                    Alu::Move => (self.v[y], None),
+                    Alu::Or => (self.v[x] | self.v[y], quirks.vf_reset.then_some(0)),
+                    Alu::And => (self.v[x] & self.v[y], quirks.vf_reset.then_some(0)),
+                    Alu::Xor => (self.v[x] ^ self.v[y], quirks.vf_reset.then_some(0)),
+                    Alu::Add => {
+                        let (sum, carry) = self.v[x].overflowing_add(self.v[y]);
+                        (sum, Some(carry as u8))
+                    }
+                    Alu::Sub => {
+                        let (difference, borrow) = self.v[x].overflowing_sub(self.v[y]);
+                        (difference, Some(!borrow as u8))
+                    }
+                    Alu::SubN => {
+                        let (difference, borrow) = self.v[y].overflowing_sub(self.v[x]);
+                        (difference, Some(!borrow as u8))
+                    }
+                    Alu::ShiftRight => {
+                        let value = shifted();
+                        (value >> 1, Some(value & 1))
+                    }
+                    Alu::ShiftLeft => {
+                        let value = shifted();
+                        (value << 1, Some(value >> 7))
+                    }
+                };
+                self.v[x] = result;
+                if let Some(flag) = flag {
+                    self.v[0xF] = flag;
+                }
+            }
+            Op::LoadI(nnn) => {
+                self.i = nnn;
+                if let Some(megachip) = &mut self.megachip {
+                    megachip.i_high = 0;
+                }
+            }
+            Op::JumpOffset { x, nnn } => {
+                let offset = if self.settings.quirks.jump_vx {
+                    self.v[x as usize]
+                } else {
+                    self.v[0]
+                };
+                self.pc = nnn.wrapping_add(offset as u16);
+            }
+            Op::Random { x, nn } => self.v[x as usize] = self.rng.next_u8() & nn,
+            Op::Draw { x, y, n } => {
+                if let Some(limit) = self.settings.sprite_limit
+                    && self.sprites_this_frame >= limit
+                {
+                    // retry the same draw after the next timer tick
+                    self.pc = self.pc.wrapping_sub(2);
+                    self.frame_done = true;
+                    return Ok(());
+                }
+                self.sprites_this_frame += 1;
+                self.counts.draws += 1;
+
+                if self.active_megachip().is_some() {
+                    self.last_sprite = None;
+                    self.draw_megachip(x, y)?;
+                    return Ok(());
+                }
+
+                let width = self.display.width();
+                let height = self.display.height();
+                let x_start = self.v[x as usize] as usize % width;
+                let y_start = self.v[y as usize] as usize % height;
+                let wrap = self.settings.quirks.wrap_sprites;
+                self.v[0xF] = 0;
+                // a sprite per selected plane, one after the other from I
+                for (nth, plane) in self.display.selected().enumerate() {
+                    let sprite = self.i as usize + nth * n as usize;
+                    for row in 0..n as usize {
+                        let sprite_byte = self.read(sprite + row, Access::Read)?;
+                        let mut y = y_start + row;
+
+                        if y >= height {
+                            if !wrap {
+                                break;
+                            }
+                            y %= height;
+                        }
+
+                        for bit in 0..8 {
+                            let mut x = x_start + bit;
+                            if x >= width {
+                                if !wrap {
+                                    break;
+                                }
+                                x %= width;
+                            }
+
+                            let sprite_pixel = ((sprite_byte >> (7 - bit)) & 1) != 0;
+                            if !sprite_pixel {
+                                continue;
+                            }
+
+                            if self.display.toggle_plane(plane, x, y) {
+                                self.v[0xF] = 1;
+                            }
+                        }
+                    }
+                }
+                self.last_sprite = Some(SpriteBox {
+                    x: x_start,
+                    y: y_start,
+                    width: 8,
+                    height: n as usize,
+                    collided: self.v[0xF] == 1,
+                });
+
+                if self.settings.quirks.display_wait {
+                    // the vip drew during vblank, the cpu waits for the next frame
+                    self.frame_done = true;
+                }
+            }
+            Op::SkipIfKey(x) | Op::SkipIfNotKey(x) => {
+                let key = (self.v[x as usize] & 0xF) as usize;
+                if self.keypad[key] == matches!(op, Op::SkipIfKey(_)) {
+                    self.skip_next();
+                }
+                self.polled_keys |= 1 << key;
+            }
+            // i := long NNNN, the address is the next word
+            Op::LoadLongI => {
+                if !self.extended_memory() {
+                    return Err(Chip8Error::InvalidOpcode { pc, opcode: 0xF000 });
+                }
+                let high = self.read(self.pc as usize, Access::Fetch)?;
+                let low = self.read(self.pc as usize + 1, Access::Fetch)?;
+                self.i = u16::from_be_bytes([high, low]);
+                self.pc = self.pc.wrapping_add(2);
+            }
+            Op::GetDelay(x) => self.v[x as usize] = self.delay_timer,
+            Op::WaitKey(x) => {
+                self.wait_held = self.held_keys();
+                self.wait_pressed = None;
+                self.run_state = RunState::WaitingForKey { register: x };
+            }
+            Op::SetDelay(x) => self.delay_timer = self.v[x as usize],
+            Op::SetSound(x) => {
+                if self.sound_timer == 0 && self.v[x as usize] > 0 {
+                    self.counts.sounds += 1;
+                }
+                self.sound_timer = self.v[x as usize];
+            }
+            // VF stays, only the amiga interpreter flagged I passing 0xFFF
+            Op::AddI(x) => self.i = self.i.wrapping_add(self.v[x as usize] as u16),
+            // only the low nibble picks the digit
+            Op::Font(x) => {
+                self.i = (FONT_START + (self.v[x as usize] & 0xF) as usize * FONT_SPRITE) as u16
+            }
+            Op::Bcd(x) => {
+                let value = self.v[x as usize];
+                let i = self.i as usize;
+                self.store(i, value / 100)?;
+                self.store(i + 1, (value / 10) % 10)?;
+                self.store(i + 2, value % 10)?;
+            }
+            Op::Store(x) => {
+                for reg in 0..=x as usize {
+                    self.store(self.i as usize + reg, self.v[reg])?;
+                }
+                self.i = self.i.wrapping_add(self.settings.quirks.memory.after(x));
+            }
+            Op::Restore(x) => {
+                for reg in 0..=x as usize {
+                    self.v[reg] = self.read(self.i as usize + reg, Access::Read)?;
+                }
+                self.i = self.i.wrapping_add(self.settings.quirks.memory.after(x));
+            }
+            // superchip has 8 flags, xo-chip all 16
+            Op::SaveFlags(x) => {
+                let count = x as usize + 1;
+                self.rpl[..count].copy_from_slice(&self.v[..count]);
+                self.rpl_store.save(&self.rom_key, &self.rpl);
+            }
+            Op::LoadFlags(x) => {
+                let count = x as usize + 1;
+                self.v[..count].copy_from_slice(&self.rpl[..count]);
+            }
+            Op::Invalid(opcode) => return Err(Chip8Error::InvalidOpcode { pc, opcode }),
+        }
+
+        Ok(())
+    }
+
+    // without set_megachip these are 0NNN machine code routines, ignored
+    fn execute_megachip(&mut self, op: MegaOp) -> Result<(), Chip8Error> {
+        let addr = self.long_i();
+        let Some(megachip) = &mut self.megachip else {
+            return Ok(());
+        };
+        match op {
+            MegaOp::Off => {
+                megachip.active = false;
+                self.switch_geometry(Geometry::LORES);
+            }
+            MegaOp::On => {
+                megachip.active = true;
+                self.switch_geometry(Geometry::MEGACHIP);
+            }
+            MegaOp::LoadHighI(nn) => {
+                megachip.i_high = nn;
+                let high = self.read(self.pc as usize, Access::Fetch)?;
+                let low = self.read(self.pc as usize + 1, Access::Fetch)?;
+                self.i = u16::from_be_bytes([high, low]);
+                self.pc = self.pc.wrapping_add(2);
+            }
+            MegaOp::LoadPalette(count) => {
+                let end = addr + count as usize * 4;
+                let colors = self
+                    .memory
+                    .get(addr..end)
+                    .ok_or(Chip8Error::MemoryOutOfBounds {
+                        pc: self.pc.wrapping_sub(2),
+                        addr: end,
+                    })?;
+                megachip.load_palette(colors);
+            }
+            MegaOp::SpriteWidth(nn) => {
+                megachip.sprite_width = if nn == 0 { 256 } else { nn as usize }
+            }
+            MegaOp::SpriteHeight(nn) => {
+                megachip.sprite_height = if nn == 0 { 256 } else { nn as usize }
+            }
+            MegaOp::ScreenAlpha(nn) => megachip.alpha = nn,
+            MegaOp::PlaySample { looped } => {
+                let sample = Sample::read(&self.memory, addr, looped);
+                megachip.set_sample(sample);
+            }
+            MegaOp::StopSample => megachip.set_sample(None),
+            MegaOp::Blend(mode) => megachip.blend = Blend::from_mode(mode),
+            MegaOp::CollisionColor(nn) => megachip.collision = nn,
+            MegaOp::ScrollUp(n) => {
+                if megachip.active {
+                    megachip.scroll(0, -(n as isize));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // DXYN on the megachip screen: a sprite of palette indices from the long I
+    fn draw_megachip(&mut self, x: u8, y: u8) -> Result<(), Chip8Error> {
+        let addr = self.long_i();
+        let (x, y) = (self.v[x as usize] as usize, self.v[y as usize] as usize);
+        let Some(megachip) = self.megachip.as_mut() else {
+            return Ok(());
+        };
+        let end = addr + megachip.sprite_width * megachip.sprite_height;
+        let sprite = self
+            .memory
+            .get(addr..end)
+            .ok_or(Chip8Error::MemoryOutOfBounds {
+                pc: self.pc.wrapping_sub(2),
+                addr: end,
+            })?;
+        self.v[0xF] = megachip.draw(x, y, sprite) as u8;
+        Ok(())
+    }
+
+    // skips over the next instruction, which is two words for F000 NNNN
+    fn skip_next(&mut self) {
+        let long = self.extended_memory()
+            && self.read_memory(self.pc as usize).ok() == Some(0xF0)
+            && self.read_memory(self.pc as usize + 1).ok() == Some(0x00);
+        self.pc = self.pc.wrapping_add(if long { 4 } else { 2 });
+    }
+}
@@ -0,0 +1,166 @@
+// the last few seconds of play as a gif for sharing: the screen, an optional
+// caption above it and a strip of the hex keypad below lighting up the keys
+// that were held
+use crate::backend::{KEYPAD_ROWS, glyph};
+use crate::display::Display;
+use crate::gif::{self, GifFrame};
+use crate::palette::Palette;
+use std::collections::VecDeque;
+
+pub const MAX_SECONDS: usize = 10;
+const MAX_FRAMES: usize = MAX_SECONDS * 60;
+const SCALE: usize = 2;
+// palette indices, see Palette
+const BACKGROUND: u8 = 0;
+const FOREGROUND: u8 = 1;
+const DIM: u8 = 3;
+// glyphs are 3x5, drawn at CAPTION_SCALE in the caption and 1 in the strip
+const CAPTION_SCALE: usize = 2;
+const CAPTION_HEIGHT: usize = 5 * CAPTION_SCALE + 4;
+const STRIP_HEIGHT: usize = 9;
+
+#[derive(Default)]
+pub struct ClipRecorder {
+    // one per 60Hz frame, oldest first
+    frames: VecDeque<(Display, [bool; 16])>,
+}
+
+impl ClipRecorder {
+    pub fn new() -> Self {
+        ClipRecorder::default()
+    }
+
+    pub fn push(&mut self, display: &Display, keypad: &[bool; 16]) {
+        // reuses the oldest frame's buffer once the clip is full
+        let mut oldest = if self.frames.len() == MAX_FRAMES {
+            self.frames.pop_front()
+        } else {
+            None
+        };
+        match &mut oldest {
+            Some((buffer, keys)) => {
+                buffer.clone_from(display);
+                *keys = *keypad;
+            }
+            None => oldest = Some((display.clone(), *keypad)),
+        }
+        self.frames.extend(oldest);
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    // 30 frames per second is as smooth as gif delays get across browsers,
+    // runs of identical frames become one longer frame
+    pub fn export(&self, palette: &Palette, caption: Option<&str>) -> Vec<u8> {
+        let width = self
+            .frames
+            .iter()
+            .map(|(d, _)| d.width())
+            .max()
+            .unwrap_or(64)
+            * SCALE;
+        let caption = caption.filter(|text| !text.trim().is_empty());
+        let top = if caption.is_some() { CAPTION_HEIGHT } else { 0 };
+        let screen_height = width / 2;
+        let height = top + screen_height + STRIP_HEIGHT;
+
+        let mut frames: Vec<GifFrame> = Vec::new();
+        let mut last = None;
+        for (n, (display, keypad)) in self.frames.iter().enumerate().step_by(2) {
+            // hundredths of a second since the start of the clip
+            let delay = ((n + 2) * 100 / 60 - n * 100 / 60) as u16;
+            if let Some(frame) = frames.last_mut()
+                && last == Some((display, keypad))
+            {
+                frame.delay += delay;
+                continue;
+            }
+            last = Some((display, keypad));
+
+            let mut canvas = Canvas::new(width, height);
+            if let Some(text) = caption {
+                canvas.text(text, 2, 2, CAPTION_SCALE, FOREGROUND);
+            }
+            let scale = width / display.width();
+            for (x, y, _) in display.iter_pixels().filter(|&(_, _, on)| on) {
+                canvas.fill_rect(x * scale, top + y * scale, scale, scale, FOREGROUND);
+            }
+            canvas.keypad_strip(keypad, top + screen_height);
+            frames.push(GifFrame {
+                pixels: canvas.pixels,
+                delay,
+            });
+        }
+        gif::encode(width, height, palette, &frames)
+    }
+}
+
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Canvas {
+            width,
+            height,
+            pixels: vec![BACKGROUND; width * height],
+        }
+    }
+
+    fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize, color: u8) {
+        for py in y..(y + h).min(self.height) {
+            for px in x..(x + w).min(self.width) {
+                self.pixels[py * self.width + px] = color;
+            }
+        }
+    }
+
+    // cut off at the right edge, characters without a glyph are blank
+    fn text(&mut self, text: &str, x: usize, y: usize, scale: usize, color: u8) {
+        for (n, c) in text.chars().enumerate() {
+            let left = x + n * 4 * scale;
+            if left + 3 * scale > self.width {
+                break;
+            }
+            let Some(rows) = glyph(c) else {
+                continue;
+            };
+            for (row, bits) in rows.iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (0b100 >> col) != 0 {
+                        self.fill_rect(left + col * scale, y + row * scale, scale, scale, color);
+                    }
+                }
+            }
+        }
+    }
+
+    // the 16 keys in hex keypad order, 123C 456D 789E A0BF
+    fn keypad_strip(&mut self, keypad: &[bool; 16], y: usize) {
+        let cell = self.width / 16;
+        for (n, key) in KEYPAD_ROWS.iter().flatten().enumerate() {
+            let (fill, label) = if keypad[*key as usize] {
+                (FOREGROUND, BACKGROUND)
+            } else {
+                (DIM, FOREGROUND)
+            };
+            let x = n * cell;
+            self.fill_rect(x + 1, y + 1, cell - 2, STRIP_HEIGHT - 2, fill);
+            let label_x = x + (cell - 3) / 2;
+            self.text(&format!("{key:X}"), label_x, y + 2, 1, label);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+// one rom on two machines at once, fed the same keys, to find the quirks a
+// rom needs: the screens go side by side and the first frame where they
+// differ is kept. each side is a quirk list and optionally a speed,
+// QUIRKS[@IPS] as compare takes it:
+//
+//     chip8 compare ROM cosmac-vip superchip-modern@1800
+use crate::chip8::{Chip8State, Keypad};
+use crate::display::{Display, Geometry};
+use crate::error::Chip8Error;
+use crate::settings::{DEFAULT_INSTRUCTIONS_PER_SECOND, Quirks};
+use crate::state_diff::{StateDiff, diff};
+
+// blank columns between the screens, lit once they diverged
+pub const GAP: usize = 2;
+const SEED: u32 = 1;
+const FRAMES_PER_SECOND: u32 = 60;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Side {
+    // as given, for the status line
+    pub label: String,
+    pub quirks: Quirks,
+    pub instructions_per_second: u32,
+}
+
+impl Side {
+    // "cosmac-vip" or "vf-reset,shift@1200"
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (list, speed) = match text.split_once('@') {
+            Some((list, speed)) => (list, Some(speed)),
+            None => (text, None),
+        };
+        let mut quirks = Quirks::default();
+        quirks
+            .enable(list)
+            .map_err(|name| format!("unknown quirk or preset {name}"))?;
+        let instructions_per_second = match speed {
+            Some(speed) => speed
+                .parse()
+                .ok()
+                .filter(|&ips| ips >= FRAMES_PER_SECOND)
+                .ok_or_else(|| {
+                    format!("{speed} isn't a speed, expected instructions per second")
+                })?,
+            None => DEFAULT_INSTRUCTIONS_PER_SECOND,
+        };
+        Ok(Side {
+            label: text.to_owned(),
+            quirks,
+            instructions_per_second,
+        })
+    }
+
+    fn instructions_per_frame(&self) -> usize {
+        (self.instructions_per_second / FRAMES_PER_SECOND) as usize
+    }
+}
+
+pub struct Comparison {
+    sides: [Side; 2],
+    states: [Chip8State; 2],
+    // a side that failed stops where it failed
+    errors: [Option<Chip8Error>; 2],
+    frame: u64,
+    diverged: Option<u64>,
+}
+
+impl Comparison {
+    pub fn new(rom: &[u8], sides: [Side; 2]) -> Result<Self, Chip8Error> {
+        let load = |side: &Side| {
+            let mut state = Chip8State::with_seed(SEED);
+            state.settings.quirks = side.quirks;
+            state.settings.instructions_per_second = side.instructions_per_second;
+            state.load(rom).map(|()| state)
+        };
+        let states = [load(&sides[0])?, load(&sides[1])?];
+        Ok(Comparison {
+            sides,
+            states,
+            errors: [None, None],
+            frame: 0,
+            diverged: None,
+        })
+    }
+
+    pub fn sides(&self) -> &[Side; 2] {
+        &self.sides
+    }
+
+    pub fn states(&self) -> &[Chip8State; 2] {
+        &self.states
+    }
+
+    pub fn errors(&self) -> &[Option<Chip8Error>; 2] {
+        &self.errors
+    }
+
+    // frames run so far
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    // the first frame after which the screens differed, or one side failed
+    pub fn diverged(&self) -> Option<u64> {
+        self.diverged
+    }
+
+    // both sides see the same keys
+    pub fn set_keypad(&mut self, keypad: &Keypad) {
+        for state in &mut self.states {
+            state.keypad = *keypad;
+        }
+    }
+
+    // a frame of each side at its own speed, then the screens compared
+    pub fn run_frame(&mut self) {
+        for ((side, state), error) in self
+            .sides
+            .iter()
+            .zip(&mut self.states)
+            .zip(&mut self.errors)
+        {
+            if error.is_none()
+                && let Err(err) = state.run_frame(side.instructions_per_frame())
+            {
+                *error = Some(err);
+            }
+        }
+        self.frame += 1;
+        let failed = self.errors.iter().any(Option::is_some);
+        if self.diverged.is_none() && (failed || self.states[0].display != self.states[1].display) {
+            self.diverged = Some(self.frame);
+        }
+    }
+
+    // everything that differs between the machines now, not only the screen
+    pub fn state_diff(&self) -> StateDiff {
+        diff(&self.states[0].snapshot(), &self.states[1].snapshot())
+    }
+
+    // both screens in one display, GAP columns apart and aligned at the top
+    pub fn screen(&self) -> Display {
+        let [a, b] = [&self.states[0].display, &self.states[1].display];
+        let mut screen = Display::new(Geometry::new(
+            a.width() + GAP + b.width(),
+            a.height().max(b.height()),
+        ));
+        for (left, display) in [(0, a), (a.width() + GAP, b)] {
+            for (x, y, on) in display.iter_pixels().filter(|&(_, _, on)| on) {
+                screen.set(left + x, y, on);
+            }
+        }
+        if self.diverged.is_some() {
+            for y in 0..screen.height() {
+                for x in a.width()..a.width() + GAP {
+                    screen.set(x, y, true);
+                }
+            }
+        }
+        screen
+    }
+
+    // for the status line: the frame and where they parted, and why a side
+    // stopped
+    pub fn status(&self) -> String {
+        let mut status = match self.diverged {
+            Some(frame) => format!("frame {}, diverged at frame {frame}", self.frame),
+            None => format!("frame {}, identical", self.frame),
+        };
+        for (side, error) in self.sides.iter().zip(&self.errors) {
+            if let Some(err) = error {
+                status.push_str(&format!(" - {} stopped: {err}", side.label));
+            }
+        }
+        status
+    }
+}
@@ -0,0 +1,251 @@
+// the debug console: reads and writes memory and registers of a running
+// machine, so a cheat or a "what if V3 were 0 here" needs no rebuilt rom.
+// numbers are hex, writes only go to a paused or halted machine
+//
+//     peek 2A0 8        8 bytes from 2A0, 16 without a count
+//     poke 2A0 FF 00    writes them
+//     regs              v0-vF, I, pc and the timers
+//     set v3 0          one register, also i, pc, dt and st
+//     dump mem.bin      all of memory to a file, for a hex editor
+//     load mem.bin      and back, the file has to be the size of memory
+//     stats             what the run did so far, see stats
+use crate::chip8::{Chip8State, RunState};
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Register {
+    V(u8),
+    I,
+    Pc,
+    Delay,
+    Sound,
+}
+
+impl Register {
+    pub fn parse(text: &str) -> Option<Self> {
+        let lower = text.to_ascii_lowercase();
+        Some(match lower.as_str() {
+            "i" => Register::I,
+            "pc" => Register::Pc,
+            "dt" => Register::Delay,
+            "st" => Register::Sound,
+            _ => {
+                let x = lower.strip_prefix('v')?;
+                Register::V(u8::from_str_radix(x, 16).ok().filter(|_| x.len() == 1)?)
+            }
+        })
+    }
+
+    pub fn get(&self, state: &Chip8State) -> u16 {
+        match self {
+            Register::V(x) => state.v[*x as usize] as u16,
+            Register::I => state.i,
+            Register::Pc => state.pc,
+            Register::Delay => state.delay_timer as u16,
+            Register::Sound => state.sound_timer as u16,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Register::V(x) => format!("v{x:X}"),
+            Register::I => "i".to_owned(),
+            Register::Pc => "pc".to_owned(),
+            Register::Delay => "dt".to_owned(),
+            Register::Sound => "st".to_owned(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DebugCommand {
+    Peek { addr: usize, len: usize },
+    Poke { addr: usize, bytes: Vec<u8> },
+    Registers,
+    Set(Register, u16),
+    Dump(PathBuf),
+    Load(PathBuf),
+    Stats,
+}
+
+const PEEK_LEN: usize = 16;
+
+// "2A0" or "0x2A0"
+pub fn hex(text: &str) -> Result<u16, String> {
+    let digits = text
+        .strip_prefix("0x")
+        .or_else(|| text.strip_prefix("0X"))
+        .unwrap_or(text);
+    u16::from_str_radix(digits, 16).map_err(|_| format!("{text} is not a hex number"))
+}
+
+fn byte(text: &str) -> Result<u8, String> {
+    u8::try_from(hex(text)?).map_err(|_| format!("{text} doesn't fit in a byte"))
+}
+
+impl DebugCommand {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        // paths are the rest of the line, spaces and all
+        let (word, path) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+        let path = PathBuf::from(path.trim());
+        match word {
+            "dump" | "load" if path.as_os_str().is_empty() => {
+                return Err(format!("{word} expects a path"));
+            }
+            "dump" => return Ok(DebugCommand::Dump(path)),
+            "load" => return Ok(DebugCommand::Load(path)),
+            _ => {}
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        Ok(match words.as_slice() {
+            ["peek", addr] => DebugCommand::Peek {
+                addr: hex(addr)? as usize,
+                len: PEEK_LEN,
+            },
+            ["peek", addr, len] => DebugCommand::Peek {
+                addr: hex(addr)? as usize,
+                len: hex(len)? as usize,
+            },
+            ["poke", addr, bytes @ ..] if !bytes.is_empty() => DebugCommand::Poke {
+                addr: hex(addr)? as usize,
+                bytes: bytes
+                    .iter()
+                    .map(|text| byte(text))
+                    .collect::<Result<_, _>>()?,
+            },
+            ["regs"] => DebugCommand::Registers,
+            ["stats"] => DebugCommand::Stats,
+            ["set", register, value] => DebugCommand::Set(
+                Register::parse(register).ok_or_else(|| format!("unknown register {register}"))?,
+                hex(value)?,
+            ),
+            _ => {
+                return Err(
+                    "expected peek ADDR [LEN], poke ADDR BYTE..., regs, set REG VALUE, dump PATH, load PATH \
+                     or stats"
+                        .to_owned(),
+                );
+            }
+        })
+    }
+
+    // changes the machine
+    pub fn writes(&self) -> bool {
+        matches!(
+            self,
+            DebugCommand::Poke { .. } | DebugCommand::Set(..) | DebugCommand::Load(_)
+        )
+    }
+
+    // what to show for it, the registers or bytes it read or wrote
+    pub fn apply(&self, state: &mut Chip8State) -> Result<String, String> {
+        if self.writes() && !matches!(state.run_state(), RunState::Paused | RunState::Halted) {
+            return Err("pause the machine before changing it".to_owned());
+        }
+        self.execute(state)
+    }
+
+    // apply without asking for a paused machine, for callers that run
+    // between two instructions anyway like scripts
+    pub fn execute(&self, state: &mut Chip8State) -> Result<String, String> {
+        match self {
+            DebugCommand::Peek { addr, len } => peek(state, *addr, *len),
+            DebugCommand::Poke { addr, bytes } => {
+                if addr + bytes.len() > state.memory.len() {
+                    return Err(format!("{addr:03X} is out of memory"));
+                }
+                for (n, value) in bytes.iter().enumerate() {
+                    state
+                        .write_memory(addr + n, *value)
+                        .map_err(|err| err.to_string())?;
+                }
+                peek(state, *addr, bytes.len())
+            }
+            DebugCommand::Registers => Ok(registers(state)),
+            DebugCommand::Stats => Ok(state.stats().to_string()),
+            DebugCommand::Set(register, value) => {
+                let fits_byte =
+                    || u8::try_from(*value).map_err(|_| format!("{value:X} doesn't fit in a byte"));
+                match register {
+                    Register::V(x) => state.v[*x as usize] = fits_byte()?,
+                    Register::I => state.i = *value,
+                    Register::Pc if *value as usize >= state.memory.len() => {
+                        return Err(format!("{value:03X} is out of memory"));
+                    }
+                    Register::Pc => state.pc = *value,
+                    Register::Delay => state.delay_timer = fits_byte()?,
+                    Register::Sound => state.sound_timer = fits_byte()?,
+                }
+                Ok(format!("{} = {value:X}", register.name()))
+            }
+            DebugCommand::Dump(path) => {
+                fs::write(path, &state.memory)
+                    .map_err(|err| format!("unable to write {}: {err}", path.display()))?;
+                Ok(format!(
+                    "{} bytes to {}",
+                    state.memory.len(),
+                    path.display()
+                ))
+            }
+            DebugCommand::Load(path) => {
+                let image = fs::read(path)
+                    .map_err(|err| format!("unable to read {}: {err}", path.display()))?;
+                if image.len() != state.memory.len() {
+                    return Err(format!(
+                        "{} is {} bytes, memory is {}",
+                        path.display(),
+                        image.len(),
+                        state.memory.len()
+                    ));
+                }
+                for (addr, value) in image.into_iter().enumerate() {
+                    state
+                        .write_memory(addr, value)
+                        .map_err(|err| err.to_string())?;
+                }
+                Ok(format!(
+                    "{} bytes from {}",
+                    state.memory.len(),
+                    path.display()
+                ))
+            }
+        }
+    }
+}
+
+fn peek(state: &Chip8State, addr: usize, len: usize) -> Result<String, String> {
+    let bytes = state
+        .memory
+        .get(addr..addr.saturating_add(len))
+        .ok_or_else(|| format!("{addr:03X} is out of memory"))?;
+    let mut out = String::new();
+    for (row, chunk) in bytes.chunks(PEEK_LEN).enumerate() {
+        if row > 0 {
+            out.push('\n');
+        }
+        let _ = write!(out, "{:03X} ", addr + row * PEEK_LEN);
+        for byte in chunk {
+            let _ = write!(out, " {byte:02X}");
+        }
+    }
+    Ok(out)
+}
+
+fn registers(state: &Chip8State) -> String {
+    let v: Vec<String> = state
+        .v
+        .iter()
+        .enumerate()
+        .map(|(x, value)| format!("v{x:X}={value:02X}"))
+        .collect();
+    format!(
+        "{}\ni={:03X} pc={:03X} dt={:02X} st={:02X}",
+        v.join(" "),
+        state.i,
+        state.pc,
+        state.delay_timer,
+        state.sound_timer
+    )
+}
@@ -0,0 +1,86 @@
+// renders one rom under every quirks preset side by side, each screen
+// labeled with its preset, to see at a glance what a quirk changes
+use crate::backend::glyph;
+use crate::chip8::Chip8State;
+use crate::display::Display;
+use crate::error::Chip8Error;
+use crate::palette::{Palette, Rgb};
+use crate::png::RgbImage;
+use crate::settings::Quirks;
+use crate::thumbnail::{self, MAX_FRAMES};
+
+const SCALE: usize = 4;
+const LABEL_SCALE: usize = 2;
+const PADDING: usize = 8;
+const INSTRUCTIONS_PER_FRAME: usize = 10;
+const SEED: u32 = 1;
+
+// the screen after `frames` frames with these quirks
+pub fn capture(rom: &[u8], quirks: Quirks, frames: usize) -> Result<Display, Chip8Error> {
+    let mut state = Chip8State::with_seed(SEED);
+    state.settings.quirks = quirks;
+    state.load(rom)?;
+    for _ in 0..frames {
+        state.run_frame(INSTRUCTIONS_PER_FRAME)?;
+    }
+    Ok(state.display)
+}
+
+// frames None captures each preset's title screen, see thumbnail
+pub fn render(
+    rom: &[u8],
+    frames: Option<usize>,
+    palette: &Palette,
+) -> Result<RgbImage, Chip8Error> {
+    let screens = Quirks::PRESETS
+        .iter()
+        .map(|(name, quirks)| {
+            let display = match frames {
+                Some(frames) => capture(rom, *quirks, frames)?,
+                None => thumbnail::capture_title_screen(rom, *quirks, MAX_FRAMES)?,
+            };
+            Ok((*name, display))
+        })
+        .collect::<Result<Vec<_>, Chip8Error>>()?;
+
+    let label_height = 5 * LABEL_SCALE + PADDING;
+    let cell_width = screens[0].1.width() * SCALE;
+    let cell_height = screens[0].1.height() * SCALE;
+    let mut sheet = RgbImage::new(
+        PADDING + screens.len() * (cell_width + PADDING),
+        PADDING + label_height + cell_height + PADDING,
+        (0x20, 0x20, 0x20),
+    );
+
+    for (n, (name, display)) in screens.iter().enumerate() {
+        let left = PADDING + n * (cell_width + PADDING);
+        draw_label(&mut sheet, name, left, PADDING);
+
+        let top = PADDING + label_height;
+        for y in 0..display.height() {
+            for x in 0..display.width() {
+                let Rgb(r, g, b) = palette.colors[display.pixel(x, y) as usize];
+                sheet.fill_rect(left + x * SCALE, top + y * SCALE, SCALE, SCALE, (r, g, b));
+            }
+        }
+    }
+    Ok(sheet)
+}
+
+fn draw_label(sheet: &mut RgbImage, text: &str, left: usize, top: usize) {
+    for (n, c) in text.chars().enumerate() {
+        // the glyph font has no punctuation, leave a gap
+        let Some(rows) = glyph(c) else {
+            continue;
+        };
+        let x0 = left + n * 4 * LABEL_SCALE;
+        for (dy, bits) in rows.iter().enumerate() {
+            for dx in 0..3 {
+                if bits & (0b100 >> dx) != 0 {
+                    let (x, y) = (x0 + dx * LABEL_SCALE, top + dy * LABEL_SCALE);
+                    sheet.fill_rect(x, y, LABEL_SCALE, LABEL_SCALE, (0xFF, 0xFF, 0xFF));
+                }
+            }
+        }
+    }
+}
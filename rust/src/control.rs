@@ -0,0 +1,110 @@
+// a local socket for launchers and scripts to drive a running emulator,
+// chip8 --control PATH. every request is one line, every reply ends in "ok"
+// or "err MESSAGE":
+//
+//     load PATH          play another rom
+//     pause              stop the cpu
+//     resume             run again
+//     reset              start the rom over
+//     save PATH          write a savestate
+//     restore PATH       go back to one
+//     poke ADDR BYTES..  as the console's, numbers are hex
+//     screenshot PATH    a png of the screen
+//
+// a unix domain socket, there are no named pipes for windows yet
+use crate::console::DebugCommand;
+use crate::line_server::{self, LineServer};
+use std::fs;
+use std::io;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::TryIter;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ControlCommand {
+    Load(PathBuf),
+    Pause,
+    Resume,
+    Reset,
+    Save(PathBuf),
+    Restore(PathBuf),
+    // always a DebugCommand::Poke
+    Poke(DebugCommand),
+    Screenshot(PathBuf),
+}
+
+impl ControlCommand {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        // paths are the rest of the line, spaces and all
+        let (word, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let path = || match rest.trim() {
+            "" => Err(format!("{word} expects a path")),
+            path => Ok(PathBuf::from(path)),
+        };
+        Ok(match word {
+            "load" => ControlCommand::Load(path()?),
+            "pause" => ControlCommand::Pause,
+            "resume" => ControlCommand::Resume,
+            "reset" => ControlCommand::Reset,
+            "save" => ControlCommand::Save(path()?),
+            "restore" => ControlCommand::Restore(path()?),
+            "poke" => ControlCommand::Poke(DebugCommand::parse(line)?),
+            "screenshot" => ControlCommand::Screenshot(path()?),
+            _ => return Err(format!("unknown command {word}")),
+        })
+    }
+}
+
+pub type Request = line_server::Request<ControlCommand>;
+
+// a LineServer over a unix socket, which it removes when dropped
+pub struct ControlServer {
+    path: PathBuf,
+    server: LineServer<ControlCommand>,
+}
+
+impl ControlServer {
+    // a socket left behind by an emulator that didn't exit cleanly is
+    // replaced, one that still answers is not, and anything else there is
+    // left alone
+    pub fn bind(path: &Path) -> io::Result<Self> {
+        if UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                "another emulator is listening there",
+            ));
+        }
+        match fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.file_type().is_socket() => fs::remove_file(path)?,
+            Ok(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    "something other than a socket is there",
+                ));
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+        let listener = UnixListener::bind(path)?;
+        Ok(ControlServer {
+            path: path.to_owned(),
+            server: LineServer::spawn(listener, ControlCommand::parse),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn requests(&self) -> TryIter<'_, Request> {
+        self.server.requests()
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
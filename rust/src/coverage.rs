@@ -0,0 +1,156 @@
+// which opcode handlers a run reached and what it did with each byte of
+// memory: executed, only read as data, or written. did the test rom hit
+// 8XY6, where does the code of an unknown rom end and its data begin
+use crate::heatmap::Access;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::profile::{handler, pattern};
+use alloc::collections::BTreeSet;
+use core::fmt;
+
+// the handler keys decode_and_execute implements, see profile::handler.
+// keep in sync with it
+pub const IMPLEMENTED: &[u16] = &[
+    0x0000, 0x00C0, 0x00D0, 0x00E0, 0x00EE, 0x00FB, 0x00FC, 0x00FE, 0x00FF, 0x1000, 0x2000, 0x3000,
+    0x4000, 0x6000, 0x7000, 0x8000, 0xA000, 0xC000, 0xD000, 0xE09E, 0xE0A1, 0xF000, 0xF007, 0xF00A,
+    0xF015, 0xF018, 0xF033, 0xF055, 0xF065, 0xF075, 0xF085,
+];
+
+const FETCHED: u8 = 1;
+const READ: u8 = 2;
+const WRITTEN: u8 = 4;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Coverage {
+    handlers: BTreeSet<u16>,
+    // FETCHED | READ | WRITTEN per byte, grown to the highest address seen
+    memory: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Region {
+    Executed,
+    Read,
+    Written,
+    // read and written, variables and the like
+    ReadWritten,
+}
+
+impl Region {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Region::Executed => "executed",
+            Region::Read => "read",
+            Region::Written => "written",
+            Region::ReadWritten => "read and written",
+        }
+    }
+}
+
+impl Coverage {
+    pub fn new() -> Self {
+        Coverage::default()
+    }
+
+    pub fn record_opcode(&mut self, opcode: u16) {
+        self.handlers.insert(handler(opcode));
+    }
+
+    pub fn record_access(&mut self, addr: usize, access: Access) {
+        if addr >= self.memory.len() {
+            self.memory.resize(addr + 1, 0);
+        }
+        self.memory[addr] |= match access {
+            Access::Fetch => FETCHED,
+            Access::Read => READ,
+            Access::Write => WRITTEN,
+        };
+    }
+
+    // handler keys that ran, implemented or not
+    pub fn handlers(&self) -> &BTreeSet<u16> {
+        &self.handlers
+    }
+
+    pub fn missed(&self) -> impl Iterator<Item = u16> + '_ {
+        IMPLEMENTED
+            .iter()
+            .copied()
+            .filter(|key| !self.handlers.contains(key))
+    }
+
+    // runs the interpreter has no handler for, executed as whatever it
+    // falls through to or skipped
+    pub fn unimplemented(&self) -> impl Iterator<Item = u16> + '_ {
+        self.handlers
+            .iter()
+            .copied()
+            .filter(|key| !IMPLEMENTED.contains(key))
+    }
+
+    // (first, last, region) for every run of addresses used the same way,
+    // a byte that was executed counts as executed whatever else happened to it
+    pub fn regions(&self) -> Vec<(usize, usize, Region)> {
+        let region = |flags: u8| match flags {
+            0 => None,
+            f if f & FETCHED != 0 => Some(Region::Executed),
+            READ => Some(Region::Read),
+            WRITTEN => Some(Region::Written),
+            _ => Some(Region::ReadWritten),
+        };
+        let mut regions: Vec<(usize, usize, Region)> = Vec::new();
+        for (addr, flags) in self.memory.iter().enumerate() {
+            let Some(region) = region(*flags) else {
+                continue;
+            };
+            match regions.last_mut() {
+                Some((_, last, previous)) if *last + 1 == addr && *previous == region => {
+                    *last = addr;
+                }
+                _ => regions.push((addr, addr, region)),
+            }
+        }
+        regions
+    }
+}
+
+fn patterns(keys: impl Iterator<Item = u16>) -> String {
+    let names: Vec<String> = keys.map(pattern).collect();
+    if names.is_empty() {
+        "none".to_owned()
+    } else {
+        names.join(" ")
+    }
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reached = IMPLEMENTED
+            .iter()
+            .filter(|key| self.handlers.contains(key))
+            .count();
+        writeln!(
+            f,
+            "opcodes: {reached} of {} implemented ones ran",
+            IMPLEMENTED.len()
+        )?;
+        let ran = self
+            .handlers
+            .iter()
+            .copied()
+            .filter(|key| IMPLEMENTED.contains(key));
+        writeln!(f, "  ran:           {}", patterns(ran))?;
+        writeln!(f, "  missed:        {}", patterns(self.missed()))?;
+        writeln!(f, "  unimplemented: {}", patterns(self.unimplemented()))?;
+        writeln!(f, "memory:")?;
+        for (first, last, region) in self.regions() {
+            writeln!(
+                f,
+                "  {first:03X}-{last:03X} {:>5} bytes {}",
+                last - first + 1,
+                region.name()
+            )?;
+        }
+        Ok(())
+    }
+}
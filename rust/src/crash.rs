@@ -0,0 +1,115 @@
+// crash dumps: everything a bug report needs when a program dies on an
+// error the interpreter can't go on from, the registers, the stack, the
+// instructions that led there and all of memory, as one text file
+use crate::chip8::{Chip8State, Instruction};
+use crate::dirs::state_dir;
+use crate::disasm::{self, HEXDUMP_COLUMNS, HexLine};
+use crate::error::Chip8Error;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+// errors of the running program, as opposed to ones of the rom file or the
+// host
+pub fn is_crash(err: &Chip8Error) -> bool {
+    matches!(
+        err,
+        Chip8Error::InvalidOpcode { .. }
+            | Chip8Error::StackOverflow { .. }
+            | Chip8Error::StackUnderflow { .. }
+            | Chip8Error::MemoryOutOfBounds { .. }
+            | Chip8Error::ProtectedWrite { .. }
+    )
+}
+
+pub fn dump(state: &Chip8State, err: &Chip8Error) -> String {
+    let mut out = String::new();
+    let time = state.emulated_time();
+    let _ = writeln!(out, "error: {err}");
+    let _ = writeln!(out, "rom:   sha1 {}", state.rom_key());
+    let _ = writeln!(out, "time:  frame {}, cycle {}", time.frames, time.cycles);
+    let _ = writeln!(
+        out,
+        "speed: {} instructions per second",
+        state.settings.instructions_per_second
+    );
+    let _ = writeln!(out, "{:?}", state.settings.quirks);
+
+    let _ = writeln!(
+        out,
+        "\npc {:03X}  i {:03X}  dt {:02X}  st {:02X}",
+        state.pc, state.i, state.delay_timer, state.sound_timer
+    );
+    for (row, registers) in state.v.chunks(8).enumerate() {
+        let line: Vec<String> = registers
+            .iter()
+            .enumerate()
+            .map(|(n, value)| format!("v{:X} {value:02X}", row * 8 + n))
+            .collect();
+        let _ = writeln!(out, "{}", line.join("  "));
+    }
+    let stack: Vec<String> = state
+        .stack
+        .iter()
+        .map(|addr| format!("{addr:03X}"))
+        .collect();
+    let _ = writeln!(
+        out,
+        "stack: {}",
+        if stack.is_empty() {
+            "empty".to_owned()
+        } else {
+            stack.join(" ")
+        }
+    );
+
+    let _ = writeln!(out, "\nlast instructions, oldest first:");
+    for (pc, opcode) in state.recent_instructions() {
+        let [high, low] = opcode.to_be_bytes();
+        let mnemonic = disasm::mnemonic(&Instruction::new(high, low));
+        let _ = writeln!(out, "  {pc:03X}: {opcode:04X}  {mnemonic}");
+    }
+
+    // rows like the one before are left out, hexdump -C style
+    let _ = writeln!(out, "\nmemory:");
+    let mut previous: Option<&[u8]> = None;
+    let mut skipping = false;
+    for (row, bytes) in state.memory.chunks(HEXDUMP_COLUMNS).enumerate() {
+        if previous == Some(bytes) {
+            if !skipping {
+                let _ = writeln!(out, "*");
+                skipping = true;
+            }
+            continue;
+        }
+        let line = HexLine {
+            address: (row * HEXDUMP_COLUMNS) as u16,
+            bytes: bytes.to_vec(),
+            mnemonics: Vec::new(),
+        };
+        let _ = writeln!(out, "{}", line.to_string().trim_end());
+        previous = Some(bytes);
+        skipping = false;
+    }
+    out
+}
+
+// the crashes directory of state_dir
+pub fn default_dir() -> Option<PathBuf> {
+    state_dir("crashes")
+}
+
+// dir/crash-<unix seconds>.txt, a number appended if that one exists
+pub fn write(dir: &Path, state: &Chip8State, err: &Chip8Error) -> Result<PathBuf, String> {
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    std::fs::create_dir_all(dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+    let mut path = dir.join(format!("crash-{seconds}.txt"));
+    let mut n = 1;
+    while path.exists() {
+        n += 1;
+        path = dir.join(format!("crash-{seconds}-{n}.txt"));
+    }
+    std::fs::write(&path, dump(state, err)).map_err(|err| format!("{}: {err}", path.display()))?;
+    Ok(path)
+}
@@ -0,0 +1,203 @@
+// rom identification by sha1 against the programs.json of the chip-8
+// community archive (github.com/chip-8/chip-8-database). the built in copy
+// only knows the roms that ship with the emulator, a full programs.json at
+// Database::default_file adds the archive
+use crate::json::{self, Value};
+use crate::settings::{MemoryIncrement, Quirks};
+pub use crate::sha1::{sha1, sha1_hex};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RomInfo {
+    pub title: String,
+    pub authors: Vec<String>,
+    pub release: Option<String>,
+    // archive platform ids like "superchip", the one to run it on first
+    pub platforms: Vec<String>,
+    // instructions per 60Hz frame
+    pub tickrate: Option<u32>,
+    // archive quirk names like "shift" and whether the rom wants them, on
+    // top of the first platform's own
+    pub quirks: Vec<(String, bool)>,
+}
+
+impl RomInfo {
+    pub fn platform(&self) -> Option<&str> {
+        self.platforms.first().map(String::as_str)
+    }
+
+    // the quirks of the first platform as the closest preset, with the
+    // rom's own quirks on top, and the tickrate
+    pub fn apply(&self, quirks: &mut Quirks, instructions_per_second: &mut u32) {
+        *quirks = match self.platform() {
+            Some("originalChip8" | "hybridVIP") => Quirks::COSMAC_VIP,
+            Some("chip48") => Quirks::CHIP_48,
+            Some("superchip1") => Quirks::SUPERCHIP_LEGACY,
+            Some("superchip") => Quirks::SUPERCHIP_MODERN,
+            Some("xochip") => Quirks::XO_CHIP,
+            _ => Quirks::MODERN,
+        };
+        for (name, on) in &self.quirks {
+            match name.as_str() {
+                "vblank" => quirks.display_wait = *on,
+                "wrap" => quirks.wrap_sprites = *on,
+                "logic" => quirks.vf_reset = *on,
+                "shift" => quirks.shift_vx = *on,
+                "jump" => quirks.jump_vx = *on,
+                "memoryIncrementByX" if *on => quirks.memory = MemoryIncrement::ByX,
+                "memoryLeaveIUnchanged" if *on => quirks.memory = MemoryIncrement::Unchanged,
+                _ => {}
+            }
+        }
+        if let Some(tickrate) = self.tickrate {
+            *instructions_per_second = tickrate.saturating_mul(60);
+        }
+    }
+
+    // quirks the rom turns on that this interpreter doesn't have
+    pub fn unsupported(&self) -> Vec<&str> {
+        self.quirks
+            .iter()
+            .filter(|(name, on)| *on && !SUPPORTED_QUIRKS.contains(&name.as_str()))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    // "Tetris by Fran Dachille"
+    pub fn byline(&self) -> String {
+        if self.authors.is_empty() {
+            self.title.clone()
+        } else {
+            format!("{} by {}", self.title, self.authors.join(", "))
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Database {
+    // by lowercase hex sha1
+    roms: HashMap<String, RomInfo>,
+}
+
+// archive quirk names apply() knows
+const SUPPORTED_QUIRKS: [&str; 7] = [
+    "vblank",
+    "wrap",
+    "logic",
+    "shift",
+    "jump",
+    "memoryIncrementByX",
+    "memoryLeaveIUnchanged",
+];
+
+const BUNDLED: &str = include_str!("database.json");
+
+impl Database {
+    pub fn bundled() -> Self {
+        Database::parse(BUNDLED).expect("bundled database is valid")
+    }
+
+    // the bundled roms and the ones in default_file, a broken file only
+    // costs its entries
+    pub fn load_default() -> (Self, Option<String>) {
+        let mut database = Database::bundled();
+        let Some(file) = Database::default_file().filter(|file| file.is_file()) else {
+            return (database, None);
+        };
+        match Database::load(&file) {
+            Ok(archive) => {
+                database.extend(archive);
+                (database, None)
+            }
+            Err(err) => (database, Some(format!("{}: {err}", file.display()))),
+        }
+    }
+
+    // $XDG_DATA_HOME/chip8/programs.json, falling back to ~/.local/share and
+    // on windows to %APPDATA%
+    pub fn default_file() -> Option<PathBuf> {
+        let env = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+        let base = env("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env("HOME").map(|home| Path::new(&home).join(".local/share")))
+            .or_else(|| env("APPDATA").map(PathBuf::from))?;
+        Some(base.join("chip8").join("programs.json"))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+        Database::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let Value::Array(programs) = json::parse(text)? else {
+            return Err("expected a list of programs".to_owned());
+        };
+        let mut roms = HashMap::new();
+        for program in &programs {
+            let title = program.get("title").and_then(Value::as_str).unwrap_or("?");
+            let authors = strings(program.get("authors"));
+            let release = program.get("release").and_then(Value::as_str);
+            let Some(Value::Object(hashes)) = program.get("roms") else {
+                continue;
+            };
+            for (hash, rom) in hashes {
+                let platforms = strings(rom.get("platforms"));
+                let quirks = platforms
+                    .first()
+                    .and_then(|platform| rom.get("quirkyPlatforms")?.get(platform))
+                    .map(|quirks| match quirks {
+                        Value::Object(fields) => fields
+                            .iter()
+                            .filter_map(|(name, on)| Some((name.clone(), on.as_bool()?)))
+                            .collect(),
+                        _ => Vec::new(),
+                    })
+                    .unwrap_or_default();
+                let info = RomInfo {
+                    title: title.to_owned(),
+                    authors: authors.clone(),
+                    release: release.map(str::to_owned),
+                    platforms,
+                    tickrate: rom
+                        .get("tickrate")
+                        .and_then(Value::as_f64)
+                        .filter(|rate| *rate >= 1.0)
+                        .map(|rate| rate as u32),
+                    quirks,
+                };
+                roms.insert(hash.to_ascii_lowercase(), info);
+            }
+        }
+        Ok(Database { roms })
+    }
+
+    // entries of other win
+    pub fn extend(&mut self, other: Database) {
+        self.roms.extend(other.roms);
+    }
+
+    pub fn len(&self) -> usize {
+        self.roms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.roms.is_empty()
+    }
+
+    pub fn lookup(&self, rom: &[u8]) -> Option<&RomInfo> {
+        self.roms.get(&sha1_hex(rom))
+    }
+}
+
+fn strings(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_owned)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
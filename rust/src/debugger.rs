@@ -0,0 +1,54 @@
+// the debugger panel: registers, the code around pc, memory around I and the
+// stack, copied out of the core with every frame while the panel is open.
+// ui::debugger_panel lays it out next to the step, run and reset buttons
+use crate::chip8::{Chip8State, CpuSnapshot, RunState};
+
+// instructions listed, CODE_BEFORE of them ahead of pc
+pub const DEBUGGER_CODE_ROWS: usize = 8;
+const CODE_BEFORE: usize = 2;
+pub const DEBUGGER_MEMORY_ROWS: usize = 6;
+pub const DEBUGGER_MEMORY_COLUMNS: usize = 8;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DebuggerView {
+    pub cpu: CpuSnapshot,
+    pub paused: bool,
+    // return addresses, the innermost call last
+    pub stack: Vec<u16>,
+    // (address, opcode) of every listed instruction, 0 past the end of memory
+    pub code: Vec<(u16, u16)>,
+    // address of memory[0], the start of the row I is in
+    pub memory_start: u16,
+    pub memory: Vec<u8>,
+}
+
+impl DebuggerView {
+    pub fn new(state: &Chip8State) -> Self {
+        let mut view = DebuggerView::default();
+        view.capture(state);
+        view
+    }
+
+    // refills the view keeping its buffers, frames are recycled
+    pub fn capture(&mut self, state: &Chip8State) {
+        self.cpu = state.cpu_snapshot();
+        self.paused = state.run_state() == RunState::Paused;
+        self.stack.clone_from(&state.stack);
+
+        let byte = |addr: usize| state.memory.get(addr).copied().unwrap_or(0);
+        let first = state.pc.saturating_sub(2 * CODE_BEFORE as u16);
+        self.code.clear();
+        self.code.extend((0..DEBUGGER_CODE_ROWS as u16).map(|row| {
+            let addr = first.saturating_add(2 * row);
+            let opcode = u16::from_be_bytes([byte(addr as usize), byte(addr as usize + 1)]);
+            (addr, opcode)
+        }));
+
+        let len = DEBUGGER_MEMORY_ROWS * DEBUGGER_MEMORY_COLUMNS;
+        let start = (state.i as usize / DEBUGGER_MEMORY_COLUMNS * DEBUGGER_MEMORY_COLUMNS)
+            .min(state.memory.len().saturating_sub(len));
+        self.memory_start = start as u16;
+        self.memory.clear();
+        self.memory.extend((start..start + len).map(byte));
+    }
+}
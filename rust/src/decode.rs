@@ -0,0 +1,225 @@
+// opcodes decoded once into an Op with their operands pulled out, so the
+// cycle dispatches on a single flat match instead of matching the indicator
+// and then the low bits of a fresh Instruction
+
+// the 8XYN group, they all write V[x] and most of them VF
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alu {
+    Move,
+    Or,
+    And,
+    Xor,
+    Add,
+    Sub,
+    ShiftRight,
+    SubN,
+    ShiftLeft,
+}
+
+// the megachip additions to 0NNN, only with Chip8State::set_megachip, the
+// machine code routines were ignored anyway
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MegaOp {
+    // 0010 and 0011
+    Off,
+    On,
+    // 01NN NNNN, NN is the high byte of I and the next word the rest
+    LoadHighI(u8),
+    // 02NN colors from I
+    LoadPalette(u8),
+    // 03NN and 04NN, 0 is 256
+    SpriteWidth(u8),
+    SpriteHeight(u8),
+    // 05NN
+    ScreenAlpha(u8),
+    // 0600 loops, 0601 once
+    PlaySample { looped: bool },
+    // 0700
+    StopSample,
+    // 080N
+    Blend(u8),
+    // 09NN
+    CollisionColor(u8),
+    // 00BN
+    ScrollUp(u8),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    // 00E0
+    Clear,
+    // 00EE
+    Return,
+    // 00CN, 00DN, 00FB and 00FC
+    ScrollDown(u8),
+    ScrollUp(u8),
+    ScrollRight,
+    ScrollLeft,
+    // 00FE and 00FF
+    Lores,
+    Hires,
+    // 0NNN machine code routines are ignored
+    Sys,
+    Mega(MegaOp),
+    Jump(u16),
+    Call(u16),
+    SkipIfEqual { x: u8, nn: u8 },
+    SkipIfNotEqual { x: u8, nn: u8 },
+    // 5XY0 and 9XY0
+    SkipIfEqualReg { x: u8, y: u8 },
+    SkipIfNotEqualReg { x: u8, y: u8 },
+    Load { x: u8, nn: u8 },
+    Add { x: u8, nn: u8 },
+    Alu { op: Alu, x: u8, y: u8 },
+    LoadI(u16),
+    // BNNN, or BXNN with the jump quirk
+    JumpOffset { x: u8, nnn: u16 },
+    Random { x: u8, nn: u8 },
+    Draw { x: u8, y: u8, n: u8 },
+    SkipIfKey(u8),
+    SkipIfNotKey(u8),
+    // F000 NNNN, only with extended memory
+    LoadLongI,
+    // FN01, xo-chip's planes as a bit mask
+    Planes(u8),
+    GetDelay(u8),
+    WaitKey(u8),
+    SetDelay(u8),
+    SetSound(u8),
+    AddI(u8),
+    // FX29, I to the font sprite of the digit in V[x]
+    Font(u8),
+    Bcd(u8),
+    Store(u8),
+    Restore(u8),
+    SaveFlags(u8),
+    LoadFlags(u8),
+    Invalid(u16),
+}
+
+// a 60Hz frame of the vip is 3668 machine cycles of 8 clocks at 1.76 MHz,
+// the display dma takes 1024 of them and the interrupt routine about 100
+pub const VIP_FRAME_CYCLES: u32 = 2544;
+
+impl Op {
+    // machine cycles the cosmac vip interpreter spent on this instruction,
+    // fetch and dispatch included, averaged where it depends on the data.
+    // skipped is whether a conditional skip was taken. the vip waited for the
+    // next frame after DXYN, that's the display-wait quirk and not in here.
+    // superchip and xo-chip instructions never ran on it, they cost what
+    // their nearest vip relative does
+    pub fn vip_cycles(&self, skipped: bool) -> u32 {
+        let skip = |taken, not_taken| if skipped { taken } else { not_taken };
+        match *self {
+            Op::Clear => 24,
+            Op::Return | Op::Jump(_) | Op::Call(_) | Op::JumpOffset { .. } => 23,
+            Op::ScrollDown(_) | Op::ScrollUp(_) | Op::ScrollRight | Op::ScrollLeft => 24,
+            Op::Lores | Op::Hires | Op::Planes(_) => 24,
+            // the machine code routines were the programs own business
+            Op::Sys | Op::Mega(_) => 10,
+            Op::SkipIfEqual { .. } | Op::SkipIfNotEqual { .. } => skip(14, 10),
+            Op::SkipIfEqualReg { .. } | Op::SkipIfNotEqualReg { .. } => skip(18, 14),
+            Op::Load { .. } => 6,
+            Op::Add { .. } => 10,
+            Op::Alu { .. } => 44,
+            Op::LoadI(_) | Op::LoadLongI => 12,
+            Op::Random { .. } => 36,
+            // the sprite routine shifts every row into place byte by byte
+            Op::Draw { n, .. } => 26 + 34 * n as u32,
+            Op::SkipIfKey(_) | Op::SkipIfNotKey(_) => skip(16, 14),
+            Op::GetDelay(_) | Op::WaitKey(_) | Op::SetDelay(_) | Op::SetSound(_) => 10,
+            Op::AddI(_) => 19,
+            Op::Font(_) => 16,
+            Op::Bcd(_) => 204,
+            Op::Store(x) | Op::Restore(x) | Op::SaveFlags(x) | Op::LoadFlags(x) => {
+                20 + 7 * (x as u32 + 1)
+            }
+            Op::Invalid(_) => 10,
+        }
+    }
+}
+
+#[inline]
+pub fn decode(opcode: u16) -> Op {
+    let x = ((opcode >> 8) & 0xF) as u8;
+    let y = ((opcode >> 4) & 0xF) as u8;
+    let n = (opcode & 0xF) as u8;
+    let nn = (opcode & 0xFF) as u8;
+    let nnn = opcode & 0xFFF;
+    match opcode >> 12 {
+        0x0 => match opcode {
+            0x00E0 => Op::Clear,
+            0x00EE => Op::Return,
+            0x00C0..=0x00CF => Op::ScrollDown(n),
+            0x00D0..=0x00DF => Op::ScrollUp(n),
+            0x00FB => Op::ScrollRight,
+            0x00FC => Op::ScrollLeft,
+            0x00FE => Op::Lores,
+            0x00FF => Op::Hires,
+            0x0010 => Op::Mega(MegaOp::Off),
+            0x0011 => Op::Mega(MegaOp::On),
+            0x00B0..=0x00BF => Op::Mega(MegaOp::ScrollUp(n)),
+            0x0100..=0x01FF => Op::Mega(MegaOp::LoadHighI(nn)),
+            0x0200..=0x02FF => Op::Mega(MegaOp::LoadPalette(nn)),
+            0x0300..=0x03FF => Op::Mega(MegaOp::SpriteWidth(nn)),
+            0x0400..=0x04FF => Op::Mega(MegaOp::SpriteHeight(nn)),
+            0x0500..=0x05FF => Op::Mega(MegaOp::ScreenAlpha(nn)),
+            0x0600 | 0x0601 => Op::Mega(MegaOp::PlaySample {
+                looped: opcode == 0x0600,
+            }),
+            0x0700 => Op::Mega(MegaOp::StopSample),
+            0x0800..=0x080F => Op::Mega(MegaOp::Blend(n)),
+            0x0900..=0x09FF => Op::Mega(MegaOp::CollisionColor(nn)),
+            _ => Op::Sys,
+        },
+        0x1 => Op::Jump(nnn),
+        0x2 => Op::Call(nnn),
+        0x3 => Op::SkipIfEqual { x, nn },
+        0x4 => Op::SkipIfNotEqual { x, nn },
+        0x5 if n == 0 => Op::SkipIfEqualReg { x, y },
+        0x6 => Op::Load { x, nn },
+        0x7 => Op::Add { x, nn },
+        0x8 => {
+            let op = match n {
+                0x0 => Alu::Move,
+                0x1 => Alu::Or,
+                0x2 => Alu::And,
+                0x3 => Alu::Xor,
+                0x4 => Alu::Add,
+                0x5 => Alu::Sub,
+                0x6 => Alu::ShiftRight,
+                0x7 => Alu::SubN,
+                0xE => Alu::ShiftLeft,
+                _ => return Op::Invalid(opcode),
+            };
+            Op::Alu { op, x, y }
+        }
+        0x9 if n == 0 => Op::SkipIfNotEqualReg { x, y },
+        0xA => Op::LoadI(nnn),
+        0xB => Op::JumpOffset { x, nnn },
+        0xC => Op::Random { x, nn },
+        0xD => Op::Draw { x, y, n },
+        0xE => match nn {
+            0x9E => Op::SkipIfKey(x),
+            0xA1 => Op::SkipIfNotKey(x),
+            _ => Op::Invalid(opcode),
+        },
+        0xF => match nn {
+            0x00 if x == 0 => Op::LoadLongI,
+            0x01 => Op::Planes(x),
+            0x07 => Op::GetDelay(x),
+            0x0A => Op::WaitKey(x),
+            0x15 => Op::SetDelay(x),
+            0x18 => Op::SetSound(x),
+            0x1E => Op::AddI(x),
+            0x29 => Op::Font(x),
+            0x33 => Op::Bcd(x),
+            0x55 => Op::Store(x),
+            0x65 => Op::Restore(x),
+            0x75 => Op::SaveFlags(x),
+            0x85 => Op::LoadFlags(x),
+            _ => Op::Invalid(opcode),
+        },
+        _ => Op::Invalid(opcode),
+    }
+}
@@ -0,0 +1,13 @@
+// where the emulator keeps what it writes for itself between runs
+use std::path::{Path, PathBuf};
+
+// $XDG_STATE_HOME/chip8/sub, falling back to ~/.local/state and on windows
+// to %APPDATA%. None without any of them
+pub fn state_dir(sub: &str) -> Option<PathBuf> {
+    let env = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+    let base = env("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env("HOME").map(|home| Path::new(&home).join(".local/state")))
+        .or_else(|| env("APPDATA").map(PathBuf::from))?;
+    Some(base.join("chip8").join(sub))
+}
@@ -0,0 +1,339 @@
+// rom listings as data, so the cli, gui panes and exports all format the
+// same lines their own way. source follows the code from the entry point
+// instead, so sprites come out as DB and not as garbage instructions
+use crate::chip8::Instruction;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use alloc::collections::{BTreeMap, BTreeSet};
+use core::fmt::{self, Write};
+
+// one decoded instruction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Line {
+    pub address: u16,
+    // two bytes, four for F000 NNNN, one for a trailing odd byte
+    pub bytes: Vec<u8>,
+    pub mnemonic: String,
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:03X}: ", self.address)?;
+        for byte in &self.bytes {
+            write!(f, "{byte:02X}")?;
+        }
+        // keep the mnemonics in one column
+        let pad = 9usize.saturating_sub(self.bytes.len() * 2);
+        write!(f, "{:pad$}{}", "", self.mnemonic)
+    }
+}
+
+// bytes per hexdump row
+pub const HEXDUMP_COLUMNS: usize = 16;
+
+// one row of the rom inspector: the bytes at address, and the instructions
+// starting among them as disassemble decodes them
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HexLine {
+    pub address: u16,
+    pub bytes: Vec<u8>,
+    pub mnemonics: Vec<String>,
+}
+
+impl fmt::Display for HexLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:03X}  ", self.address)?;
+        for column in 0..HEXDUMP_COLUMNS {
+            match self.bytes.get(column) {
+                Some(byte) => write!(f, "{byte:02X} ")?,
+                None => f.write_str("   ")?,
+            }
+        }
+        let ascii: String = self
+            .bytes
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        write!(
+            f,
+            " |{ascii:<HEXDUMP_COLUMNS$}|  {}",
+            self.mnemonics.join("; ")
+        )
+    }
+}
+
+pub fn hexdump(rom: &[u8], origin: u16) -> Vec<HexLine> {
+    let mut rows: Vec<HexLine> = rom
+        .chunks(HEXDUMP_COLUMNS)
+        .enumerate()
+        .map(|(row, chunk)| HexLine {
+            address: origin.wrapping_add((row * HEXDUMP_COLUMNS) as u16),
+            bytes: chunk.to_vec(),
+            mnemonics: Vec::new(),
+        })
+        .collect();
+    for line in disassemble(rom, origin) {
+        let row = line.address.wrapping_sub(origin) as usize / HEXDUMP_COLUMNS;
+        rows[row].mnemonics.push(line.mnemonic);
+    }
+    rows
+}
+
+// decodes rom as if it was loaded at origin. data mixed into the code is
+// decoded too, analyze tells them apart as far as it can without running it
+pub fn disassemble(rom: &[u8], origin: u16) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    while offset < rom.len() {
+        let address = origin.wrapping_add(offset as u16);
+        let rest = &rom[offset..];
+        let (len, mnemonic) = match rest {
+            // xo-chip's i := long NNNN
+            [0xF0, 0x00, high, low, ..] => (
+                4,
+                format!("LD I, long {:#06X}", u16::from_be_bytes([*high, *low])),
+            ),
+            [first, second, ..] => (2, mnemonic(&Instruction::new(*first, *second))),
+            [byte] => (1, format!("DB {byte:#04X}")),
+            [] => unreachable!(),
+        };
+        lines.push(Line {
+            address,
+            bytes: rest[..len].to_vec(),
+            mnemonic,
+        });
+        offset += len;
+    }
+    lines
+}
+
+// cowgod style assembly for one instruction, DW for anything unknown
+pub fn mnemonic(inst: &Instruction) -> String {
+    let (x, y, n, nn, nnn) = (inst.x(), inst.y(), inst.n(), inst.nn(), inst.nnn());
+    match inst.indicator() {
+        0x0 => match inst.opcode() {
+            0x00E0 => "CLS".to_owned(),
+            0x00EE => "RET".to_owned(),
+            0x00C0..=0x00CF => format!("SCD {n}"),
+            0x00D0..=0x00DF => format!("SCU {n}"),
+            0x00FB => "SCR".to_owned(),
+            0x00FC => "SCL".to_owned(),
+            0x00FE => "LOW".to_owned(),
+            0x00FF => "HIGH".to_owned(),
+            _ => format!("SYS {nnn:#05X}"),
+        },
+        0x1 => format!("JP {nnn:#05X}"),
+        0x2 => format!("CALL {nnn:#05X}"),
+        0x3 => format!("SE V{x:X}, {nn:#04X}"),
+        0x4 => format!("SNE V{x:X}, {nn:#04X}"),
+        0x5 if n == 0 => format!("SE V{x:X}, V{y:X}"),
+        0x6 => format!("LD V{x:X}, {nn:#04X}"),
+        0x7 => format!("ADD V{x:X}, {nn:#04X}"),
+        0x8 => {
+            let op = match n {
+                0x0 => "LD",
+                0x1 => "OR",
+                0x2 => "AND",
+                0x3 => "XOR",
+                0x4 => "ADD",
+                0x5 => "SUB",
+                0x6 => "SHR",
+                0x7 => "SUBN",
+                0xE => "SHL",
+                _ => return format!("DW {:#06X}", inst.opcode()),
+            };
+            format!("{op} V{x:X}, V{y:X}")
+        }
+        0x9 if n == 0 => format!("SNE V{x:X}, V{y:X}"),
+        0xA => format!("LD I, {nnn:#05X}"),
+        0xB => format!("JP V0, {nnn:#05X}"),
+        0xC => format!("RND V{x:X}, {nn:#04X}"),
+        0xD => format!("DRW V{x:X}, V{y:X}, {n}"),
+        0xE if nn == 0x9E => format!("SKP V{x:X}"),
+        0xE if nn == 0xA1 => format!("SKNP V{x:X}"),
+        0xF => match nn {
+            0x01 => format!("PLANE {x}"),
+            0x07 => format!("LD V{x:X}, DT"),
+            0x0A => format!("LD V{x:X}, K"),
+            0x15 => format!("LD DT, V{x:X}"),
+            0x18 => format!("LD ST, V{x:X}"),
+            0x1E => format!("ADD I, V{x:X}"),
+            0x29 => format!("LD F, V{x:X}"),
+            0x33 => format!("LD B, V{x:X}"),
+            0x55 => format!("LD [I], V{x:X}"),
+            0x65 => format!("LD V{x:X}, [I]"),
+            0x75 => format!("LD R, V{x:X}"),
+            0x85 => format!("LD V{x:X}, R"),
+            _ => format!("DW {:#06X}", inst.opcode()),
+        },
+        _ => format!("DW {:#06X}", inst.opcode()),
+    }
+}
+
+// what following the code from origin found: where instructions start, and
+// names for the addresses jumped to, called and pointed I at. anything never
+// reached is data, mostly sprites
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Analysis {
+    pub code: BTreeSet<u16>,
+    pub labels: BTreeMap<u16, String>,
+}
+
+// the instruction at offset and its size, None past the end of the rom
+fn decode(rom: &[u8], offset: usize) -> Option<(usize, u16, String)> {
+    match rom.get(offset..)? {
+        [0xF0, 0x00, high, low, ..] => {
+            let target = u16::from_be_bytes([*high, *low]);
+            Some((4, target, format!("LD I, long {target:#06X}")))
+        }
+        [first, second, ..] => {
+            let inst = Instruction::new(*first, *second);
+            Some((2, inst.nnn(), mnemonic(&inst)))
+        }
+        _ => None,
+    }
+}
+
+// follows jumps, calls and both sides of skips from origin. BNNN's table at
+// NNN is taken for code, where else it goes depends on V0
+pub fn analyze(rom: &[u8], origin: u16) -> Analysis {
+    let mut analysis = Analysis::default();
+    let mut claimed = vec![false; rom.len()];
+    let mut data = BTreeSet::new();
+    let mut pending = vec![origin];
+    let offset = |address: u16| {
+        let offset = address.wrapping_sub(origin) as usize;
+        (offset < rom.len()).then_some(offset)
+    };
+    while let Some(address) = pending.pop() {
+        let Some(at) = offset(address) else {
+            continue;
+        };
+        let Some((len, target, _)) = decode(rom, at) else {
+            continue;
+        };
+        // reached already, or overlapping an instruction
+        if claimed[at..at + len].iter().any(|&claimed| claimed) {
+            continue;
+        }
+        analysis.code.insert(address);
+        claimed[at..at + len].fill(true);
+        let next = address.wrapping_add(len as u16);
+        let opcode = u16::from_be_bytes([rom[at], rom[at + 1]]);
+        let mut label = |target: u16, name: &str| {
+            if offset(target).is_some() {
+                analysis
+                    .labels
+                    .entry(target)
+                    .or_insert_with(|| format!("{name}_{target:03X}"));
+            }
+        };
+        match opcode >> 12 {
+            _ if opcode == 0x00EE || opcode == 0x00FD => {}
+            0x1 => {
+                label(target, "label");
+                pending.push(target);
+            }
+            0x2 => {
+                label(target, "sub");
+                pending.extend([next, target]);
+            }
+            0xB => {
+                label(target, "table");
+                pending.push(target);
+            }
+            _ if len == 4 => {
+                data.insert(target);
+                pending.push(next);
+            }
+            0xA => {
+                data.insert(target);
+                pending.push(next);
+            }
+            0x3 | 0x4 | 0x5 | 0x9 | 0xE => {
+                // a skip over F000 NNNN skips all four bytes
+                let skipped = match offset(next).and_then(|at| decode(rom, at)) {
+                    Some((len, ..)) => len,
+                    None => 2,
+                };
+                pending.extend([next, next.wrapping_add(skipped as u16)]);
+            }
+            _ => pending.push(next),
+        }
+    }
+    // a jump into the middle of an instruction keeps its number
+    let code = &analysis.code;
+    analysis.labels.retain(|target, _| code.contains(target));
+    // I pointing into code is self modifying or a coincidence, leave it be
+    for target in data {
+        if let Some(at) = offset(target)
+            && !claimed[at]
+        {
+            analysis
+                .labels
+                .entry(target)
+                .or_insert_with(|| format!("data_{target:03X}"));
+        }
+    }
+    analysis
+}
+
+// bytes per DB line of data
+const DATA_COLUMNS: usize = 8;
+
+// the rom as source asm assembles back into it: the code analyze reached
+// with labels for its targets, and the rest as DB lines
+pub fn source(rom: &[u8], origin: u16) -> String {
+    let analysis = analyze(rom, origin);
+    let mut out = String::new();
+    let mut data: Vec<u8> = Vec::new();
+    let flush = |out: &mut String, data: &mut Vec<u8>| {
+        for row in data.chunks(DATA_COLUMNS) {
+            let bytes: Vec<String> = row.iter().map(|byte| format!("{byte:#04X}")).collect();
+            let _ = writeln!(out, "    DB {}", bytes.join(", "));
+        }
+        data.clear();
+    };
+    let mut offset = 0;
+    while offset < rom.len() {
+        let address = origin.wrapping_add(offset as u16);
+        let label = analysis.labels.get(&address);
+        let code = analysis.code.contains(&address);
+        if label.is_some() || code || data.len() == DATA_COLUMNS {
+            flush(&mut out, &mut data);
+        }
+        if let Some(label) = label {
+            let _ = writeln!(out, "{label}:");
+        }
+        match decode(rom, offset).filter(|_| code) {
+            Some((len, target, mnemonic)) => {
+                let mnemonic = match analysis.labels.get(&target) {
+                    Some(name) if len == 4 => mnemonic.replace(&format!("{target:#06X}"), name),
+                    Some(name) if has_address(rom[offset]) => {
+                        mnemonic.replace(&format!("{target:#05X}"), name)
+                    }
+                    _ => mnemonic,
+                };
+                let _ = writeln!(out, "    {mnemonic}");
+                offset += len;
+            }
+            None => {
+                data.push(rom[offset]);
+                offset += 1;
+            }
+        }
+    }
+    flush(&mut out, &mut data);
+    out
+}
+
+// 1NNN, 2NNN, ANNN and BNNN, whose NNN is an address
+fn has_address(first: u8) -> bool {
+    matches!(first >> 4, 0x1 | 0x2 | 0xA | 0xB)
+}
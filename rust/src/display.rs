@@ -0,0 +1,332 @@
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+// source of generations, unique across all displays so a frame from a reset
+// machine never looks like one the renderer already showed
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+fn next_generation() -> u64 {
+    NEXT_GENERATION.fetch_add(1, Ordering::Relaxed)
+}
+
+// xo-chip's bit planes, a pixel's color is its bit from each, see pixel
+pub const PLANES: usize = 2;
+
+// packed 1 bit framebuffer of any size, a plane each. each row is
+// width.div_ceil(64) words and the msb of a word is its leftmost pixel.
+// chip8 and schip only ever see the first plane, xo-chip's FN01 selects
+// which ones its clears, sprites and scrolls work on
+#[derive(Debug)]
+pub struct Display {
+    width: usize,
+    height: usize,
+    planes: [Vec<u64>; PLANES],
+    // one bit per plane, the first one unless FN01 picked others
+    selected: u8,
+    // changes with every modification, copies keep it
+    generation: u64,
+}
+
+impl Clone for Display {
+    fn clone(&self) -> Self {
+        Display {
+            width: self.width,
+            height: self.height,
+            planes: self.planes.clone(),
+            selected: self.selected,
+            generation: self.generation,
+        }
+    }
+
+    // reuses the buffers, which is what frame recycling relies on
+    fn clone_from(&mut self, source: &Self) {
+        self.width = source.width;
+        self.height = source.height;
+        for (plane, from) in self.planes.iter_mut().zip(&source.planes) {
+            plane.clone_from(from);
+        }
+        self.selected = source.selected;
+        self.generation = source.generation;
+    }
+}
+
+// equal pixels and selected planes, regardless of how they got there
+impl PartialEq for Display {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.planes == other.planes
+            && self.selected == other.selected
+    }
+}
+
+impl Eq for Display {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Geometry {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Geometry {
+    pub const LORES: Geometry = Geometry::new(64, 32);
+    // aspect-corrected variant of some VIP interpreters
+    pub const TALL: Geometry = Geometry::new(64, 48);
+    // two page hires chip8
+    pub const HIRES: Geometry = Geometry::new(64, 64);
+    pub const SCHIP_HIRES: Geometry = Geometry::new(128, 64);
+    pub const MEGACHIP: Geometry = Geometry::new(256, 192);
+
+    pub const fn new(width: usize, height: usize) -> Self {
+        Geometry { width, height }
+    }
+
+    // parses "64x48"
+    pub fn parse(text: &str) -> Option<Self> {
+        let (width, height) = text.split_once('x')?;
+        let geometry = Geometry::new(width.parse().ok()?, height.parse().ok()?);
+        (geometry.width > 0 && geometry.height > 0).then_some(geometry)
+    }
+}
+
+impl Default for Display {
+    fn default() -> Self {
+        Display::new(Geometry::LORES)
+    }
+}
+
+impl Display {
+    pub fn new(geometry: Geometry) -> Self {
+        let words = geometry.width.div_ceil(64) * geometry.height;
+        Display {
+            width: geometry.width,
+            height: geometry.height,
+            planes: [vec![0; words], vec![0; words]],
+            selected: 1,
+            generation: next_generation(),
+        }
+    }
+
+    // packed rows of the first plane as words() returns them, None if the
+    // count doesn't match
+    pub fn from_words(geometry: Geometry, words: Vec<u64>) -> Option<Self> {
+        let second = vec![0; words.len()];
+        Display::from_planes(geometry, [words, second], 1)
+    }
+
+    // every plane as plane_words returns them and the selected ones
+    pub fn from_planes(
+        geometry: Geometry,
+        planes: [Vec<u64>; PLANES],
+        selected: u8,
+    ) -> Option<Self> {
+        let display = Display {
+            planes,
+            selected,
+            ..Display::new(geometry)
+        };
+        let words = display.words_per_row() * display.height;
+        (display.planes.iter().all(|plane| plane.len() == words) && selected < 1 << PLANES)
+            .then_some(display)
+    }
+
+    // lets renderers skip frames that show nothing new
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn geometry(&self) -> Geometry {
+        Geometry::new(self.width, self.height)
+    }
+
+    pub fn words_per_row(&self) -> usize {
+        self.width.div_ceil(64)
+    }
+
+    // raw packed rows of the first plane, for frontends that upload the
+    // buffer as is
+    pub fn words(&self) -> &[u64] {
+        &self.planes[0]
+    }
+
+    pub fn plane_words(&self, plane: usize) -> &[u64] {
+        &self.planes[plane]
+    }
+
+    // FN01, a bit per plane. 0 leaves every plane alone
+    pub fn select_planes(&mut self, mask: u8) {
+        self.selected = mask & ((1 << PLANES) - 1);
+    }
+
+    pub fn selected_planes(&self) -> u8 {
+        self.selected
+    }
+
+    // the selected planes in order, what a two plane sprite is drawn into
+    pub fn selected(&self) -> impl Iterator<Item = usize> + use<> {
+        let selected = self.selected;
+        (0..PLANES).filter(move |plane| selected & 1 << plane != 0)
+    }
+
+    fn index(&self, x: usize, y: usize) -> (usize, u64) {
+        let word = y * self.words_per_row() + x / 64;
+        (word, 1u64 << (63 - x % 64))
+    }
+
+    // lit in any plane
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.pixel(x, y) != 0
+    }
+
+    // the palette index of a pixel, a bit from each plane with the first
+    // plane's lowest
+    pub fn pixel(&self, x: usize, y: usize) -> u8 {
+        let (word, mask) = self.index(x, y);
+        self.planes
+            .iter()
+            .enumerate()
+            .map(|(plane, words)| ((words[word] & mask != 0) as u8) << plane)
+            .sum()
+    }
+
+    // xors the pixel of the first plane on, returns true if it was already
+    // set (a collision)
+    pub fn toggle(&mut self, x: usize, y: usize) -> bool {
+        self.toggle_plane(0, x, y)
+    }
+
+    pub fn toggle_plane(&mut self, plane: usize, x: usize, y: usize) -> bool {
+        let (word, mask) = self.index(x, y);
+        let words = &mut self.planes[plane];
+        let was_set = words[word] & mask != 0;
+        words[word] ^= mask;
+        self.generation = next_generation();
+        was_set
+    }
+
+    // in the first plane
+    pub fn set(&mut self, x: usize, y: usize, on: bool) {
+        let (word, mask) = self.index(x, y);
+        if on {
+            self.planes[0][word] |= mask;
+        } else {
+            self.planes[0][word] &= !mask;
+        }
+        self.generation = next_generation();
+    }
+
+    // (x, y, lit) of every pixel, a row at a time from the top left, so a
+    // renderer doesn't need to know the packing. pixel has the color
+    pub fn iter_pixels(&self) -> impl Iterator<Item = (usize, usize, bool)> + '_ {
+        (0..self.height).flat_map(move |y| (0..self.width).map(move |x| (x, y, self.get(x, y))))
+    }
+
+    // every plane
+    pub fn clear(&mut self) {
+        for plane in &mut self.planes {
+            plane.fill(0);
+        }
+        self.generation = next_generation();
+    }
+
+    // 00E0, the selected planes
+    pub fn clear_selected(&mut self) {
+        for plane in self.selected() {
+            self.planes[plane].fill(0);
+        }
+        self.generation = next_generation();
+    }
+
+    // a blank screen of another size in the same buffer, the schip 00FE and
+    // 00FF switches
+    pub fn resize(&mut self, geometry: Geometry) {
+        self.width = geometry.width;
+        self.height = geometry.height;
+        for plane in &mut self.planes {
+            plane.clear();
+            plane.resize(geometry.width.div_ceil(64) * geometry.height, 0);
+        }
+        self.generation = next_generation();
+    }
+
+    // schip and xo-chip scrolling of the selected planes, pixels moved off
+    // an edge are lost and the ones moved in are off. whole rows move at
+    // once, sideways scrolls shift the packed words of each row
+    pub fn scroll_down(&mut self, rows: usize) {
+        let words = rows.min(self.height) * self.words_per_row();
+        for plane in self.selected() {
+            let plane = &mut self.planes[plane];
+            let len = plane.len();
+            plane.copy_within(..len - words, words);
+            plane[..words].fill(0);
+        }
+        self.generation = next_generation();
+    }
+
+    pub fn scroll_up(&mut self, rows: usize) {
+        let words = rows.min(self.height) * self.words_per_row();
+        for plane in self.selected() {
+            let plane = &mut self.planes[plane];
+            let len = plane.len();
+            plane.copy_within(words.., 0);
+            plane[len - words..].fill(0);
+        }
+        self.generation = next_generation();
+    }
+
+    pub fn scroll_right(&mut self, pixels: usize) {
+        let (skip, bits) = (pixels / 64, pixels % 64);
+        let (tail, words_per_row) = (self.tail_mask(), self.words_per_row());
+        for plane in self.selected() {
+            for row in self.planes[plane].chunks_mut(words_per_row) {
+                for i in (0..row.len()).rev() {
+                    let word = |at: Option<usize>| at.map_or(0, |at| row[at]);
+                    let (near, far) = (i.checked_sub(skip), i.checked_sub(skip + 1));
+                    row[i] = match bits {
+                        0 => word(near),
+                        _ => word(near) >> bits | word(far) << (64 - bits),
+                    };
+                }
+                // the unused low bits of the last word stay off
+                if let Some(last) = row.last_mut() {
+                    *last &= tail;
+                }
+            }
+        }
+        self.generation = next_generation();
+    }
+
+    pub fn scroll_left(&mut self, pixels: usize) {
+        let (skip, bits) = (pixels / 64, pixels % 64);
+        let words_per_row = self.words_per_row();
+        for plane in self.selected() {
+            for row in self.planes[plane].chunks_mut(words_per_row) {
+                for i in 0..row.len() {
+                    let word = |at: usize| row.get(at).copied().unwrap_or(0);
+                    row[i] = match bits {
+                        0 => word(i + skip),
+                        _ => word(i + skip) << bits | word(i + skip + 1) >> (64 - bits),
+                    };
+                }
+            }
+        }
+        self.generation = next_generation();
+    }
+
+    // the bits of a row's last word that are pixels
+    fn tail_mask(&self) -> u64 {
+        match self.width % 64 {
+            0 => u64::MAX,
+            used => !(u64::MAX >> used),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+// `doctor`: environment checks with a suggested fix for everything that fails
+use crate::browser::RomBrowser;
+use crate::keymap::Keymap;
+use crate::timing::{TimerResolution, TimerStrategy};
+use std::fmt;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    // works, but probably not the way the user wants
+    Warn,
+    Fail,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Status::Ok => " ok ",
+            Status::Warn => "warn",
+            Status::Fail => "FAIL",
+        })
+    }
+}
+
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub message: String,
+    pub fix: Option<String>,
+}
+
+impl Check {
+    pub fn ok(name: &'static str, message: impl Into<String>) -> Self {
+        Check {
+            name,
+            status: Status::Ok,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    pub fn warn(name: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Check {
+            name,
+            status: Status::Warn,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    pub fn fail(name: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Check {
+            name,
+            status: Status::Fail,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+// two chip8 keys or two controls on one physical key, or a control that
+// shadows a chip8 key
+pub fn check_keymap(keymap: &Keymap) -> Check {
+    let same = |a: &str, b: &str| a.eq_ignore_ascii_case(b);
+    for (key, name) in keymap.keypad.iter().enumerate() {
+        if let Some(other) = keymap.keypad[key + 1..]
+            .iter()
+            .position(|other| same(other, name))
+        {
+            return Check::fail(
+                "keymap",
+                format!("'{name}' is bound to {key:X} and {:X}", key + 1 + other),
+                "give every chip8 key its own physical key in the [keymap] config",
+            );
+        }
+    }
+
+    for (n, (name, hotkey)) in keymap.hotkeys.iter().enumerate() {
+        if keymap.keypad.iter().any(|key| same(key, name)) {
+            return Check::fail(
+                "keymap",
+                format!("hotkey {name} ({}) is also a chip8 key", hotkey.name()),
+                "move the hotkey off the keypad keys",
+            );
+        }
+        if let Some((_, other)) = keymap.hotkeys[n + 1..]
+            .iter()
+            .find(|(other, _)| same(other, name))
+        {
+            return Check::fail(
+                "keymap",
+                format!("{name} is bound to {} and {}", hotkey.name(), other.name()),
+                "give every control its own key",
+            );
+        }
+    }
+
+    Check::ok("keymap", "no conflicts")
+}
+
+// the directory the rom browser lists when started without a rom
+pub fn check_rom_dir(dir: &Path) -> Check {
+    match RomBrowser::open(dir).map(|browser| browser.names().len()) {
+        Ok(0) => Check::warn(
+            "roms",
+            format!("no .ch8 files in {}", dir.display()),
+            "put your roms there, or point --rom-dir at them",
+        ),
+        Ok(roms) => Check::ok("roms", format!("{}: found {roms} .ch8", dir.display())),
+        Err(err) => Check::fail(
+            "roms",
+            format!("{}: {err}", dir.display()),
+            format!(
+                "create {} and put your .ch8 files there, or pass --rom-dir",
+                dir.display()
+            ),
+        ),
+    }
+}
+
+// a window needs a display server on linux and the bsds
+pub fn check_display_server() -> Check {
+    if !cfg!(unix) || cfg!(target_os = "macos") {
+        return Check::ok("display", "native windowing");
+    }
+
+    let found = ["WAYLAND_DISPLAY", "DISPLAY"]
+        .iter()
+        .find(|var| std::env::var_os(var).is_some_and(|value| !value.is_empty()));
+    match found {
+        Some(var) => Check::ok("display", format!("{var} is set")),
+        None => Check::fail(
+            "display",
+            "neither DISPLAY nor WAYLAND_DISPLAY is set",
+            "run from a desktop session, or use selftest for headless runs",
+        ),
+    }
+}
+
+pub fn check_timer() -> Check {
+    let strategy = TimerResolution::acquire().strategy();
+    if cfg!(windows) && strategy != TimerStrategy::HighResolution {
+        return Check::warn(
+            "timer",
+            "timeBeginPeriod failed, frame pacing spins more",
+            "close tools that lock the timer resolution",
+        );
+    }
+    Check::ok("timer", strategy.name())
+}
+
+pub fn print_report(checks: &[Check]) {
+    for check in checks {
+        println!("[{}] {}: {}", check.status, check.name, check.message);
+        if let Some(fix) = &check.fix {
+            println!("       fix: {fix}");
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Chip8Error {
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    InvalidOpcode {
+        pc: u16,
+        opcode: u16,
+    },
+    StackOverflow {
+        pc: u16,
+    },
+    StackUnderflow {
+        pc: u16,
+    },
+    MemoryOutOfBounds {
+        pc: u16,
+        addr: usize,
+    },
+    // below the program start under WriteProtection::Halt
+    ProtectedWrite {
+        pc: u16,
+        addr: usize,
+    },
+    RomTooLarge {
+        size: usize,
+        max: usize,
+    },
+    EmptyRom,
+    InvalidSaveState {
+        reason: &'static str,
+    },
+    // written by a newer release
+    UnsupportedSaveState {
+        version: u16,
+    },
+}
+
+impl fmt::Display for Chip8Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            Chip8Error::Io(err) => write!(f, "io error: {err}"),
+            Chip8Error::InvalidOpcode { pc, opcode } => {
+                write!(f, "unknown opcode {opcode:04X} at {pc:03X}")
+            }
+            Chip8Error::StackOverflow { pc } => write!(f, "stack overflow at {pc:03X}"),
+            Chip8Error::StackUnderflow { pc } => {
+                write!(f, "return without subroutine at {pc:03X}")
+            }
+            Chip8Error::MemoryOutOfBounds { pc, addr } => {
+                write!(f, "memory access {addr:04X} out of bounds at {pc:03X}")
+            }
+            Chip8Error::ProtectedWrite { pc, addr } => {
+                write!(f, "write to protected memory {addr:03X} at {pc:03X}")
+            }
+            Chip8Error::RomTooLarge { size, max } => {
+                write!(f, "rom is {size} bytes, at most {max} fit into memory")
+            }
+            Chip8Error::EmptyRom => write!(f, "rom is empty"),
+            Chip8Error::InvalidSaveState { reason } => write!(f, "invalid savestate: {reason}"),
+            Chip8Error::UnsupportedSaveState { version } => {
+                write!(f, "savestate version {version} is newer than this build")
+            }
+        }
+    }
+}
+
+impl core::error::Error for Chip8Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            Chip8Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for Chip8Error {
+    fn from(err: io::Error) -> Self {
+        Chip8Error::Io(err)
+    }
+}
@@ -0,0 +1,124 @@
+// roms straight from a url, "chip8 run http://host/game.ch8". a plain
+// http/1.0 get that follows a few redirects, there is no tls client in the
+// tree so an https url has to come in through curl and "chip8 run -"
+use crate::rom::is_url;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+const MAX_REDIRECTS: usize = 5;
+const TIMEOUT: Duration = Duration::from_secs(10);
+// status line and headers, beyond the rom itself
+const MAX_HEADER_SIZE: usize = 16 * 1024;
+
+enum Response {
+    Body(Vec<u8>),
+    Redirect(String),
+}
+
+// the body of url, at most max bytes
+pub fn fetch(url: &str, max: usize) -> Result<Vec<u8>, String> {
+    let mut url = url.to_owned();
+    for _ in 0..=MAX_REDIRECTS {
+        match get(&url, max)? {
+            Response::Body(body) => return Ok(body),
+            Response::Redirect(location) => url = resolve(&url, &location),
+        }
+    }
+    Err(format!("{url}: more than {MAX_REDIRECTS} redirects"))
+}
+
+// (host, port, path) of an http url
+fn split_url(url: &str) -> Result<(&str, u16, &str), String> {
+    if url.starts_with("https://") {
+        return Err(format!(
+            "{url}: https isn't supported, try curl -sL {url} | chip8 run -"
+        ));
+    }
+    let Some(rest) = url.strip_prefix("http://") else {
+        return Err(format!("{url}: not an http url"));
+    };
+    let (authority, path) = rest.find('/').map_or((rest, "/"), |at| rest.split_at(at));
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (
+            host,
+            port.parse()
+                .map_err(|_| format!("{url}: bad port {port}"))?,
+        ),
+        None => (authority, 80),
+    };
+    if host.is_empty() {
+        return Err(format!("{url}: no host"));
+    }
+    Ok((host, port, path))
+}
+
+// location against the url it came from
+fn resolve(url: &str, location: &str) -> String {
+    if is_url(location) {
+        return location.to_owned();
+    }
+    let rest = url.strip_prefix("http://").unwrap_or(url);
+    let host_end = rest.find('/').map_or(rest.len(), |at| at);
+    let origin = &url[..url.len() - rest.len() + host_end];
+    if location.starts_with('/') {
+        return format!("{origin}{location}");
+    }
+    let dir = rest[host_end..]
+        .rfind('/')
+        .map_or("", |at| &rest[host_end..host_end + at]);
+    format!("{origin}{dir}/{location}")
+}
+
+fn get(url: &str, max: usize) -> Result<Response, String> {
+    let (host, port, path) = split_url(url)?;
+    let fail = |err: std::io::Error| format!("{url}: {err}");
+    let mut stream = TcpStream::connect((host, port)).map_err(fail)?;
+    stream.set_read_timeout(Some(TIMEOUT)).map_err(fail)?;
+    // in one write, a server may answer the first segment and hang up
+    let request = format!(
+        "GET {path} HTTP/1.0\r\nHost: {host}\r\nUser-Agent: chip8\r\nConnection: close\r\n\r\n"
+    );
+    stream.write_all(request.as_bytes()).map_err(fail)?;
+    let mut response = Vec::new();
+    stream
+        .take((MAX_HEADER_SIZE + max + 1) as u64)
+        .read_to_end(&mut response)
+        .map_err(fail)?;
+
+    let Some(end) = response.windows(4).position(|window| window == b"\r\n\r\n") else {
+        return Err(format!("{url}: no http response"));
+    };
+    let head = String::from_utf8_lossy(&response[..end]);
+    let status = head
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| format!("{url}: no http response"))?;
+    let header = |name: &str| {
+        head.lines().skip(1).find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim()
+                .eq_ignore_ascii_case(name)
+                .then(|| value.trim().to_owned())
+        })
+    };
+
+    match status {
+        200 => {}
+        301 | 302 | 303 | 307 | 308 => {
+            return header("location")
+                .map(Response::Redirect)
+                .ok_or_else(|| format!("{url}: redirect without a location"));
+        }
+        _ => return Err(format!("{url}: http {status}")),
+    }
+    // don't take a huge download just to reject it
+    if let Some(size) = header("content-length").and_then(|size| size.parse::<usize>().ok())
+        && size > max
+    {
+        return Err(format!("{url}: {size} bytes, at most {max} fit in memory"));
+    }
+    Ok(Response::Body(response[end + 4..].to_vec()))
+}
@@ -0,0 +1,171 @@
+// the core for other languages, through the cdylib and include/chip8.h. a
+// machine is an opaque pointer from chip8_new, every call takes it and
+// chip8_free ends it. like the browser's exports the buffers are the
+// machine's, the caller copies the rom in and the screen out. calls
+// returning int give 0, or -1 on an error or a null machine; a machine that
+// failed stays where it stopped
+use crate::chip8::Chip8State;
+use crate::settings;
+
+pub struct Machine {
+    state: Chip8State,
+    rom: Vec<u8>,
+    // a byte per pixel, what chip8_framebuffer filled in
+    pixels: Vec<u8>,
+}
+
+// machine is null or from chip8_new and not freed yet, and the caller keeps
+// it on one thread at a time, what every export's safety section asks
+unsafe fn with_machine<T>(machine: *mut Machine, or: T, f: impl FnOnce(&mut Machine) -> T) -> T {
+    match unsafe { machine.as_mut() } {
+        Some(machine) => f(machine),
+        None => or,
+    }
+}
+
+// a cosmac vip: its quirks, speed and memory, the rng seeded with seed.
+// the timing stays flat so chip8_run_frame runs as many instructions as asked
+#[unsafe(no_mangle)]
+pub extern "C" fn chip8_new(seed: u32) -> *mut Machine {
+    let vip = settings::Machine::find("cosmac-vip").unwrap();
+    let mut state = Chip8State::with_seed(seed);
+    state.settings.quirks = vip.quirks;
+    state.settings.instructions_per_second = vip.instructions_per_second;
+    state.set_program_start(vip.program_start);
+    state.set_geometry(vip.geometry);
+    Box::into_raw(Box::new(Machine {
+        state,
+        rom: Vec::new(),
+        pixels: Vec::new(),
+    }))
+}
+
+/// # Safety
+/// machine is null or from chip8_new, and isn't used again
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chip8_free(machine: *mut Machine) {
+    if !machine.is_null() {
+        drop(unsafe { Box::from_raw(machine) });
+    }
+}
+
+// the caller copies len bytes of rom here before calling chip8_load
+/// # Safety
+/// machine is null or a live one from chip8_new
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chip8_rom_buffer(machine: *mut Machine, len: usize) -> *mut u8 {
+    unsafe {
+        with_machine(machine, core::ptr::null_mut(), |machine| {
+            machine.rom = vec![0; len];
+            machine.rom.as_mut_ptr()
+        })
+    }
+}
+
+// starts the machine over with the rom, -1 for one too big
+/// # Safety
+/// machine is null or a live one from chip8_new
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chip8_load(machine: *mut Machine) -> i32 {
+    unsafe {
+        with_machine(machine, -1, |machine| {
+            machine.state.reset();
+            match machine.state.load(&machine.rom) {
+                Ok(()) => 0,
+                Err(_) => -1,
+            }
+        })
+    }
+}
+
+// instructions and then a tick of the timers, call it 60 times a second
+/// # Safety
+/// machine is null or a live one from chip8_new
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chip8_run_frame(machine: *mut Machine, instructions: u32) -> i32 {
+    unsafe {
+        with_machine(machine, -1, |machine| {
+            match machine.state.run_frame(instructions as usize) {
+                Ok(()) => 0,
+                Err(_) => -1,
+            }
+        })
+    }
+}
+
+// a single instruction, for hosts with a clock of their own. the timers only
+// move with chip8_tick_timers then
+/// # Safety
+/// machine is null or a live one from chip8_new
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chip8_step(machine: *mut Machine) -> i32 {
+    unsafe {
+        with_machine(machine, -1, |machine| match machine.state.cycle() {
+            Ok(_) => 0,
+            Err(_) => -1,
+        })
+    }
+}
+
+/// # Safety
+/// machine is null or a live one from chip8_new
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chip8_tick_timers(machine: *mut Machine) {
+    unsafe {
+        with_machine(machine, (), |machine| machine.state.tick_timers());
+    }
+}
+
+// key 0 to F
+/// # Safety
+/// machine is null or a live one from chip8_new
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chip8_set_key(machine: *mut Machine, key: u8, down: bool) {
+    unsafe {
+        with_machine(machine, (), |machine| {
+            machine.state.keypad[(key & 0xF) as usize] = down;
+        });
+    }
+}
+
+// the sound timer is running
+/// # Safety
+/// machine is null or a live one from chip8_new
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chip8_beeping(machine: *mut Machine) -> bool {
+    unsafe { with_machine(machine, false, |machine| machine.state.sound_timer > 0) }
+}
+
+// 64x32 at first, roms can switch to 128x64 and back
+/// # Safety
+/// machine is null or a live one from chip8_new
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chip8_width(machine: *mut Machine) -> u32 {
+    unsafe { with_machine(machine, 0, |machine| machine.state.display.width() as u32) }
+}
+
+/// # Safety
+/// machine is null or a live one from chip8_new
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chip8_height(machine: *mut Machine) -> u32 {
+    unsafe { with_machine(machine, 0, |machine| machine.state.display.height() as u32) }
+}
+
+// the screen now, a byte per pixel row by row, 1 lit and 0 dark. width times
+// height bytes that stay until the next call
+/// # Safety
+/// machine is null or a live one from chip8_new
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chip8_framebuffer(machine: *mut Machine) -> *const u8 {
+    unsafe {
+        with_machine(machine, core::ptr::null(), |machine| {
+            let display = &machine.state.display;
+            machine.pixels.clear();
+            machine.pixels.resize(display.width() * display.height(), 0);
+            for (x, y, on) in display.iter_pixels() {
+                machine.pixels[y * display.width() + x] = on as u8;
+            }
+            machine.pixels.as_ptr()
+        })
+    }
+}
@@ -0,0 +1,116 @@
+use crate::chip8::{CpuSnapshot, EmulatedTime, RunState, SpriteBox};
+#[cfg(feature = "debugger")]
+use crate::debugger::DebuggerView;
+use crate::display::Display;
+use crate::heatmap::Heatmap;
+use crate::megachip::Sample;
+use crate::memory_view::MemoryView;
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+
+// what the producer does when the queue is full
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackPressure {
+    // discard the oldest queued frame, the core never waits on the renderer
+    DropOldest,
+    // stall the core until the renderer caught up
+    Block,
+}
+
+pub struct Frame {
+    pub display: Display,
+    pub beep: bool,
+    pub run_state: RunState,
+    // when this frame was produced, in emulated time
+    pub time: EmulatedTime,
+    // memory activity, only while the core tracks it
+    pub heatmap: Option<Heatmap>,
+    // Chip8State::polled_keys
+    pub polled_keys: u16,
+    // Chip8State::geometry_changes, a new value means the display has another
+    // size even if this frame was dropped
+    pub geometry_changes: u64,
+    pub cpu: CpuSnapshot,
+    // Chip8State::last_sprite
+    pub last_sprite: Option<SpriteBox>,
+    // the megachip screen while it's on, see Renderer::set_colors
+    pub colors: Option<Vec<u32>>,
+    // the megachip sample started last, the same Arc until another starts
+    pub sample: Option<Arc<Sample>>,
+    // only while the memory viewer is open
+    pub memory: Option<MemoryView>,
+    // only while the debugger panel is open
+    #[cfg(feature = "debugger")]
+    pub debugger: Option<DebuggerView>,
+}
+
+// anything besides the renderer that wants every frame, a second window, a
+// network mirror or an encoder, see Chip8Handle::add_sink. the cpu thread
+// publishes to each in the order added before queueing the frame for the
+// renderer, so a sink has to be quick. false drops it, for a consumer that
+// went away
+pub trait FrameSink: Send {
+    fn publish(&mut self, frame: &Frame) -> bool;
+}
+
+impl<F: FnMut(&Frame) -> bool + Send> FrameSink for F {
+    fn publish(&mut self, frame: &Frame) -> bool {
+        self(frame)
+    }
+}
+
+// bounded handoff between the cpu thread and the renderer
+pub struct FrameQueue<T> {
+    frames: Mutex<VecDeque<T>>,
+    space: Condvar,
+    capacity: usize,
+    policy: BackPressure,
+}
+
+impl<T> FrameQueue<T> {
+    pub fn new(capacity: usize, policy: BackPressure) -> Self {
+        assert!(capacity > 0, "frame queue needs room for one frame");
+        FrameQueue {
+            frames: Mutex::new(VecDeque::with_capacity(capacity)),
+            space: Condvar::new(),
+            capacity,
+            policy,
+        }
+    }
+
+    pub fn push(&self, frame: T) {
+        let mut frames = self.frames.lock().unwrap();
+
+        if frames.len() >= self.capacity {
+            match self.policy {
+                BackPressure::DropOldest => {
+                    frames.pop_front();
+                }
+                BackPressure::Block => {
+                    frames = self
+                        .space
+                        .wait_while(frames, |frames| frames.len() >= self.capacity)
+                        .unwrap();
+                }
+            }
+        }
+
+        frames.push_back(frame);
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let frame = self.frames.lock().unwrap().pop_front();
+        if frame.is_some() {
+            self.space.notify_one();
+        }
+        frame
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
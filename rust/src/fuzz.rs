@@ -0,0 +1,238 @@
+// batch fuzzing of the interpreter: mutated roms run on every core, inputs
+// that reach an opcode handler nothing reached before join the corpus, and
+// roms that make the interpreter panic are shrunk to a small reproducer.
+// errors a program causes on purpose (stack overflow, bad opcodes) are
+// outcomes, only panics count as crashes
+use crate::chip8::{Chip8State, MEMORY_SIZE, PROGRAM_START, RunState, StepOutcome};
+pub use crate::profile::handler;
+use crate::rng::Rng;
+use std::collections::BTreeSet;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+use std::thread;
+
+pub const DEFAULT_MAX_FRAMES: usize = 120;
+const INSTRUCTIONS_PER_FRAME: usize = 10;
+const MAX_INPUT: usize = MEMORY_SIZE - PROGRAM_START;
+// panics are common while a crash is fresh, reporting each one once is enough
+const MAX_CRASHES: usize = 16;
+
+#[derive(Clone, Debug)]
+pub struct FuzzConfig {
+    pub threads: usize,
+    // executions across all threads
+    pub iterations: u64,
+    pub max_frames: usize,
+    pub seed: u32,
+}
+
+impl Default for FuzzConfig {
+    fn default() -> Self {
+        FuzzConfig {
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            iterations: 100_000,
+            max_frames: DEFAULT_MAX_FRAMES,
+            seed: 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Crash {
+    // the panic message, crashes with the same one are the same bug
+    pub message: String,
+    // minimized
+    pub rom: Vec<u8>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FuzzReport {
+    pub executions: u64,
+    // handler keys reached, see handler
+    pub coverage: BTreeSet<u16>,
+    pub corpus: Vec<Vec<u8>>,
+    pub crashes: Vec<Crash>,
+}
+
+pub struct Execution {
+    pub coverage: BTreeSet<u16>,
+    pub panic: Option<String>,
+}
+
+// runs rom for up to max_frames with a deterministic seed, pressing one key
+// after the other so the keypad instructions get somewhere
+pub fn execute(rom: &[u8], max_frames: usize) -> Execution {
+    let mut coverage = BTreeSet::new();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut state = Chip8State::with_seed(1);
+        if state.load(rom).is_err() {
+            return;
+        }
+        for frame in 0..max_frames {
+            state.keypad = [false; 16];
+            state.keypad[frame / 4 % 16] = frame % 4 < 2;
+            for _ in 0..INSTRUCTIONS_PER_FRAME {
+                let pc = state.pc as usize;
+                if let (Ok(high), Ok(low)) = (state.read_memory(pc), state.read_memory(pc + 1))
+                    && state.run_state() == RunState::Running
+                {
+                    coverage.insert(handler(u16::from_be_bytes([high, low])));
+                }
+                match state.cycle() {
+                    Err(_) | Ok(StepOutcome::Halted(_)) => return,
+                    Ok(_) => {}
+                }
+            }
+            state.run_frame(0).ok();
+        }
+    }));
+    let panic = result.err().map(|payload| {
+        payload
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+            .unwrap_or_else(|| "panic".to_owned())
+    });
+    Execution { coverage, panic }
+}
+
+// the smallest input fails still accepts, found by deleting ever smaller
+// chunks and then zeroing single bytes
+pub fn minimize(input: &[u8], fails: impl Fn(&[u8]) -> bool) -> Vec<u8> {
+    let mut best = input.to_vec();
+    let mut chunk = best.len().div_ceil(2).max(1);
+    loop {
+        let mut start = 0;
+        while start < best.len() {
+            let end = (start + chunk).min(best.len());
+            let candidate = [&best[..start], &best[end..]].concat();
+            if !candidate.is_empty() && fails(&candidate) {
+                best = candidate;
+            } else {
+                start += chunk;
+            }
+        }
+        if chunk == 1 {
+            break;
+        }
+        chunk = chunk.div_ceil(2);
+    }
+    for i in 0..best.len() {
+        if best[i] != 0 {
+            let mut candidate = best.clone();
+            candidate[i] = 0;
+            if fails(&candidate) {
+                best = candidate;
+            }
+        }
+    }
+    best
+}
+
+// seeds can't be empty, an empty list starts from a single jump to itself
+pub fn fuzz(seeds: &[Vec<u8>], config: &FuzzConfig) -> FuzzReport {
+    let mut corpus: Vec<Vec<u8>> = seeds.iter().filter(|s| !s.is_empty()).cloned().collect();
+    if corpus.is_empty() {
+        corpus.push(vec![0x12, 0x00]);
+    }
+    let mut coverage = BTreeSet::new();
+    for seed in &corpus {
+        coverage.extend(execute(seed, config.max_frames).coverage);
+    }
+    let shared = Mutex::new(FuzzReport {
+        executions: 0,
+        coverage,
+        corpus,
+        crashes: Vec::new(),
+    });
+
+    // the default hook would print every caught panic
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    thread::scope(|scope| {
+        for worker in 0..config.threads.max(1) {
+            let shared = &shared;
+            scope.spawn(move || {
+                let mut rng = Rng::new(
+                    config
+                        .seed
+                        .wrapping_add((worker as u32).wrapping_mul(0x9E37_79B9))
+                        | 1,
+                );
+                loop {
+                    let input = {
+                        let mut report = shared.lock().unwrap();
+                        if report.executions >= config.iterations {
+                            return;
+                        }
+                        report.executions += 1;
+                        let corpus = &report.corpus;
+                        let base = &corpus[below(&mut rng, corpus.len())];
+                        let other = &corpus[below(&mut rng, corpus.len())];
+                        mutate(&mut rng, base, other)
+                    };
+                    let run = execute(&input, config.max_frames);
+
+                    let mut report = shared.lock().unwrap();
+                    if !run.coverage.is_subset(&report.coverage) {
+                        report.coverage.extend(run.coverage);
+                        report.corpus.push(input.clone());
+                    }
+                    let Some(message) = run.panic else {
+                        continue;
+                    };
+                    if report.crashes.len() >= MAX_CRASHES
+                        || report.crashes.iter().any(|c| c.message == message)
+                    {
+                        continue;
+                    }
+                    drop(report);
+                    let rom = minimize(&input, |candidate| {
+                        execute(candidate, config.max_frames).panic.as_ref() == Some(&message)
+                    });
+                    let mut report = shared.lock().unwrap();
+                    if !report.crashes.iter().any(|c| c.message == message) {
+                        report.crashes.push(Crash { message, rom });
+                    }
+                }
+            });
+        }
+    });
+    panic::set_hook(hook);
+    shared.into_inner().unwrap()
+}
+
+fn below(rng: &mut Rng, n: usize) -> usize {
+    let wide = u32::from_be_bytes([rng.next_u8(), rng.next_u8(), rng.next_u8(), rng.next_u8()]);
+    wide as usize % n.max(1)
+}
+
+// one to four of: flip a bit, random byte, random opcode word, insert,
+// delete, or splice in a piece of other
+fn mutate(rng: &mut Rng, base: &[u8], other: &[u8]) -> Vec<u8> {
+    let mut out = base.to_vec();
+    for _ in 0..1 + below(rng, 4) {
+        let at = below(rng, out.len() + 1);
+        match below(rng, 6) {
+            0 if at < out.len() => out[at] ^= 1 << below(rng, 8),
+            1 if at < out.len() => out[at] = rng.next_u8(),
+            2 => {
+                let at = at & !1;
+                let word = [rng.next_u8(), rng.next_u8()];
+                out.splice(at.min(out.len())..(at + 2).min(out.len()), word);
+            }
+            3 => out.insert(at, rng.next_u8()),
+            4 if out.len() > 2 && at < out.len() => {
+                out.remove(at);
+            }
+            5 => {
+                let start = below(rng, other.len());
+                let len = 1 + below(rng, (other.len() - start).min(16));
+                out.splice(at..at, other[start..start + len].iter().copied());
+            }
+            _ => {}
+        }
+    }
+    out.truncate(MAX_INPUT);
+    out
+}
@@ -0,0 +1,286 @@
+// minimal animated gif writer for clips: a global four color table, one
+// full size frame per image and plain lzw, looping forever. decode reads
+// back the palette indices of any gif, for octo cartridges
+use crate::palette::Palette;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GifFrame {
+    // width * height palette indices, row by row
+    pub pixels: Vec<u8>,
+    // in hundredths of a second, browsers treat anything below 2 as 10
+    pub delay: u16,
+}
+
+// color indices are 2 bits, gif's smallest lzw code size
+const MIN_CODE_SIZE: u8 = 2;
+const MAX_CODE: u16 = 4095;
+
+pub fn encode(width: usize, height: usize, palette: &Palette, frames: &[GifFrame]) -> Vec<u8> {
+    let mut gif = b"GIF89a".to_vec();
+    gif.extend_from_slice(&(width as u16).to_le_bytes());
+    gif.extend_from_slice(&(height as u16).to_le_bytes());
+    // global color table of 2^(1+1) entries, background index 0, square pixels
+    gif.extend_from_slice(&[0x91, 0, 0]);
+    for color in palette.colors {
+        gif.extend_from_slice(&[color.0, color.1, color.2]);
+    }
+    // netscape extension, loop forever
+    gif.extend_from_slice(b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00");
+
+    for frame in frames {
+        // graphic control: leave the frame in place, no transparency
+        gif.extend_from_slice(&[0x21, 0xF9, 0x04, 0x04]);
+        gif.extend_from_slice(&frame.delay.to_le_bytes());
+        gif.extend_from_slice(&[0, 0]);
+
+        gif.push(0x2C);
+        gif.extend_from_slice(&[0, 0, 0, 0]);
+        gif.extend_from_slice(&(width as u16).to_le_bytes());
+        gif.extend_from_slice(&(height as u16).to_le_bytes());
+        gif.push(0);
+
+        gif.push(MIN_CODE_SIZE);
+        for block in lzw(&frame.pixels).chunks(255) {
+            gif.push(block.len() as u8);
+            gif.extend_from_slice(block);
+        }
+        gif.push(0);
+    }
+
+    gif.push(0x3B);
+    gif
+}
+
+// variable width codes packed lsb first, the width grows the way giflib
+// expects it to
+struct CodeWriter {
+    out: Vec<u8>,
+    bits: u32,
+    pending: u8,
+    width: u8,
+    next: u16,
+}
+
+impl CodeWriter {
+    fn emit(&mut self, code: u16) {
+        self.bits |= (code as u32) << self.pending;
+        self.pending += self.width;
+        while self.pending >= 8 {
+            self.out.push(self.bits as u8);
+            self.bits >>= 8;
+            self.pending -= 8;
+        }
+        if self.next >= 1 << self.width && self.width < 12 {
+            self.width += 1;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.pending > 0 {
+            self.out.push(self.bits as u8);
+        }
+        self.out
+    }
+}
+
+fn lzw(pixels: &[u8]) -> Vec<u8> {
+    let clear = 1 << MIN_CODE_SIZE;
+    let end = clear + 1;
+    let mut codes = CodeWriter {
+        out: Vec::new(),
+        bits: 0,
+        pending: 0,
+        width: MIN_CODE_SIZE + 1,
+        next: end + 1,
+    };
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    codes.emit(clear);
+
+    let Some((first, rest)) = pixels.split_first() else {
+        codes.emit(end);
+        return codes.finish();
+    };
+    let mut prefix = *first as u16;
+    for pixel in rest {
+        if let Some(code) = table.get(&(prefix, *pixel)) {
+            prefix = *code;
+            continue;
+        }
+        codes.emit(prefix);
+        if codes.next >= MAX_CODE {
+            codes.emit(clear);
+            table.clear();
+            codes.width = MIN_CODE_SIZE + 1;
+            codes.next = end + 1;
+        } else {
+            table.insert((prefix, *pixel), codes.next);
+            codes.next += 1;
+        }
+        prefix = *pixel as u16;
+    }
+    codes.emit(prefix);
+    codes.emit(end);
+    codes.finish()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedImage {
+    pub width: usize,
+    pub height: usize,
+    // width * height palette indices, row by row, deinterlaced
+    pub pixels: Vec<u8>,
+}
+
+// the images in file order, as stored: not composited onto the screen and
+// without their colors
+pub fn decode(gif: &[u8]) -> Result<Vec<DecodedImage>, String> {
+    let mut input = Input { gif, at: 0 };
+    let signature = input.take(6)?;
+    if signature != b"GIF87a" && signature != b"GIF89a" {
+        return Err("not a gif".to_owned());
+    }
+    let screen = input.take(7)?;
+    if screen[4] & 0x80 != 0 {
+        input.take(3 << ((screen[4] & 0x07) + 1))?;
+    }
+
+    let mut images = Vec::new();
+    loop {
+        match input.take(1)?[0] {
+            0x21 => {
+                input.take(1)?;
+                input.sub_blocks()?;
+            }
+            0x2C => {
+                let descriptor = input.take(9)?;
+                let width = u16::from_le_bytes([descriptor[4], descriptor[5]]) as usize;
+                let height = u16::from_le_bytes([descriptor[6], descriptor[7]]) as usize;
+                let flags = descriptor[8];
+                if flags & 0x80 != 0 {
+                    input.take(3 << ((flags & 0x07) + 1))?;
+                }
+                let min_code_size = input.take(1)?[0];
+                if !(1..=11).contains(&min_code_size) {
+                    return Err(format!("bad lzw code size {min_code_size}"));
+                }
+                let data = input.sub_blocks()?;
+                let mut pixels = unlzw(min_code_size, &data, width * height)?;
+                pixels.resize(width * height, 0);
+                if flags & 0x40 != 0 {
+                    pixels = deinterlace(&pixels, width, height);
+                }
+                images.push(DecodedImage {
+                    width,
+                    height,
+                    pixels,
+                });
+            }
+            0x3B => return Ok(images),
+            other => return Err(format!("unknown block {other:02X}")),
+        }
+    }
+}
+
+struct Input<'a> {
+    gif: &'a [u8],
+    at: usize,
+}
+
+impl<'a> Input<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .gif
+            .get(self.at..self.at + len)
+            .ok_or_else(|| "gif is truncated".to_owned())?;
+        self.at += len;
+        Ok(bytes)
+    }
+
+    fn sub_blocks(&mut self) -> Result<Vec<u8>, String> {
+        let mut data = Vec::new();
+        loop {
+            let len = self.take(1)?[0] as usize;
+            if len == 0 {
+                return Ok(data);
+            }
+            data.extend_from_slice(self.take(len)?);
+        }
+    }
+}
+
+// stops at the end code or after len pixels, whichever comes first
+fn unlzw(min_code_size: u8, data: &[u8], len: usize) -> Result<Vec<u8>, String> {
+    let clear = 1usize << min_code_size;
+    let end = clear + 1;
+    let fresh = || -> Vec<Vec<u8>> {
+        (0..clear)
+            .map(|i| vec![i as u8])
+            .chain([Vec::new(), Vec::new()])
+            .collect()
+    };
+    let mut table = fresh();
+    let mut width = min_code_size + 1;
+    let mut previous: Option<usize> = None;
+    let (mut bits, mut pending, mut bytes) = (0u32, 0u8, data.iter());
+    let mut out = Vec::with_capacity(len);
+
+    while out.len() < len {
+        while pending < width {
+            let Some(byte) = bytes.next() else {
+                return Ok(out);
+            };
+            bits |= (*byte as u32) << pending;
+            pending += 8;
+        }
+        let code = (bits & ((1 << width) - 1)) as usize;
+        bits >>= width;
+        pending -= width;
+
+        if code == clear {
+            table = fresh();
+            width = min_code_size + 1;
+            previous = None;
+            continue;
+        }
+        if code == end {
+            break;
+        }
+        let entry = match (table.get(code), previous) {
+            (Some(entry), _) => entry.clone(),
+            (None, Some(previous)) if code == table.len() => {
+                let mut entry = table[previous].clone();
+                entry.push(entry[0]);
+                entry
+            }
+            _ => return Err(format!("bad lzw code {code}")),
+        };
+        out.extend_from_slice(&entry);
+        if let Some(previous) = previous
+            && table.len() < 1 << 12
+        {
+            let mut grown = table[previous].clone();
+            grown.push(entry[0]);
+            table.push(grown);
+        }
+        previous = Some(code);
+        if table.len() == 1 << width && width < 12 {
+            width += 1;
+        }
+    }
+    Ok(out)
+}
+
+// interlaced rows come as every 8th from 0, every 8th from 4, every 4th
+// from 2, then every 2nd from 1
+fn deinterlace(pixels: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut out = vec![0; pixels.len()];
+    let rows = [(0, 8), (4, 8), (2, 4), (1, 2)]
+        .into_iter()
+        .flat_map(|(start, step)| (start..height).step_by(step));
+    for (from, to) in rows.enumerate() {
+        out[to * width..(to + 1) * width]
+            .copy_from_slice(&pixels[from * width..(from + 1) * width]);
+    }
+    out
+}
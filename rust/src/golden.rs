@@ -0,0 +1,130 @@
+// golden screens for rom tests: a rom runs deterministically for a number of
+// frames and its final screen is compared with a text bitmap checked in next
+// to the tests, one line per row, '#' on and '.' off. blessing writes the
+// screen instead, to add or update a golden file:
+//
+//     CHIP8_BLESS=1 cargo test
+use crate::chip8::Chip8State;
+use crate::display::Display;
+use crate::error::Chip8Error;
+use crate::settings::Settings;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+pub const BLESS_VAR: &str = "CHIP8_BLESS";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GoldenMode {
+    Check,
+    // write the screen over the golden file
+    Bless,
+}
+
+impl GoldenMode {
+    // Bless when CHIP8_BLESS is set to anything but "" or "0"
+    pub fn from_env() -> Self {
+        match std::env::var_os(BLESS_VAR) {
+            Some(value) if !value.is_empty() && value != "0" => GoldenMode::Bless,
+            _ => GoldenMode::Check,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct GoldenRun<'a> {
+    pub frames: usize,
+    pub instructions_per_frame: usize,
+    pub seed: u32,
+    // bytes poked after loading, e.g. the quirks rom reads its platform from 0x1FF
+    pub pokes: &'a [(usize, u8)],
+}
+
+impl Default for GoldenRun<'_> {
+    fn default() -> Self {
+        GoldenRun {
+            frames: 60,
+            instructions_per_frame: 10,
+            seed: 1,
+            pokes: &[],
+        }
+    }
+}
+
+impl GoldenRun<'_> {
+    // the same rom, settings and run always end on the same screen
+    pub fn run(&self, rom: &[u8], settings: &Settings) -> Result<Chip8State, Chip8Error> {
+        let mut state = Chip8State::with_seed(self.seed);
+        state.settings = settings.clone();
+        state.load(rom)?;
+        for &(addr, value) in self.pokes {
+            state.write_memory(addr, value)?;
+        }
+        for _ in 0..self.frames {
+            state.run_frame(self.instructions_per_frame)?;
+        }
+        Ok(state)
+    }
+}
+
+pub fn screen_text(display: &Display) -> String {
+    let mut text = String::with_capacity((display.width() + 1) * display.height());
+    for y in 0..display.height() {
+        text.extend((0..display.width()).map(|x| if display.get(x, y) { '#' } else { '.' }));
+        text.push('\n');
+    }
+    text
+}
+
+// None when the screens match, otherwise what differs: the sizes, or every
+// row that isn't the same
+pub fn screen_diff(expected: &str, actual: &str) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+    let size = |text: &str| {
+        let width = text.lines().next().map_or(0, str::len);
+        (width, text.lines().count())
+    };
+    let (want, got) = (size(expected), size(actual));
+    if want != got {
+        return Some(format!(
+            "golden screen is {}x{}, the screen is {}x{}",
+            want.0, want.1, got.0, got.1
+        ));
+    }
+    let mut diff = String::new();
+    for (y, (want, got)) in expected.lines().zip(actual.lines()).enumerate() {
+        if want != got {
+            let _ = writeln!(diff, "row {y}:\n  want {want}\n  got  {got}");
+        }
+    }
+    Some(diff)
+}
+
+// compares the display with the golden file at path, or writes it when blessing
+pub fn check_screen(path: &Path, display: &Display, mode: GoldenMode) -> Result<(), String> {
+    let actual = screen_text(display);
+    if mode == GoldenMode::Bless {
+        if fs::read_to_string(path).ok().as_deref() != Some(actual.as_str()) {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+            }
+            fs::write(path, &actual).map_err(|err| format!("{}: {err}", path.display()))?;
+        }
+        return Ok(());
+    }
+    let expected = fs::read_to_string(path).map_err(|_| {
+        format!(
+            "missing golden screen {}, run with {BLESS_VAR}=1 to write it",
+            path.display()
+        )
+    })?;
+    match screen_diff(&expected, &actual) {
+        None => Ok(()),
+        Some(diff) => Err(format!(
+            "the screen differs from {}, run with {BLESS_VAR}=1 to accept it\n{diff}",
+            path.display()
+        )),
+    }
+}
@@ -0,0 +1,522 @@
+// concurrency contract of the core:
+//
+// Chip8State is plain owned data (no Rc, Cell or RefCell), so it is Send and can
+// move to another thread, but it is never shared. Once spawned, the cpu thread is
+// the single writer; every other thread talks to it through Chip8Handle commands
+// and only ever sees copies of the display through the frame queue, or in a
+// FrameSink the cpu thread calls with each frame before queueing it. Frames the
+// renderer is done with go back through recycle(), so in steady state the two
+// threads trade the same few display buffers instead of allocating new ones.
+// Events the core queues move over once a frame for take_events.
+use crate::chip8::{
+    Chip8State, EmulatedTime, Event, MAX_QUEUED_EVENTS, RUN_TO_FRAMES, RunState, RunTarget,
+};
+use crate::console::DebugCommand;
+#[cfg(feature = "debugger")]
+use crate::debugger::DebuggerView;
+use crate::error::Chip8Error;
+use crate::frame_queue::{BackPressure, Frame, FrameQueue, FrameSink};
+use crate::memory_view::{MemoryCenter, MemoryView};
+use crate::recording::GifRecorder;
+#[cfg(feature = "network")]
+use crate::remote::RemoteCommand;
+use crate::rom::validate_rom;
+use crate::settings::Quirks;
+use crate::slots;
+use crate::timing::{self, TimerResolution};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+const FRAME_TIME: Duration = Duration::from_millis(16);
+
+const _: () = {
+    const fn assert_send<T: Send>() {}
+    assert_send::<Chip8State>();
+};
+
+pub enum Command {
+    SetKeypad([bool; 16]),
+    // reset the machine and start this rom. one that doesn't fit in memory
+    // leaves the machine running and goes out as an Err message
+    LoadRom(Vec<u8>),
+    // track memory accesses and send them along with the frames
+    SetHeatmap(bool),
+    // publish every frame to this too, next to the renderer's queue
+    AddSink(Box<dyn FrameSink>),
+    // send a MemoryView around pc or I with the frames, None stops. tracks
+    // memory accesses too, for the recent writes
+    SetMemoryView(Option<MemoryCenter>),
+    SetPaused(bool),
+    // one frame of a paused machine, see Chip8State::step_frame
+    StepFrame,
+    // one instruction of a paused machine, see Chip8State::step_instruction
+    StepInstruction,
+    // a paused machine up to its next DXYN or FX18, see Chip8State::run_to
+    RunTo(RunTarget),
+    // send a DebuggerView with the frames, false stops
+    #[cfg(feature = "debugger")]
+    SetDebugger(bool),
+    // settings.instructions_per_second
+    SetSpeed(u32),
+    // settings.quirks
+    SetQuirks(Quirks),
+    // start a GifRecorder of every frame, stopping sends it back, see
+    // Chip8Handle::finished_recording
+    SetRecording(bool),
+    // the profile of a profiling machine as a message, see
+    // Chip8Handle::take_messages
+    ReportProfile,
+    // writes the machine to a state file or replaces it with one, see slots.
+    // what happened goes out as a message, and "ok" or "err MESSAGE" to the
+    // sender if there is one
+    SaveState(PathBuf, Option<Sender<String>>),
+    LoadState(PathBuf, Option<Sender<String>>),
+    // a console command, what it shows goes out as a message
+    Debug(DebugCommand),
+    // a command of a remote debugger, its reply goes to the sender
+    #[cfg(feature = "network")]
+    Remote(RemoteCommand, Sender<String>),
+    Shutdown,
+}
+
+// the core's emulated time, readable from any thread without a lock
+#[derive(Default)]
+struct Clock {
+    frames: AtomicU64,
+    cycles: AtomicU64,
+}
+
+pub struct Chip8Handle {
+    commands: Sender<Command>,
+    frames: Arc<FrameQueue<Frame>>,
+    spare: Arc<FrameQueue<Frame>>,
+    clock: Arc<Clock>,
+    events: Arc<Mutex<Vec<Event>>>,
+    recordings: Receiver<GifRecorder>,
+    messages: Receiver<Result<String, String>>,
+    thread: JoinHandle<(Chip8State, Result<(), Chip8Error>)>,
+}
+
+impl Chip8Handle {
+    // moves the state onto its own thread, step runs once per frame with
+    // the real time elapsed since the previous step
+    pub fn spawn(
+        state: Chip8State,
+        frame_queue_len: usize,
+        policy: BackPressure,
+        step: impl FnMut(&mut Chip8State, Duration) -> Result<(), Chip8Error> + Send + 'static,
+    ) -> Self {
+        let (commands, receiver) = mpsc::channel();
+        let frames = Arc::new(FrameQueue::new(frame_queue_len, policy));
+        // one more than can be queued, for the frame the renderer is showing
+        let spare = Arc::new(FrameQueue::new(
+            frame_queue_len + 1,
+            BackPressure::DropOldest,
+        ));
+
+        let clock = Arc::new(Clock::default());
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let (finished, recordings) = mpsc::channel();
+        let (reports, messages) = mpsc::channel();
+
+        let thread = {
+            let shared = Shared {
+                frames: Arc::clone(&frames),
+                spare: Arc::clone(&spare),
+                clock: Arc::clone(&clock),
+                events: Arc::clone(&events),
+            };
+            thread::spawn(move || cpu_loop(state, receiver, &shared, &finished, &reports, step))
+        };
+
+        Chip8Handle {
+            commands,
+            frames,
+            spare,
+            clock,
+            events,
+            recordings,
+            messages,
+            thread,
+        }
+    }
+
+    pub fn set_keypad(&self, keypad: [bool; 16]) {
+        let _ = self.commands.send(Command::SetKeypad(keypad));
+    }
+
+    pub fn load_rom(&self, rom: Vec<u8>) {
+        let _ = self.commands.send(Command::LoadRom(rom));
+    }
+
+    pub fn set_heatmap(&self, enabled: bool) {
+        let _ = self.commands.send(Command::SetHeatmap(enabled));
+    }
+
+    pub fn set_memory_view(&self, center: Option<MemoryCenter>) {
+        let _ = self.commands.send(Command::SetMemoryView(center));
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        let _ = self.commands.send(Command::SetPaused(paused));
+    }
+
+    pub fn step_frame(&self) {
+        let _ = self.commands.send(Command::StepFrame);
+    }
+
+    pub fn step_instruction(&self) {
+        let _ = self.commands.send(Command::StepInstruction);
+    }
+
+    pub fn run_to(&self, target: RunTarget) {
+        let _ = self.commands.send(Command::RunTo(target));
+    }
+
+    #[cfg(feature = "debugger")]
+    pub fn set_debugger(&self, on: bool) {
+        let _ = self.commands.send(Command::SetDebugger(on));
+    }
+
+    pub fn set_speed(&self, instructions_per_second: u32) {
+        let _ = self
+            .commands
+            .send(Command::SetSpeed(instructions_per_second));
+    }
+
+    pub fn set_quirks(&self, quirks: Quirks) {
+        let _ = self.commands.send(Command::SetQuirks(quirks));
+    }
+
+    pub fn set_recording(&self, recording: bool) {
+        let _ = self.commands.send(Command::SetRecording(recording));
+    }
+
+    pub fn report_profile(&self) {
+        let _ = self.commands.send(Command::ReportProfile);
+    }
+
+    pub fn save_state(&self, path: PathBuf) {
+        let _ = self.commands.send(Command::SaveState(path, None));
+    }
+
+    pub fn load_state(&self, path: PathBuf) {
+        let _ = self.commands.send(Command::LoadState(path, None));
+    }
+
+    // save_state and load_state for a client that waits for the outcome
+    pub fn save_state_replying(&self, path: PathBuf, reply_to: Sender<String>) {
+        let _ = self.commands.send(Command::SaveState(path, Some(reply_to)));
+    }
+
+    pub fn load_state_replying(&self, path: PathBuf, reply_to: Sender<String>) {
+        let _ = self.commands.send(Command::LoadState(path, Some(reply_to)));
+    }
+
+    pub fn debug(&self, command: DebugCommand) {
+        let _ = self.commands.send(Command::Debug(command));
+    }
+
+    #[cfg(feature = "network")]
+    pub fn remote(&self, command: RemoteCommand, reply_to: Sender<String>) {
+        let _ = self.commands.send(Command::Remote(command, reply_to));
+    }
+
+    // a recording stopped with set_recording(false), once
+    pub fn finished_recording(&self) -> Option<GifRecorder> {
+        self.recordings.try_recv().ok()
+    }
+
+    // the same, waiting for the cpu thread to get to the command
+    pub fn wait_for_recording(&self, timeout: Duration) -> Option<GifRecorder> {
+        self.recordings.recv_timeout(timeout).ok()
+    }
+
+    // latest emulated time of the core, ahead of the frames still queued
+    pub fn emulated_time(&self) -> EmulatedTime {
+        EmulatedTime {
+            frames: self.clock.frames.load(Ordering::Relaxed),
+            cycles: self.clock.cycles.load(Ordering::Relaxed),
+        }
+    }
+
+    // what happened in the core since the last call, oldest first. frames
+    // can be dropped, events aren't until MAX_QUEUED_EVENTS pile up
+    pub fn take_events(&self) -> Vec<Event> {
+        std::mem::take(&mut *self.events.lock().unwrap())
+    }
+
+    // what load_rom, report_profile, save_state, load_state and debug had to
+    // say since the last call, oldest first, Err for a failure. the frontend
+    // shows them
+    pub fn take_messages(&self) -> Vec<Result<String, String>> {
+        self.messages.try_iter().collect()
+    }
+
+    pub fn frames(&self) -> &FrameQueue<Frame> {
+        &self.frames
+    }
+
+    // another consumer of the frames, called on the cpu thread with each one
+    // from the next frame on, see FrameSink
+    pub fn add_sink(&self, sink: impl FrameSink + 'static) {
+        let _ = self.commands.send(Command::AddSink(Box::new(sink)));
+    }
+
+    // hands a shown frame back so the cpu thread can reuse its buffer
+    pub fn recycle(&self, frame: Frame) {
+        self.spare.push(frame);
+    }
+
+    // false once the core stopped on an error
+    pub fn is_running(&self) -> bool {
+        !self.thread.is_finished()
+    }
+
+    // stops the cpu thread and hands the state back, along with the
+    // error that stopped it early if there was one
+    pub fn shutdown(self) -> (Chip8State, Result<(), Chip8Error>) {
+        let _ = self.commands.send(Command::Shutdown);
+
+        // a blocking back-pressure policy could keep the core parked on a full queue
+        while !self.thread.is_finished() {
+            self.frames.pop();
+            thread::yield_now();
+        }
+
+        self.thread.join().expect("cpu thread panicked")
+    }
+}
+
+// what the cpu thread hands over to the handle
+struct Shared {
+    frames: Arc<FrameQueue<Frame>>,
+    spare: Arc<FrameQueue<Frame>>,
+    clock: Arc<Clock>,
+    events: Arc<Mutex<Vec<Event>>>,
+}
+
+fn cpu_loop(
+    mut state: Chip8State,
+    commands: Receiver<Command>,
+    shared: &Shared,
+    recordings: &Sender<GifRecorder>,
+    messages: &Sender<Result<String, String>>,
+    mut step: impl FnMut(&mut Chip8State, Duration) -> Result<(), Chip8Error>,
+) -> (Chip8State, Result<(), Chip8Error>) {
+    let Shared {
+        frames,
+        spare,
+        clock,
+        events,
+    } = shared;
+    let _resolution = TimerResolution::acquire();
+    state.set_event_queue(true);
+    let mut last_step = Instant::now();
+    let mut sinks: Vec<Box<dyn FrameSink>> = Vec::new();
+    let mut recording: Option<GifRecorder> = None;
+    let mut heatmap = false;
+    let mut memory_view: Option<MemoryCenter> = None;
+    #[cfg(feature = "debugger")]
+    let mut debugger = false;
+    loop {
+        // a halted or paused program can't change anything on its own, so
+        // sleep until a command comes in. a halted one still runs out its
+        // timers first, or the buzzer would never stop
+        let idle = match state.run_state() {
+            RunState::Paused => true,
+            RunState::Halted => state.delay_timer == 0 && state.sound_timer == 0,
+            _ => false,
+        };
+        let pending = if idle {
+            match commands.recv() {
+                Ok(command) => {
+                    // the time spent blocked doesn't count as run time
+                    last_step = Instant::now();
+                    Some(command)
+                }
+                Err(_) => return (state, Ok(())),
+            }
+        } else {
+            None
+        };
+
+        for command in pending.into_iter().chain(commands.try_iter()) {
+            match command {
+                Command::SetKeypad(keypad) => state.keypad = keypad,
+                Command::LoadRom(rom) => {
+                    let max = state.memory_size().saturating_sub(state.program_start());
+                    let loaded = validate_rom(&rom, max).and_then(|()| {
+                        state.reset();
+                        state.load(&rom)
+                    });
+                    if let Err(err) = loaded {
+                        let _ = messages.send(Err(err.to_string()));
+                    }
+                }
+                Command::SetHeatmap(enabled) => {
+                    heatmap = enabled;
+                    state.set_heatmap_enabled(heatmap || memory_view.is_some());
+                }
+                Command::SetMemoryView(center) => {
+                    memory_view = center;
+                    state.set_heatmap_enabled(heatmap || memory_view.is_some());
+                }
+                Command::AddSink(sink) => sinks.push(sink),
+                Command::SetPaused(paused) => state.set_paused(paused),
+                Command::StepFrame => {
+                    if let Err(err) = state.step_frame() {
+                        return (state, Err(err));
+                    }
+                }
+                Command::StepInstruction => {
+                    if let Err(err) = state.step_instruction() {
+                        return (state, Err(err));
+                    }
+                }
+                Command::RunTo(target) => {
+                    if let Err(err) = state.run_to(target, RUN_TO_FRAMES) {
+                        return (state, Err(err));
+                    }
+                }
+                #[cfg(feature = "debugger")]
+                Command::SetDebugger(on) => debugger = on,
+                Command::SetSpeed(speed) => state.settings.instructions_per_second = speed,
+                Command::SetQuirks(quirks) => state.settings.quirks = quirks,
+                Command::SetRecording(true) => {
+                    recording.get_or_insert_with(GifRecorder::new);
+                }
+                Command::SetRecording(false) => {
+                    if let Some(finished) = recording.take() {
+                        let _ = recordings.send(finished);
+                    }
+                }
+                Command::ReportProfile => {
+                    let _ = messages.send(Ok(match state.profile() {
+                        Some(profile) => profile.to_string(),
+                        None => "not profiling, start with --profile".to_owned(),
+                    }));
+                }
+                Command::SaveState(path, reply_to) => {
+                    let saved = slots::save(&path, &state);
+                    let shown = format!("saved state to {}", path.display());
+                    let _ = messages.send(saved.clone().map(|()| shown));
+                    reply(reply_to, &saved);
+                }
+                Command::LoadState(path, reply_to) => {
+                    let loaded = slots::load(&path, &mut state);
+                    let shown = format!("loaded state {}", path.display());
+                    let _ = messages.send(loaded.clone().map(|()| shown));
+                    reply(reply_to, &loaded);
+                }
+                Command::Debug(command) => {
+                    let _ = messages.send(command.apply(&mut state));
+                }
+                #[cfg(feature = "network")]
+                Command::Remote(command, reply_to) => {
+                    let _ = reply_to.send(command.apply(&mut state));
+                }
+                Command::Shutdown => return (state, Ok(())),
+            }
+        }
+
+        let now = Instant::now();
+        let elapsed = now - last_step;
+        last_step = now;
+        if let Err(err) = step(&mut state, elapsed) {
+            return (state, Err(err));
+        }
+        let time = state.emulated_time();
+        clock.frames.store(time.frames, Ordering::Relaxed);
+        clock.cycles.store(time.cycles, Ordering::Relaxed);
+        if let Some(recording) = &mut recording {
+            recording.push(&state.display, time.frames);
+        }
+
+        let frame = match spare.pop() {
+            Some(mut frame) => {
+                frame.display.clone_from(&state.display);
+                frame.beep = state.sound_timer > 0;
+                frame.run_state = state.run_state();
+                frame.time = time;
+                frame.polled_keys = state.polled_keys();
+                frame.geometry_changes = state.geometry_changes();
+                frame.cpu = state.cpu_snapshot();
+                frame.last_sprite = state.last_sprite();
+                match (&mut frame.colors, megachip_screen(&state)) {
+                    (Some(colors), Some(screen)) => colors.clone_from_slice(screen),
+                    (colors, screen) => *colors = screen.map(<[u32]>::to_vec),
+                }
+                frame.sample = state
+                    .megachip()
+                    .and_then(|megachip| megachip.sample().cloned());
+                match (&mut frame.heatmap, state.heatmap()) {
+                    (Some(heatmap), Some(source)) => heatmap.clone_from(source),
+                    (heatmap, source) => *heatmap = source.cloned(),
+                }
+                match (&mut frame.memory, memory_view) {
+                    (Some(view), Some(center)) => view.capture(&state, center),
+                    (view, center) => *view = center.map(|c| MemoryView::new(&state, c)),
+                }
+                #[cfg(feature = "debugger")]
+                match &mut frame.debugger {
+                    Some(view) if debugger => view.capture(&state),
+                    view => *view = debugger.then(|| DebuggerView::new(&state)),
+                }
+                frame
+            }
+            None => Frame {
+                display: state.display.clone(),
+                beep: state.sound_timer > 0,
+                run_state: state.run_state(),
+                time,
+                heatmap: state.heatmap().cloned(),
+                polled_keys: state.polled_keys(),
+                geometry_changes: state.geometry_changes(),
+                cpu: state.cpu_snapshot(),
+                last_sprite: state.last_sprite(),
+                colors: megachip_screen(&state).map(<[u32]>::to_vec),
+                sample: state
+                    .megachip()
+                    .and_then(|megachip| megachip.sample().cloned()),
+                memory: memory_view.map(|center| MemoryView::new(&state, center)),
+                #[cfg(feature = "debugger")]
+                debugger: debugger.then(|| DebuggerView::new(&state)),
+            },
+        };
+        sinks.retain_mut(|sink| sink.publish(&frame));
+        frames.push(frame);
+        let happened = state.take_events();
+        if !happened.is_empty() {
+            let mut events = events.lock().unwrap();
+            let room = MAX_QUEUED_EVENTS.saturating_sub(events.len());
+            events.extend(happened.into_iter().take(room));
+        }
+
+        // sleep off the rest of the frame, the elapsed time passed to step
+        // keeps the cpu speed right even when this oversleeps
+        timing::sleep_until(last_step + FRAME_TIME);
+    }
+}
+
+// the colors of a megachip screen that's on
+fn megachip_screen(state: &Chip8State) -> Option<&[u32]> {
+    state
+        .megachip()
+        .filter(|megachip| megachip.active)
+        .map(|megachip| megachip.screen())
+}
+
+// the "ok" or "err MESSAGE" a client of save_state_replying waits for
+fn reply(reply_to: Option<Sender<String>>, outcome: &Result<(), String>) {
+    if let Some(reply_to) = reply_to {
+        let _ = reply_to.send(match outcome {
+            Ok(()) => "ok\n".to_owned(),
+            Err(err) => format!("err {err}\n"),
+        });
+    }
+}
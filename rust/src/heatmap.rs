@@ -0,0 +1,93 @@
+// recent memory activity, one cell per byte that lights up on access and
+// fades out over about a second
+use crate::chip8::MEMORY_SIZE;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+
+// per timer tick, 255 fades out in 64 ticks
+const DECAY: u8 = 4;
+
+// cells per row when drawn as a square grid
+pub const HEATMAP_SIDE: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    // opcode fetch
+    Fetch,
+    Read,
+    Write,
+}
+
+#[derive(Debug)]
+pub struct Heatmap {
+    fetch: Vec<u8>,
+    read: Vec<u8>,
+    write: Vec<u8>,
+}
+
+impl Clone for Heatmap {
+    fn clone(&self) -> Self {
+        Heatmap {
+            fetch: self.fetch.clone(),
+            read: self.read.clone(),
+            write: self.write.clone(),
+        }
+    }
+
+    // frames are recycled, so keep their buffers
+    fn clone_from(&mut self, source: &Self) {
+        self.fetch.clone_from(&source.fetch);
+        self.read.clone_from(&source.read);
+        self.write.clone_from(&source.write);
+    }
+}
+
+impl Default for Heatmap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Heatmap {
+    pub fn new() -> Self {
+        Heatmap {
+            fetch: vec![0; MEMORY_SIZE],
+            read: vec![0; MEMORY_SIZE],
+            write: vec![0; MEMORY_SIZE],
+        }
+    }
+
+    pub fn record(&mut self, addr: usize, access: Access) {
+        let channel = match access {
+            Access::Fetch => &mut self.fetch,
+            Access::Read => &mut self.read,
+            Access::Write => &mut self.write,
+        };
+        if let Some(heat) = channel.get_mut(addr) {
+            *heat = u8::MAX;
+        }
+    }
+
+    pub fn decay(&mut self) {
+        for heat in self
+            .fetch
+            .iter_mut()
+            .chain(&mut self.read)
+            .chain(&mut self.write)
+        {
+            *heat = heat.saturating_sub(DECAY);
+        }
+    }
+
+    // 0 for addresses outside the map
+    pub fn write_heat(&self, addr: usize) -> u8 {
+        self.write.get(addr).copied().unwrap_or(0)
+    }
+
+    // rgb of a memory cell, writes red, fetches green and reads blue,
+    // None while it has been quiet for a while
+    pub fn color(&self, addr: usize) -> Option<(u8, u8, u8)> {
+        let color = (self.write[addr], self.fetch[addr], self.read[addr]);
+        (color != (0, 0, 0)).then_some(color)
+    }
+}
@@ -0,0 +1,128 @@
+// key presses and releases by emulated frame, for --record-input and
+// --replay-input. with the fixed seed and instruction budget of
+// --deterministic a replay reproduces the recorded run exactly, so a log is
+// a bug report or a gameplay regression test. the file is text:
+//
+//     # frame key state
+//     120 5 down
+//     128 5 up
+use std::fmt::Write;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InputEvent {
+    // the key changed before this emulated frame ran, see EmulatedTime
+    pub frame: u64,
+    pub key: u8,
+    pub pressed: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InputLog {
+    events: Vec<InputEvent>,
+    // the keypad as of the last record
+    keypad: [bool; 16],
+}
+
+impl InputLog {
+    pub fn new() -> Self {
+        InputLog::default()
+    }
+
+    // call before running frame with the keypad it will see, only changes
+    // are kept
+    pub fn record(&mut self, frame: u64, keypad: &[bool; 16]) {
+        for (key, (pressed, was)) in keypad.iter().zip(&self.keypad).enumerate() {
+            if pressed != was {
+                self.events.push(InputEvent {
+                    frame,
+                    key: key as u8,
+                    pressed: *pressed,
+                });
+            }
+        }
+        self.keypad = *keypad;
+    }
+
+    pub fn events(&self) -> &[InputEvent] {
+        &self.events
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = "# frame key state\n".to_owned();
+        for event in &self.events {
+            let state = if event.pressed { "down" } else { "up" };
+            let _ = writeln!(text, "{} {:X} {state}", event.frame, event.key);
+        }
+        text
+    }
+
+    // the events must be in frame order, blank lines and # comments are skipped
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut log = InputLog::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let bad = || format!("line {}: expected frame, key and down or up", n + 1);
+            let mut fields = line.split_whitespace();
+            let (Some(frame), Some(key), Some(state), None) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                return Err(bad());
+            };
+            let frame: u64 = frame.parse().map_err(|_| bad())?;
+            let key = u8::from_str_radix(key, 16)
+                .ok()
+                .filter(|key| *key < 16)
+                .ok_or_else(bad)?;
+            let pressed = match state {
+                "down" => true,
+                "up" => false,
+                _ => return Err(bad()),
+            };
+            if log.events.last().is_some_and(|last| last.frame > frame) {
+                return Err(format!("line {}: frame {frame} is out of order", n + 1));
+            }
+            log.events.push(InputEvent {
+                frame,
+                key,
+                pressed,
+            });
+            log.keypad[key as usize] = pressed;
+        }
+        Ok(log)
+    }
+}
+
+// plays a log back, ignoring whatever the keyboard does
+pub struct InputReplay {
+    events: Vec<InputEvent>,
+    next: usize,
+    keypad: [bool; 16],
+}
+
+impl InputReplay {
+    pub fn new(log: &InputLog) -> Self {
+        InputReplay {
+            events: log.events.clone(),
+            next: 0,
+            keypad: [false; 16],
+        }
+    }
+
+    // the keypad for frame, after every event up to it
+    pub fn keypad(&mut self, frame: u64) -> [bool; 16] {
+        while let Some(event) = self.events.get(self.next)
+            && event.frame <= frame
+        {
+            self.keypad[event.key as usize] = event.pressed;
+            self.next += 1;
+        }
+        self.keypad
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next == self.events.len()
+    }
+}
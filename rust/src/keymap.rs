@@ -0,0 +1,303 @@
+// which physical key does what: the 16 chip8 keys and the emulator controls.
+// defaults to KEY_LAYOUT and HOTKEYS, a config file and the command line can
+// rebind any of them
+//
+//     [keymap]
+//     # a LAYOUTS preset first, the lines after it change single keys
+//     layout = qwertz
+//     # chip8 keys by hex digit
+//     0 = x
+//     a = z
+//     # emulator controls by Hotkey::NAMES
+//     pause = Space
+//     speed-up = =
+//
+//     # GAMEPAD_BUTTONS to chip8 keys, for every rom
+//     [gamepad]
+//     a = 6
+//     # only while this rom (by file name) runs
+//     [gamepad pong.ch8]
+//     up = 1
+//     down = 4
+use crate::backend::{GAMEPAD_BUTTONS, GAMEPAD_LAYOUT, HOTKEYS, Hotkey, KEY_LAYOUT};
+
+// keys are bound by where they sit on a qwerty keyboard, so the 1234/qwer/
+// asdf/zxcv block stays in place on any keyboard and a layout mostly changes
+// what the key legend calls them
+#[derive(Debug, PartialEq, Eq)]
+pub struct Layout {
+    pub name: &'static str,
+    // physical key of every chip8 key 0x0..=0xF
+    pub keypad: [char; 16],
+    // (physical key, what the layout prints on it) where the two differ
+    pub printed: &'static [(char, char)],
+}
+
+pub const LAYOUTS: &[Layout] = &[
+    Layout {
+        name: "qwerty",
+        keypad: KEY_LAYOUT,
+        printed: &[],
+    },
+    Layout {
+        name: "qwertz",
+        keypad: KEY_LAYOUT,
+        printed: &[('y', 'z'), ('z', 'y')],
+    },
+    Layout {
+        name: "azerty",
+        keypad: KEY_LAYOUT,
+        printed: &[('q', 'a'), ('a', 'q'), ('w', 'z'), ('z', 'w'), (';', 'm')],
+    },
+    // as the cosmac vip's hex keypad labels them, every chip8 key on the key
+    // of its digit
+    Layout {
+        name: "cosmac",
+        keypad: [
+            '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e', 'f',
+        ],
+        printed: &[],
+    },
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Keymap {
+    // key name of every chip8 key 0x0..=0xF
+    pub keypad: [String; 16],
+    // the keyboard the legend is printed for, see set_layout
+    pub layout: &'static Layout,
+    pub hotkeys: Vec<(String, Hotkey)>,
+    // button and the chip8 key it presses
+    pub gamepad: Vec<(String, u8)>,
+    // per rom file name, applied over gamepad
+    pub rom_gamepads: Vec<(String, Vec<(String, u8)>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap {
+            keypad: KEY_LAYOUT.map(|c| c.to_string()),
+            layout: &LAYOUTS[0],
+            hotkeys: HOTKEYS
+                .iter()
+                .map(|(name, hotkey)| (name.to_string(), *hotkey))
+                .collect(),
+            gamepad: GAMEPAD_LAYOUT
+                .iter()
+                .map(|(button, key)| (button.to_string(), *key))
+                .collect(),
+            rom_gamepads: Vec::new(),
+        }
+    }
+}
+
+impl Keymap {
+    // a LAYOUTS preset by name, replacing all 16 chip8 keys
+    pub fn set_layout(&mut self, name: &str) -> Result<(), String> {
+        let layout = LAYOUTS
+            .iter()
+            .find(|layout| layout.name == name.trim())
+            .ok_or_else(|| {
+                let names: Vec<_> = LAYOUTS.iter().map(|layout| layout.name).collect();
+                format!(
+                    "unknown layout {name}, expected one of {}",
+                    names.join(", ")
+                )
+            })?;
+        self.keypad = layout.keypad.map(|c| c.to_string());
+        self.layout = layout;
+        Ok(())
+    }
+
+    // what the keypad overlay shows for every chip8 key: the bound key as
+    // the layout prints it, the first letter of longer names
+    pub fn legend(&self) -> [char; 16] {
+        self.keypad.each_ref().map(|name| {
+            let c = name.chars().next().unwrap_or(' ').to_ascii_lowercase();
+            let printed = match name.chars().count() {
+                1 => self.layout.printed.iter().find(|(key, _)| *key == c),
+                _ => None,
+            };
+            printed.map_or(c, |(_, label)| *label).to_ascii_uppercase()
+        })
+    }
+
+    // all 16 chip8 keys at once, one character each in key order 0..F,
+    // e.g. "x123qweasdzc4rfv" for the default
+    pub fn set_keypad_layout(&mut self, layout: &str) -> Result<(), String> {
+        let keys: Vec<char> = layout.chars().collect();
+        if keys.len() != 16 {
+            return Err(format!("keypad layout needs 16 keys, got {}", keys.len()));
+        }
+        for (key, c) in self.keypad.iter_mut().zip(keys) {
+            *key = c.to_string();
+        }
+        Ok(())
+    }
+
+    // action is a chip8 key as a hex digit or a Hotkey::NAMES name, binding
+    // a hotkey replaces its previous key
+    pub fn bind(&mut self, action: &str, key: &str) -> Result<(), String> {
+        let (action, key) = (action.trim(), key.trim());
+        if key.is_empty() {
+            return Err(format!("no key given for {action}"));
+        }
+
+        if action.len() == 1
+            && let Some(digit) = action.chars().next().and_then(|c| c.to_digit(16))
+        {
+            self.keypad[digit as usize] = key.to_owned();
+            return Ok(());
+        }
+
+        let hotkey = Hotkey::parse(action).ok_or_else(|| format!("unknown action {action}"))?;
+        self.hotkeys.retain(|(_, bound)| *bound != hotkey);
+        self.hotkeys.push((key.to_owned(), hotkey));
+        Ok(())
+    }
+
+    // "action = key" lines of the [keymap] section and "button = key" lines
+    // of the [gamepad] ones, other sections are left to whoever reads them
+    pub fn load_config(&mut self, text: &str) -> Result<(), String> {
+        enum Section {
+            Keymap,
+            // None for every rom
+            Gamepad(Option<String>),
+            Other,
+        }
+
+        let mut section = Section::Other;
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = match name.trim().split_once(' ') {
+                    None if name.trim() == "keymap" => Section::Keymap,
+                    None if name.trim() == "gamepad" => Section::Gamepad(None),
+                    Some(("gamepad", rom)) => Section::Gamepad(Some(rom.trim().to_owned())),
+                    _ => Section::Other,
+                };
+                continue;
+            }
+
+            let result = match &section {
+                Section::Keymap => line
+                    .split_once('=')
+                    .ok_or_else(|| "expected action = key".to_owned())
+                    .and_then(|(action, key)| match action.trim() {
+                        "layout" => self.set_layout(key),
+                        _ => self.bind(action, key),
+                    }),
+                Section::Gamepad(rom) => line
+                    .split_once('=')
+                    .ok_or_else(|| "expected button = key".to_owned())
+                    .and_then(|(button, key)| self.bind_button(rom.as_deref(), button, key)),
+                Section::Other => Ok(()),
+            };
+            result.map_err(|err| format!("line {}: {err}", n + 1))?;
+        }
+        Ok(())
+    }
+
+    // a chip8 key as a hex digit for a GAMEPAD_BUTTONS button, for every rom
+    // or only for one
+    pub fn bind_button(
+        &mut self,
+        rom: Option<&str>,
+        button: &str,
+        key: &str,
+    ) -> Result<(), String> {
+        let (button, key) = (button.trim(), key.trim());
+        if !GAMEPAD_BUTTONS.contains(&button) {
+            return Err(format!("unknown button {button}"));
+        }
+        let key = u8::from_str_radix(key, 16)
+            .ok()
+            .filter(|key| *key < 16)
+            .ok_or_else(|| format!("{button} expects a chip8 key 0 to F"))?;
+
+        let mapping = match rom {
+            None => &mut self.gamepad,
+            Some(rom) => {
+                let index = match self.rom_gamepads.iter().position(|(name, _)| name == rom) {
+                    Some(index) => index,
+                    None => {
+                        self.rom_gamepads.push((rom.to_owned(), Vec::new()));
+                        self.rom_gamepads.len() - 1
+                    }
+                };
+                &mut self.rom_gamepads[index].1
+            }
+        };
+        mapping.retain(|(bound, _)| bound != button);
+        mapping.push((button.to_owned(), key));
+        Ok(())
+    }
+
+    // the gamepad mapping while rom (a file name) runs
+    pub fn gamepad_for(&self, rom: Option<&str>) -> Vec<(String, u8)> {
+        let mut mapping = self.gamepad.clone();
+        let overrides = self
+            .rom_gamepads
+            .iter()
+            .filter(|(name, _)| Some(name.as_str()) == rom)
+            .flat_map(|(_, overrides)| overrides);
+        for (button, key) in overrides {
+            mapping.retain(|(bound, _)| bound != button);
+            mapping.push((button.clone(), *key));
+        }
+        mapping
+    }
+}
+
+// arrow keys and space for whatever keys a game reads (Chip8State::polled_keys),
+// so it plays without learning its layout. directions go to the first layout
+// that fits, space to the action key the layout usually comes with or the
+// first other key the game uses
+pub fn auto_keys(polled: u16) -> Vec<(String, u8)> {
+    // up, down, left and right, then the action keys in order of preference
+    const LAYOUTS: &[([Option<u8>; 4], &[u8])] = &[
+        // wasd on the default keyboard layout, octo's convention
+        ([Some(0x5), Some(0x8), Some(0x7), Some(0x9)], &[0x6, 0x4]),
+        // the 2/4/6/8 cross of the hex keypad
+        ([Some(0x2), Some(0x8), Some(0x4), Some(0x6)], &[0x5, 0x0]),
+        // paddles going up and down, left and right player
+        ([Some(0x1), Some(0x4), None, None], &[]),
+        ([Some(0xC), Some(0xD), None, None], &[]),
+    ];
+    const ARROWS: [&str; 4] = ["Up", "Down", "Left", "Right"];
+    let polls = |key: u8| polled & (1 << key) != 0;
+
+    let covered = |directions: &[Option<u8>; 4]| {
+        directions
+            .iter()
+            .flatten()
+            .filter(|key| polls(**key))
+            .count()
+    };
+    let Some((directions, actions)) = LAYOUTS
+        .iter()
+        .filter(|(directions, _)| covered(directions) >= 2)
+        .max_by_key(|(directions, _)| covered(directions))
+    else {
+        return Vec::new();
+    };
+
+    let mut mapping: Vec<(String, u8)> = ARROWS
+        .iter()
+        .zip(directions)
+        .filter_map(|(arrow, key)| Some((arrow.to_string(), (*key).filter(|key| polls(*key))?)))
+        .collect();
+
+    let action = actions
+        .iter()
+        .copied()
+        .chain(0..16)
+        .find(|key| polls(*key) && mapping.iter().all(|(_, bound)| bound != key));
+    if let Some(key) = action {
+        mapping.push(("Space".to_owned(), key));
+    }
+    mapping
+}
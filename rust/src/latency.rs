@@ -0,0 +1,68 @@
+// end to end input latency: the time from the frontend seeing a key go down
+// to it receiving the first frame that shows a changed display
+use crate::display::Display;
+use std::time::{Duration, Instant};
+
+// waits for a key, toggles a pixel in column V0 and waits for the release
+pub const LATENCY_ROM: [u8; 13] = [
+    0xF0, 0x0A, // 200: V0 = key
+    0xA2, 0x0C, // 202: I = sprite
+    0xD0, 0x11, // 204: draw 1 row at (V0, V1)
+    0xE0, 0xA1, // 206: skip if V0 is released
+    0x12, 0x06, // 208: keep waiting
+    0x12, 0x00, // 20A: next key
+    0x80, // 20C: sprite, a single pixel
+];
+
+#[derive(Default)]
+pub struct LatencyProbe {
+    pressed_at: Option<Instant>,
+    any_pressed: bool,
+    last_display: Option<Display>,
+    samples: Vec<Duration>,
+}
+
+impl LatencyProbe {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // call right after polling the host keyboard
+    pub fn keypad(&mut self, keypad: &[bool; 16]) {
+        let any_pressed = keypad.iter().any(|pressed| *pressed);
+        if any_pressed && !self.any_pressed && self.pressed_at.is_none() {
+            self.pressed_at = Some(Instant::now());
+        }
+        self.any_pressed = any_pressed;
+    }
+
+    // call with every frame the frontend is about to show
+    pub fn frame(&mut self, display: &Display) {
+        if self.last_display.as_ref() == Some(display) {
+            return;
+        }
+        if let Some(pressed_at) = self.pressed_at.take() {
+            self.samples.push(pressed_at.elapsed());
+        }
+        self.last_display = Some(display.clone());
+    }
+
+    pub fn samples(&self) -> &[Duration] {
+        &self.samples
+    }
+
+    pub fn report(&self) -> String {
+        let Some(min) = self.samples.iter().min() else {
+            return "latency: no key presses measured".to_owned();
+        };
+        let max = self.samples.iter().max().unwrap_or(min);
+        let average = self.samples.iter().sum::<Duration>() / self.samples.len() as u32;
+        format!(
+            "latency over {} key presses: min {:.1} ms, avg {:.1} ms, max {:.1} ms",
+            self.samples.len(),
+            min.as_secs_f64() * 1000.0,
+            average.as_secs_f64() * 1000.0,
+            max.as_secs_f64() * 1000.0,
+        )
+    }
+}
@@ -0,0 +1,148 @@
+// the interpreter core for frontends outside this crate, cycle() tells them
+// what each instruction did with a StepOutcome, or on_event observers hear
+// about it as it happens. the root exports
+// below are the stable api and follow semver: until 1.0 a breaking change to
+// them bumps the minor version, after that the major one. error, run state,
+// settings and quirk types are non_exhaustive, so new variants, quirks and
+// settings don't need a breaking release; build them from Default and match with
+// a wildcard arm. the modules themselves are public for this crate's own
+// frontends and tools and may change in any release. without the std
+// feature only the core is built, no_std with alloc
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+// what the std prelude brings along, for the core modules without it
+#[cfg(not(feature = "std"))]
+mod prelude {
+    pub use alloc::borrow::ToOwned;
+    pub use alloc::boxed::Box;
+    pub use alloc::format;
+    pub use alloc::string::String;
+    pub use alloc::vec;
+    pub use alloc::vec::Vec;
+}
+
+#[cfg(feature = "std")]
+pub mod asm;
+#[cfg(feature = "std")]
+pub mod assert;
+#[cfg(feature = "std")]
+pub mod backend;
+pub mod breakpoint;
+#[cfg(feature = "std")]
+pub mod browser;
+pub mod builtin;
+#[cfg(feature = "std")]
+pub mod buzzer;
+#[cfg(feature = "std")]
+pub mod cartridge;
+pub mod chip8;
+#[cfg(feature = "std")]
+pub mod clip;
+#[cfg(feature = "std")]
+pub mod compare;
+#[cfg(feature = "std")]
+pub mod console;
+#[cfg(feature = "std")]
+pub mod contact_sheet;
+#[cfg(all(feature = "network", unix))]
+pub mod control;
+pub mod coverage;
+#[cfg(feature = "std")]
+pub mod crash;
+#[cfg(feature = "std")]
+pub mod database;
+#[cfg(feature = "debugger")]
+pub mod debugger;
+pub mod decode;
+#[cfg(feature = "std")]
+pub mod dirs;
+pub mod disasm;
+pub mod display;
+#[cfg(feature = "std")]
+pub mod doctor;
+pub mod error;
+#[cfg(feature = "network")]
+pub mod fetch;
+// the wasm exports take the same names
+#[cfg(all(feature = "ffi", not(target_arch = "wasm32")))]
+pub mod ffi;
+#[cfg(feature = "std")]
+pub mod frame_queue;
+#[cfg(feature = "std")]
+pub mod fuzz;
+#[cfg(feature = "std")]
+pub mod gif;
+#[cfg(feature = "std")]
+pub mod golden;
+#[cfg(feature = "std")]
+pub mod handle;
+pub mod heatmap;
+#[cfg(feature = "std")]
+pub mod input_log;
+#[cfg(feature = "std")]
+pub mod json;
+#[cfg(feature = "std")]
+pub mod keymap;
+#[cfg(feature = "std")]
+pub mod latency;
+#[cfg(feature = "network")]
+pub mod line_server;
+pub mod megachip;
+#[cfg(feature = "std")]
+pub mod memory_view;
+#[cfg(feature = "std")]
+pub mod palette;
+#[cfg(feature = "std")]
+pub mod phosphor;
+#[cfg(feature = "std")]
+pub mod png;
+pub mod profile;
+#[cfg(feature = "std")]
+pub mod recent;
+#[cfg(feature = "std")]
+pub mod recording;
+#[cfg(feature = "network")]
+pub mod remote;
+pub mod rng;
+pub mod rom;
+pub mod rpl;
+pub mod savestate;
+#[cfg(feature = "std")]
+pub mod screenshot;
+#[cfg(feature = "scripting")]
+pub mod script;
+#[cfg(feature = "std")]
+pub mod selftest;
+pub mod settings;
+pub mod sha1;
+#[cfg(feature = "std")]
+pub mod slots;
+pub mod state_diff;
+pub mod stats;
+#[cfg(feature = "std")]
+pub mod thumbnail;
+#[cfg(feature = "std")]
+pub mod timing;
+pub mod trace;
+#[cfg(feature = "std")]
+pub mod tutorial;
+#[cfg(feature = "std")]
+pub mod watch;
+#[cfg(feature = "std")]
+pub mod zip;
+
+#[cfg(all(target_arch = "wasm32", feature = "std"))]
+mod wasm;
+
+pub use chip8::{
+    Chip8State, CpuSnapshot, EmulatedTime, Event, HaltReason, Instruction, Keypad, Observer,
+    RunState, RunTarget, SpriteBox, StepOutcome,
+};
+pub use display::{Display, Geometry};
+pub use error::Chip8Error;
+pub use savestate::Snapshot;
+pub use settings::{
+    MemoryIncrement, Quirks, Settings, Timing, UnknownOpcodePolicy, WriteProtection,
+};
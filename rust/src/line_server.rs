@@ -0,0 +1,101 @@
+// the line protocol the remote debugger and the control socket share. the
+// listener accepts on its own thread and serves a thread per client, every
+// nonempty line is parsed into a command that waits in requests() for
+// whoever owns the machine, and the reply it gets back is written out.
+// lines that don't parse are answered "err MESSAGE" right away
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::mpsc::{self, Receiver, Sender, TryIter};
+use std::thread;
+
+// a command from a client and where its reply goes
+pub type Request<C> = (C, Sender<String>);
+
+// what LineServer listens on, tcp or a unix socket
+pub trait Listener: Send + 'static {
+    type Stream: Read + Write + Send + 'static;
+
+    fn accept(&self) -> io::Result<Self::Stream>;
+    // the writing half of a stream that is read line by line
+    fn split(stream: &Self::Stream) -> io::Result<Self::Stream>;
+}
+
+impl Listener for TcpListener {
+    type Stream = TcpStream;
+
+    fn accept(&self) -> io::Result<TcpStream> {
+        TcpListener::accept(self).map(|(stream, _)| stream)
+    }
+
+    fn split(stream: &TcpStream) -> io::Result<TcpStream> {
+        stream.try_clone()
+    }
+}
+
+#[cfg(unix)]
+impl Listener for UnixListener {
+    type Stream = UnixStream;
+
+    fn accept(&self) -> io::Result<UnixStream> {
+        UnixListener::accept(self).map(|(stream, _)| stream)
+    }
+
+    fn split(stream: &UnixStream) -> io::Result<UnixStream> {
+        stream.try_clone()
+    }
+}
+
+pub struct LineServer<C> {
+    requests: Receiver<Request<C>>,
+}
+
+impl<C: Send + 'static> LineServer<C> {
+    pub fn spawn<L: Listener>(listener: L, parse: fn(&str) -> Result<C, String>) -> Self {
+        let (sender, requests) = mpsc::channel();
+        thread::spawn(move || {
+            while let Ok(stream) = listener.accept() {
+                let sender = sender.clone();
+                thread::spawn(move || serve::<L, C>(stream, parse, &sender));
+            }
+        });
+        LineServer { requests }
+    }
+
+    pub fn requests(&self) -> TryIter<'_, Request<C>> {
+        self.requests.try_iter()
+    }
+}
+
+// until the client hangs up or the machine is gone
+fn serve<L: Listener, C>(
+    stream: L::Stream,
+    parse: fn(&str) -> Result<C, String>,
+    requests: &Sender<Request<C>>,
+) {
+    let Ok(mut out) = L::split(&stream) else {
+        return;
+    };
+    for line in BufReader::new(stream).lines().map_while(Result::ok) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match parse(&line) {
+            Ok(command) => {
+                let (reply_to, reply) = mpsc::channel();
+                if requests.send((command, reply_to)).is_err() {
+                    return;
+                }
+                match reply.recv() {
+                    Ok(reply) => reply,
+                    Err(_) => return,
+                }
+            }
+            Err(err) => format!("err {err}\n"),
+        };
+        if out.write_all(reply.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
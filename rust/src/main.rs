@@ -1,233 +1,2304 @@
-use raylib::prelude::*;
-use std::fs::{self, File};
-use std::io::Read;
+use chip8::Geometry;
+#[cfg(any(feature = "raylib", feature = "sdl2"))]
+use chip8::RunTarget;
+use chip8::asm;
+#[cfg(all(feature = "raylib", not(feature = "sdl2")))]
+use chip8::backend::raylib::RaylibBackend;
+#[cfg(feature = "sdl2")]
+use chip8::backend::sdl2::Sdl2Backend;
+#[cfg(any(feature = "raylib", feature = "sdl2"))]
+use chip8::backend::{
+    Backend, Hotkey,
+    ui::{self, DebugHud, KeyHistory, Menu},
+};
+use chip8::breakpoint::{self, BreakAction};
+use chip8::browser::{DEFAULT_ROM_DIR, RomBrowser};
+use chip8::builtin::{BUILTIN_ROMS, BuiltinRom, builtin_rom};
+use chip8::buzzer::Buzzer;
+use chip8::cartridge;
+use chip8::chip8::{EXTENDED_MEMORY_SIZE, MEMORY_SIZE, PROGRAM_START};
+#[cfg(any(feature = "raylib", feature = "sdl2"))]
+use chip8::clip::ClipRecorder;
+use chip8::compare::{Comparison, Side};
+#[cfg(any(feature = "raylib", feature = "sdl2"))]
+use chip8::console::DebugCommand;
+use chip8::contact_sheet;
+#[cfg(all(feature = "network", unix, any(feature = "raylib", feature = "sdl2")))]
+use chip8::control::ControlCommand;
+#[cfg(all(feature = "network", unix))]
+use chip8::control::ControlServer;
+use chip8::crash;
+use chip8::database::{self, Database};
+use chip8::disasm;
+use chip8::doctor::{self, Check, Status};
+use chip8::frame_queue::BackPressure;
+#[cfg(any(feature = "raylib", feature = "sdl2"))]
+use chip8::frame_queue::Frame;
+use chip8::fuzz::{self, FuzzConfig};
+use chip8::golden;
+use chip8::handle::Chip8Handle;
+use chip8::input_log::{InputLog, InputReplay};
+use chip8::keymap::Keymap;
+use chip8::latency::{LATENCY_ROM, LatencyProbe};
+#[cfg(any(feature = "raylib", feature = "sdl2"))]
+use chip8::megachip::Sample;
+#[cfg(any(feature = "raylib", feature = "sdl2"))]
+use chip8::memory_view::MemoryCenter;
+use chip8::palette::{Palette, Rgb};
+#[cfg(any(feature = "raylib", feature = "sdl2"))]
+use chip8::phosphor;
+use chip8::png;
+use chip8::recent::RecentRoms;
+#[cfg(any(feature = "raylib", feature = "sdl2"))]
+use chip8::recording::GifRecorder;
+#[cfg(feature = "network")]
+use chip8::remote::RemoteServer;
+use chip8::rng::RngAlgorithm;
+use chip8::rom::{
+    self, MAX_EXTENDED_ROM_SIZE, MAX_MEGACHIP_ROM_SIZE, MAX_ROM_SIZE, is_url, load_rom,
+    load_rom_with_limit, read_rom,
+};
+use chip8::rpl::RplStore;
+use chip8::savestate;
+use chip8::screenshot;
+#[cfg(feature = "scripting")]
+use chip8::script::Script;
+use chip8::selftest::{self, Outcome, SELFTESTS};
+use chip8::settings::{
+    DEFAULT_INSTRUCTIONS_PER_SECOND, Machine, Quirks, RomOverrides, Timing, UnknownOpcodePolicy,
+    WriteProtection,
+};
+use chip8::slots::{self, SLOTS, StateSlots};
+use chip8::state_diff;
+use chip8::stats::RunStats;
+use chip8::thumbnail;
+use chip8::trace::{self, TraceFormat, Tracer};
+use chip8::tutorial::{TUTORIAL_ROM, Walkthrough};
+use chip8::watch::RomWatcher;
+use chip8::zip::{self, ZipArchive};
+use chip8::{Chip8State, Display, RunState};
+#[cfg(any(feature = "raylib", feature = "sdl2"))]
+use chip8::{CpuSnapshot, EmulatedTime, Event};
+#[cfg(all(feature = "raylib", not(feature = "sdl2")))]
+use raylib::core::audio::RaylibAudio;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
 use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-fn main() {
-    let width = 640;
-    let height = 480;
-    let width_pixel = 64;
-    let height_pixel = 32;
-    let width_pixel_len = width / width_pixel;
-    let height_pixel_len = height / height_pixel;
+#[cfg(any(feature = "raylib", feature = "sdl2"))]
+const WIDTH: i32 = 640;
+#[cfg(any(feature = "raylib", feature = "sdl2"))]
+const HEIGHT: i32 = 480;
 
-    let (mut rl, thread) = raylib::init().size(width, height).title("CHIP-8").build();
+// frames the cpu may run ahead of a slow renderer
+const FRAME_QUEUE_LEN: usize = 3;
+const FRAME_POLICY: BackPressure = BackPressure::DropOldest;
 
-    let bytes = load_rom("../../roms/ibm.ch8");
-    println!("\n\n{} bytes\n", bytes.len());
-    let hexdump = get_hexdump(&bytes);
-    println!("{}", hexdump);
+// --deterministic: fixed rng seed and a fixed instruction budget per frame,
+// the configured speed's instructions_per_frame
+const DETERMINISTIC_SEED: u32 = 0xC8C8_C8C8;
+// the budget of the headless bench and coverage runs
+const DETERMINISTIC_INSTRUCTIONS_PER_FRAME: usize = 10;
 
-    let mut chip8_state = Chip8State::new();
-    chip8_state.memory[0x200..0x200 + bytes.len()].copy_from_slice(&bytes);
-    let chip8 = Arc::new(Mutex::new(chip8_state));
+// a jump to itself
+const IDLE_ROM: [u8; 2] = [0x12, 0x00];
 
-    {
-        let chip8 = Arc::clone(&chip8);
-        thread::spawn(move || loop {
+struct Args {
+    deterministic: bool,
+    sprite_limit: Option<usize>,
+    geometry: Geometry,
+    unknown_opcode: UnknownOpcodePolicy,
+    write_protection: WriteProtection,
+    measure_latency: bool,
+    instructions_per_second: u32,
+    watch: Option<String>,
+    // --ghosting, the persistence Hotkey::ToggleGhosting starts with
+    ghosting: Option<u8>,
+    // the rom to run, without one the browser lists rom_dir
+    rom: Option<String>,
+    // --builtin: a rom bundled in the binary instead
+    builtin: Option<&'static BuiltinRom>,
+    rom_dir: String,
+    quirks: Quirks,
+    timing: Timing,
+    palette: Palette,
+    extended_memory: bool,
+    // --memory-size, in place of the 4 KB or --extended-memory
+    memory_size: Option<usize>,
+    // where the rom loads and runs from, 0x600 for eti-660 roms
+    program_start: usize,
+    // megachip-8, also on for roms that start with 0011
+    megachip: bool,
+    fullscreen: bool,
+    // the clickable keypad next to the display
+    virtual_keypad: bool,
+    rng: RngAlgorithm,
+    keymap: Keymap,
+    auto_keys: bool,
+    caption: Option<String>,
+    key_history: bool,
+    // print the recent roms and exit
+    list_recent: bool,
+    dim_idle: Option<Duration>,
+    auto_pause: bool,
+    buzzer: Buzzer,
+    screenshot_scale: usize,
+    // --screenshot: the screen the run ended on as a png, or as text with -
+    screenshot: Option<String>,
+    // --frames: quit after this many, how a build without a frontend runs
+    frames: Option<u64>,
+    tutorial: bool,
+    // input log files, either implies deterministic
+    record_input: Option<String>,
+    replay_input: Option<String>,
+    // --trace writes to stderr, --trace-file to a file, either one traces
+    trace: bool,
+    trace_file: Option<String>,
+    trace_range: Option<RangeInclusive<u16>>,
+    trace_format: TraceFormat,
+    breakpoints: Vec<(u16, BreakAction)>,
+    // report where the program spent its cycles at exit
+    profile: bool,
+    // and which opcodes and memory it used
+    coverage: bool,
+    // --quirks, --ips or a cartridge chose them, known roms don't get theirs
+    quirks_given: bool,
+    ips_given: bool,
+    // --no-auto-config: the database doesn't pick quirks and speed either
+    auto_config: bool,
+    // [rom NAME] sections of the config file
+    rom_overrides: RomOverrides,
+    // --load-state: the slot to resume the rom from
+    load_state: Option<u8>,
+    // debug console commands on stdin, see console
+    console: bool,
+    // --script: hooks to run, see script
+    script: Option<String>,
+    // --debug-port: where a remote debugger attaches, see remote
+    debug_port: Option<u16>,
+    // --control: the socket launchers and scripts drive the emulator through,
+    // see control
+    control: Option<PathBuf>,
+    // --stats: print what the run did at exit, see stats
+    stats: bool,
+}
+
+// picks the quirks and speed of every rom that gets loaded
+struct RomConfig {
+    database: Database,
+    overrides: RomOverrides,
+    // from the command line, None where it left them to the rom
+    quirks: Option<Quirks>,
+    instructions_per_second: Option<u32>,
+    // false with --no-auto-config, the database isn't asked then
+    auto: bool,
+}
+
+impl RomConfig {
+    // the command line, then the [rom NAME] section of the config, then
+    // the database, then the defaults
+    fn settings(&self, rom: &[u8], name: Option<&str>) -> (Quirks, u32) {
+        let mut quirks = Quirks::default();
+        let mut instructions_per_second = DEFAULT_INSTRUCTIONS_PER_SECOND;
+        if self.auto
+            && let Some(info) = self.database.lookup(rom)
+        {
+            info.apply(&mut quirks, &mut instructions_per_second);
+            println!(
+                "{}, running it as {}",
+                info.byline(),
+                info.platform().unwrap_or("chip8")
+            );
+            for quirk in info.unsupported() {
+                eprintln!("{}: the {quirk} quirk isn't supported, ignored", info.title);
+            }
+        }
+        if let Some(own) = name.and_then(|name| self.overrides.get(name)) {
+            quirks = own.quirks.unwrap_or(quirks);
+            instructions_per_second = own
+                .instructions_per_second
+                .unwrap_or(instructions_per_second);
+        }
+        (
+            self.quirks.unwrap_or(quirks),
+            self.instructions_per_second
+                .unwrap_or(instructions_per_second),
+        )
+    }
+}
+
+// frontend options and the optional helpers it drives alongside the emulator
+#[cfg_attr(not(any(feature = "raylib", feature = "sdl2")), allow(dead_code))]
+struct Tools {
+    keymap: Keymap,
+    // what Hotkey::Reset starts over
+    rom: Vec<u8>,
+    // file name of rom, picks the gamepad mapping
+    rom_name: Option<String>,
+    // quirks and speed of the roms loaded later, and their titles
+    rom_config: RomConfig,
+    // arrows and space for the keys the game reads, see keymap::auto_keys
+    auto_keys: bool,
+    // a ticker of recent presses under the keypad overlay
+    key_history: bool,
+    // what the memory past the program start holds, roms loaded later have
+    // to fit in it too
+    rom_limit: usize,
+    // instructions per second, changed with Hotkey::SpeedUp and SpeedDown
+    speed: u32,
+    palette: Palette,
+    fullscreen: bool,
+    virtual_keypad: bool,
+    // written above Hotkey::ExportClip gifs
+    caption: Option<String>,
+    latency: Option<LatencyProbe>,
+    // frames the window showed, for --stats
+    frames_shown: u64,
+    watcher: Option<RomWatcher>,
+    // on from the start with --ghosting
+    ghosting: Option<u8>,
+    // started without a rom, Hotkey::Menu goes back to it
+    browser: Option<RomBrowser>,
+    // None without a place to keep the state file
+    recent: Option<RecentRoms>,
+    // --tutorial, shown in the status line until it's done
+    tutorial: Option<Walkthrough>,
+    // pixels per chip8 pixel in Hotkey::Screenshot and --screenshot pngs
+    screenshot_scale: usize,
+    // --frames
+    frames: Option<u64>,
+    // where Hotkey::SaveState and LoadState keep the states of every rom,
+    // None without a place for them
+    state_slots: Option<StateSlots>,
+    // a running program that hasn't drawn for this long is shown dimmed and
+    // busy, a halted one as finished
+    dim_idle: Option<Duration>,
+    // pause and mute while the window doesn't have the focus
+    auto_pause: bool,
+    buzzer: Buzzer,
+    // lines typed on stdin with --console
+    console: Option<Receiver<String>>,
+    // --debug-port
+    #[cfg(feature = "network")]
+    remote: Option<RemoteServer>,
+    // --control
+    #[cfg(all(feature = "network", unix))]
+    control: Option<ControlServer>,
+}
+
+fn parse_args() -> Args {
+    let mut args = Args {
+        deterministic: false,
+        sprite_limit: None,
+        geometry: Geometry::LORES,
+        unknown_opcode: UnknownOpcodePolicy::Halt,
+        write_protection: WriteProtection::Off,
+        measure_latency: false,
+        instructions_per_second: DEFAULT_INSTRUCTIONS_PER_SECOND,
+        watch: None,
+        ghosting: None,
+        rom: None,
+        builtin: None,
+        rom_dir: DEFAULT_ROM_DIR.to_owned(),
+        quirks: Quirks::default(),
+        timing: Timing::Flat,
+        palette: Palette::default(),
+        extended_memory: false,
+        memory_size: None,
+        program_start: PROGRAM_START,
+        megachip: false,
+        fullscreen: false,
+        virtual_keypad: false,
+        rng: RngAlgorithm::default(),
+        keymap: Keymap::default(),
+        auto_keys: false,
+        caption: None,
+        key_history: false,
+        list_recent: false,
+        dim_idle: None,
+        auto_pause: true,
+        buzzer: Buzzer::default(),
+        screenshot_scale: screenshot::DEFAULT_SCALE,
+        screenshot: None,
+        frames: None,
+        tutorial: false,
+        record_input: None,
+        replay_input: None,
+        trace: false,
+        trace_file: None,
+        trace_range: None,
+        trace_format: TraceFormat::Text,
+        breakpoints: Vec::new(),
+        profile: false,
+        coverage: false,
+        quirks_given: false,
+        ips_given: false,
+        auto_config: true,
+        rom_overrides: RomOverrides::new(),
+        load_state: None,
+        console: false,
+        script: None,
+        debug_port: None,
+        control: None,
+        stats: false,
+    };
+    // the command line overrides the config file wherever they come
+    let mut config = None;
+    // applied after the config file so it wins over its [buzzer] section
+    let mut buzzer = None;
+    let mut layout = None;
+    let mut preset = None;
+    let mut bindings = Vec::new();
+
+    // "chip8 run ROM" and plain "chip8 ROM" are the same
+    let mut iter = std::env::args().skip(1).peekable();
+    iter.next_if(|arg| arg == "run");
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--deterministic" => args.deterministic = true,
+            "--measure-latency" => args.measure_latency = true,
+            "--extended-memory" => args.extended_memory = true,
+            // hex like the debuggers' addresses, --memory-size 800 is 2 KB
+            "--memory-size" => match iter.next().map(|size| chip8::console::hex(&size)) {
+                Some(Ok(size)) => args.memory_size = Some(size as usize),
+                _ => exit_with_error("--memory-size expects a hex size like 800 or 1000"),
+            },
+            "--load-address" => match iter.next().map(|addr| chip8::console::hex(&addr)) {
+                Some(Ok(addr)) => args.program_start = addr as usize,
+                _ => exit_with_error("--load-address expects a hex address like 200 or 600"),
+            },
+            "--megachip" => args.megachip = true,
+            "--fullscreen" => args.fullscreen = true,
+            "--virtual-keypad" => args.virtual_keypad = true,
+            "--auto-keys" => args.auto_keys = true,
+            "--key-history" => args.key_history = true,
+            "--recent" => args.list_recent = true,
+            "--tutorial" => args.tutorial = true,
+            "--trace" => args.trace = true,
+            "--profile" => args.profile = true,
+            "--coverage" => args.coverage = true,
+            "--no-auto-config" => args.auto_config = false,
+            "--no-auto-pause" => args.auto_pause = false,
+            "--console" => args.console = true,
+            "--stats" => args.stats = true,
+            "--builtin" => match iter.next().as_deref().and_then(builtin_rom) {
+                Some(rom) => args.builtin = Some(rom),
+                None => {
+                    let names: Vec<_> = BUILTIN_ROMS.iter().map(|rom| rom.name).collect();
+                    exit_with_error(&format!("--builtin expects one of {}", names.join(", ")))
+                }
+            },
+            "--script" => match iter.next() {
+                Some(path) => args.script = Some(path),
+                None => exit_with_error("--script expects a file of hooks"),
+            },
+            "--trace-file" => match iter.next() {
+                Some(path) => args.trace_file = Some(path),
+                None => exit_with_error("--trace-file expects a file to write"),
+            },
+            "--break" => match iter.next().as_deref().and_then(breakpoint::parse) {
+                Some(breakpoint) => args.breakpoints.push(breakpoint),
+                None => {
+                    exit_with_error("--break expects a hex address like 2A0, 2A0:log or 2A0:count")
+                }
+            },
+            "--trace-format" => match iter.next().as_deref().and_then(TraceFormat::parse) {
+                Some(format) => args.trace_format = format,
+                None => exit_with_error("--trace-format expects text or json"),
+            },
+            "--trace-range" => match iter.next().as_deref().and_then(trace::parse_range) {
+                Some(range) => args.trace_range = Some(range),
+                None => exit_with_error("--trace-range expects hex addresses like 200-2FF"),
+            },
+            "--caption" => match iter.next() {
+                Some(caption) => args.caption = Some(caption),
+                None => exit_with_error("--caption expects a text for exported clips"),
+            },
+            "--config" => match iter.next() {
+                Some(path) => config = Some(path),
+                None => exit_with_error("--config expects a file"),
+            },
+            "--buzzer" => match iter.next() {
+                Some(list) => buzzer = Some(list),
+                None => exit_with_error("--buzzer expects settings like wave=sine,volume=30"),
+            },
+            "--keys" => match iter.next() {
+                Some(keys) => layout = Some(keys),
+                None => exit_with_error("--keys expects 16 keys for chip8 keys 0 to F"),
+            },
+            "--layout" => match iter.next() {
+                Some(name) => preset = Some(name),
+                None => exit_with_error("--layout expects qwerty, qwertz, azerty or cosmac"),
+            },
+            "--bind" => match iter.next() {
+                Some(binding) => bindings.push(binding),
+                None => exit_with_error("--bind expects action=key"),
+            },
+            "--sprite-limit" => {
+                let limit = iter.next().and_then(|value| value.parse().ok());
+                if limit.is_none() {
+                    exit_with_error("--sprite-limit expects a number of sprites per frame");
+                }
+                args.sprite_limit = limit;
+            }
+            "--dim-idle" => match iter.next().and_then(|value| value.parse::<f64>().ok()) {
+                Some(seconds) if seconds > 0.0 => {
+                    args.dim_idle = Some(Duration::from_secs_f64(seconds))
+                }
+                _ => exit_with_error("--dim-idle expects a number of seconds without drawing"),
+            },
+            "--screenshot-scale" => match iter.next().and_then(|value| value.parse().ok()) {
+                Some(scale) if (1..=64).contains(&scale) => args.screenshot_scale = scale,
+                _ => exit_with_error("--screenshot-scale expects pixels per chip8 pixel, 1 to 64"),
+            },
+            "--screenshot" => match iter.next() {
+                Some(path) => args.screenshot = Some(path),
+                None => exit_with_error("--screenshot expects a png to write, or - for text"),
+            },
+            "--frames" => match iter.next().and_then(|value| value.parse().ok()) {
+                Some(frames) if frames > 0 => args.frames = Some(frames),
+                _ => exit_with_error("--frames expects a number of frames"),
+            },
+            "--debug-port" => match iter.next().and_then(|value| value.parse().ok()) {
+                Some(port) => args.debug_port = Some(port),
+                None => exit_with_error("--debug-port expects a tcp port"),
+            },
+            "--control" => match iter.next() {
+                Some(path) => args.control = Some(PathBuf::from(path)),
+                None => exit_with_error("--control expects a socket path"),
+            },
+            "--load-state" => match iter.next().and_then(|value| value.parse().ok()) {
+                Some(slot) if (1..=SLOTS).contains(&slot) => args.load_state = Some(slot),
+                _ => exit_with_error(&format!("--load-state expects a slot, 1 to {SLOTS}")),
+            },
+            "--record-input" => match iter.next() {
+                Some(path) => args.record_input = Some(path),
+                None => exit_with_error("--record-input expects a file to write"),
+            },
+            "--replay-input" => match iter.next() {
+                Some(path) => args.replay_input = Some(path),
+                None => exit_with_error("--replay-input expects a file from --record-input"),
+            },
+            "--ips" => match iter.next().and_then(|value| value.parse().ok()) {
+                Some(ips) if ips > 0 => {
+                    args.instructions_per_second = ips;
+                    args.ips_given = true;
+                }
+                _ => exit_with_error("--ips expects a number of instructions per second"),
+            },
+            "--watch" => match iter.next() {
+                Some(path) if path.to_ascii_lowercase().ends_with(".8o") => exit_with_error(
+                    "--watch: .8o sources need an assembler, watch the rom your build writes",
+                ),
+                Some(path) => args.watch = Some(path),
+                None => exit_with_error("--watch expects a rom or a directory"),
+            },
+            // percent of its brightness a dark pixel keeps each frame
+            "--ghosting" => match iter.next().and_then(|percent| percent.parse().ok()) {
+                Some(percent @ 1..=99) => args.ghosting = Some(percent),
+                _ => exit_with_error("--ghosting expects a percent from 1 to 99"),
+            },
+            "--rom-dir" => match iter.next() {
+                Some(dir) => args.rom_dir = dir,
+                None => exit_with_error("--rom-dir expects a directory"),
+            },
+            "--quirks" => {
+                let Some(list) = iter.next() else {
+                    exit_with_error("--quirks expects a list like display-wait");
+                };
+                args.quirks_given = true;
+                if let Err(name) = args.quirks.enable(&list) {
+                    let presets = Quirks::PRESETS.iter().map(|(name, _)| *name);
+                    let names: Vec<_> = Quirks::NAMES.iter().copied().chain(presets).collect();
+                    exit_with_error(&format!(
+                        "unknown quirk {name}, expected one of {}",
+                        names.join(", ")
+                    ));
+                }
+            }
+            // a whole interpreter, --quirks, --ips and --geometry after it change parts
+            "--machine" => match iter.next().as_deref().and_then(Machine::find) {
+                Some(machine) => {
+                    args.quirks = machine.quirks;
+                    args.geometry = machine.geometry;
+                    args.instructions_per_second = machine.instructions_per_second;
+                    args.extended_memory |= machine.extended_memory;
+                    args.program_start = machine.program_start;
+                    args.timing = machine.timing;
+                    args.quirks_given = true;
+                    args.ips_given = true;
+                }
+                None => {
+                    let names: Vec<_> = Machine::ALL.iter().map(|machine| machine.name).collect();
+                    exit_with_error(&format!("--machine expects one of {}", names.join(", ")))
+                }
+            },
+            // cosmac-vip: every instruction takes as long as on the vip, --ips is ignored
+            "--timing" => match iter.next().as_deref().and_then(Timing::find) {
+                Some(timing) => args.timing = timing,
+                None => {
+                    let names: Vec<_> = Timing::NAMES.iter().map(|(name, _)| *name).collect();
+                    exit_with_error(&format!("--timing expects one of {}", names.join(", ")))
+                }
+            },
+            "--palette" => match iter.next().as_deref().and_then(Palette::preset) {
+                Some(palette) => args.palette = palette,
+                None => {
+                    let names: Vec<_> = Palette::PRESETS.iter().map(|(name, _)| *name).collect();
+                    exit_with_error(&format!("--palette expects one of {}", names.join(", ")))
+                }
+            },
+            "--fg" => match iter.next().as_deref().and_then(Rgb::parse) {
+                Some(color) => args.palette.colors[1] = color,
+                None => exit_with_error("--fg expects a hex color like #33ff66"),
+            },
+            "--bg" => match iter.next().as_deref().and_then(Rgb::parse) {
+                Some(color) => args.palette.colors[0] = color,
+                None => exit_with_error("--bg expects a hex color like #000000"),
+            },
+            "--rng" => match iter.next().as_deref().and_then(RngAlgorithm::parse) {
+                Some(rng) => args.rng = rng,
+                None => exit_with_error(&format!(
+                    "--rng expects one of {}",
+                    RngAlgorithm::NAMES.join(", ")
+                )),
+            },
+            "--geometry" => match iter.next().as_deref().and_then(Geometry::parse) {
+                Some(geometry) => args.geometry = geometry,
+                None => exit_with_error("--geometry expects a size like 64x48"),
+            },
+            "--unknown-opcode" => {
+                match iter.next().as_deref().and_then(UnknownOpcodePolicy::parse) {
+                    Some(policy) => args.unknown_opcode = policy,
+                    None => exit_with_error("--unknown-opcode expects halt, skip or trap"),
+                }
+            }
+            "--write-protect" => match iter.next().as_deref().and_then(WriteProtection::parse) {
+                Some(protection) => args.write_protection = protection,
+                None => exit_with_error("--write-protect expects off, halt or pause"),
+            },
+            path if !path.starts_with("--")
+                && args.rom.is_none()
+                && cartridge::is_cartridge(Path::new(path)) =>
             {
-                let mut state = chip8.lock().unwrap();
-                state.cycle();
+                args.rom = Some(open_cartridge(&mut args, path))
             }
-            thread::sleep(Duration::from_millis(16));
+            path if !path.starts_with("--") && args.rom.is_none() => args.rom = Some(arg),
+            _ => exit_with_error(&format!("unknown argument {arg}")),
+        }
+    }
+
+    if let Some(path) = config {
+        load_config(
+            &mut args.keymap,
+            &mut args.rom_overrides,
+            &mut args.buzzer,
+            &path,
+        );
+    }
+    if let Some(list) = buzzer
+        && let Err(err) = args.buzzer.set_list(&list)
+    {
+        exit_with_error(&format!("--buzzer: {err}"));
+    }
+    if let Some(name) = preset
+        && let Err(err) = args.keymap.set_layout(&name)
+    {
+        exit_with_error(&format!("--layout: {err}"));
+    }
+    if let Some(layout) = layout
+        && let Err(err) = args.keymap.set_keypad_layout(&layout)
+    {
+        exit_with_error(&format!("--keys: {err}"));
+    }
+    for binding in bindings {
+        let Some((action, key)) = binding.split_once('=') else {
+            exit_with_error("--bind expects action=key");
+        };
+        if let Err(err) = args.keymap.bind(action, key) {
+            exit_with_error(&format!("--bind: {err}"));
+        }
+    }
+
+    if args.builtin.is_some() && args.rom.is_some() {
+        exit_with_error("--builtin runs instead of a rom, pass one or the other");
+    }
+    if args.load_state.is_some() && args.rom.is_none() {
+        exit_with_error("--load-state needs a rom to resume");
+    }
+
+    args
+}
+
+// applies the options of an octo cartridge where it stands on the command
+// line, so flags after it still win, and returns the rom to run instead
+fn open_cartridge(args: &mut Args, path: &str) -> String {
+    let bytes =
+        std::fs::read(path).unwrap_or_else(|err| exit_with_error(&format!("{path}: {err}")));
+    let cartridge =
+        cartridge::parse(&bytes).unwrap_or_else(|err| exit_with_error(&format!("{path}: {err}")));
+    let options = &cartridge.options;
+    options.apply(
+        &mut args.quirks,
+        &mut args.palette,
+        &mut args.instructions_per_second,
+    );
+    for quirk in &options.unsupported {
+        eprintln!("{path}: {quirk} isn't supported, ignored");
+    }
+    args.quirks_given |= options.vblank.is_some() || options.clip.is_some();
+    args.ips_given |= options.tickrate.is_some();
+
+    let program = cartridge::program_path(Path::new(path));
+    if !program.is_file() {
+        exit_with_error(&format!(
+            "{path}: cartridges hold octo source, export {} from octo to run it",
+            program.display()
+        ));
+    }
+    program.to_string_lossy().into_owned()
+}
+
+// "Tetris by Fran Dachille" for a rom in the database, else its file name
+#[cfg(any(feature = "raylib", feature = "sdl2"))]
+fn rom_title(tools: &Tools) -> Option<String> {
+    match tools.rom_config.database.lookup(&tools.rom) {
+        Some(info) => Some(info.byline()),
+        None => tools.rom_name.clone(),
+    }
+}
+
+fn rom_name(path: &Path) -> Option<String> {
+    Some(path.file_name()?.to_string_lossy().into_owned())
+}
+
+// the roms in the browser and --recent, a broken state file only costs the list
+fn open_recent() -> Option<RecentRoms> {
+    let file = RecentRoms::default_file()?;
+    RecentRoms::load(&file)
+        .inspect_err(|err| eprintln!("err: {}: {err}", file.display()))
+        .ok()
+}
+
+// --memory-size, or the size --extended-memory picks
+fn memory_size(args: &Args) -> usize {
+    args.memory_size.unwrap_or(if args.extended_memory {
+        EXTENDED_MEMORY_SIZE
+    } else {
+        MEMORY_SIZE
+    })
+}
+
+// the rom run was given: a path, a zip and maybe #MEMBER, - for stdin or an
+// http url
+fn read_rom_arg(rom: &str, max: usize) -> Result<Vec<u8>, String> {
+    if rom == "-" {
+        return read_rom(std::io::stdin().lock(), max).map_err(|err| format!("stdin: {err}"));
+    }
+    if is_url(rom) {
+        #[cfg(feature = "network")]
+        return chip8::fetch::fetch(rom, max).and_then(|bytes| {
+            rom::validate_rom(&bytes, max).map_err(|err| format!("{rom}: {err}"))?;
+            Ok(bytes)
         });
+        #[cfg(not(feature = "network"))]
+        return Err(format!("{rom}: urls need the network feature"));
+    }
+    if let Some((path, member)) = zip::split_member(rom) {
+        let archive = std::fs::read(path).map_err(|err| format!("{path}: {err}"))?;
+        let bytes = ZipArchive::parse(&archive)
+            .and_then(|zip| zip.rom(member, max))
+            .map_err(|err| format!("{path}: {err}"))?;
+        rom::validate_rom(&bytes, max).map_err(|err| format!("{rom}: {err}"))?;
+        return Ok(bytes);
     }
+    load_rom_with_limit(rom, max).map_err(|err| err.to_string())
+}
 
-    //let grid_string = get_grid_string(&grid);
-    //println!("{}", grid_string);
+fn remember_rom(tools: &mut Tools, path: &Path) {
+    let Some(recent) = &mut tools.recent else {
+        return;
+    };
+    recent.push(path);
+    if let Err(err) = recent.save() {
+        eprintln!("err: {}: {err}", recent.file().display());
+    }
+    if let Some(browser) = &mut tools.browser {
+        browser.set_recent(recent.paths());
+    }
+}
 
-    while !rl.window_should_close() {
-        let mut d = rl.begin_drawing(&thread);
+fn load_config(keymap: &mut Keymap, overrides: &mut RomOverrides, buzzer: &mut Buzzer, path: &str) {
+    let text = std::fs::read_to_string(path)
+        .unwrap_or_else(|err| exit_with_error(&format!("{path}: {err}")));
+    if let Err(err) = keymap
+        .load_config(&text)
+        .and_then(|()| overrides.load_config(&text))
+        .and_then(|()| buzzer.load_config(&text))
+    {
+        exit_with_error(&format!("{path}: {err}"));
+    }
+}
 
-        d.clear_background(Color::BLACK);
+fn exit_with_error(message: &str) -> ! {
+    eprintln!("err: {message}");
+    std::process::exit(1);
+}
 
-        let grid = chip8.lock().unwrap().display.clone();
+fn main() {
+    match std::env::args().nth(1).as_deref() {
+        Some("help" | "--help" | "-h") => print_usage(),
+        Some("info") => run_info(),
+        Some("asm") => run_asm(),
+        Some("selftest") => run_selftest(),
+        Some("doctor") => run_doctor(),
+        Some("contact-sheet") => run_contact_sheet(),
+        Some("thumbnail") => run_thumbnail(),
+        Some("bench") => run_bench(),
+        Some("disasm") => run_disasm(),
+        Some("hexdump") => run_hexdump(),
+        Some("fuzz") => run_fuzz(),
+        Some("coverage") => run_coverage(),
+        Some("compare") => run_compare(),
+        Some("diff") => run_diff(),
+        _ => {}
+    }
 
-        for (y, row) in grid.iter().enumerate() {
-            for x in 0..64 {
-                let bit = (row >> (63 - x)) & 1;
-                if bit == 1 {
-                    let px = x as i32 * width_pixel_len;
-                    let py = y as i32 * height_pixel_len;
-                    d.draw_rectangle(px, py, width_pixel_len, height_pixel_len, Color::WHITE);
+    let args = parse_args();
+    let memory_size = memory_size(&args);
+    if !(PROGRAM_START..=EXTENDED_MEMORY_SIZE).contains(&memory_size) {
+        exit_with_error("--memory-size expects 200 to 10000");
+    }
+    if args.program_start >= memory_size {
+        exit_with_error("--load-address is past the end of memory");
+    }
+    // an input log only lines up with a run that is the same every time
+    let deterministic =
+        args.deterministic || args.record_input.is_some() || args.replay_input.is_some();
+    let recent = open_recent();
+    if args.list_recent {
+        for path in recent.iter().flat_map(RecentRoms::paths) {
+            println!("{}", path.display());
+        }
+        std::process::exit(0);
+    }
+
+    // --measure-latency swaps in a rom that redraws on every key press
+    let bytes = if args.measure_latency {
+        LATENCY_ROM.to_vec()
+    } else if args.tutorial {
+        TUTORIAL_ROM.to_vec()
+    } else if let Some(builtin) = args.builtin {
+        builtin.bytes.to_vec()
+    } else if let Some(rom) = &args.rom
+        && args.watch.is_none()
+    {
+        let max = if args.megachip {
+            MAX_MEGACHIP_ROM_SIZE
+        } else {
+            memory_size - args.program_start
+        };
+        read_rom_arg(rom, max).unwrap_or_else(|err| exit_with_error(&err))
+    } else {
+        // parks the cpu until the watcher or the browser found a rom
+        IDLE_ROM.to_vec()
+    };
+    let browser = (!args.measure_latency
+        && !args.tutorial
+        && args.builtin.is_none()
+        && args.watch.is_none()
+        && args.rom.is_none())
+    .then(|| {
+        let mut browser = RomBrowser::open(&args.rom_dir).unwrap_or_else(|err| {
+            exit_with_error(&format!(
+                "{}: {err}, pass a rom or point --rom-dir at your roms",
+                args.rom_dir
+            ))
+        });
+        if let Some(recent) = &recent {
+            browser.set_recent(recent.paths());
+        }
+        browser
+    });
+    let (database, err) = Database::load_default();
+    if let Some(err) = err {
+        eprintln!("err: {err}");
+    }
+    let rom_config = RomConfig {
+        database,
+        overrides: args.rom_overrides,
+        quirks: args.quirks_given.then_some(args.quirks),
+        instructions_per_second: args.ips_given.then_some(args.instructions_per_second),
+        auto: args.auto_config,
+    };
+    let name = args.rom.as_deref().and_then(|rom| rom_name(Path::new(rom)));
+    let (quirks, instructions_per_second) = rom_config.settings(&bytes, name.as_deref());
+
+    let mut chip8_state = if deterministic {
+        Chip8State::with_seed(DETERMINISTIC_SEED)
+    } else {
+        Chip8State::new()
+    };
+    chip8_state.set_geometry(args.geometry);
+    chip8_state.set_memory_size(memory_size);
+    chip8_state.set_program_start(args.program_start);
+    chip8_state.set_megachip(args.megachip || rom::is_megachip(&bytes));
+    chip8_state.rng.set_algorithm(args.rng);
+    chip8_state.set_rpl_store(RplStore::default_dir().map_or_else(RplStore::new, RplStore::in_dir));
+    if let Err(err) = chip8_state.load(&bytes) {
+        exit_with_error(&err.to_string());
+    }
+    chip8_state.settings.sprite_limit = args.sprite_limit;
+    chip8_state.settings.unknown_opcode = args.unknown_opcode;
+    chip8_state.settings.write_protection = args.write_protection;
+    chip8_state.settings.instructions_per_second = instructions_per_second;
+    chip8_state.settings.quirks = quirks;
+    chip8_state.settings.timing = args.timing;
+    chip8_state.set_profiling(args.profile);
+    chip8_state.set_coverage(args.coverage);
+    chip8_state.set_decode_cache(true);
+    let rom_limit = chip8_state.memory_size() - chip8_state.program_start();
+    let state_slots = StateSlots::default_dir().map(StateSlots::new);
+    if let Some(slot) = args.load_state {
+        let Some(state_slots) = &state_slots else {
+            exit_with_error("--load-state: no state directory, set HOME or XDG_STATE_HOME");
+        };
+        if let Err(err) = slots::load(&state_slots.path(&bytes, slot), &mut chip8_state) {
+            exit_with_error(&err);
+        }
+    }
+    for (addr, action) in &args.breakpoints {
+        chip8_state.breakpoints.add(*addr, *action);
+    }
+    if args.trace || args.trace_file.is_some() {
+        let out: Box<dyn Write + Send> = match &args.trace_file {
+            Some(path) => match File::create(path) {
+                Ok(file) => Box::new(BufWriter::new(file)),
+                Err(err) => exit_with_error(&format!("{path}: {err}")),
+            },
+            None => Box::new(BufWriter::new(std::io::stderr())),
+        };
+        let tracer = Tracer::new(out).with_format(args.trace_format);
+        chip8_state.set_trace(Some(match args.trace_range.clone() {
+            Some(range) => tracer.with_range(range),
+            None => tracer,
+        }));
+    }
+
+    #[cfg(not(feature = "network"))]
+    if args.debug_port.is_some() {
+        exit_with_error("--debug-port needs a build with the network feature");
+    }
+    #[cfg(not(all(feature = "network", unix)))]
+    if args.control.is_some() {
+        exit_with_error("--control needs a unix build with the network feature");
+    }
+
+    let mut script = load_script(args.script.as_deref());
+    if let Some(script) = &script {
+        script.install(&mut chip8_state);
+    }
+
+    let mut replay = args.replay_input.as_deref().map(|path| {
+        let text = std::fs::read_to_string(path)
+            .unwrap_or_else(|err| exit_with_error(&format!("{path}: {err}")));
+        let log =
+            InputLog::parse(&text).unwrap_or_else(|err| exit_with_error(&format!("{path}: {err}")));
+        InputReplay::new(&log)
+    });
+    let input_log = args
+        .record_input
+        .as_ref()
+        .map(|_| Arc::new(Mutex::new(InputLog::new())));
+    let recorder = input_log.clone();
+
+    let handle = Chip8Handle::spawn(
+        chip8_state,
+        FRAME_QUEUE_LEN,
+        FRAME_POLICY,
+        move |state, elapsed| {
+            // keys change between frames, the replay's keypad replaces the live one
+            let frame = state.emulated_time().frames;
+            if let Some(replay) = &mut replay {
+                state.keypad = replay.keypad(frame);
+            }
+            if let Some(recorder) = &recorder {
+                recorder.lock().unwrap().record(frame, &state.keypad);
+            }
+            let result = if deterministic {
+                state.run_frame(state.instructions_per_frame())
+            } else {
+                state.run_for(elapsed)
+            };
+            for message in state.breakpoints.take_log() {
+                eprintln!("{message}");
+            }
+            for shown in script.iter_mut().flat_map(|script| script.run(state)) {
+                match shown {
+                    Ok(text) => println!("{text}"),
+                    Err(err) => eprintln!("err: {err}"),
                 }
             }
+            if let Some((pc, addr)) = state.take_protected_write() {
+                eprintln!("write to protected memory {addr:03X} at {pc:03X}, paused");
+            }
+            if let Some(err) = state.rpl_store_mut().take_error() {
+                eprintln!("err: {err}");
+            }
+            result
+        },
+    );
+
+    let mut tools = Tools {
+        keymap: args.keymap,
+        rom: bytes,
+        rom_config,
+        auto_keys: args.auto_keys,
+        key_history: args.key_history,
+        rom_name: args
+            .rom
+            .as_deref()
+            .and_then(|rom| rom_name(Path::new(rom)))
+            .filter(|_| !args.measure_latency && args.watch.is_none()),
+        browser,
+        rom_limit,
+        speed: instructions_per_second,
+        palette: args.palette,
+        fullscreen: args.fullscreen,
+        virtual_keypad: args.virtual_keypad,
+        caption: args.caption,
+        latency: args.measure_latency.then(LatencyProbe::new),
+        frames_shown: 0,
+        watcher: args.watch.map(|target| RomWatcher::new(target, rom_limit)),
+        ghosting: args.ghosting,
+        recent,
+        dim_idle: args.dim_idle,
+        auto_pause: args.auto_pause,
+        buzzer: args.buzzer,
+        screenshot_scale: args.screenshot_scale,
+        frames: args.frames,
+        tutorial: args.tutorial.then(Walkthrough::new),
+        state_slots,
+        console: args.console.then(read_console),
+        #[cfg(feature = "network")]
+        remote: args.debug_port.map(start_remote),
+        #[cfg(all(feature = "network", unix))]
+        control: args.control.as_deref().map(start_control),
+    };
+    if let Some(rom) = &args.rom
+        && !args.measure_latency
+        && tools.watcher.is_none()
+        && rom != "-"
+        && !is_url(rom)
+        && zip::split_member(rom).is_none()
+    {
+        remember_rom(&mut tools, Path::new(rom));
+    }
+    let started = Instant::now();
+    start_frontend(&handle, &mut tools);
+    if let Some(latency) = &tools.latency {
+        println!("{}", latency.report());
+    }
+
+    let (mut state, result) = handle.shutdown();
+    // flushes the trace before exit_with_error skips the destructors
+    state.set_trace(None);
+    if args.stats {
+        print_stats(&state.stats(), tools.frames_shown, started.elapsed());
+    }
+    if let (Some(path), Some(log)) = (&args.record_input, input_log) {
+        let text = log.lock().unwrap().to_text();
+        match std::fs::write(path, text) {
+            Ok(()) => println!("wrote input log {path}"),
+            Err(err) => eprintln!("err: {path}: {err}"),
         }
     }
+    if let Some(profile) = state.profile() {
+        println!("\n{profile}");
+    }
+    if let Some(coverage) = state.coverage() {
+        print!("\n{coverage}");
+    }
+    for breakpoint in state.breakpoints.iter() {
+        println!(
+            "breakpoint {:03X}: {} hits",
+            breakpoint.addr, breakpoint.hits
+        );
+    }
+    match args.screenshot.as_deref() {
+        Some("-") => print!("{}", golden::screen_text(&state.display)),
+        Some(path) => {
+            if let Err(err) = write_screenshot(&state.display, &tools, Path::new(path)) {
+                eprintln!("err: {err}");
+            }
+        }
+        None => {}
+    }
+    if let Some((pc, opcode)) = state.trap() {
+        eprintln!("trapped on unknown opcode {opcode:04X} at {pc:03X}");
+    }
+    if let Err(err) = result {
+        if crash::is_crash(&err) {
+            match crash::default_dir().map(|dir| crash::write(&dir, &state, &err)) {
+                Some(Ok(path)) => eprintln!("wrote crash dump {}", path.display()),
+                Some(Err(err)) => eprintln!("err: crash dump: {err}"),
+                None => eprintln!("err: no place for a crash dump, set HOME or XDG_STATE_HOME"),
+            }
+        }
+        exit_with_error(&err.to_string());
+    }
 }
 
-fn load_rom(filename: &str) -> Vec<u8> {
-    let mut f = File::open(&filename).expect("no file found");
-    let metadata = fs::metadata(&filename).expect("unable to read metadata");
-    let mut buffer = vec![0; metadata.len() as usize];
-    f.read(&mut buffer).expect("buffer overflow");
+#[cfg(feature = "scripting")]
+fn load_script(path: Option<&str>) -> Option<Script> {
+    let path = path?;
+    let text = std::fs::read_to_string(path)
+        .unwrap_or_else(|err| exit_with_error(&format!("{path}: {err}")));
+    Some(Script::parse(&text).unwrap_or_else(|err| exit_with_error(&format!("{path}: {err}"))))
+}
 
-    buffer
+#[cfg(not(feature = "scripting"))]
+fn load_script(path: Option<&str>) -> Option<NoScript> {
+    path.map(|_| exit_with_error("--script needs a build with the scripting feature"))
 }
 
-fn get_hexdump(bytes: &[u8]) -> String {
-    let bytes_per_line = 10;
-    let mut output = String::new();
+// what a build without scripting runs instead
+#[cfg(not(feature = "scripting"))]
+enum NoScript {}
+
+#[cfg(not(feature = "scripting"))]
+impl NoScript {
+    fn install(&self, _state: &mut Chip8State) {}
+
+    fn run(&mut self, _state: &mut Chip8State) -> Vec<Result<String, String>> {
+        match *self {}
+    }
+}
 
-    for (i, chunk) in bytes.chunks(bytes_per_line).enumerate() {
-        output.push_str(&format!("{:04}: ", i * bytes_per_line));
+// only on this machine, a debugger can change memory
+#[cfg(feature = "network")]
+fn start_remote(port: u16) -> RemoteServer {
+    let server = RemoteServer::bind(&format!("127.0.0.1:{port}"))
+        .unwrap_or_else(|err| exit_with_error(&format!("--debug-port {port}: {err}")));
+    println!("remote debugger on {}", server.local_addr());
+    server
+}
 
-        for (j, b) in chunk.iter().enumerate() {
-            output.push_str(&format!("{:02X}", b));
+#[cfg(all(feature = "network", unix))]
+fn start_control(path: &Path) -> ControlServer {
+    let server = ControlServer::bind(path)
+        .unwrap_or_else(|err| exit_with_error(&format!("--control {}: {err}", path.display())));
+    println!("control socket at {}", server.path().display());
+    server
+}
 
-            // formatting twobytes chip8 opcode
-            if j % 2 != 0 {
-                output.push_str(" ");
+// stdin line by line, on a thread of its own so the frontend never waits
+// for a line
+fn read_console() -> Receiver<String> {
+    let (lines, console) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lines().map_while(Result::ok) {
+            if lines.send(line).is_err() {
+                break;
             }
         }
+    });
+    console
+}
 
-        output.push('\n');
+// runs the bundled test roms headlessly, exits with 1 if any of them failed
+fn run_selftest() -> ! {
+    let mut results = Vec::new();
+    for (n, test) in SELFTESTS.iter().enumerate() {
+        print!("[{:2}/{}] {} ... ", n + 1, SELFTESTS.len(), test.name);
+        let _ = std::io::stdout().flush();
+        let outcome = selftest::run(test);
+        println!("{outcome}");
+        results.push((test.name, outcome));
     }
 
-    output
+    let name_width = results
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    println!("\n{:name_width$}  result", "test");
+    println!("{:-<name_width$}  ------", "");
+    for (name, outcome) in &results {
+        println!("{name:name_width$}  {outcome}");
+    }
+
+    let passed = results
+        .iter()
+        .filter(|(_, outcome)| matches!(outcome, Outcome::Pass))
+        .count();
+    println!("\n{passed}/{} passed", results.len());
+    std::process::exit(if passed == results.len() { 0 } else { 1 });
+}
+
+// contact-sheet ROM OUT.png [FRAMES]: the rom under every quirks preset,
+// without FRAMES at the point its screen settles
+fn run_contact_sheet() -> ! {
+    let mut args = std::env::args().skip(2);
+    let (Some(rom), Some(out)) = (args.next(), args.next()) else {
+        exit_with_error("usage: contact-sheet ROM OUT.png [FRAMES]");
+    };
+    let frames = args.next().map(|frames| {
+        frames
+            .parse()
+            .unwrap_or_else(|_| exit_with_error("FRAMES expects a number of frames"))
+    });
+
+    let bytes = load_rom(&rom).unwrap_or_else(|err| exit_with_error(&err.to_string()));
+    let sheet = contact_sheet::render(&bytes, frames, &Palette::default())
+        .unwrap_or_else(|err| exit_with_error(&err.to_string()));
+    if let Err(err) = std::fs::write(&out, png::encode(&sheet)) {
+        exit_with_error(&format!("{out}: {err}"));
+    }
+    println!("wrote {out}");
+    std::process::exit(0);
 }
 
-fn get_empty_grid() -> Vec<u64> {
-    vec![0; 32]
+// disasm ROM [--labels]: the rom as assembly, as it would be loaded at
+// PROGRAM_START. --labels follows the code for source that asm takes back
+fn run_disasm() -> ! {
+    let mut args = std::env::args().skip(2);
+    let (Some(rom), labels) = (args.next(), args.next()) else {
+        exit_with_error("usage: disasm ROM [--labels]");
+    };
+    let bytes = load_rom(&rom).unwrap_or_else(|err| exit_with_error(&err.to_string()));
+    match labels.as_deref() {
+        None => {
+            for line in disasm::disassemble(&bytes, PROGRAM_START as u16) {
+                println!("{line}");
+            }
+        }
+        Some("--labels") => print!("{}", disasm::source(&bytes, PROGRAM_START as u16)),
+        Some(other) => exit_with_error(&format!("unknown option {other}")),
+    }
+    std::process::exit(0);
 }
 
-//fn get_grid_string(grid: &Vec<u64>) -> String {
-//    let mut output = String::new();
-//    for (i, row) in grid.iter().enumerate() {
-//        let line = format!("{:02}: {:064b}\n", i, row);
-//        output.push_str(&line);
-//    }
-//    output
-//}
+// the core's counts, and how they went in real time
+fn print_stats(stats: &RunStats, frames_shown: u64, elapsed: Duration) {
+    let seconds = elapsed.as_secs_f64();
+    println!("\n{stats}");
+    println!(
+        "shown         {frames_shown} frames in {seconds:.1} s, {:.0} instructions a second",
+        stats.instructions as f64 / seconds.max(f64::EPSILON)
+    );
+}
 
-struct Instruction(u16);
+fn print_usage() -> ! {
+    println!(
+        "usage: chip8 [run] ROM [OPTIONS]   run a rom, or pick one in the browser without
+                                     - reads it from stdin, http urls need the network feature,
+                                     a .zip the rom in it or ARCHIVE.zip#MEMBER one of them
+       chip8 [run] --builtin NAME    a bundled rom: ibm, opcodes or catch
+       chip8 [run] ROM --frames N [--screenshot OUT.png]
+                                     quit after N frames and save the last screen, - prints it
+                                     as text. a build without raylib or sdl2 only runs this way
+       chip8 disasm ROM [--labels]   list its instructions, or source with labels for its code
+       chip8 hexdump ROM             bytes, ascii and instructions, 16 a row
+       chip8 asm SRC -o OUT.ch8      assemble what disasm lists
+       chip8 info ROM                size, hashes, platform and what the database knows
+       chip8 coverage ROM [FRAMES]   which opcodes and memory a run reached
+       chip8 compare ROM A B         side by side under two quirk sets, A and B
+                                     like cosmac-vip or vf-reset,shift@1200 ips
+       chip8 diff A.state B.state    registers, memory and pixels that differ
+       chip8 diff ROM A B [FRAMES]   the same for the first frame two quirk sets part
+       chip8 bench ROM [CYCLES]      time per opcode class
+       chip8 fuzz SEEDS OUT [ITERATIONS]
+       chip8 contact-sheet ROM OUT.png [FRAMES]
+       chip8 thumbnail ROM OUT.png
+       chip8 selftest
+       chip8 doctor [--config FILE] [--rom-dir DIR]"
+    );
+    std::process::exit(0);
+}
 
-impl Instruction {
-    fn new(first: u8, second: u8) -> Self {
-        Instruction(((first as u16) << 8) | (second as u16))
+// info ROM
+fn run_info() -> ! {
+    let Some(rom) = std::env::args().nth(2) else {
+        exit_with_error("usage: info ROM");
+    };
+    let bytes = load_rom_with_limit(&rom, MAX_EXTENDED_ROM_SIZE)
+        .unwrap_or_else(|err| exit_with_error(&err.to_string()));
+    println!("size:     {} bytes", bytes.len());
+    println!("crc32:    {:08x}", png::crc32(&bytes));
+    println!("sha1:     {}", database::sha1_hex(&bytes));
+    println!("opcodes:  {}", rom::detect_platform(&bytes).name());
+    if bytes.len() > MAX_ROM_SIZE {
+        println!("needs --extended-memory");
+    }
+    let (database, err) = Database::load_default();
+    if let Some(err) = err {
+        eprintln!("err: {err}");
+    }
+    let Some(info) = database.lookup(&bytes) else {
+        println!("not in the database ({} roms)", database.len());
+        std::process::exit(0);
+    };
+    println!("title:    {}", info.title);
+    if !info.authors.is_empty() {
+        println!("authors:  {}", info.authors.join(", "));
+    }
+    if let Some(release) = &info.release {
+        println!("release:  {release}");
     }
+    if let Some(platform) = info.platform() {
+        println!("platform: {platform}");
+    }
+    if let Some(tickrate) = info.tickrate {
+        println!("tickrate: {tickrate} instructions per frame");
+    }
+    if !info.quirks.is_empty() {
+        let quirks: Vec<String> = info
+            .quirks
+            .iter()
+            .map(|(name, on)| format!("{name}={on}"))
+            .collect();
+        println!("quirks:   {}", quirks.join(" "));
+    }
+    std::process::exit(0);
+}
 
-    fn opcode(&self) -> u16 {
-        self.0
+// asm SRC -o OUT.ch8
+fn run_asm() -> ! {
+    const USAGE: &str = "usage: asm SRC -o OUT.ch8, -o - writes to stdout";
+    let args: Vec<String> = std::env::args().skip(2).collect();
+    let [source, flag, out] = args.as_slice() else {
+        exit_with_error(USAGE);
+    };
+    if flag != "-o" {
+        exit_with_error(USAGE);
     }
+    let text = std::fs::read_to_string(source)
+        .unwrap_or_else(|err| exit_with_error(&format!("{source}: {err}")));
+    let bytes = asm::assemble(&text, PROGRAM_START as u16)
+        .unwrap_or_else(|err| exit_with_error(&format!("{source}: {err}")));
+    // -o - for "chip8 asm game.asm -o - | chip8 run -"
+    if out == "-" {
+        if let Err(err) = std::io::stdout().write_all(&bytes) {
+            exit_with_error(&format!("stdout: {err}"));
+        }
+        eprintln!("{} bytes", bytes.len());
+        std::process::exit(0);
+    }
+    if let Err(err) = std::fs::write(out, &bytes) {
+        exit_with_error(&format!("{out}: {err}"));
+    }
+    println!("{out}: {} bytes", bytes.len());
+    std::process::exit(0);
+}
 
-    fn indicator(&self) -> u8 {
-        ((self.opcode() & 0xF000) >> 12) as u8
+// hexdump ROM: the rom as loaded, 16 bytes a row with their ascii and the
+// instructions in them
+fn run_hexdump() -> ! {
+    let Some(rom) = std::env::args().nth(2) else {
+        exit_with_error("usage: hexdump ROM");
+    };
+    let bytes = load_rom(&rom).unwrap_or_else(|err| exit_with_error(&err.to_string()));
+    println!("{} bytes", bytes.len());
+    for line in disasm::hexdump(&bytes, PROGRAM_START as u16) {
+        println!("{line}");
     }
+    std::process::exit(0);
+}
 
-    fn x(&self) -> u8 {
-        ((self.opcode() & 0x0F00) >> 8) as u8
+// bench ROM [CYCLES]: runs the rom headlessly as fast as possible and breaks
+// the time down per opcode class
+fn run_bench() -> ! {
+    const DEFAULT_CYCLES: u64 = 5_000_000;
+    let mut args = std::env::args().skip(2);
+    let Some(rom) = args.next() else {
+        exit_with_error("usage: bench ROM [CYCLES]");
+    };
+    let cycles = match args.next() {
+        Some(cycles) => cycles
+            .parse()
+            .unwrap_or_else(|_| exit_with_error("CYCLES expects a number of instructions")),
+        None => DEFAULT_CYCLES,
+    };
+
+    let bytes = load_rom(&rom).unwrap_or_else(|err| exit_with_error(&err.to_string()));
+    let mut state = Chip8State::with_seed(DETERMINISTIC_SEED);
+    if let Err(err) = state.load(&bytes) {
+        exit_with_error(&err.to_string());
     }
+    state.set_profiling(true);
+    state.set_decode_cache(true);
 
-    fn y(&self) -> u8 {
-        ((self.opcode() & 0x00F0) >> 4) as u8
+    let start = Instant::now();
+    while state.emulated_time().cycles < cycles {
+        if let Err(err) = state.run_frame(DETERMINISTIC_INSTRUCTIONS_PER_FRAME) {
+            exit_with_error(&err.to_string());
+        }
+        // nothing presses keys here, a program waiting for one is done too
+        if state.run_state() != RunState::Running {
+            break;
+        }
     }
+    let elapsed = start.elapsed();
 
-    fn n(&self) -> u8 {
-        (self.opcode() & 0x000F) as u8
+    let executed = state.emulated_time().cycles;
+    println!(
+        "{executed} instructions in {:.1}ms, {:.2} MIPS",
+        elapsed.as_secs_f64() * 1e3,
+        executed as f64 / elapsed.as_secs_f64().max(f64::EPSILON) / 1e6,
+    );
+    if let Some(profile) = state.profile() {
+        println!("\n{profile}");
     }
+    std::process::exit(0);
+}
+
+// coverage ROM [FRAMES]: runs the rom headlessly without key presses and
+// reports the opcodes it reached and how it used memory
+fn run_coverage() -> ! {
+    const DEFAULT_FRAMES: u64 = 600;
+    let mut args = std::env::args().skip(2);
+    let Some(rom) = args.next() else {
+        exit_with_error("usage: coverage ROM [FRAMES]");
+    };
+    let frames = match args.next() {
+        Some(frames) => frames
+            .parse()
+            .unwrap_or_else(|_| exit_with_error("FRAMES expects a number of frames")),
+        None => DEFAULT_FRAMES,
+    };
 
-    fn nn(&self) -> u8 {
-        (self.opcode() & 0x00FF) as u8
+    let bytes = load_rom(&rom).unwrap_or_else(|err| exit_with_error(&err.to_string()));
+    let mut state = Chip8State::with_seed(DETERMINISTIC_SEED);
+    if let Err(err) = state.load(&bytes) {
+        exit_with_error(&err.to_string());
     }
+    state.set_coverage(true);
+    while state.emulated_time().frames < frames {
+        // an error ends the run, what ran up to it is still covered
+        if let Err(err) = state.run_frame(DETERMINISTIC_INSTRUCTIONS_PER_FRAME) {
+            eprintln!("err: {err}");
+            break;
+        }
+        if state.run_state() != RunState::Running {
+            break;
+        }
+    }
+    if let Some(coverage) = state.coverage() {
+        print!("{coverage}");
+    }
+    std::process::exit(0);
+}
 
-    fn nnn(&self) -> u16 {
-        self.opcode() & 0x0FFF
+// fuzz SEEDS OUT [ITERATIONS]: mutates the roms in SEEDS on every core and
+// writes minimized roms that crash the interpreter to OUT
+fn run_fuzz() -> ! {
+    let mut args = std::env::args().skip(2);
+    let (Some(seeds), Some(out)) = (args.next(), args.next()) else {
+        exit_with_error("usage: fuzz SEEDS OUT [ITERATIONS]");
+    };
+    let mut config = FuzzConfig::default();
+    if let Some(iterations) = args.next() {
+        config.iterations = iterations
+            .parse()
+            .unwrap_or_else(|_| exit_with_error("ITERATIONS expects a number of executions"));
     }
+
+    let entries =
+        std::fs::read_dir(&seeds).unwrap_or_else(|err| exit_with_error(&format!("{seeds}: {err}")));
+    let mut roms = Vec::new();
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if chip8::rom::is_rom_file(&path) {
+            match load_rom(&path) {
+                Ok(rom) => roms.push(rom),
+                Err(err) => eprintln!("err: {}: {err}", path.display()),
+            }
+        }
+    }
+    println!(
+        "fuzzing {} seeds on {} threads, {} executions",
+        roms.len(),
+        config.threads,
+        config.iterations
+    );
+
+    let start = Instant::now();
+    let report = fuzz::fuzz(&roms, &config);
+    println!(
+        "{} executions in {:.1}s, {} handlers reached, corpus of {}",
+        report.executions,
+        start.elapsed().as_secs_f64(),
+        report.coverage.len(),
+        report.corpus.len()
+    );
+    if let Err(err) = std::fs::create_dir_all(&out) {
+        exit_with_error(&format!("{out}: {err}"));
+    }
+    for crash in &report.crashes {
+        let path = Path::new(&out).join(format!("crash-{:08x}.ch8", png::crc32(&crash.rom)));
+        match std::fs::write(&path, &crash.rom) {
+            Ok(()) => println!(
+                "{}: {} bytes, {}",
+                path.display(),
+                crash.rom.len(),
+                crash.message
+            ),
+            Err(err) => eprintln!("err: {}: {err}", path.display()),
+        }
+    }
+    std::process::exit(if report.crashes.is_empty() { 0 } else { 1 });
 }
 
-struct Chip8State {
-    display: Vec<u64>,
-    memory: Vec<u8>,
-    v: Vec<u8>,
-    pc: u16,
-    i: u16,
+// thumbnail ROM OUT.png: the rom's title screen as a small png
+fn run_thumbnail() -> ! {
+    let mut args = std::env::args().skip(2);
+    let (Some(rom), Some(out)) = (args.next(), args.next()) else {
+        exit_with_error("usage: thumbnail ROM OUT.png");
+    };
+
+    let bytes = load_rom(&rom).unwrap_or_else(|err| exit_with_error(&err.to_string()));
+    let display = thumbnail::capture_title_screen(&bytes, Quirks::default(), thumbnail::MAX_FRAMES)
+        .unwrap_or_else(|err| exit_with_error(&err.to_string()));
+    let image = thumbnail::render(&display, &Palette::default());
+    if let Err(err) = std::fs::write(&out, png::encode(&image)) {
+        exit_with_error(&format!("{out}: {err}"));
+    }
+    println!("wrote {out}");
+    std::process::exit(0);
 }
 
-impl Chip8State {
-    fn new() -> Self {
-        Chip8State {
-            display: get_empty_grid(),
-            memory: vec![0; 4096],
-            v: vec![0; 16],
-            pc: 0x200,
-            i: 0,
+// doctor [--config FILE] [--rom-dir DIR]: checks the environment the
+// frontend needs, exits with 1 if something is broken
+fn run_doctor() -> ! {
+    let mut keymap = Keymap::default();
+    let mut rom_dir = DEFAULT_ROM_DIR.to_owned();
+    let mut args = std::env::args().skip(2);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--config", Some(path)) => load_config(
+                &mut keymap,
+                &mut RomOverrides::new(),
+                &mut Buzzer::default(),
+                &path,
+            ),
+            ("--rom-dir", Some(dir)) => rom_dir = dir,
+            _ => exit_with_error("usage: doctor [--config FILE] [--rom-dir DIR]"),
         }
     }
 
-    fn cycle(&mut self) {
-        let pc = self.pc as usize;
-        let inst = Instruction::new(self.memory[pc], self.memory[pc + 1]);
-        self.pc += 2;
+    let mut checks = frontend_checks();
+    checks.push(doctor::check_rom_dir(Path::new(&rom_dir)));
+    checks.push(doctor::check_keymap(&keymap));
+    checks.push(doctor::check_timer());
+    doctor::print_report(&checks);
+
+    let failed = checks.iter().any(|check| check.status == Status::Fail);
+    std::process::exit(if failed { 1 } else { 0 });
+}
+
+#[cfg(feature = "sdl2")]
+fn frontend_checks() -> Vec<Check> {
+    use chip8::backend::sdl2::{probe_audio, probe_renderer};
+
+    let audio = if !cfg!(feature = "audio") {
+        Check::warn(
+            "audio",
+            "built without audio",
+            "rebuild with --features audio",
+        )
+    } else {
+        match probe_audio() {
+            Ok(0) => Check::warn(
+                "audio",
+                "no playback device",
+                "connect or enable an audio output",
+            ),
+            Ok(devices) => Check::ok("audio", format!("{devices} playback devices")),
+            Err(err) => Check::warn(
+                "audio",
+                err,
+                "check the sdl2 audio driver (SDL_AUDIODRIVER)",
+            ),
+        }
+    };
+    let renderer = match probe_renderer() {
+        Ok(0) => Check::fail(
+            "renderer",
+            "no render drivers",
+            "install gpu drivers for sdl2",
+        ),
+        Ok(drivers) => Check::ok("renderer", format!("sdl2, {drivers} render drivers")),
+        Err(err) => Check::fail(
+            "renderer",
+            err,
+            "check the sdl2 video driver (SDL_VIDEODRIVER)",
+        ),
+    };
+    vec![doctor::check_display_server(), renderer, audio]
+}
+
+#[cfg(all(feature = "raylib", not(feature = "sdl2")))]
+fn frontend_checks() -> Vec<Check> {
+    let audio = if !cfg!(feature = "audio") {
+        Check::warn(
+            "audio",
+            "built without audio",
+            "rebuild with --features audio",
+        )
+    } else {
+        match RaylibAudio::init_audio_device() {
+            Ok(_) => Check::ok("audio", "raylib audio device opened"),
+            Err(_) => Check::warn(
+                "audio",
+                "raylib could not open an audio device",
+                "connect or enable an audio output, the beep stays silent",
+            ),
+        }
+    };
+    vec![
+        doctor::check_display_server(),
+        Check::ok("renderer", "raylib, opengl context is created on start"),
+        audio,
+    ]
+}
+
+#[cfg(not(any(feature = "raylib", feature = "sdl2")))]
+fn frontend_checks() -> Vec<Check> {
+    vec![Check::fail(
+        "frontend",
+        "built without a frontend",
+        "rebuild with --features raylib or --features sdl2",
+    )]
+}
+
+#[cfg(feature = "sdl2")]
+fn start_frontend(handle: &Chip8Handle, tools: &mut Tools) {
+    let mut backend = Sdl2Backend::new(WIDTH, HEIGHT, &tools.keymap)
+        .unwrap_or_else(|err| exit_with_error(&format!("unable to start sdl2: {err}")));
+    run(&mut backend, handle, tools);
+}
 
-        self.decode_and_execute(inst);
+#[cfg(all(feature = "raylib", not(feature = "sdl2")))]
+fn start_frontend(handle: &Chip8Handle, tools: &mut Tools) {
+    let audio = if cfg!(feature = "audio") {
+        RaylibAudio::init_audio_device().ok()
+    } else {
+        None
+    };
+    let mut backend = RaylibBackend::new(WIDTH, HEIGHT, audio.as_ref(), &tools.keymap)
+        .unwrap_or_else(|err| exit_with_error(&err));
+    run(&mut backend, handle, tools);
+}
+
+// no window, the machine runs its --frames and main takes it from there
+#[cfg(not(any(feature = "raylib", feature = "sdl2")))]
+fn start_frontend(handle: &Chip8Handle, tools: &mut Tools) {
+    let Some(frames) = tools.frames else {
+        exit_with_error(
+            "built without a frontend, enable the raylib or sdl2 feature or run headless with --frames",
+        );
+    };
+    // a halted program draws nothing more, its frames stop counting
+    let mut halted = false;
+    while handle.is_running() && !halted && handle.emulated_time().frames < frames {
+        while let Some(frame) = handle.frames().pop() {
+            halted = frame.run_state == RunState::Halted;
+            handle.recycle(frame);
+        }
+        std::thread::sleep(Duration::from_millis(10));
     }
+}
 
-    fn decode_and_execute(&mut self, inst: Instruction) {
-        match inst.indicator() {
-            0x0 => {
-                if inst.opcode() == 0x00E0 {
-                    //self.display = get_empty_grid();
-                }
+// compare ROM A B: one rom under two quirk sets in one window, both fed the
+// keys pressed. it pauses on the first frame the screens differ, the
+// pause hotkey goes on and step frame steps
+fn run_compare() -> ! {
+    let args: Vec<String> = std::env::args().skip(2).collect();
+    let [rom, a, b] = args.as_slice() else {
+        exit_with_error("usage: compare ROM QUIRKS[@IPS] QUIRKS[@IPS]");
+    };
+    let bytes =
+        read_rom_arg(rom, MAX_EXTENDED_ROM_SIZE).unwrap_or_else(|err| exit_with_error(&err));
+    let side = |text: &str| Side::parse(text).unwrap_or_else(|err| exit_with_error(&err));
+    let mut comparison = Comparison::new(&bytes, [side(a), side(b)])
+        .unwrap_or_else(|err| exit_with_error(&format!("{rom}: {err}")));
+    start_compare(&mut comparison);
+    std::process::exit(0);
+}
+
+// diff A.state B.state, or diff ROM A B [FRAMES]. exits 1 when they differ
+fn run_diff() -> ! {
+    const DEFAULT_FRAMES: u64 = 600;
+    let args: Vec<String> = std::env::args().skip(2).collect();
+    let report = match args.as_slice() {
+        [a, b] => {
+            let read = |path: &str| {
+                std::fs::read(path)
+                    .map_err(|err| err.to_string())
+                    .and_then(|bytes| savestate::decode(&bytes).map_err(|err| err.to_string()))
+                    .unwrap_or_else(|err| exit_with_error(&format!("{path}: {err}")))
+            };
+            state_diff::diff(&read(a), &read(b))
+        }
+        [rom, a, b, rest @ ..] if rest.len() <= 1 => {
+            let frames = match rest.first() {
+                Some(frames) => frames
+                    .parse()
+                    .unwrap_or_else(|_| exit_with_error("FRAMES expects a number of frames")),
+                None => DEFAULT_FRAMES,
+            };
+            let bytes = read_rom_arg(rom, MAX_EXTENDED_ROM_SIZE)
+                .unwrap_or_else(|err| exit_with_error(&err));
+            let side = |text: &str| Side::parse(text).unwrap_or_else(|err| exit_with_error(&err));
+            let mut comparison = Comparison::new(&bytes, [side(a), side(b)])
+                .unwrap_or_else(|err| exit_with_error(&format!("{rom}: {err}")));
+            let mut report = comparison.state_diff();
+            while report.is_empty() && comparison.frame() < frames {
+                comparison.run_frame();
+                report = comparison.state_diff();
             }
-            0x1 => {}
-            0x3 => {
-                if self.v[inst.x() as usize] == inst.nn() {
-                    self.pc += 2;
+            println!("frame {}", comparison.frame());
+            for (side, error) in comparison.sides().iter().zip(comparison.errors()) {
+                if let Some(err) = error {
+                    println!("{} stopped: {err}", side.label);
                 }
             }
-            0x4 => {
-                if self.v[inst.x() as usize] != inst.nn() {
-                    self.pc += 2;
+            report
+        }
+        _ => exit_with_error(
+            "usage: diff A.state B.state, or diff ROM QUIRKS[@IPS] QUIRKS[@IPS] [FRAMES]",
+        ),
+    };
+    print!("{report}");
+    std::process::exit(if report.is_empty() { 0 } else { 1 });
+}
+
+#[cfg(feature = "sdl2")]
+fn start_compare(comparison: &mut Comparison) {
+    let mut backend = Sdl2Backend::new(WIDTH, HEIGHT, &Keymap::default())
+        .unwrap_or_else(|err| exit_with_error(&format!("unable to start sdl2: {err}")));
+    run_comparison(&mut backend, comparison);
+}
+
+#[cfg(all(feature = "raylib", not(feature = "sdl2")))]
+fn start_compare(comparison: &mut Comparison) {
+    let mut backend = RaylibBackend::new(WIDTH, HEIGHT, None, &Keymap::default())
+        .unwrap_or_else(|err| exit_with_error(&err));
+    run_comparison(&mut backend, comparison);
+}
+
+#[cfg(not(any(feature = "raylib", feature = "sdl2")))]
+fn start_compare(_comparison: &mut Comparison) {
+    exit_with_error("built without a frontend, enable the raylib or sdl2 feature");
+}
+
+#[cfg(any(feature = "raylib", feature = "sdl2"))]
+fn run_comparison(backend: &mut impl Backend, comparison: &mut Comparison) {
+    let [a, b] = comparison.sides();
+    backend.set_title(&format!("CHIP-8 - {} | {}", a.label, b.label));
+    let mut keypad = [false; 16];
+    let mut hotkeys = Vec::new();
+    let mut paused = false;
+    let mut fullscreen = false;
+    let mut geometry = None;
+    let mut next_frame = Instant::now();
+    while backend.poll(&mut keypad, &mut hotkeys) {
+        let mut step = false;
+        for hotkey in hotkeys.drain(..) {
+            match hotkey {
+                Hotkey::TogglePause => paused = !paused,
+                Hotkey::StepFrame => step = paused,
+                Hotkey::ToggleFullscreen => {
+                    fullscreen = !fullscreen;
+                    backend.set_fullscreen(fullscreen);
                 }
+                _ => {}
             }
-            0x6 => {
-                self.v[inst.x() as usize] = inst.nn();
-            }
-            0x7 => {
-                self.v[inst.x() as usize] += inst.nn();
-            }
-            0x8 => self.v[inst.x() as usize] = self.v[inst.y() as usize],
-            0xA => self.i = inst.nnn(),
-            0xD => {
-                let x_start = self.v[inst.x() as usize] & 63;
-                let y_start = self.v[inst.y() as usize] & 31;
-                self.v[0xF] = 0;
-                for row in 0..inst.n() {
-                    let sprite_byte = self.memory[(self.i + row as u16) as usize];
-                    let y = y_start + row as u8;
+        }
 
-                    if y >= 32 {
-                        break;
-                    }
+        comparison.set_keypad(&keypad);
+        if !paused || step {
+            let diverged = comparison.diverged();
+            comparison.run_frame();
+            // stop on the frame where they parted to look at it
+            paused |= diverged.is_none() && comparison.diverged().is_some();
+        }
 
-                    for bit in 0..8 {
-                        let x = x_start + bit;
-                        if x >= 64 {
-                            break;
-                        }
-                        let mask = 1u64 << (63 - x);
+        let screen = comparison.screen();
+        if geometry != Some(screen.geometry()) {
+            geometry = Some(screen.geometry());
+            backend.set_geometry(screen.geometry());
+        }
+        let status = comparison.status();
+        backend.set_status(Some(&if paused {
+            format!("{status} - paused")
+        } else {
+            status
+        }));
+        backend.draw(&screen);
 
-                        let sprite_pixel = ((sprite_byte >> (7 - bit)) & 1) != 0;
-                        if !sprite_pixel {
-                            continue;
-                        }
+        next_frame += chip8::chip8::TIMER_PERIOD;
+        match next_frame.checked_duration_since(Instant::now()) {
+            Some(wait) => std::thread::sleep(wait),
+            None => next_frame = Instant::now(),
+        }
+    }
+}
+
+#[cfg(any(feature = "raylib", feature = "sdl2"))]
+fn run(backend: &mut impl Backend, handle: &Chip8Handle, tools: &mut Tools) {
+    // each speed step doubles or halves instructions per second
+    const SPEED_RANGE: std::ops::RangeInclusive<u32> = 10..=100_000;
+    const FAST_FORWARD: u32 = 8;
+    // how long a speed change stays in the status line
+    const SPEED_NOTICE: Duration = Duration::from_millis(1500);
+    // the busy dots move on this often
+    const BUSY_STEP: Duration = Duration::from_millis(400);
+    const RATE_SAMPLE: Duration = Duration::from_millis(500);
 
-                        let screen_pixel = (self.display[y as usize] & mask) != 0;
+    backend.set_buzzer(&tools.buzzer);
+    let mut keypad = [false; 16];
+    let mut sent_keypad = keypad;
+    let mut frame = Frame {
+        display: Display::default(),
+        beep: false,
+        run_state: RunState::Running,
+        time: EmulatedTime::default(),
+        heatmap: None,
+        polled_keys: 0,
+        geometry_changes: 0,
+        cpu: CpuSnapshot::default(),
+        last_sprite: None,
+        colors: None,
+        sample: None,
+        memory: None,
+        #[cfg(feature = "debugger")]
+        debugger: None,
+    };
+    let mut geometry_changes = None;
+    // the megachip sample last started, see Audio::play_sample
+    let mut sample: Option<Arc<Sample>> = None;
+    let mut auto_keys_for = 0;
+    let mut clip = ClipRecorder::new();
+    let mut history = tools.key_history.then(KeyHistory::new);
+    let mut in_menu = tools.browser.is_some();
+    let mut picked = None;
+    let mut hotkeys = Vec::new();
+    let mut show_keypad = false;
+    let mut show_heatmap = false;
+    let mut ghosting = tools.ghosting;
+    backend.set_ghosting(ghosting);
+    // the pixel grid, and the last sprite's outline on it
+    let (mut show_grid, mut show_sprite) = (false, false);
+    let mut show_hud = false;
+    let mut memory_view: Option<MemoryCenter> = None;
+    #[cfg(feature = "debugger")]
+    let mut show_debugger = false;
+    // the hud's and the title's rates, measured over RATE_SAMPLE: when the
+    // sample started, the emulated cycles then and the frames drawn since
+    let mut rate_sample = (Instant::now(), 0, 0);
+    let mut hud = DebugHud::default();
+    let mut measured = false;
+    let mut title = rom_title(tools);
+    // what the window shows, set_title only when it changes
+    let mut shown_title = String::new();
+    let mut crt = false;
+    let mut paused = false;
+    // paused because the window lost the focus, resumed when it's back
+    let mut focus_paused = false;
+    let mut sent_speed = tools.speed;
+    let mut speed_notice_until = None;
+    // when the display last changed
+    let mut last_draw = Instant::now();
+    let mut dimmed = false;
+    let mut recording = false;
+    backend.set_palette(&tools.palette);
+    backend.set_fullscreen(tools.fullscreen);
+    backend.set_virtual_keypad(tools.virtual_keypad);
+    backend.set_gamepad(&tools.keymap.gamepad_for(tools.rom_name.as_deref()));
+
+    while backend.poll(&mut keypad, &mut hotkeys)
+        && handle.is_running()
+        && tools
+            .frames
+            .is_none_or(|frames| handle.emulated_time().frames < frames)
+    {
+        // the core only hears about actual key changes
+        if keypad != sent_keypad {
+            handle.set_keypad(keypad);
+            sent_keypad = keypad;
+        }
+        if let Some(latency) = &mut tools.latency {
+            latency.keypad(&keypad);
+        }
+        if let Some(history) = &mut history {
+            history.update(&keypad);
+        }
+        if let Some(tutorial) = &mut tools.tutorial {
+            tutorial.keypad(&keypad);
+        }
 
-                        if screen_pixel {
-                            self.v[0xF] = 1;
+        // a rom loaded through the control socket, read already to tell the
+        // client whether it could be
+        #[cfg(not(all(feature = "network", unix)))]
+        let controlled = None;
+        #[cfg(all(feature = "network", unix))]
+        let controlled = {
+            let mut controlled = None;
+            for (command, reply_to) in tools.control.iter().flat_map(ControlServer::requests) {
+                let mut reply = "ok\n".to_owned();
+                match command {
+                    ControlCommand::Load(path) => match load_rom_with_limit(&path, tools.rom_limit)
+                    {
+                        Ok(rom) => controlled = Some((path, Ok(rom), true)),
+                        Err(err) => reply = format!("err {err}\n"),
+                    },
+                    ControlCommand::Pause | ControlCommand::Resume => {
+                        paused = command == ControlCommand::Pause;
+                        handle.set_paused(paused);
+                    }
+                    ControlCommand::Reset => {
+                        handle.load_rom(tools.rom.clone());
+                        clip.clear();
+                        paused = false;
+                    }
+                    // the core replies itself
+                    ControlCommand::Save(path) => {
+                        handle.save_state_replying(path, reply_to);
+                        continue;
+                    }
+                    ControlCommand::Restore(path) => {
+                        handle.load_state_replying(path, reply_to);
+                        continue;
+                    }
+                    ControlCommand::Poke(poke) => {
+                        handle.remote(chip8::remote::RemoteCommand::Debug(poke), reply_to);
+                        continue;
+                    }
+                    ControlCommand::Screenshot(path) => {
+                        if let Err(err) = write_screenshot(&frame.display, tools, &path) {
+                            reply = format!("err {err}\n");
                         }
+                    }
+                }
+                let _ = reply_to.send(reply);
+            }
+            controlled
+        };
 
-                        self.display[y as usize] ^= mask;
+        // a rom dropped onto the window or picked in the browser replaces the
+        // current one the way a change in the watched directory does
+        let dropped = controlled.or_else(|| {
+            let path = backend.dropped_file().or(picked.take())?;
+            let rom = load_rom_with_limit(&path, tools.rom_limit);
+            Some((path, rom, true))
+        });
+        let watched = || {
+            let (path, rom) = tools.watcher.as_mut().and_then(RomWatcher::poll)?;
+            Some((path, rom, false))
+        };
+        if let Some((path, rom, remember)) = dropped.or_else(watched) {
+            match rom {
+                Ok(rom) => {
+                    println!("loading {}", path.display());
+                    // rebuilds of a watched rom would push everything else out
+                    if remember {
+                        remember_rom(tools, &path);
                     }
+                    tools.rom.clone_from(&rom);
+                    tools.rom_name = rom_name(&path);
+                    let (quirks, speed) =
+                        tools.rom_config.settings(&rom, tools.rom_name.as_deref());
+                    handle.set_quirks(quirks);
+                    tools.speed = speed;
+                    backend.set_gamepad(&tools.keymap.gamepad_for(tools.rom_name.as_deref()));
+                    handle.load_rom(rom);
+                    title = rom_title(tools);
+                    clip.clear();
+                    paused = false;
+                    in_menu = false;
                 }
+                Err(err) => eprintln!("err: {}: {err}", path.display()),
             }
-            _ => {
-                println!("unknown opcode: {:04X}", inst.opcode());
+        }
+
+        let focused = backend.is_focused() || !tools.auto_pause;
+        // a pause of the user's or a breakpoint's stays when the focus is back
+        if !focused && !focus_paused && !paused && frame.run_state != RunState::Paused {
+            focus_paused = true;
+            handle.set_paused(true);
+        } else if focused && focus_paused {
+            focus_paused = false;
+            handle.set_paused(paused);
+        }
+
+        for hotkey in hotkeys.drain(..) {
+            if let Some(tutorial) = &mut tools.tutorial {
+                tutorial.hotkey(hotkey);
+            }
+            match hotkey {
+                Hotkey::ToggleKeypadOverlay => show_keypad = !show_keypad,
+                Hotkey::ToggleVirtualKeypad => {
+                    tools.virtual_keypad = !tools.virtual_keypad;
+                    backend.set_virtual_keypad(tools.virtual_keypad);
+                }
+                Hotkey::ToggleCrt => {
+                    crt = !crt;
+                    backend.set_crt(crt);
+                }
+                Hotkey::TogglePause => {
+                    paused = !paused;
+                    handle.set_paused(paused);
+                }
+                Hotkey::StepFrame if paused => handle.step_frame(),
+                Hotkey::StepFrame => {}
+                // the core ignores it unless paused, at a breakpoint too
+                Hotkey::StepInstruction => handle.step_instruction(),
+                Hotkey::RunToDraw => handle.run_to(RunTarget::Draw),
+                Hotkey::RunToSound => handle.run_to(RunTarget::Sound),
+                Hotkey::Reset => {
+                    handle.load_rom(tools.rom.clone());
+                    clip.clear();
+                    paused = false;
+                }
+                Hotkey::SpeedUp | Hotkey::SpeedDown => {
+                    let speed = if hotkey == Hotkey::SpeedUp {
+                        tools.speed.saturating_mul(2)
+                    } else {
+                        tools.speed / 2
+                    };
+                    tools.speed = speed.clamp(*SPEED_RANGE.start(), *SPEED_RANGE.end());
+                    speed_notice_until = Some(Instant::now() + SPEED_NOTICE);
+                }
+                // see is_held below
+                Hotkey::FastForward => {}
+                Hotkey::ToggleFullscreen => {
+                    tools.fullscreen = !tools.fullscreen;
+                    backend.set_fullscreen(tools.fullscreen);
+                    backend.set_gamepad(&tools.keymap.gamepad_for(tools.rom_name.as_deref()));
+                }
+                Hotkey::ToggleHeatmap => {
+                    show_heatmap = !show_heatmap;
+                    handle.set_heatmap(show_heatmap);
+                }
+                Hotkey::ToggleGhosting => {
+                    ghosting = match ghosting {
+                        Some(_) => None,
+                        None => Some(tools.ghosting.unwrap_or(phosphor::DEFAULT_PERSISTENCE)),
+                    };
+                    backend.set_ghosting(ghosting);
+                }
+                Hotkey::CyclePixelGrid => {
+                    (show_grid, show_sprite) = match (show_grid, show_sprite) {
+                        (false, _) => (true, false),
+                        (true, false) => (true, true),
+                        (true, true) => (false, false),
+                    };
+                }
+                Hotkey::ToggleDebugHud => show_hud = !show_hud,
+                Hotkey::CycleMemoryView => {
+                    memory_view = match memory_view {
+                        None => Some(MemoryCenter::Pc),
+                        Some(MemoryCenter::Pc) => Some(MemoryCenter::I),
+                        Some(MemoryCenter::I) => None,
+                    };
+                    handle.set_memory_view(memory_view);
+                }
+                #[cfg(feature = "debugger")]
+                Hotkey::ToggleDebugger => {
+                    show_debugger = !show_debugger;
+                    handle.set_debugger(show_debugger);
+                }
+                #[cfg(not(feature = "debugger"))]
+                Hotkey::ToggleDebugger => {
+                    eprintln!("err: the debugger panel needs a build with the debugger feature");
+                }
+                Hotkey::ExportClip => export_clip(&clip, tools),
+                Hotkey::Screenshot => save_screenshot(&frame.display, tools),
+                Hotkey::PrintProfile => handle.report_profile(),
+                // the browser's idle rom has nothing worth keeping
+                Hotkey::SaveState(_) | Hotkey::LoadState(_) if in_menu => {}
+                Hotkey::SaveState(slot) | Hotkey::LoadState(slot) => {
+                    let Some(state_slots) = &tools.state_slots else {
+                        eprintln!("err: no state directory, set HOME or XDG_STATE_HOME");
+                        continue;
+                    };
+                    let path = state_slots.path(&tools.rom, slot);
+                    if let Hotkey::SaveState(_) = hotkey {
+                        handle.save_state(path);
+                    } else {
+                        handle.load_state(path);
+                    }
+                }
+                Hotkey::ToggleRecording => {
+                    recording = !recording;
+                    handle.set_recording(recording);
+                    if recording {
+                        println!("recording, press again to save");
+                    }
+                }
+                Hotkey::Menu => {
+                    let Some(browser) = &mut tools.browser else {
+                        continue;
+                    };
+                    // escape in the menu goes back to the game, if there is one
+                    if in_menu && tools.rom_name.is_some() {
+                        in_menu = false;
+                    } else if !in_menu {
+                        if let Err(err) = browser.rescan() {
+                            eprintln!("err: {}: {err}", browser.dir().display());
+                        }
+                        in_menu = true;
+                    }
+                    paused = in_menu;
+                    handle.set_paused(paused);
+                }
+                Hotkey::MenuUp | Hotkey::MenuDown if in_menu => {
+                    let by = if hotkey == Hotkey::MenuUp { -1 } else { 1 };
+                    if let Some(browser) = &mut tools.browser {
+                        browser.move_selection(by);
+                    }
+                }
+                Hotkey::MenuSelect if in_menu => {
+                    picked = tools
+                        .browser
+                        .as_ref()
+                        .and_then(RomBrowser::selected)
+                        .map(Path::to_path_buf);
+                }
+                Hotkey::MenuUp | Hotkey::MenuDown | Hotkey::MenuSelect => {}
             }
         }
+
+        for line in tools.console.iter().flat_map(Receiver::try_iter) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match DebugCommand::parse(&line) {
+                Ok(command) => handle.debug(command),
+                Err(err) => eprintln!("err: {err}"),
+            }
+        }
+        #[cfg(feature = "network")]
+        for (command, reply_to) in tools.remote.iter().flat_map(RemoteServer::requests) {
+            handle.remote(command, reply_to);
+        }
+
+        if let Some(finished) = handle.finished_recording() {
+            save_recording(&finished, tools);
+        }
+        for message in handle.take_messages() {
+            match message {
+                Ok(shown) => println!("{shown}"),
+                Err(err) => eprintln!("err: {err}"),
+            }
+        }
+
+        let fast_forward = backend.is_held(Hotkey::FastForward);
+        let speed = if fast_forward {
+            tools.speed.saturating_mul(FAST_FORWARD)
+        } else {
+            tools.speed
+        };
+        if speed != sent_speed {
+            handle.set_speed(speed);
+            sent_speed = speed;
+        }
+
+        for event in handle.take_events() {
+            match event {
+                Event::DisplayUpdated => last_draw = Instant::now(),
+                Event::Breakpoint(pc) => println!("paused at breakpoint {pc:03X}"),
+                _ => {}
+            }
+        }
+
+        // one frame per render keeps a blocked core in step with the display
+        if let Some(next) = handle.frames().pop() {
+            handle.recycle(std::mem::replace(&mut frame, next));
+            tools.frames_shown += 1;
+            if geometry_changes != Some(frame.geometry_changes) {
+                geometry_changes = Some(frame.geometry_changes);
+                backend.set_geometry(frame.display.geometry());
+            }
+            backend.set_colors(frame.colors.as_deref());
+            let started = match (&frame.sample, &sample) {
+                (Some(new), Some(old)) => !Arc::ptr_eq(new, old),
+                (new, old) => new.is_some() != old.is_some(),
+            };
+            if started {
+                sample = frame.sample.clone();
+                backend.play_sample(sample.as_deref());
+            }
+            clip.push(&frame.display, &keypad);
+            if let Some(latency) = &mut tools.latency {
+                latency.frame(&frame.display);
+            }
+        }
+
+        if tools.auto_keys && frame.polled_keys != auto_keys_for {
+            auto_keys_for = frame.polled_keys;
+            let mapping = chip8::keymap::auto_keys(auto_keys_for);
+            let names: Vec<_> = mapping
+                .iter()
+                .map(|(name, key)| format!("{name}={key:X}"))
+                .collect();
+            println!("auto keys: {}", names.join(" "));
+            backend.set_extra_keys(&mapping);
+        }
+
+        // a paused program's sound timer stands still, it would drone on, and
+        // a finished one has nothing left to play
+        let silent = matches!(frame.run_state, RunState::Paused | RunState::Halted);
+        backend.set_beep(frame.beep && !silent && !focus_paused);
+        let speed_notice = (fast_forward
+            || speed_notice_until.is_some_and(|until| Instant::now() < until))
+        .then(|| format!("speed {speed} ips"));
+        // only a program that runs can be busy, see RunState::Halted for one
+        // stuck in a loop
+        let idle = last_draw.elapsed();
+        let busy = frame.run_state == RunState::Running
+            && !paused
+            && tools.dim_idle.is_some_and(|after| idle >= after);
+        if busy != dimmed {
+            dimmed = busy;
+            let palette = if busy {
+                tools.palette.dimmed()
+            } else {
+                tools.palette
+            };
+            backend.set_palette(&palette);
+        }
+        let busy_notice = busy.then(|| {
+            let dots = (idle.as_millis() / BUSY_STEP.as_millis()) % 3 + 1;
+            format!("busy{}", ".".repeat(dots as usize))
+        });
+        let tutorial = tools
+            .tutorial
+            .as_ref()
+            .and_then(|tutorial| tutorial.text(&tools.keymap));
+        backend.set_status(speed_notice.or(busy_notice).or(tutorial).as_deref().or(
+            match frame.run_state {
+                RunState::Running if recording => Some("recording"),
+                RunState::Running => None,
+                RunState::Halted => Some("program finished"),
+                RunState::WaitingForKey { .. } => Some("waiting for key"),
+                RunState::Paused => Some("paused"),
+                _ => None,
+            },
+        ));
+        backend.set_keypad_overlay(
+            show_keypad.then_some(&keypad),
+            history.as_ref().filter(|_| show_keypad),
+        );
+        backend.set_heatmap_overlay(frame.heatmap.as_ref().filter(|_| show_heatmap));
+        backend.set_pixel_grid(
+            show_grid,
+            frame.last_sprite.as_ref().filter(|_| show_sprite),
+        );
+        let menu = tools
+            .browser
+            .as_ref()
+            .filter(|_| in_menu)
+            .map(|browser| Menu {
+                title: format!("roms in {}", browser.dir().display()),
+                items: browser.names(),
+                selected: browser.selected_index(),
+                empty: "no .ch8 files here".to_owned(),
+            });
+        backend.set_menu(menu.as_ref());
+        let sampled = rate_sample.0.elapsed();
+        if sampled >= RATE_SAMPLE {
+            let seconds = sampled.as_secs_f64();
+            hud.instructions_per_second =
+                frame.time.cycles.saturating_sub(rate_sample.1) as f64 / seconds;
+            hud.frames_per_second = rate_sample.2 as f64 / seconds;
+            rate_sample = (Instant::now(), frame.time.cycles, 0);
+            measured = true;
+        }
+        let rates = measured.then_some((hud.frames_per_second, hud.instructions_per_second));
+        let window_title = ui::window_title(
+            title.as_deref().filter(|_| !in_menu),
+            frame.run_state,
+            rates,
+        );
+        if window_title != shown_title {
+            backend.set_title(&window_title);
+            shown_title = window_title;
+        }
+        hud.cpu = frame.cpu;
+        backend.set_debug_hud(show_hud.then_some(&hud));
+        backend.set_memory_view(frame.memory.as_ref().filter(|_| memory_view.is_some()));
+        #[cfg(feature = "debugger")]
+        backend.set_debugger(frame.debugger.as_ref().filter(|_| show_debugger));
+        backend.draw(&frame.display);
+        rate_sample.2 += 1;
+    }
+
+    // closing the window saves a recording still running
+    if recording {
+        handle.set_recording(false);
+        if let Some(finished) = handle.wait_for_recording(Duration::from_secs(1)) {
+            save_recording(&finished, tools);
+        }
+    }
+}
+
+// <rom>-clip-<unix time>.gif in the working directory
+#[cfg(any(feature = "raylib", feature = "sdl2"))]
+fn export_clip(clip: &ClipRecorder, tools: &Tools) {
+    if clip.is_empty() {
+        return;
+    }
+    let time = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let name = tools.rom_name.as_deref().unwrap_or("chip8");
+    let path = format!("{name}-clip-{time}.gif");
+    let gif = clip.export(&tools.palette, tools.caption.as_deref());
+    match std::fs::write(&path, gif) {
+        Ok(()) => println!("saved {:.1}s clip to {path}", clip.len() as f32 / 60.0),
+        Err(err) => eprintln!("err: unable to write {path}: {err}"),
     }
 }
+
+// <rom>-rec-<unix time>.gif in the working directory
+#[cfg(any(feature = "raylib", feature = "sdl2"))]
+fn save_recording(recording: &GifRecorder, tools: &Tools) {
+    if recording.is_empty() {
+        return;
+    }
+    let time = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let name = tools.rom_name.as_deref().unwrap_or("chip8");
+    let path = format!("{name}-rec-{time}.gif");
+    let seconds = recording.duration_frames() as f32 / 60.0;
+    match std::fs::write(&path, recording.encode(&tools.palette)) {
+        Ok(()) => println!("saved {seconds:.1}s recording to {path}"),
+        Err(err) => eprintln!("err: unable to write {path}: {err}"),
+    }
+}
+
+// <rom>-shot-<unix time>.png in the working directory
+#[cfg(any(feature = "raylib", feature = "sdl2"))]
+fn save_screenshot(display: &Display, tools: &Tools) {
+    let time = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let name = tools.rom_name.as_deref().unwrap_or("chip8");
+    let path = format!("{name}-shot-{time}.png");
+    match write_screenshot(display, tools, Path::new(&path)) {
+        Ok(()) => println!("saved screenshot to {path}"),
+        Err(err) => eprintln!("err: {err}"),
+    }
+}
+
+fn write_screenshot(display: &Display, tools: &Tools, path: &Path) -> Result<(), String> {
+    let png = screenshot::encode(display, &tools.palette, tools.screenshot_scale);
+    std::fs::write(path, png).map_err(|err| format!("unable to write {}: {err}", path.display()))
+}
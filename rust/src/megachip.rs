@@ -0,0 +1,244 @@
+// megachip-8: 0011 switches to a 256x192 screen of 32 bit colors drawn
+// with sprites of palette indices, 00E0 shows what was drawn since the last
+// one and starts over, and 060N plays 8 bit samples from memory. the other
+// opcodes set up the sprite size, palette, blending and collisions, see
+// MegaOp. the cpu, timers and the chip8 opcodes stay as they are, I gets a
+// high byte from 01NN NNNN to reach the 16 MB of memory
+use crate::display::{Display, Geometry};
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use alloc::sync::Arc;
+
+pub const MEGACHIP_MEMORY_SIZE: usize = 0x100_0000;
+pub const WIDTH: usize = Geometry::MEGACHIP.width;
+pub const HEIGHT: usize = Geometry::MEGACHIP.height;
+
+// how a sprite pixel goes onto what's drawn already
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Blend {
+    // by the alpha of its palette color
+    #[default]
+    Normal,
+    Alpha25,
+    Alpha50,
+    Add,
+    Multiply,
+}
+
+impl Blend {
+    // 080N, unknown modes draw normally
+    pub fn from_mode(mode: u8) -> Blend {
+        match mode {
+            1 => Blend::Alpha25,
+            2 => Blend::Alpha50,
+            3 => Blend::Add,
+            4 => Blend::Multiply,
+            _ => Blend::Normal,
+        }
+    }
+
+    // the 080N mode that picks it
+    pub fn mode(self) -> u8 {
+        match self {
+            Blend::Normal => 0,
+            Blend::Alpha25 => 1,
+            Blend::Alpha50 => 2,
+            Blend::Add => 3,
+            Blend::Multiply => 4,
+        }
+    }
+
+    fn apply(self, src: u32, dst: u32) -> u32 {
+        let channel = |color: u32, shift: u32| (color >> shift) & 0xFF;
+        let alpha = match self {
+            Blend::Normal => channel(src, 24),
+            Blend::Alpha25 => channel(src, 24) / 4,
+            Blend::Alpha50 => channel(src, 24) / 2,
+            Blend::Add | Blend::Multiply => 0xFF,
+        };
+        let mut out = 0xFF00_0000;
+        for shift in [16, 8, 0] {
+            let (s, d) = (channel(src, shift), channel(dst, shift));
+            let mixed = match self {
+                Blend::Add => (s + d).min(0xFF),
+                Blend::Multiply => s * d / 0xFF,
+                _ => (s * alpha + d * (0xFF - alpha)) / 0xFF,
+            };
+            out |= mixed << shift;
+        }
+        out
+    }
+}
+
+// digitized sound as 060N found it: a 16 bit rate, a 24 bit length, a
+// reserved byte and that many unsigned 8 bit mono samples
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sample {
+    pub rate: u32,
+    pub data: Vec<u8>,
+    // 0600 repeats it until 0700 or the next sample, 0601 plays it once
+    pub looped: bool,
+}
+
+impl Sample {
+    // None if the header or the samples run past the end of memory
+    pub fn read(memory: &[u8], addr: usize, looped: bool) -> Option<Sample> {
+        let header = memory.get(addr..addr + 6)?;
+        let rate = u16::from_be_bytes([header[0], header[1]]) as u32;
+        let len = u32::from_be_bytes([0, header[2], header[3], header[4]]) as usize;
+        let data = memory.get(addr + 6..addr + 6 + len)?.to_vec();
+        Some(Sample { rate, data, looped })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MegaChip {
+    // off until 0011, the machine is a plain chip8 until then
+    pub active: bool,
+    // the high byte of I, 01NN NNNN sets it and ANNN clears it
+    pub i_high: u8,
+    // argb, 02NN loads 1 to NN, 0 is transparent
+    pub palette: [u32; 256],
+    // 03NN and 04NN
+    pub sprite_width: usize,
+    pub sprite_height: usize,
+    // 05NN, how opaque 00E0 shows the screen
+    pub alpha: u8,
+    pub blend: Blend,
+    // 09NN: drawing over a pixel of this palette index sets VF
+    pub collision: u8,
+    // what is being drawn, argb and the palette index of every pixel
+    back: Vec<u32>,
+    indices: Vec<u8>,
+    // what the last 00E0 showed
+    screen: Vec<u32>,
+    // the sample playing, a new Arc each time one starts
+    sample: Option<Arc<Sample>>,
+}
+
+impl Default for MegaChip {
+    fn default() -> Self {
+        MegaChip {
+            active: false,
+            i_high: 0,
+            palette: [0; 256],
+            sprite_width: 0,
+            sprite_height: 0,
+            alpha: 0xFF,
+            blend: Blend::Normal,
+            collision: 0,
+            back: vec![0xFF00_0000; WIDTH * HEIGHT],
+            indices: vec![0; WIDTH * HEIGHT],
+            screen: vec![0xFF00_0000; WIDTH * HEIGHT],
+            sample: None,
+        }
+    }
+}
+
+impl MegaChip {
+    // 02NN: count argb colors from memory into palette entries 1 to count
+    pub fn load_palette(&mut self, colors: &[u8]) {
+        for (entry, color) in self.palette[1..].iter_mut().zip(colors.chunks_exact(4)) {
+            *entry = u32::from_be_bytes([color[0], color[1], color[2], color[3]]);
+        }
+    }
+
+    // a sprite of sprite_width by sprite_height palette indices at x, y,
+    // clipped at the edges. true if it covered a pixel of the collision color
+    pub fn draw(&mut self, x: usize, y: usize, sprite: &[u8]) -> bool {
+        let mut collided = false;
+        for (row, indices) in sprite
+            .chunks(self.sprite_width.max(1))
+            .take(self.sprite_height)
+            .enumerate()
+        {
+            let y = y + row;
+            if y >= HEIGHT {
+                break;
+            }
+            for (column, &index) in indices.iter().enumerate() {
+                let x = x + column;
+                if x >= WIDTH {
+                    break;
+                }
+                if index == 0 {
+                    continue;
+                }
+                let pixel = y * WIDTH + x;
+                collided |= self.collision != 0 && self.indices[pixel] == self.collision;
+                self.indices[pixel] = index;
+                self.back[pixel] = self
+                    .blend
+                    .apply(self.palette[index as usize], self.back[pixel]);
+            }
+        }
+        collided
+    }
+
+    // 00E0: what was drawn goes on screen at the screen alpha, mirrored into
+    // display for frontends without colors, and drawing starts over
+    pub fn present(&mut self, display: &mut Display) {
+        let alpha = self.alpha as u32;
+        for (shown, &drawn) in self.screen.iter_mut().zip(&self.back) {
+            let channel = |shift: u32| (((drawn >> shift) & 0xFF) * alpha / 0xFF) << shift;
+            *shown = 0xFF00_0000 | channel(16) | channel(8) | channel(0);
+        }
+        display.clear();
+        for (pixel, &shown) in self.screen.iter().enumerate() {
+            if shown & 0x00FF_FFFF != 0 {
+                display.set(pixel % WIDTH, pixel / WIDTH, true);
+            }
+        }
+        self.back.fill(0xFF00_0000);
+        self.indices.fill(0);
+    }
+
+    // moves what is being drawn, uncovering black
+    pub fn scroll(&mut self, dx: isize, dy: isize) {
+        let (back, indices) = (self.back.clone(), self.indices.clone());
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                let (from_x, from_y) = (x as isize - dx, y as isize - dy);
+                let pixel = y * WIDTH + x;
+                if (0..WIDTH as isize).contains(&from_x) && (0..HEIGHT as isize).contains(&from_y) {
+                    let from = from_y as usize * WIDTH + from_x as usize;
+                    self.back[pixel] = back[from];
+                    self.indices[pixel] = indices[from];
+                } else {
+                    self.back[pixel] = 0xFF00_0000;
+                    self.indices[pixel] = 0;
+                }
+            }
+        }
+    }
+
+    // argb rows of what the last 00E0 showed, WIDTH by HEIGHT
+    pub fn screen(&self) -> &[u32] {
+        &self.screen
+    }
+
+    // what is being drawn, argb and palette index rows, WIDTH by HEIGHT
+    pub fn drawn(&self) -> (&[u32], &[u8]) {
+        (&self.back, &self.indices)
+    }
+
+    // puts back what drawn and screen returned, as a savestate has them.
+    // false and unchanged unless all three are WIDTH by HEIGHT
+    pub fn set_buffers(&mut self, back: Vec<u32>, indices: Vec<u8>, screen: Vec<u32>) -> bool {
+        let size = WIDTH * HEIGHT;
+        if back.len() != size || indices.len() != size || screen.len() != size {
+            return false;
+        }
+        (self.back, self.indices, self.screen) = (back, indices, screen);
+        true
+    }
+
+    pub fn sample(&self) -> Option<&Arc<Sample>> {
+        self.sample.as_ref()
+    }
+
+    // 060N starts a sample, 0700 stops it with None
+    pub fn set_sample(&mut self, sample: Option<Sample>) {
+        self.sample = sample.map(Arc::new);
+    }
+}
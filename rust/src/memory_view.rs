@@ -0,0 +1,70 @@
+// the memory viewer: a few rows of memory around pc or I copied out of the
+// core with every frame, with how recently each byte was written so FX33,
+// FX55 and friends light up where they land
+use crate::chip8::Chip8State;
+
+pub const MEMORY_VIEW_ROWS: usize = 16;
+pub const MEMORY_VIEW_COLUMNS: usize = 8;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MemoryCenter {
+    #[default]
+    Pc,
+    I,
+}
+
+impl MemoryCenter {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MemoryCenter::Pc => "PC",
+            MemoryCenter::I => "I",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryView {
+    pub center: MemoryCenter,
+    // pc or I, whichever the view is centered on
+    pub addr: u16,
+    // address of bytes[0], the start of a row
+    pub start: u16,
+    pub bytes: Vec<u8>,
+    // per byte, 255 when just written fading to 0, see Heatmap. all 0 unless
+    // the core tracks memory accesses
+    pub written: Vec<u8>,
+}
+
+impl MemoryView {
+    pub fn new(state: &Chip8State, center: MemoryCenter) -> Self {
+        let mut view = MemoryView::default();
+        view.capture(state, center);
+        view
+    }
+
+    // refills the view keeping its buffers, frames are recycled
+    pub fn capture(&mut self, state: &Chip8State, center: MemoryCenter) {
+        let len = MEMORY_VIEW_ROWS * MEMORY_VIEW_COLUMNS;
+        let addr = match center {
+            MemoryCenter::Pc => state.pc,
+            MemoryCenter::I => state.i,
+        };
+        let row = addr as usize / MEMORY_VIEW_COLUMNS * MEMORY_VIEW_COLUMNS;
+        let start = row
+            .saturating_sub(MEMORY_VIEW_ROWS / 2 * MEMORY_VIEW_COLUMNS)
+            .min(state.memory.len().saturating_sub(len));
+        let end = (start + len).min(state.memory.len());
+
+        self.center = center;
+        self.addr = addr;
+        self.start = start as u16;
+        self.bytes.clear();
+        self.bytes.extend_from_slice(&state.memory[start..end]);
+        self.written.clear();
+        self.written.extend((start..end).map(|addr| {
+            state
+                .heatmap()
+                .map_or(0, |heatmap| heatmap.write_heat(addr))
+        }));
+    }
+}
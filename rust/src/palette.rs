@@ -0,0 +1,94 @@
+// screen colors, indexed by the pixel value: 0 background, 1 foreground, and
+// for xo-chip's second plane 2 and 3 (both planes set)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    // "#rrggbb" or "rrggbb"
+    pub fn parse(text: &str) -> Option<Self> {
+        let hex = text.strip_prefix('#').unwrap_or(text);
+        if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        Some(Rgb(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Palette {
+    pub colors: [Rgb; 4],
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::CLASSIC
+    }
+}
+
+impl Palette {
+    pub const CLASSIC: Palette = Palette {
+        colors: [
+            Rgb(0x00, 0x00, 0x00),
+            Rgb(0xFF, 0xFF, 0xFF),
+            Rgb(0xAA, 0xAA, 0xAA),
+            Rgb(0x55, 0x55, 0x55),
+        ],
+    };
+    pub const GREEN_PHOSPHOR: Palette = Palette {
+        colors: [
+            Rgb(0x0A, 0x14, 0x0A),
+            Rgb(0x33, 0xFF, 0x66),
+            Rgb(0x1A, 0x99, 0x3D),
+            Rgb(0xB3, 0xFF, 0xC6),
+        ],
+    };
+    pub const AMBER: Palette = Palette {
+        colors: [
+            Rgb(0x14, 0x0C, 0x00),
+            Rgb(0xFF, 0xB0, 0x00),
+            Rgb(0x99, 0x66, 0x00),
+            Rgb(0xFF, 0xDD, 0x88),
+        ],
+    };
+    // the defaults of the octo ide
+    pub const OCTO: Palette = Palette {
+        colors: [
+            Rgb(0x99, 0x66, 0x00),
+            Rgb(0xFF, 0xCC, 0x00),
+            Rgb(0xFF, 0x66, 0x00),
+            Rgb(0x66, 0x22, 0x00),
+        ],
+    };
+
+    pub const PRESETS: &[(&str, Palette)] = &[
+        ("classic", Palette::CLASSIC),
+        ("green", Palette::GREEN_PHOSPHOR),
+        ("amber", Palette::AMBER),
+        ("octo", Palette::OCTO),
+    ];
+
+    pub fn preset(name: &str) -> Option<Self> {
+        Palette::PRESETS
+            .iter()
+            .find(|(preset, _)| *preset == name)
+            .map(|(_, palette)| *palette)
+    }
+
+    pub fn background(&self) -> Rgb {
+        self.colors[0]
+    }
+
+    pub fn foreground(&self) -> Rgb {
+        self.colors[1]
+    }
+
+    // every color at a third of its brightness, the screen of a program that
+    // is busy computing
+    pub fn dimmed(&self) -> Palette {
+        let dim = |Rgb(r, g, b): Rgb| Rgb(r / 3, g / 3, b / 3);
+        Palette {
+            colors: self.colors.map(dim),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+// ghosting for the flicker of XOR drawn sprites: a pixel going dark fades
+// out over a few frames instead of vanishing, the way the phosphor of an old
+// crt kept glowing. backends keep one next to the display, see
+// Renderer::set_ghosting
+use crate::display::Display;
+use crate::palette::{Palette, Rgb};
+
+// of its brightness a dark pixel keeps each frame, in percent
+pub const DEFAULT_PERSISTENCE: u8 = 60;
+
+pub struct Phosphor {
+    persistence: u8,
+    // brightness of every pixel row by row, 255 lit
+    levels: Vec<u8>,
+    size: (usize, usize),
+}
+
+impl Phosphor {
+    // persistence in percent, at most 99 so every ghost fades out
+    pub fn new(persistence: u8) -> Self {
+        Phosphor {
+            persistence: persistence.min(99),
+            levels: Vec::new(),
+            size: (0, 0),
+        }
+    }
+
+    pub fn persistence(&self) -> u8 {
+        self.persistence
+    }
+
+    // once per frame drawn, lit pixels light up and the others fade a step.
+    // true if any pixel changed, so a display that didn't still needs drawing
+    // while it fades. a new size starts over without ghosts
+    pub fn update(&mut self, display: &Display) -> bool {
+        let size = (display.width(), display.height());
+        if size != self.size {
+            self.size = size;
+            self.levels.clear();
+            self.levels.resize(size.0 * size.1, 0);
+        }
+        let mut changed = false;
+        for ((_, _, on), level) in display.iter_pixels().zip(&mut self.levels) {
+            let next = if on {
+                u8::MAX
+            } else {
+                (*level as u16 * self.persistence as u16 / 100) as u8
+            };
+            changed |= next != *level;
+            *level = next;
+        }
+        changed
+    }
+
+    pub fn levels(&self) -> &[u8] {
+        &self.levels
+    }
+
+    // the color of every pixel row by row, background to foreground by level
+    pub fn colors<'a>(&'a self, palette: &'a Palette) -> impl Iterator<Item = Rgb> + 'a {
+        self.levels
+            .iter()
+            .map(|level| blend(palette.background(), palette.foreground(), *level))
+    }
+}
+
+// level 0 is from, 255 is to
+pub fn blend(from: Rgb, to: Rgb, level: u8) -> Rgb {
+    let mix = |a: u8, b: u8| (a as i32 + (b as i32 - a as i32) * level as i32 / 255) as u8;
+    Rgb(mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+}
@@ -0,0 +1,105 @@
+// minimal png writer for screenshots: 8 bit rgb, no filtering and stored
+// (uncompressed) deflate blocks, which every decoder accepts
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RgbImage {
+    pub width: usize,
+    pub height: usize,
+    // width * height * 3 bytes, row by row
+    pub pixels: Vec<u8>,
+}
+
+impl RgbImage {
+    pub fn new(width: usize, height: usize, background: (u8, u8, u8)) -> Self {
+        let (r, g, b) = background;
+        RgbImage {
+            width,
+            height,
+            pixels: [r, g, b].repeat(width * height),
+        }
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, (r, g, b): (u8, u8, u8)) {
+        if x < self.width && y < self.height {
+            let i = (y * self.width + x) * 3;
+            self.pixels[i..i + 3].copy_from_slice(&[r, g, b]);
+        }
+    }
+
+    pub fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize, color: (u8, u8, u8)) {
+        for py in y..y + h {
+            for px in x..x + w {
+                self.set(px, py, color);
+            }
+        }
+    }
+}
+
+pub fn encode(image: &RgbImage) -> Vec<u8> {
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(image.width as u32).to_be_bytes());
+    header.extend_from_slice(&(image.height as u32).to_be_bytes());
+    // bit depth 8, color type rgb, deflate, no filter, no interlace
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+    write_chunk(&mut png, b"IHDR", &header);
+
+    let mut raw = Vec::with_capacity((image.width * 3 + 1) * image.height);
+    for row in image.pixels.chunks(image.width * 3) {
+        raw.push(0); // filter type none
+        raw.extend_from_slice(row);
+    }
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 0xFFFF;
+    let mut out = vec![0x78, 0x01];
+
+    let blocks = data.len().div_ceil(MAX_BLOCK).max(1);
+    for n in 0..blocks {
+        let block = &data[n * MAX_BLOCK..((n + 1) * MAX_BLOCK).min(data.len())];
+        out.push((n + 1 == blocks) as u8);
+        out.extend_from_slice(&(block.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(!(block.len() as u16)).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
@@ -0,0 +1,205 @@
+// time per opcode class for bench and --profile, only measured while enabled
+// since every instruction pays for two clock reads. also counts executions
+// per opcode handler and per address, for where a program spends its cycles
+use crate::chip8::Instruction;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use alloc::collections::BTreeMap;
+use core::fmt;
+use core::time::Duration;
+
+// rows of the hot address and opcode tables
+const REPORT_ROWS: usize = 12;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OpClass {
+    // reading and decoding the opcode
+    Fetch,
+    // jumps, calls, returns and skips
+    Flow,
+    // register arithmetic and CXNN
+    Alu,
+    // DXYN and 00E0
+    Draw,
+    // I and the FX33/FX55/FX65 family
+    Memory,
+    // timers and the keypad
+    Io,
+}
+
+impl OpClass {
+    pub const ALL: [OpClass; 6] = [
+        OpClass::Fetch,
+        OpClass::Flow,
+        OpClass::Alu,
+        OpClass::Draw,
+        OpClass::Memory,
+        OpClass::Io,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OpClass::Fetch => "fetch",
+            OpClass::Flow => "flow",
+            OpClass::Alu => "alu",
+            OpClass::Draw => "draw",
+            OpClass::Memory => "memory",
+            OpClass::Io => "io",
+        }
+    }
+
+    // the class executing inst falls into, never Fetch
+    pub fn of(inst: &Instruction) -> OpClass {
+        match (inst.indicator(), inst.nn()) {
+            (0x0, 0xC0..=0xDF | 0xE0 | 0xFB | 0xFC | 0xFE | 0xFF) => OpClass::Draw,
+            (0x0..=0x5 | 0x9 | 0xB, _) => OpClass::Flow,
+            (0x6..=0x8 | 0xC, _) => OpClass::Alu,
+            (0xD, _) | (0xF, 0x01) => OpClass::Draw,
+            (0xA, _) => OpClass::Memory,
+            (0xF, 0x00 | 0x1E | 0x29 | 0x33 | 0x55 | 0x65) => OpClass::Memory,
+            _ => OpClass::Io,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Profile {
+    counts: [u64; OpClass::ALL.len()],
+    time: [Duration; OpClass::ALL.len()],
+    // by handler key, see handler
+    opcodes: BTreeMap<u16, u64>,
+    // by pc, grown to the highest address seen
+    addresses: Vec<u64>,
+}
+
+impl Profile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, class: OpClass, elapsed: Duration) {
+        self.counts[class as usize] += 1;
+        self.time[class as usize] += elapsed;
+    }
+
+    pub fn record_address(&mut self, pc: u16, opcode: u16) {
+        *self.opcodes.entry(handler(opcode)).or_default() += 1;
+        let pc = pc as usize;
+        if pc >= self.addresses.len() {
+            self.addresses.resize(pc + 1, 0);
+        }
+        self.addresses[pc] += 1;
+    }
+
+    // (handler key, executions), most executed first
+    pub fn opcode_counts(&self) -> Vec<(u16, u64)> {
+        let mut counts: Vec<_> = self.opcodes.iter().map(|(k, n)| (*k, *n)).collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        counts
+    }
+
+    // (pc, executions), most executed first
+    pub fn hot_addresses(&self) -> Vec<(u16, u64)> {
+        let mut counts: Vec<_> = (self.addresses.iter().enumerate())
+            .filter(|(_, n)| **n > 0)
+            .map(|(pc, n)| (pc as u16, *n))
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        counts
+    }
+
+    pub fn count(&self, class: OpClass) -> u64 {
+        self.counts[class as usize]
+    }
+
+    pub fn time(&self, class: OpClass) -> Duration {
+        self.time[class as usize]
+    }
+
+    pub fn total_time(&self) -> Duration {
+        self.time.iter().sum()
+    }
+}
+
+// one line per class: count, total time, share of the total and time per op
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total_time().as_secs_f64().max(f64::EPSILON);
+        writeln!(f, "class        count      time   share    ns/op")?;
+        for class in OpClass::ALL {
+            let (count, time) = (self.count(class), self.time(class));
+            writeln!(
+                f,
+                "{:<6} {:>11} {:>7.1}ms {:>6.1}% {:>8.1}",
+                class.name(),
+                count,
+                time.as_secs_f64() * 1e3,
+                time.as_secs_f64() / total * 100.0,
+                time.as_nanos() as f64 / count.max(1) as f64,
+            )?;
+        }
+
+        let executed = self.counts[1..].iter().sum::<u64>().max(1) as f64;
+        writeln!(f, "\naddress     count   share")?;
+        for (pc, count) in self.hot_addresses().into_iter().take(REPORT_ROWS) {
+            let share = count as f64 / executed * 100.0;
+            writeln!(f, "{pc:03X}    {count:>11} {share:>6.1}%")?;
+        }
+        writeln!(f, "\nopcode      count   share")?;
+        for (key, count) in self.opcode_counts().into_iter().take(REPORT_ROWS) {
+            let share = count as f64 / executed * 100.0;
+            writeln!(f, "{:<6} {count:>11} {share:>6.1}%", pattern(key))?;
+        }
+        Ok(())
+    }
+}
+
+// the opcode handler an instruction lands in, as the opcode with its operand
+// nibbles cleared: 8XY4 is 0x8004, FX33 is 0xF033, DXYN is 0xD000. opcodes
+// no chip8 variant defines are 0xFFF in their family, 8XYF is 0x8FFF
+pub fn handler(opcode: u16) -> u16 {
+    let family = opcode & 0xF000;
+    let (n, nn) = (opcode & 0x000F, opcode & 0x00FF);
+    match family >> 12 {
+        0x0 => match opcode {
+            0x00C0..=0x00CF => 0x00C0,
+            0x00D0..=0x00DF => 0x00D0,
+            0x00E0 | 0x00EE | 0x00FB | 0x00FC | 0x00FE | 0x00FF => opcode,
+            _ => 0x0000,
+        },
+        0x5 | 0x9 if n == 0 => family,
+        0x8 if matches!(n, 0x0..=0x7 | 0xE) => family | n,
+        0xE if matches!(nn, 0x9E | 0xA1) => family | nn,
+        0xF if FX_HANDLERS.contains(&(nn as u8)) => family | nn,
+        0x5 | 0x8 | 0x9 | 0xE | 0xF => family | 0x0FFF,
+        _ => family,
+    }
+}
+
+// every FXNN of chip8, schip and xo-chip
+const FX_HANDLERS: &[u8] = &[
+    0x00, 0x01, 0x02, 0x07, 0x0A, 0x15, 0x18, 0x1E, 0x29, 0x30, 0x33, 0x3A, 0x55, 0x65, 0x75, 0x85,
+];
+
+// a handler key as the opcode pattern it stands for, 0x8004 is 8XY4 and
+// undefined ones are ???? in their family, 8??? for 0x8FFF
+pub fn pattern(key: u16) -> String {
+    let family = key >> 12;
+    if family != 0 && key & 0x0FFF == 0x0FFF {
+        return format!("{family:X}???");
+    }
+    match family {
+        0x0 => match key {
+            0x00C0 => "00CN".to_owned(),
+            0x00D0 => "00DN".to_owned(),
+            0x0000 => "0NNN".to_owned(),
+            _ => format!("{key:04X}"),
+        },
+        0x1 | 0x2 | 0xA | 0xB => format!("{family:X}NNN"),
+        0x3 | 0x4 | 0x6 | 0x7 | 0xC => format!("{family:X}XNN"),
+        0x5 | 0x9 => format!("{family:X}XY0"),
+        0x8 => format!("8XY{:X}", key & 0xF),
+        0xD => "DXYN".to_owned(),
+        _ => format!("{family:X}X{:02X}", key & 0xFF),
+    }
+}
@@ -0,0 +1,66 @@
+// the roms loaded last, newest first, kept in a plain text state file with
+// one absolute path per line so they survive restarts
+use crate::dirs::state_dir;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const MAX_RECENT: usize = 8;
+
+pub struct RecentRoms {
+    file: PathBuf,
+    paths: Vec<PathBuf>,
+}
+
+impl RecentRoms {
+    // a missing file is an empty list, the file appears with the first save
+    pub fn load(file: impl Into<PathBuf>) -> io::Result<Self> {
+        let file = file.into();
+        let text = match fs::read_to_string(&file) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+        let mut paths = Vec::new();
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let path = PathBuf::from(line);
+            if !paths.contains(&path) && paths.len() < MAX_RECENT {
+                paths.push(path);
+            }
+        }
+        Ok(RecentRoms { file, paths })
+    }
+
+    // the recent file of state_dir
+    pub fn default_file() -> Option<PathBuf> {
+        state_dir("recent")
+    }
+
+    pub fn file(&self) -> &Path {
+        &self.file
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    // moves path to the front, dropping the oldest past MAX_RECENT
+    pub fn push(&mut self, path: &Path) {
+        let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        self.paths.retain(|recent| *recent != path);
+        self.paths.insert(0, path);
+        self.paths.truncate(MAX_RECENT);
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(dir) = self.file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut text = String::new();
+        for path in &self.paths {
+            text.push_str(&path.to_string_lossy());
+            text.push('\n');
+        }
+        fs::write(&self.file, text)
+    }
+}
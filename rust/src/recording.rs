@@ -0,0 +1,94 @@
+// gameplay recordings made from the emulated frames themselves, not the
+// window, so they work the same without a frontend. the gif has the
+// display's own resolution and every frame lasts as many 60Hz frames as the
+// screen stayed unchanged
+use crate::display::Display;
+use crate::gif::{self, GifFrame};
+use crate::palette::Palette;
+
+// ten minutes of a screen that changes every other frame
+pub const MAX_FRAMES: usize = 10 * 60 * 30;
+// gif delays are hundredths of a second and browsers stretch anything under
+// 2 to 10, so a screen shown for a single 60Hz frame merges into the next
+const MIN_FRAMES_SHOWN: u64 = 2;
+
+#[derive(Default)]
+pub struct GifRecorder {
+    // each screen with the emulated frame it appeared on
+    frames: Vec<(Display, u64)>,
+    // the emulated frame of the latest push
+    end: u64,
+}
+
+impl GifRecorder {
+    pub fn new() -> Self {
+        GifRecorder::default()
+    }
+
+    // the display after emulated frame number frame, see EmulatedTime
+    pub fn push(&mut self, display: &Display, frame: u64) {
+        self.end = frame;
+        let full = self.frames.len() == MAX_FRAMES;
+        match self.frames.last_mut() {
+            Some((last, _)) if last == display => {}
+            Some((last, since)) if frame.saturating_sub(*since) < MIN_FRAMES_SHOWN => {
+                last.clone_from(display);
+            }
+            _ if full => {}
+            _ => self.frames.push((display.clone(), frame)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    // emulated frames from the first push to the last
+    pub fn duration_frames(&self) -> u64 {
+        self.frames
+            .first()
+            .map_or(0, |(_, start)| self.end.saturating_sub(*start))
+    }
+
+    // one gif pixel per chip8 pixel, a resolution switch during the
+    // recording scales the smaller screens up to the largest
+    pub fn encode(&self, palette: &Palette) -> Vec<u8> {
+        let width = self
+            .frames
+            .iter()
+            .map(|(d, _)| d.width())
+            .max()
+            .unwrap_or(64);
+        let height = self
+            .frames
+            .iter()
+            .map(|(d, _)| d.height())
+            .max()
+            .unwrap_or(32);
+        let start = self.frames.first().map_or(0, |(_, start)| *start);
+        let hundredths = |frame: u64| (frame - start) * 100 / 60;
+
+        let mut frames = Vec::with_capacity(self.frames.len());
+        for (n, (display, shown)) in self.frames.iter().enumerate() {
+            let until = self
+                .frames
+                .get(n + 1)
+                .map_or(self.end + 1, |(_, next)| *next);
+            let (sx, sy) = (width / display.width(), height / display.height());
+            let mut pixels = vec![0; width * height];
+            for (y, row) in pixels.chunks_mut(width).enumerate() {
+                for (x, pixel) in row.iter_mut().enumerate() {
+                    let (dx, dy) = (x / sx.max(1), y / sy.max(1));
+                    if dx < display.width() && dy < display.height() {
+                        *pixel = display.pixel(dx, dy);
+                    }
+                }
+            }
+            frames.push(GifFrame {
+                pixels,
+                delay: (hundredths(until) - hundredths(*shown)).clamp(1, u16::MAX as u64) as u16,
+            });
+        }
+        gif::encode(width, height, palette, &frames)
+    }
+}
@@ -0,0 +1,169 @@
+// a line based debug protocol over tcp, so an editor or a script can attach
+// to a running emulator. every request is one line, every reply is the
+// lines it shows and then "ok" or "err MESSAGE":
+//
+//     pause              stop the cpu
+//     continue           run again
+//     step [N]           N instructions of a paused machine, 1 without
+//     frame              one frame of a paused machine
+//     next draw|sound    a paused machine at full speed until a DXYN or FX18
+//                        ran, then paused again
+//     break ADDR         a pause breakpoint, delete ADDR removes it
+//     breaks             every breakpoint and its hits
+//     status             run state, pc and time
+//
+// and the console's peek, poke, regs and set. numbers are hex
+use crate::breakpoint::BreakAction;
+use crate::chip8::{Chip8State, RUN_TO_FRAMES, RunState, RunTarget};
+use crate::console::{DebugCommand, hex};
+use crate::line_server::{self, LineServer};
+use std::fmt::Write as _;
+use std::io;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::mpsc::TryIter;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RemoteCommand {
+    Pause,
+    Continue,
+    Step(u16),
+    Frame,
+    Next(RunTarget),
+    Break(u16),
+    Delete(u16),
+    Breaks,
+    Status,
+    Debug(DebugCommand),
+}
+
+impl RemoteCommand {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        Ok(match words.as_slice() {
+            ["pause"] => RemoteCommand::Pause,
+            ["continue"] => RemoteCommand::Continue,
+            ["step"] => RemoteCommand::Step(1),
+            ["step", count] => RemoteCommand::Step(hex(count)?),
+            ["frame"] => RemoteCommand::Frame,
+            ["next", "draw"] => RemoteCommand::Next(RunTarget::Draw),
+            ["next", "sound"] => RemoteCommand::Next(RunTarget::Sound),
+            ["break", addr] => RemoteCommand::Break(hex(addr)?),
+            ["delete", addr] => RemoteCommand::Delete(hex(addr)?),
+            ["breaks"] => RemoteCommand::Breaks,
+            ["status"] => RemoteCommand::Status,
+            _ => RemoteCommand::Debug(DebugCommand::parse(line)?),
+        })
+    }
+
+    // the whole reply, ending in its ok or err line
+    pub fn apply(&self, state: &mut Chip8State) -> String {
+        match self.run(state) {
+            Ok(shown) if shown.is_empty() => "ok\n".to_owned(),
+            Ok(shown) => format!("{shown}\nok\n"),
+            Err(err) => format!("err {err}\n"),
+        }
+    }
+
+    fn run(&self, state: &mut Chip8State) -> Result<String, String> {
+        let paused = || "pause the machine first".to_owned();
+        match self {
+            RemoteCommand::Pause => state.set_paused(true),
+            RemoteCommand::Continue => state.set_paused(false),
+            RemoteCommand::Step(count) => {
+                if state.run_state() != RunState::Paused {
+                    return Err(paused());
+                }
+                for _ in 0..*count {
+                    state.step_instruction().map_err(|err| err.to_string())?;
+                }
+                return Ok(status(state));
+            }
+            RemoteCommand::Frame => {
+                if state.run_state() != RunState::Paused {
+                    return Err(paused());
+                }
+                state.step_frame().map_err(|err| err.to_string())?;
+                return Ok(status(state));
+            }
+            RemoteCommand::Next(target) => {
+                if state.run_state() != RunState::Paused {
+                    return Err(paused());
+                }
+                let found = state
+                    .run_to(*target, RUN_TO_FRAMES)
+                    .map_err(|err| err.to_string())?;
+                if !found {
+                    let what = match target {
+                        RunTarget::Draw => "draw",
+                        RunTarget::Sound => "sound",
+                    };
+                    return Err(format!("stopped before a {what}, {}", status(state)));
+                }
+                return Ok(status(state));
+            }
+            RemoteCommand::Break(addr) => state.breakpoints.add(*addr, BreakAction::Pause),
+            RemoteCommand::Delete(addr) => {
+                if state.breakpoints.get(*addr).is_none() {
+                    return Err(format!("no breakpoint at {addr:03X}"));
+                }
+                state.breakpoints.remove(*addr);
+            }
+            RemoteCommand::Breaks => {
+                let mut out = String::new();
+                for breakpoint in state.breakpoints.iter() {
+                    if !out.is_empty() {
+                        out.push('\n');
+                    }
+                    let _ = write!(
+                        out,
+                        "{:03X} {:?} {} hits",
+                        breakpoint.addr, breakpoint.action, breakpoint.hits
+                    );
+                }
+                return Ok(out.to_ascii_lowercase());
+            }
+            RemoteCommand::Status => return Ok(status(state)),
+            RemoteCommand::Debug(command) => return command.apply(state),
+        }
+        Ok(String::new())
+    }
+}
+
+fn status(state: &Chip8State) -> String {
+    let run_state = match state.run_state() {
+        RunState::Running => "running",
+        RunState::Paused => "paused",
+        RunState::Halted => "halted",
+        RunState::WaitingForKey { .. } => "waiting for a key",
+    };
+    let time = state.emulated_time();
+    format!(
+        "{run_state} pc={:03X} frame={} cycle={}",
+        state.pc, time.frames, time.cycles
+    )
+}
+
+pub type Request = line_server::Request<RemoteCommand>;
+
+// a LineServer over tcp
+pub struct RemoteServer {
+    addr: SocketAddr,
+    server: LineServer<RemoteCommand>,
+}
+
+impl RemoteServer {
+    pub fn bind(addr: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let server = LineServer::spawn(listener, RemoteCommand::parse);
+        Ok(RemoteServer { addr, server })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn requests(&self) -> TryIter<'_, Request> {
+        self.server.requests()
+    }
+}
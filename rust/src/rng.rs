@@ -0,0 +1,93 @@
+// CXNN's random source, every algorithm is fully reproducible from its seed
+// and keeps all of its state in Rng, so copying it copies the stream
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rng {
+    algorithm: RngAlgorithm,
+    state: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RngAlgorithm {
+    // xorshift32, evenly spread with no visible pattern
+    #[default]
+    Xorshift,
+    // the classic c library lcg, its low bits repeat quickly so only the high
+    // byte is used
+    Lcg,
+    // vip inspired: the interpreter added a byte of its own code, picked by a
+    // pointer that moved one step per call, to the previous result. the vip
+    // code isn't in this tree, a fixed mix of the pointer stands in for it, so
+    // the stream repeats every 512 calls and consecutive values are correlated
+    Vip,
+}
+
+impl RngAlgorithm {
+    pub const NAMES: &[&str] = &["xorshift", "lcg", "vip"];
+
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "xorshift" => Some(RngAlgorithm::Xorshift),
+            "lcg" => Some(RngAlgorithm::Lcg),
+            "vip" => Some(RngAlgorithm::Vip),
+            _ => None,
+        }
+    }
+}
+
+impl Rng {
+    pub fn new(seed: u32) -> Self {
+        Rng::with_algorithm(RngAlgorithm::default(), seed)
+    }
+
+    pub fn with_algorithm(algorithm: RngAlgorithm, seed: u32) -> Self {
+        // xorshift gets stuck on zero
+        let state = if algorithm == RngAlgorithm::Xorshift && seed == 0 {
+            0x2545_F491
+        } else {
+            seed
+        };
+        Rng { algorithm, state }
+    }
+
+    pub fn algorithm(&self) -> RngAlgorithm {
+        self.algorithm
+    }
+
+    // with algorithm, all with_algorithm needs to continue the stream
+    pub fn state(&self) -> u32 {
+        self.state
+    }
+
+    // switches algorithms, seeding the new one from the current state so a
+    // seeded machine stays reproducible
+    pub fn set_algorithm(&mut self, algorithm: RngAlgorithm) {
+        if algorithm != self.algorithm {
+            *self = Rng::with_algorithm(algorithm, self.state);
+        }
+    }
+
+    pub fn next_u8(&mut self) -> u8 {
+        match self.algorithm {
+            RngAlgorithm::Xorshift => {
+                let mut x = self.state;
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                self.state = x;
+                (x >> 24) as u8
+            }
+            RngAlgorithm::Lcg => {
+                self.state = self.state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (self.state >> 16) as u8
+            }
+            RngAlgorithm::Vip => {
+                // low byte the pointer, the byte above it the last result
+                let pointer = (self.state as u8).wrapping_add(1);
+                let code = pointer.wrapping_mul(0x6D).rotate_left(3) ^ 0xA5;
+                let value = ((self.state >> 8) as u8).wrapping_add(code);
+                self.state = (self.state & 0xFFFF_0000) | ((value as u32) << 8) | pointer as u32;
+                value
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use crate::chip8::{EXTENDED_MEMORY_SIZE, MEMORY_SIZE, PROGRAM_START};
+use crate::error::Chip8Error;
+use crate::megachip::MEGACHIP_MEMORY_SIZE;
+#[cfg(feature = "std")]
+use std::fs;
+#[cfg(feature = "std")]
+use std::io::Read;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+pub const MAX_ROM_SIZE: usize = MEMORY_SIZE - PROGRAM_START;
+// with Chip8State::set_extended_memory
+pub const MAX_EXTENDED_ROM_SIZE: usize = EXTENDED_MEMORY_SIZE - PROGRAM_START;
+// with Chip8State::set_megachip
+pub const MAX_MEGACHIP_ROM_SIZE: usize = MEGACHIP_MEMORY_SIZE - PROGRAM_START;
+
+// megachip roms switch their screen on first thing
+pub fn is_megachip(rom: &[u8]) -> bool {
+    rom.starts_with(&[0x00, 0x11])
+}
+
+// two page hires chip8 roms jump over the vip code that patched the
+// interpreter for 64x64, see Chip8State::load
+pub fn is_hires(rom: &[u8]) -> bool {
+    rom.starts_with(&[0x12, 0x60])
+}
+
+// .ch8 in any case, what the watcher and the rom browser pick up
+#[cfg(feature = "std")]
+pub fn is_rom_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ch8"))
+}
+
+#[cfg(feature = "std")]
+pub fn load_rom(path: impl AsRef<Path>) -> Result<Vec<u8>, Chip8Error> {
+    load_rom_with_limit(path, MAX_ROM_SIZE)
+}
+
+#[cfg(feature = "std")]
+pub fn load_rom_with_limit(path: impl AsRef<Path>, max: usize) -> Result<Vec<u8>, Chip8Error> {
+    let path = path.as_ref();
+
+    // don't read a huge file just to reject it
+    let size = fs::metadata(path)?.len();
+    if size > max as u64 {
+        return Err(Chip8Error::RomTooLarge {
+            size: size as usize,
+            max,
+        });
+    }
+
+    let bytes = fs::read(path)?;
+    validate_rom(&bytes, max)?;
+    Ok(bytes)
+}
+
+// what run fetches instead of opening, see fetch
+pub fn is_url(text: &str) -> bool {
+    text.starts_with("http://") || text.starts_with("https://")
+}
+
+// a rom piped in, "chip8 run -" reads stdin. stops one byte past max so a
+// stream that never ends is still rejected
+#[cfg(feature = "std")]
+pub fn read_rom(reader: impl Read, max: usize) -> Result<Vec<u8>, Chip8Error> {
+    let mut bytes = Vec::new();
+    reader.take(max as u64 + 1).read_to_end(&mut bytes)?;
+    validate_rom(&bytes, max)?;
+    Ok(bytes)
+}
+
+// a rom has to be non empty and fit between PROGRAM_START and the end of memory
+pub fn validate_rom(bytes: &[u8], max: usize) -> Result<(), Chip8Error> {
+    if bytes.is_empty() {
+        return Err(Chip8Error::EmptyRom);
+    }
+    if bytes.len() > max {
+        return Err(Chip8Error::RomTooLarge {
+            size: bytes.len(),
+            max,
+        });
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Platform {
+    Chip8,
+    SuperChip,
+    XoChip,
+}
+
+impl Platform {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Platform::Chip8 => "chip8",
+            Platform::SuperChip => "superchip",
+            Platform::XoChip => "xo-chip",
+        }
+    }
+}
+
+// a guess from the opcodes of the rom, the newest platform any of them
+// belongs to. data mixed into the code decodes too and can make it guess high
+pub fn detect_platform(rom: &[u8]) -> Platform {
+    rom.chunks_exact(2)
+        .map(|word| match u16::from_be_bytes([word[0], word[1]]) {
+            0xF000 | 0xF002 => Platform::XoChip,
+            op if op & 0xFFF0 == 0x00D0 => Platform::XoChip,
+            op if op & 0xF00E == 0x5002 => Platform::XoChip,
+            op if op & 0xF0FF == 0xF001 || op & 0xF0FF == 0xF03A => Platform::XoChip,
+            op if op & 0xFFF0 == 0x00C0 || (0x00FB..=0x00FF).contains(&op) => Platform::SuperChip,
+            op if op & 0xF00F == 0xD000 => Platform::SuperChip,
+            op if matches!(op & 0xF0FF, 0xF030 | 0xF075 | 0xF085) => Platform::SuperChip,
+            _ => Platform::Chip8,
+        })
+        .max()
+        .unwrap_or(Platform::Chip8)
+}
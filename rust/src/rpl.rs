@@ -0,0 +1,103 @@
+// the superchip's rpl user flags, what FX75 saves and FX85 loads. they are
+// kept per rom, in a file named by its sha1, so high scores survive a
+// restart. without a directory, or once writing to it failed, they only
+// last as long as the process, as they always do without std
+#[cfg(feature = "std")]
+use crate::dirs::state_dir;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::sha1::sha1_hex;
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+
+pub const RPL_FLAGS: usize = 16;
+
+pub type RplFlags = [u8; RPL_FLAGS];
+
+#[derive(Clone, Debug, Default)]
+pub struct RplStore {
+    #[cfg(feature = "std")]
+    dir: Option<PathBuf>,
+    // by rom sha1, what this process saved
+    memory: BTreeMap<String, RplFlags>,
+    // why the directory was given up on, until take_error
+    error: Option<String>,
+}
+
+impl RplStore {
+    // only in memory
+    pub fn new() -> Self {
+        RplStore::default()
+    }
+
+    #[cfg(feature = "std")]
+    pub fn in_dir(dir: impl Into<PathBuf>) -> Self {
+        RplStore {
+            dir: Some(dir.into()),
+            ..RplStore::default()
+        }
+    }
+
+    // the rpl directory of state_dir
+    #[cfg(feature = "std")]
+    pub fn default_dir() -> Option<PathBuf> {
+        state_dir("rpl")
+    }
+
+    #[cfg(feature = "std")]
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+
+    pub fn key(rom: &[u8]) -> String {
+        sha1_hex(rom)
+    }
+
+    // all zero for a rom that never saved, or whose file is unreadable
+    pub fn load(&self, key: &str) -> RplFlags {
+        if let Some(flags) = self.memory.get(key) {
+            return *flags;
+        }
+        #[cfg(feature = "std")]
+        if let Some(flags) = self.load_file(key) {
+            return flags;
+        }
+        [0; RPL_FLAGS]
+    }
+
+    #[cfg(feature = "std")]
+    fn load_file(&self, key: &str) -> Option<RplFlags> {
+        let bytes = std::fs::read(self.dir.as_ref()?.join(key)).ok()?;
+        let mut flags = [0; RPL_FLAGS];
+        let len = bytes.len().min(RPL_FLAGS);
+        flags[..len].copy_from_slice(&bytes[..len]);
+        Some(flags)
+    }
+
+    pub fn save(&mut self, key: &str, flags: &RplFlags) {
+        self.memory.insert(key.to_owned(), *flags);
+        #[cfg(feature = "std")]
+        self.save_file(key, flags);
+    }
+
+    #[cfg(feature = "std")]
+    fn save_file(&mut self, key: &str, flags: &RplFlags) {
+        let Some(dir) = &self.dir else {
+            return;
+        };
+        let written =
+            std::fs::create_dir_all(dir).and_then(|()| std::fs::write(dir.join(key), flags));
+        if let Err(err) = written {
+            self.error = Some(format!(
+                "{}: {err}, rpl flags kept in memory only",
+                dir.display()
+            ));
+            self.dir = None;
+        }
+    }
+
+    pub fn take_error(&mut self) -> Option<String> {
+        self.error.take()
+    }
+}
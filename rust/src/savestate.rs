@@ -0,0 +1,367 @@
+// versioned machine snapshots. a format change bumps VERSION and appends a
+// migration from the previous version to MIGRATIONS instead of dropping
+// support, so a state saved by any release keeps loading. the fixtures in
+// tests/savestates pin every version that ever shipped
+use crate::chip8::{Chip8State, EXTENDED_MEMORY_SIZE, EmulatedTime, PROGRAM_START, RunState};
+use crate::display::{Display, Geometry, PLANES};
+use crate::error::Chip8Error;
+use crate::megachip::{self, Blend, MEGACHIP_MEMORY_SIZE, MegaChip, Sample};
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::rng::{Rng, RngAlgorithm};
+
+const MAGIC: &[u8; 4] = b"C8SS";
+pub const VERSION: u16 = 3;
+
+// MIGRATIONS[n] turns the body of version n + 1 into the body of version
+// n + 2, everything after the header
+type Migration = fn(&[u8]) -> Result<Vec<u8>, Chip8Error>;
+const MIGRATIONS: &[Migration] = &[v1_to_v2, v2_to_v3];
+// a VERSION bump without its migration doesn't build
+const _: () = assert!(MIGRATIONS.len() == VERSION as usize - 1);
+
+// 2 added the megachip state, a v1 machine had none
+fn v1_to_v2(body: &[u8]) -> Result<Vec<u8>, Chip8Error> {
+    let mut body = body.to_vec();
+    body.push(0);
+    Ok(body)
+}
+
+// 3 added xo-chip's second plane, a v2 machine drew into the first only
+fn v2_to_v3(body: &[u8]) -> Result<Vec<u8>, Chip8Error> {
+    let mut input = Reader(body);
+    let geometry = Geometry::new(input.u16()? as usize, input.u16()? as usize);
+    let mut body = body.to_vec();
+    body.push(1);
+    body.resize(
+        body.len() + geometry.width.div_ceil(64) * geometry.height * 8,
+        0,
+    );
+    Ok(body)
+}
+
+// everything a program can observe, the rest of Chip8State (settings,
+// profiling, time owed to run_for) belongs to the session, not the machine
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub display: Display,
+    pub memory: Vec<u8>,
+    pub v: Vec<u8>,
+    pub pc: u16,
+    pub i: u16,
+    pub stack: Vec<u16>,
+    pub delay_timer: u8,
+    pub sound_timer: u8,
+    pub rng: Rng,
+    pub run_state: RunState,
+    // what a paused machine resumes to
+    pub resume_state: RunState,
+    // FX0A bookkeeping, see Chip8State
+    pub wait_held: u16,
+    pub wait_pressed: Option<u8>,
+    pub polled_keys: u16,
+    pub time: EmulatedTime,
+    // with Chip8State::set_megachip, its palette, colors and sample
+    pub megachip: Option<MegaChip>,
+}
+
+pub fn save(state: &Chip8State) -> Vec<u8> {
+    encode(&state.snapshot())
+}
+
+// replaces the machine in state, keeping its settings
+pub fn load(state: &mut Chip8State, bytes: &[u8]) -> Result<(), Chip8Error> {
+    state.restore(decode(bytes)?);
+    Ok(())
+}
+
+pub fn encode(snapshot: &Snapshot) -> Vec<u8> {
+    let mut out = Writer(MAGIC.to_vec());
+    out.u16(VERSION);
+
+    let geometry = snapshot.display.geometry();
+    out.u16(geometry.width as u16);
+    out.u16(geometry.height as u16);
+    for word in snapshot.display.words() {
+        out.u64(*word);
+    }
+    out.u32(snapshot.memory.len() as u32);
+    out.0.extend_from_slice(&snapshot.memory);
+    out.u8(snapshot.v.len() as u8);
+    out.0.extend_from_slice(&snapshot.v);
+    out.u16(snapshot.pc);
+    out.u16(snapshot.i);
+    out.u8(snapshot.stack.len() as u8);
+    for address in &snapshot.stack {
+        out.u16(*address);
+    }
+    out.u8(snapshot.delay_timer);
+    out.u8(snapshot.sound_timer);
+    out.u8(match snapshot.rng.algorithm() {
+        RngAlgorithm::Xorshift => 0,
+        RngAlgorithm::Lcg => 1,
+        RngAlgorithm::Vip => 2,
+    });
+    out.u32(snapshot.rng.state());
+    out.run_state(snapshot.run_state);
+    out.run_state(snapshot.resume_state);
+    out.u16(snapshot.wait_held);
+    out.u8(snapshot.wait_pressed.unwrap_or(NO_KEY));
+    out.u16(snapshot.polled_keys);
+    out.u64(snapshot.time.frames);
+    out.u64(snapshot.time.cycles);
+    match &snapshot.megachip {
+        Some(megachip) => {
+            out.u8(1);
+            out.megachip(megachip);
+        }
+        None => out.u8(0),
+    }
+    // the first plane went in with the geometry
+    out.u8(snapshot.display.selected_planes());
+    for plane in 1..PLANES {
+        for word in snapshot.display.plane_words(plane) {
+            out.u64(*word);
+        }
+    }
+    out.0
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Snapshot, Chip8Error> {
+    let invalid = |reason| Chip8Error::InvalidSaveState { reason };
+    if bytes.len() < 6 || &bytes[..4] != MAGIC {
+        return Err(invalid("not a savestate"));
+    }
+    let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+    if version == 0 || version > VERSION {
+        return Err(Chip8Error::UnsupportedSaveState { version });
+    }
+
+    let mut body = bytes[6..].to_vec();
+    for migrate in &MIGRATIONS[version as usize - 1..] {
+        body = migrate(&body)?;
+    }
+
+    let mut input = Reader(&body);
+    let geometry = Geometry::new(input.u16()? as usize, input.u16()? as usize);
+    let plane_len = geometry.width.div_ceil(64) * geometry.height;
+    let first = (0..plane_len)
+        .map(|_| input.u64())
+        .collect::<Result<_, _>>()?;
+    let memory_len = input.u32()? as usize;
+    let memory = input.bytes(memory_len)?.to_vec();
+    let v_len = input.u8()? as usize;
+    let v = input.bytes(v_len)?.to_vec();
+    let pc = input.u16()?;
+    let i = input.u16()?;
+    let stack_len = input.u8()?;
+    let stack = (0..stack_len)
+        .map(|_| input.u16())
+        .collect::<Result<_, _>>()?;
+    let delay_timer = input.u8()?;
+    let sound_timer = input.u8()?;
+    let algorithm = match input.u8()? {
+        0 => RngAlgorithm::Xorshift,
+        1 => RngAlgorithm::Lcg,
+        2 => RngAlgorithm::Vip,
+        _ => return Err(invalid("unknown rng")),
+    };
+    let rng = Rng::with_algorithm(algorithm, input.u32()?);
+    let run_state = input.run_state()?;
+    let resume_state = input.run_state()?;
+    let wait_held = input.u16()?;
+    let wait_pressed = Some(input.u8()?).filter(|key| *key != NO_KEY);
+    let polled_keys = input.u16()?;
+    let time = EmulatedTime {
+        frames: input.u64()?,
+        cycles: input.u64()?,
+    };
+    let megachip = match input.u8()? {
+        0 => None,
+        1 => Some(input.megachip()?),
+        _ => return Err(invalid("bad megachip")),
+    };
+    let selected = input.u8()?;
+    let second = (0..plane_len)
+        .map(|_| input.u64())
+        .collect::<Result<_, _>>()?;
+    let display =
+        Display::from_planes(geometry, [first, second], selected).ok_or(invalid("bad display"))?;
+    if !input.0.is_empty() {
+        return Err(invalid("trailing bytes"));
+    }
+    if v.len() != 16
+        // any size set_memory_size or set_megachip takes
+        || !((PROGRAM_START..=EXTENDED_MEMORY_SIZE).contains(&memory.len())
+            || memory.len() == MEGACHIP_MEMORY_SIZE)
+        || wait_pressed.is_some_and(|key| key > 0xF)
+    {
+        return Err(invalid("bad registers"));
+    }
+
+    Ok(Snapshot {
+        display,
+        memory,
+        v,
+        pc,
+        i,
+        stack,
+        delay_timer,
+        sound_timer,
+        rng,
+        run_state,
+        resume_state,
+        wait_held,
+        wait_pressed,
+        polled_keys,
+        time,
+        megachip,
+    })
+}
+
+const NO_KEY: u8 = 0xFF;
+
+// little endian throughout
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    // tag, then the FX0A register for WaitingForKey
+    fn run_state(&mut self, run_state: RunState) {
+        let (tag, register) = match run_state {
+            RunState::Running => (0, 0),
+            RunState::Halted => (1, 0),
+            RunState::WaitingForKey { register } => (2, register),
+            RunState::Paused => (3, 0),
+        };
+        self.u8(tag);
+        self.u8(register);
+    }
+
+    fn megachip(&mut self, megachip: &MegaChip) {
+        self.u8(megachip.active as u8);
+        self.u8(megachip.i_high);
+        for color in megachip.palette {
+            self.u32(color);
+        }
+        self.u16(megachip.sprite_width as u16);
+        self.u16(megachip.sprite_height as u16);
+        self.u8(megachip.alpha);
+        self.u8(megachip.blend.mode());
+        self.u8(megachip.collision);
+        let (back, indices) = megachip.drawn();
+        for color in back {
+            self.u32(*color);
+        }
+        self.0.extend_from_slice(indices);
+        for color in megachip.screen() {
+            self.u32(*color);
+        }
+        match megachip.sample() {
+            Some(sample) => {
+                self.u8(1 + sample.looped as u8);
+                self.u32(sample.rate);
+                self.u32(sample.data.len() as u32);
+                self.0.extend_from_slice(&sample.data);
+            }
+            None => self.u8(0),
+        }
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn bytes(&mut self, len: usize) -> Result<&[u8], Chip8Error> {
+        if self.0.len() < len {
+            return Err(Chip8Error::InvalidSaveState {
+                reason: "truncated",
+            });
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, Chip8Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, Chip8Error> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, Chip8Error> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, Chip8Error> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    fn run_state(&mut self) -> Result<RunState, Chip8Error> {
+        let (tag, register) = (self.u8()?, self.u8()?);
+        match tag {
+            0 => Ok(RunState::Running),
+            1 => Ok(RunState::Halted),
+            2 if register < 16 => Ok(RunState::WaitingForKey { register }),
+            3 => Ok(RunState::Paused),
+            _ => Err(Chip8Error::InvalidSaveState {
+                reason: "unknown run state",
+            }),
+        }
+    }
+
+    fn megachip(&mut self) -> Result<MegaChip, Chip8Error> {
+        let invalid = |reason| Chip8Error::InvalidSaveState { reason };
+        let size = megachip::WIDTH * megachip::HEIGHT;
+        let mut megachip = MegaChip::default();
+        megachip.active = self.u8()? != 0;
+        megachip.i_high = self.u8()?;
+        for color in &mut megachip.palette {
+            *color = self.u32()?;
+        }
+        megachip.sprite_width = self.u16()? as usize;
+        megachip.sprite_height = self.u16()? as usize;
+        megachip.alpha = self.u8()?;
+        megachip.blend = Blend::from_mode(self.u8()?);
+        megachip.collision = self.u8()?;
+        let back = (0..size).map(|_| self.u32()).collect::<Result<_, _>>()?;
+        let indices = self.bytes(size)?.to_vec();
+        let screen = (0..size).map(|_| self.u32()).collect::<Result<_, _>>()?;
+        if !megachip.set_buffers(back, indices, screen) {
+            return Err(invalid("bad megachip"));
+        }
+        let sample = match self.u8()? {
+            0 => None,
+            tag @ (1 | 2) => {
+                let rate = self.u32()?;
+                let len = self.u32()? as usize;
+                let data = self.bytes(len)?.to_vec();
+                Some(Sample {
+                    rate,
+                    data,
+                    looped: tag == 2,
+                })
+            }
+            _ => return Err(invalid("bad sample")),
+        };
+        megachip.set_sample(sample);
+        Ok(megachip)
+    }
+}
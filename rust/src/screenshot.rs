@@ -0,0 +1,22 @@
+// the framebuffer as a png, every chip8 pixel a scale by scale square in the
+// colors of the palette the window shows
+use crate::display::Display;
+use crate::palette::{Palette, Rgb};
+use crate::png::{self, RgbImage};
+
+// 640 pixels across for a lores screen, the width of the default window
+pub const DEFAULT_SCALE: usize = 10;
+
+pub fn render(display: &Display, palette: &Palette, scale: usize) -> RgbImage {
+    let Rgb(r, g, b) = palette.background();
+    let mut image = RgbImage::new(display.width() * scale, display.height() * scale, (r, g, b));
+    for (x, y, _) in display.iter_pixels() {
+        let Rgb(r, g, b) = palette.colors[display.pixel(x, y) as usize];
+        image.fill_rect(x * scale, y * scale, scale, scale, (r, g, b));
+    }
+    image
+}
+
+pub fn encode(display: &Display, palette: &Palette, scale: usize) -> Vec<u8> {
+    png::encode(&render(display, palette, scale))
+}
@@ -0,0 +1,290 @@
+// hook scripts: console commands that run on events, for bots, automated
+// rom tests and instrumentation without forking the emulator. there is no
+// room for an embedded language without dependencies, so this is a small
+// one of its own:
+//
+//     # every 60th frame, frame alone is every frame
+//     on frame 60
+//         regs
+//     # before the instruction at 2A0 runs
+//     on break 2A0
+//         set v3 0 if v3 > 5
+//         pause if [1F0] == 0
+//     # when the byte at 1F0 changed since the last look
+//     on write 1F0
+//         print score changed
+//         press 5
+//
+// actions are the console's peek, poke, regs and set, press and release of
+// a chip8 key, print and pause, each with an optional "if LHS OP VALUE"
+// where LHS is a register or [ADDR] and OP one of == != < > <= >=. frame
+// counts are decimal, addresses and values hex. scripts run on the cpu
+// thread between instructions, once per step of the machine, so write and
+// frame hooks see a frame at a time
+use crate::breakpoint::BreakAction;
+use crate::chip8::{Chip8State, RunState};
+use crate::console::{DebugCommand, Register, hex};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Event {
+    Frame { every: u64 },
+    // hits of the breakpoint already handled
+    Break { addr: u16, hits: u64 },
+    // the byte as last seen, None before the first look
+    Write { addr: usize, last: Option<u8> },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operand {
+    Register(Register),
+    Memory(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    Greater,
+    LessOrEqual,
+    GreaterOrEqual,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Condition {
+    operand: Operand,
+    comparison: Comparison,
+    value: u16,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Kind {
+    Console(DebugCommand),
+    Press(u8),
+    Release(u8),
+    Print(String),
+    Pause,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Action {
+    line: usize,
+    kind: Kind,
+    condition: Option<Condition>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Hook {
+    event: Event,
+    actions: Vec<Action>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Script {
+    hooks: Vec<Hook>,
+    // frame of the last run, frame hooks fire when it moves on
+    frame: u64,
+}
+
+impl Script {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut hooks: Vec<Hook> = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            let line_number = n + 1;
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |err: String| format!("line {line_number}: {err}");
+            if let Some(event) = line.strip_prefix("on ") {
+                let event = parse_event(event.trim()).map_err(error)?;
+                hooks.push(Hook {
+                    event,
+                    actions: Vec::new(),
+                });
+                continue;
+            }
+            let Some(hook) = hooks.last_mut() else {
+                return Err(error(
+                    "actions go under an on frame, on break or on write".to_owned(),
+                ));
+            };
+            hook.actions
+                .push(parse_action(line_number, line).map_err(error)?);
+        }
+        Ok(Script { hooks, frame: 0 })
+    }
+
+    // pause breakpoints for the break hooks, call it once before running
+    pub fn install(&self, state: &mut Chip8State) {
+        for hook in &self.hooks {
+            if let Event::Break { addr, .. } = hook.event {
+                state.breakpoints.add(addr, BreakAction::Pause);
+            }
+        }
+    }
+
+    // fires the hooks whose events happened since the last run, the shown
+    // text of every action that printed something or failed
+    pub fn run(&mut self, state: &mut Chip8State) -> Vec<Result<String, String>> {
+        let mut out = Vec::new();
+        let frame = state.emulated_time().frames;
+        let previous = std::mem::replace(&mut self.frame, frame);
+        for hook in &mut self.hooks {
+            let fired = match &mut hook.event {
+                Event::Frame { every } => frame != previous && frame / *every != previous / *every,
+                Event::Break { addr, hits } => {
+                    let now = state.breakpoints.get(*addr).map_or(0, |b| b.hits);
+                    let hit =
+                        now > *hits && state.run_state() == RunState::Paused && state.pc == *addr;
+                    *hits = now;
+                    if hit {
+                        // the breakpoint only stands in for the hook
+                        state.set_paused(false);
+                    }
+                    hit
+                }
+                Event::Write { addr, last } => {
+                    let now = state.memory.get(*addr).copied();
+                    let changed = last.is_some() && *last != now;
+                    *last = now;
+                    changed
+                }
+            };
+            if fired {
+                for action in &hook.actions {
+                    if let Some(shown) = run_action(action, state) {
+                        out.push(shown);
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+fn parse_event(text: &str) -> Result<Event, String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    Ok(match words.as_slice() {
+        ["frame"] => Event::Frame { every: 1 },
+        ["frame", every] => match every.parse() {
+            Ok(every) if every > 0 => Event::Frame { every },
+            _ => return Err("on frame expects a number of frames above 0".to_owned()),
+        },
+        ["break", addr] => Event::Break {
+            addr: hex(addr)?,
+            hits: 0,
+        },
+        ["write", addr] => Event::Write {
+            addr: hex(addr)? as usize,
+            last: None,
+        },
+        _ => {
+            return Err(format!(
+                "unknown event {text}, expected frame, break or write"
+            ));
+        }
+    })
+}
+
+fn parse_action(line: usize, text: &str) -> Result<Action, String> {
+    let (text, condition) = match text.split_once(" if ") {
+        Some((text, condition)) => (text.trim(), Some(parse_condition(condition.trim())?)),
+        None => (text, None),
+    };
+    let (word, rest) = text.split_once(' ').unwrap_or((text, ""));
+    let key = |text: &str| {
+        u8::from_str_radix(text.trim(), 16)
+            .ok()
+            .filter(|key| *key < 16)
+            .ok_or_else(|| format!("{word} expects a chip8 key 0 to F"))
+    };
+    let kind = match word {
+        "press" => Kind::Press(key(rest)?),
+        "release" => Kind::Release(key(rest)?),
+        "print" => Kind::Print(rest.trim().to_owned()),
+        "pause" if rest.is_empty() => Kind::Pause,
+        _ => Kind::Console(DebugCommand::parse(text)?),
+    };
+    Ok(Action {
+        line,
+        kind,
+        condition,
+    })
+}
+
+fn parse_condition(text: &str) -> Result<Condition, String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let [operand, comparison, value] = words.as_slice() else {
+        return Err(format!("expected LHS OP VALUE after if, got {text}"));
+    };
+    let operand = match operand
+        .strip_prefix('[')
+        .and_then(|addr| addr.strip_suffix(']'))
+    {
+        Some(addr) => Operand::Memory(hex(addr)? as usize),
+        None => Operand::Register(
+            Register::parse(operand).ok_or_else(|| format!("unknown register {operand}"))?,
+        ),
+    };
+    let comparison = match *comparison {
+        "==" => Comparison::Equal,
+        "!=" => Comparison::NotEqual,
+        "<" => Comparison::Less,
+        ">" => Comparison::Greater,
+        "<=" => Comparison::LessOrEqual,
+        ">=" => Comparison::GreaterOrEqual,
+        _ => return Err(format!("unknown comparison {comparison}")),
+    };
+    Ok(Condition {
+        operand,
+        comparison,
+        value: hex(value)?,
+    })
+}
+
+impl Condition {
+    fn holds(&self, state: &Chip8State) -> bool {
+        let lhs = match self.operand {
+            Operand::Register(register) => register.get(state),
+            Operand::Memory(addr) => state.memory.get(addr).map_or(0, |byte| *byte as u16),
+        };
+        match self.comparison {
+            Comparison::Equal => lhs == self.value,
+            Comparison::NotEqual => lhs != self.value,
+            Comparison::Less => lhs < self.value,
+            Comparison::Greater => lhs > self.value,
+            Comparison::LessOrEqual => lhs <= self.value,
+            Comparison::GreaterOrEqual => lhs >= self.value,
+        }
+    }
+}
+
+fn run_action(action: &Action, state: &mut Chip8State) -> Option<Result<String, String>> {
+    if action
+        .condition
+        .is_some_and(|condition| !condition.holds(state))
+    {
+        return None;
+    }
+    match &action.kind {
+        Kind::Console(command) => Some(
+            command
+                .execute(state)
+                .map_err(|err| format!("line {}: {err}", action.line)),
+        ),
+        Kind::Press(key) => {
+            state.keypad[*key as usize] = true;
+            None
+        }
+        Kind::Release(key) => {
+            state.keypad[*key as usize] = false;
+            None
+        }
+        Kind::Print(text) => Some(Ok(text.clone())),
+        Kind::Pause => {
+            state.set_paused(true);
+            None
+        }
+    }
+}
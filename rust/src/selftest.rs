@@ -0,0 +1,212 @@
+// one command health check: small bundled roms exercise groups of opcodes
+// and end on a pass or fail mark, judged by the signature of the final screen.
+//
+// every case shares the same frame: 0x200 jumps to the case body at BODY,
+// which jumps to FAIL when a check goes wrong and falls through to PASS.
+use crate::chip8::{Chip8State, RunState};
+use crate::display::Display;
+use crate::error::Chip8Error;
+use std::fmt;
+
+const PASS: u16 = 0x202;
+const FAIL: u16 = 0x20A;
+const PASS_SPRITE: u16 = 0x212;
+const FAIL_SPRITE: u16 = 0x217;
+const BODY: u16 = 0x21C;
+
+const INSTRUCTIONS_PER_FRAME: usize = 20;
+const MAX_FRAMES: usize = 120;
+const SEED: u32 = 1;
+
+const PASS_MARK: [u8; 5] = [0x01, 0x02, 0x84, 0x48, 0x30];
+const FAIL_MARK: [u8; 5] = [0x88, 0x50, 0x20, 0x50, 0x88];
+
+pub struct SelfTest {
+    pub name: &'static str,
+    // opcodes placed at BODY, in groups so checks read as one item
+    pub body: &'static [&'static [u16]],
+}
+
+// skip the jump to FAIL if V[x] == nn
+macro_rules! expect {
+    ($x:literal, $nn:literal) => {
+        &[0x3000 | ($x << 8) | $nn, 0x1000 | FAIL]
+    };
+}
+
+pub const SELFTESTS: &[SelfTest] = &[
+    SelfTest {
+        name: "load immediate",
+        body: &[&[0x6005], expect!(0x0, 0x05), &[0x6A33], expect!(0xA, 0x33)],
+    },
+    SelfTest {
+        name: "add immediate",
+        body: &[&[0x6005, 0x7003], expect!(0x0, 0x08)],
+    },
+    SelfTest {
+        name: "conditional skips",
+        body: &[
+            &[0x6007],
+            expect!(0x0, 0x07),
+            // skip the jump to FAIL if V0 != 8
+            &[0x4008, 0x1000 | FAIL],
+        ],
+    },
+    SelfTest {
+        name: "register alu",
+        body: &[
+            &[0x6103, 0x620C, 0x8120],
+            expect!(0x1, 0x0C),
+            &[0x6103, 0x8121],
+            expect!(0x1, 0x0F),
+            &[0x610F, 0x620C, 0x8122],
+            expect!(0x1, 0x0C),
+            &[0x8123],
+            expect!(0x1, 0x00),
+            &[0x61FF, 0x6202, 0x8124],
+            expect!(0x1, 0x01),
+            expect!(0xF, 0x01),
+            &[0x6105, 0x6207, 0x8125],
+            expect!(0x1, 0xFE),
+            expect!(0xF, 0x00),
+        ],
+    },
+    SelfTest {
+        name: "subroutines",
+        body: &[
+            // 21C: call 224, 222: jump over the subroutine, 224: V3 = 1, return
+            &[0x2224],
+            expect!(0x3, 0x01),
+            &[0x1228, 0x6301, 0x00EE],
+        ],
+    },
+    SelfTest {
+        name: "bcd",
+        body: &[
+            &[0x607B, 0xA300, 0xF033, 0xF265],
+            expect!(0x0, 0x01),
+            expect!(0x1, 0x02),
+            expect!(0x2, 0x03),
+        ],
+    },
+    SelfTest {
+        name: "store and load",
+        body: &[
+            &[0x6011, 0x6122, 0xA300, 0xF155, 0x6000, 0x6100, 0xF165],
+            expect!(0x0, 0x11),
+            expect!(0x1, 0x22),
+        ],
+    },
+    SelfTest {
+        name: "sprite collision",
+        body: &[
+            &[0xA000 | PASS_SPRITE, 0x6000, 0xD001],
+            expect!(0xF, 0x00),
+            &[0xD001],
+            expect!(0xF, 0x01),
+        ],
+    },
+    SelfTest {
+        name: "clear screen",
+        body: &[&[0xA000 | FAIL_SPRITE, 0x6000, 0xD005, 0x00E0]],
+    },
+    SelfTest {
+        name: "delay timer",
+        // 21C: delay = 16, 220: loop until it reads 0
+        body: &[&[0x6010, 0xF015, 0xF007, 0x3000, 0x1220]],
+    },
+];
+
+#[derive(Debug)]
+pub enum Outcome {
+    Pass,
+    // the rom drew its fail mark, a check inside the body went wrong
+    Fail,
+    // the final screen matches neither mark
+    WrongScreen,
+    // still running after MAX_FRAMES
+    Timeout,
+    Error(Chip8Error),
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Pass => write!(f, "ok"),
+            Outcome::Fail => write!(f, "FAIL"),
+            Outcome::WrongScreen => write!(f, "FAIL (unexpected screen)"),
+            Outcome::Timeout => write!(f, "FAIL (timeout)"),
+            Outcome::Error(err) => write!(f, "FAIL ({err})"),
+        }
+    }
+}
+
+pub fn rom(test: &SelfTest) -> Vec<u8> {
+    let code = [
+        0x1000 | BODY,
+        // PASS: draw the pass mark at (0, 0) and stop
+        0xA000 | PASS_SPRITE,
+        0x6000,
+        0xD005,
+        0x1000 | (PASS + 6),
+        // FAIL: draw the fail mark at (0, 0) and stop
+        0xA000 | FAIL_SPRITE,
+        0x6000,
+        0xD005,
+        0x1000 | (FAIL + 6),
+    ];
+
+    let mut rom: Vec<u8> = code.iter().flat_map(|op: &u16| op.to_be_bytes()).collect();
+    rom.extend_from_slice(&PASS_MARK);
+    rom.extend_from_slice(&FAIL_MARK);
+    for op in test.body.iter().copied().flatten().chain(&[0x1000 | PASS]) {
+        rom.extend_from_slice(&op.to_be_bytes());
+    }
+    rom
+}
+
+// fnv-1a over the packed display
+pub fn signature(display: &Display) -> u64 {
+    let mut hash = 0xCBF2_9CE4_8422_2325u64;
+    for byte in display.words().iter().flat_map(|word| word.to_be_bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01B3);
+    }
+    hash
+}
+
+fn mark_signature(mark: &[u8; 5]) -> u64 {
+    let mut display = Display::default();
+    for (y, row) in mark.iter().enumerate() {
+        for x in 0..8 {
+            if row & (0x80 >> x) != 0 {
+                display.toggle(x, y);
+            }
+        }
+    }
+    signature(&display)
+}
+
+pub fn run(test: &SelfTest) -> Outcome {
+    let mut state = Chip8State::with_seed(SEED);
+    if let Err(err) = state.load(&rom(test)) {
+        return Outcome::Error(err);
+    }
+
+    for _ in 0..MAX_FRAMES {
+        if let Err(err) = state.run_frame(INSTRUCTIONS_PER_FRAME) {
+            return Outcome::Error(err);
+        }
+        if state.run_state() == RunState::Halted {
+            let screen = signature(&state.display);
+            return if screen == mark_signature(&PASS_MARK) {
+                Outcome::Pass
+            } else if screen == mark_signature(&FAIL_MARK) {
+                Outcome::Fail
+            } else {
+                Outcome::WrongScreen
+            };
+        }
+    }
+    Outcome::Timeout
+}
@@ -0,0 +1,415 @@
+// user facing knobs of the core, everything defaults to accurate behavior
+use crate::chip8::{ETI_660_PROGRAM_START, PROGRAM_START};
+use crate::display::Geometry;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Settings {
+    // anti-flicker hack: DXYN calls past this many per frame wait for the next frame
+    pub sprite_limit: Option<usize>,
+    pub unknown_opcode: UnknownOpcodePolicy,
+    pub write_protection: WriteProtection,
+    // cpu speed of run_for, timers tick at 60Hz regardless
+    pub instructions_per_second: u32,
+    pub quirks: Quirks,
+    pub timing: Timing,
+}
+
+// how much cpu time each 60Hz frame holds
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Timing {
+    // every instruction takes the same time, instructions_per_second of them
+    #[default]
+    Flat,
+    // every instruction takes as many machine cycles as it did in the cosmac
+    // vip interpreter, see Op::vip_cycles. a frame holds VIP_FRAME_CYCLES of
+    // them, instructions_per_second and the budget of run_frame don't apply
+    CosmacVip,
+}
+
+impl Timing {
+    pub const NAMES: &[(&str, Timing)] =
+        &[("flat", Timing::Flat), ("cosmac-vip", Timing::CosmacVip)];
+
+    pub fn find(name: &str) -> Option<Timing> {
+        Timing::NAMES
+            .iter()
+            .find(|(timing, _)| *timing == name)
+            .map(|&(_, timing)| timing)
+    }
+}
+
+// behaviors that differ between interpreters, all off is the modern default
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Quirks {
+    // cosmac vip: DXYN waits for the next 60Hz tick, one sprite per frame
+    pub display_wait: bool,
+    // sprites crossing an edge continue on the opposite side instead of clipping
+    pub wrap_sprites: bool,
+    // cosmac vip: FX0A finishes when the key goes up again, not when it goes down
+    pub key_release: bool,
+    // cosmac vip: 8XY1, 8XY2 and 8XY3 clear VF
+    pub vf_reset: bool,
+    // how far FX55 and FX65 move I
+    pub memory: MemoryIncrement,
+    // chip-48 and superchip: 8XY6 and 8XYE shift VX in place and ignore VY
+    pub shift_vx: bool,
+    // chip-48 and superchip: BNNN is BXNN, a jump to XNN + VX
+    pub jump_vx: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MemoryIncrement {
+    #[default]
+    Unchanged,
+    // chip-48
+    ByX,
+    // cosmac vip and xo-chip, I ends up past the last register
+    ByXPlusOne,
+}
+
+impl MemoryIncrement {
+    pub fn after(self, x: u8) -> u16 {
+        match self {
+            MemoryIncrement::Unchanged => 0,
+            MemoryIncrement::ByX => x as u16,
+            MemoryIncrement::ByXPlusOne => x as u16 + 1,
+        }
+    }
+}
+
+impl Quirks {
+    pub const NAMES: &[&str] = &[
+        "display-wait",
+        "wrap",
+        "key-release",
+        "vf-reset",
+        "memory",
+        "memory-x",
+        "shift",
+        "jump",
+    ];
+
+    // the columns of the quirks test's table, see Machine for the rest of
+    // each interpreter
+    pub const MODERN: Quirks = Quirks {
+        display_wait: false,
+        wrap_sprites: false,
+        key_release: false,
+        vf_reset: false,
+        memory: MemoryIncrement::Unchanged,
+        shift_vx: false,
+        jump_vx: false,
+    };
+    pub const COSMAC_VIP: Quirks = Quirks {
+        display_wait: true,
+        key_release: true,
+        vf_reset: true,
+        memory: MemoryIncrement::ByXPlusOne,
+        ..Quirks::MODERN
+    };
+    pub const CHIP_48: Quirks = Quirks {
+        memory: MemoryIncrement::ByX,
+        shift_vx: true,
+        jump_vx: true,
+        ..Quirks::MODERN
+    };
+    // superchip 1.0 and 1.1 on the hp48, lores sprites waited for the tick
+    pub const SUPERCHIP_LEGACY: Quirks = Quirks {
+        display_wait: true,
+        shift_vx: true,
+        jump_vx: true,
+        ..Quirks::MODERN
+    };
+    // superchip as modern interpreters do it
+    pub const SUPERCHIP_MODERN: Quirks = Quirks {
+        shift_vx: true,
+        jump_vx: true,
+        ..Quirks::MODERN
+    };
+    pub const XO_CHIP: Quirks = Quirks {
+        wrap_sprites: true,
+        memory: MemoryIncrement::ByXPlusOne,
+        ..Quirks::MODERN
+    };
+
+    // named quirk sets of well known interpreters
+    pub const PRESETS: &[(&str, Quirks)] = &[
+        ("modern", Quirks::MODERN),
+        ("cosmac-vip", Quirks::COSMAC_VIP),
+        // its interpreter is the vip's moved up to make room
+        ("eti-660", Quirks::COSMAC_VIP),
+        ("chip-48", Quirks::CHIP_48),
+        ("superchip-legacy", Quirks::SUPERCHIP_LEGACY),
+        ("superchip-modern", Quirks::SUPERCHIP_MODERN),
+        ("xo-chip", Quirks::XO_CHIP),
+    ];
+
+    // enables quirks from a comma separated list of quirk or preset names
+    // like "display-wait" or "cosmac-vip", returns the first unknown name
+    pub fn enable(&mut self, list: &str) -> Result<(), String> {
+        for name in list
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            match name {
+                "display-wait" => self.display_wait = true,
+                "wrap" => self.wrap_sprites = true,
+                "key-release" => self.key_release = true,
+                "vf-reset" => self.vf_reset = true,
+                "memory" => self.memory = MemoryIncrement::ByXPlusOne,
+                "memory-x" => self.memory = MemoryIncrement::ByX,
+                "shift" => self.shift_vx = true,
+                "jump" => self.jump_vx = true,
+                _ => {
+                    let (_, preset) = Quirks::PRESETS
+                        .iter()
+                        .find(|(preset, _)| *preset == name)
+                        .ok_or_else(|| name.to_owned())?;
+                    self.display_wait |= preset.display_wait;
+                    self.wrap_sprites |= preset.wrap_sprites;
+                    self.key_release |= preset.key_release;
+                    self.vf_reset |= preset.vf_reset;
+                    if preset.memory != MemoryIncrement::Unchanged {
+                        self.memory = preset.memory;
+                    }
+                    self.shift_vx |= preset.shift_vx;
+                    self.jump_vx |= preset.jump_vx;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// a whole interpreter: its quirks, the screen it boots with and a speed
+// that runs its games the way they were written for it. hires modes still
+// switch with 00FF, xo-chip also gets 64K of memory and eti-660 roms load
+// at 0x600
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Machine {
+    pub name: &'static str,
+    pub quirks: Quirks,
+    pub geometry: Geometry,
+    pub instructions_per_second: u32,
+    pub extended_memory: bool,
+    pub program_start: usize,
+    pub timing: Timing,
+}
+
+impl Machine {
+    pub const ALL: &[Machine] = &[
+        Machine {
+            name: "cosmac-vip",
+            quirks: Quirks::COSMAC_VIP,
+            geometry: Geometry::LORES,
+            instructions_per_second: 600,
+            extended_memory: false,
+            program_start: PROGRAM_START,
+            timing: Timing::CosmacVip,
+        },
+        Machine {
+            name: "eti-660",
+            quirks: Quirks::COSMAC_VIP,
+            geometry: Geometry::LORES,
+            instructions_per_second: 600,
+            extended_memory: false,
+            program_start: ETI_660_PROGRAM_START,
+            timing: Timing::Flat,
+        },
+        Machine {
+            name: "chip-48",
+            quirks: Quirks::CHIP_48,
+            geometry: Geometry::LORES,
+            instructions_per_second: 900,
+            extended_memory: false,
+            program_start: PROGRAM_START,
+            timing: Timing::Flat,
+        },
+        Machine {
+            name: "superchip-legacy",
+            quirks: Quirks::SUPERCHIP_LEGACY,
+            geometry: Geometry::LORES,
+            instructions_per_second: 1800,
+            extended_memory: false,
+            program_start: PROGRAM_START,
+            timing: Timing::Flat,
+        },
+        Machine {
+            name: "superchip-modern",
+            quirks: Quirks::SUPERCHIP_MODERN,
+            geometry: Geometry::LORES,
+            instructions_per_second: 1800,
+            extended_memory: false,
+            program_start: PROGRAM_START,
+            timing: Timing::Flat,
+        },
+        Machine {
+            name: "xo-chip",
+            quirks: Quirks::XO_CHIP,
+            geometry: Geometry::LORES,
+            instructions_per_second: 30000,
+            extended_memory: true,
+            program_start: PROGRAM_START,
+            timing: Timing::Flat,
+        },
+    ];
+
+    pub fn find(name: &str) -> Option<&'static Machine> {
+        Machine::ALL.iter().find(|machine| machine.name == name)
+    }
+}
+
+pub const DEFAULT_INSTRUCTIONS_PER_SECOND: u32 = 700;
+
+// settings for one rom from a [rom NAME] section of the config file, by
+// file name:
+//
+//     [rom tetris.ch8]
+//     quirks = cosmac-vip
+//     ips = 900
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RomOverride {
+    pub quirks: Option<Quirks>,
+    pub instructions_per_second: Option<u32>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RomOverrides {
+    roms: Vec<(String, RomOverride)>,
+}
+
+impl RomOverrides {
+    pub fn new() -> Self {
+        RomOverrides::default()
+    }
+
+    // reads the [rom NAME] sections and skips the others, the way
+    // Keymap::load_config skips these
+    pub fn load_config(&mut self, text: &str) -> Result<(), String> {
+        let mut rom: Option<usize> = None;
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                rom = match name.trim().split_once(' ') {
+                    Some(("rom", name)) => Some(self.entry(name.trim())),
+                    _ => None,
+                };
+                continue;
+            }
+            let Some(rom) = rom else {
+                continue;
+            };
+            let entry = &mut self.roms[rom].1;
+            let result = match line.split_once('=').map(|(k, v)| (k.trim(), v.trim())) {
+                Some(("quirks", list)) => {
+                    let mut quirks = Quirks::default();
+                    quirks
+                        .enable(list)
+                        .map(|()| entry.quirks = Some(quirks))
+                        .map_err(|name| format!("unknown quirk {name}"))
+                }
+                Some(("ips", ips)) => match ips.parse() {
+                    Ok(ips) if ips > 0 => {
+                        entry.instructions_per_second = Some(ips);
+                        Ok(())
+                    }
+                    _ => Err("ips expects a number of instructions per second".to_owned()),
+                },
+                Some((key, _)) => Err(format!("unknown rom setting {key}")),
+                None => Err("expected setting = value".to_owned()),
+            };
+            result.map_err(|err| format!("line {}: {err}", n + 1))?;
+        }
+        Ok(())
+    }
+
+    fn entry(&mut self, name: &str) -> usize {
+        match self.roms.iter().position(|(rom, _)| rom == name) {
+            Some(n) => n,
+            None => {
+                self.roms.push((name.to_owned(), RomOverride::default()));
+                self.roms.len() - 1
+            }
+        }
+    }
+
+    pub fn get(&self, rom_name: &str) -> Option<&RomOverride> {
+        self.roms
+            .iter()
+            .find(|(rom, _)| rom == rom_name)
+            .map(|(_, settings)| settings)
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            sprite_limit: None,
+            unknown_opcode: UnknownOpcodePolicy::default(),
+            write_protection: WriteProtection::default(),
+            instructions_per_second: DEFAULT_INSTRUCTIONS_PER_SECOND,
+            quirks: Quirks::default(),
+            timing: Timing::default(),
+        }
+    }
+}
+
+// what cycle() does when it decodes an opcode it doesn't implement
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UnknownOpcodePolicy {
+    // stop with Chip8Error::InvalidOpcode
+    #[default]
+    Halt,
+    // treat it as a no-op and continue with the next instruction
+    Skip,
+    // park the cpu on the opcode until clear_trap(), for inspection in a debugger
+    Trap,
+}
+
+// what the cpu does about writes below the program start, the font and the
+// interpreter's own area. no program means to write there, a rom that does
+// is overwriting its font through a stray I and only shows it much later
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WriteProtection {
+    #[default]
+    Off,
+    // stop with Chip8Error::ProtectedWrite before the write
+    Halt,
+    // let the instruction finish and pause the cpu after it, see
+    // Chip8State::take_protected_write
+    Pause,
+}
+
+impl WriteProtection {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "off" => Some(WriteProtection::Off),
+            "halt" => Some(WriteProtection::Halt),
+            "pause" => Some(WriteProtection::Pause),
+            _ => None,
+        }
+    }
+}
+
+impl UnknownOpcodePolicy {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "halt" => Some(UnknownOpcodePolicy::Halt),
+            "skip" => Some(UnknownOpcodePolicy::Skip),
+            "trap" => Some(UnknownOpcodePolicy::Trap),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,54 @@
+// numbered save states of every rom, what Hotkey::SaveState writes and
+// LoadState and --load-state resume. files are named by the rom's sha1 and
+// the slot, so a renamed rom keeps its states and two roms of the same name
+// don't share them
+use crate::chip8::{Chip8State, RunState};
+use crate::dirs::state_dir;
+use crate::savestate;
+use crate::sha1::sha1_hex;
+use std::path::{Path, PathBuf};
+
+pub const SLOTS: u8 = 4;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateSlots {
+    dir: PathBuf,
+}
+
+impl StateSlots {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        StateSlots { dir: dir.into() }
+    }
+
+    // the states directory of state_dir
+    pub fn default_dir() -> Option<PathBuf> {
+        state_dir("states")
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    // slot is 1..=SLOTS
+    pub fn path(&self, rom: &[u8], slot: u8) -> PathBuf {
+        self.dir.join(format!("{}-{slot}.state", sha1_hex(rom)))
+    }
+}
+
+pub fn save(path: &Path, state: &Chip8State) -> Result<(), String> {
+    let written = match path.parent() {
+        Some(dir) => std::fs::create_dir_all(dir),
+        None => Ok(()),
+    }
+    .and_then(|()| std::fs::write(path, savestate::save(state)));
+    written.map_err(|err| format!("{}: {err}", path.display()))
+}
+
+// the machine a state file holds, paused or running as state was before
+pub fn load(path: &Path, state: &mut Chip8State) -> Result<(), String> {
+    let bytes = std::fs::read(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    let paused = state.run_state() == RunState::Paused;
+    savestate::load(state, &bytes).map_err(|err| format!("{}: {err}", path.display()))?;
+    state.set_paused(paused);
+    Ok(())
+}
@@ -0,0 +1,150 @@
+// what differs between two machines: registers, the memory ranges and the
+// pixels. diff takes two snapshots, of savestates or of two runs of a rom
+// under different settings, which chip8 diff does:
+//
+//     chip8 diff A.state B.state
+//     chip8 diff ROM cosmac-vip superchip-modern [FRAMES]
+//
+// the emulated time isn't compared, two speeds always differ there
+use crate::display::Display;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::savestate::Snapshot;
+use core::fmt;
+use core::ops::Range;
+
+// a register or a bit of cpu state, both values as the report shows them
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Change {
+    pub name: String,
+    pub a: String,
+    pub b: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateDiff {
+    pub registers: Vec<Change>,
+    // runs of differing bytes, and past the end of the smaller memory
+    pub memory: Vec<Range<usize>>,
+    // the pixels lit on one side only, or all of them when the screen
+    // sizes differ
+    pub pixels: Vec<(usize, usize)>,
+}
+
+impl StateDiff {
+    pub fn is_empty(&self) -> bool {
+        self.registers.is_empty() && self.memory.is_empty() && self.pixels.is_empty()
+    }
+}
+
+pub fn diff(a: &Snapshot, b: &Snapshot) -> StateDiff {
+    let mut registers = Vec::new();
+    let mut compare = |name: &str, a: String, b: String| {
+        if a != b {
+            registers.push(Change {
+                name: name.to_owned(),
+                a,
+                b,
+            });
+        }
+    };
+    for (n, (va, vb)) in a.v.iter().zip(&b.v).enumerate() {
+        compare(&format!("V{n:X}"), format!("{va:02X}"), format!("{vb:02X}"));
+    }
+    compare("PC", format!("{:03X}", a.pc), format!("{:03X}", b.pc));
+    compare("I", format!("{:03X}", a.i), format!("{:03X}", b.i));
+    compare("stack", stack(&a.stack), stack(&b.stack));
+    compare(
+        "DT",
+        format!("{}", a.delay_timer),
+        format!("{}", b.delay_timer),
+    );
+    compare(
+        "ST",
+        format!("{}", a.sound_timer),
+        format!("{}", b.sound_timer),
+    );
+    compare("rng", format!("{:?}", a.rng), format!("{:?}", b.rng));
+    compare(
+        "state",
+        format!("{:?}", a.run_state),
+        format!("{:?}", b.run_state),
+    );
+    compare(
+        "waiting",
+        format!("{:?}", a.wait_pressed),
+        format!("{:?}", b.wait_pressed),
+    );
+
+    StateDiff {
+        registers,
+        memory: memory(&a.memory, &b.memory),
+        pixels: pixels(&a.display, &b.display),
+    }
+}
+
+fn stack(stack: &[u16]) -> String {
+    let frames: Vec<String> = stack.iter().map(|addr| format!("{addr:03X}")).collect();
+    format!("[{}]", frames.join(" "))
+}
+
+fn memory(a: &[u8], b: &[u8]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let len = a.len().max(b.len());
+    for addr in (0..len).filter(|&addr| a.get(addr) != b.get(addr)) {
+        match ranges.last_mut() {
+            Some(range) if range.end == addr => range.end += 1,
+            _ => ranges.push(addr..addr + 1),
+        }
+    }
+    ranges
+}
+
+fn pixels(a: &Display, b: &Display) -> Vec<(usize, usize)> {
+    let sizes_differ = a.geometry() != b.geometry();
+    let (width, height) = (a.width().max(b.width()), a.height().max(b.height()));
+    let lit =
+        |display: &Display, x, y| x < display.width() && y < display.height() && display.get(x, y);
+    let mut pixels = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            if sizes_differ || lit(a, x, y) != lit(b, x, y) {
+                pixels.push((x, y));
+            }
+        }
+    }
+    pixels
+}
+
+// the report chip8 diff prints, a line for each difference
+impl fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "identical");
+        }
+        for change in &self.registers {
+            writeln!(f, "{:<8} {} -> {}", change.name, change.a, change.b)?;
+        }
+        for range in &self.memory {
+            match range.len() {
+                1 => writeln!(f, "memory   {:03X}", range.start)?,
+                len => writeln!(
+                    f,
+                    "memory   {:03X}-{:03X} ({len} bytes)",
+                    range.start,
+                    range.end - 1
+                )?,
+            }
+        }
+        match self.pixels.as_slice() {
+            [] => {}
+            [(x, y)] => writeln!(f, "display  1 pixel differs, at {x},{y}")?,
+            [(x, y), ..] => writeln!(
+                f,
+                "display  {} pixels differ, the first at {x},{y}",
+                self.pixels.len()
+            )?,
+        }
+        Ok(())
+    }
+}
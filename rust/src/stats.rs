@@ -0,0 +1,58 @@
+// what a run did, for chip8 --stats at exit and the console's stats: the
+// instructions and 60Hz frames of the emulated time, and counts the core
+// keeps along the way. cheap enough to always count
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use core::fmt;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RunStats {
+    pub instructions: u64,
+    pub frames: u64,
+    // DXYN that drew, not the ones the sprite limit put off
+    pub draws: u64,
+    // times the sound timer went from silent to running
+    pub sounds: u64,
+    // frames spent in FX0A waiting for a key
+    pub key_wait_frames: u64,
+}
+
+impl RunStats {
+    // per emulated second, 0 before the first frame
+    pub fn instructions_per_second(&self) -> u64 {
+        (self.instructions * 60)
+            .checked_div(self.frames)
+            .unwrap_or(0)
+    }
+}
+
+// 60Hz frames as seconds with a decimal, "28.6 s"
+fn seconds(frames: u64) -> String {
+    let tenths = frames * 10 / 60;
+    format!("{}.{} s", tenths / 10, tenths % 10)
+}
+
+impl fmt::Display for RunStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "instructions  {}, {} a second",
+            self.instructions,
+            self.instructions_per_second()
+        )?;
+        writeln!(
+            f,
+            "frames        {}, {} emulated",
+            self.frames,
+            seconds(self.frames)
+        )?;
+        writeln!(f, "draws         {}", self.draws)?;
+        writeln!(f, "sounds        {}", self.sounds)?;
+        write!(
+            f,
+            "key waits     {} frames, {} in FX0A",
+            self.key_wait_frames,
+            seconds(self.key_wait_frames)
+        )
+    }
+}
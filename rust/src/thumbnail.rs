@@ -0,0 +1,54 @@
+// preview images of roms. most programs draw a title screen and then wait,
+// so instead of a fixed frame count the capture runs until the screen has
+// stopped changing for a while
+use crate::chip8::Chip8State;
+use crate::display::Display;
+use crate::error::Chip8Error;
+use crate::palette::Palette;
+use crate::png::RgbImage;
+use crate::screenshot;
+use crate::settings::Quirks;
+
+// half a second without a change counts as settled
+pub const STABLE_FRAMES: usize = 30;
+// gives up on programs that animate forever, ten seconds
+pub const MAX_FRAMES: usize = 600;
+const INSTRUCTIONS_PER_FRAME: usize = 10;
+const SEED: u32 = 1;
+const SCALE: usize = 2;
+
+// the first screen that stays the same for STABLE_FRAMES with something on
+// it, or the screen after max_frames if it never settles
+pub fn capture_title_screen(
+    rom: &[u8],
+    quirks: Quirks,
+    max_frames: usize,
+) -> Result<Display, Chip8Error> {
+    let mut state = Chip8State::with_seed(SEED);
+    state.settings.quirks = quirks;
+    state.load(rom)?;
+
+    let mut last = state.display.clone();
+    let mut unchanged = 0;
+    for _ in 0..max_frames {
+        state.run_frame(INSTRUCTIONS_PER_FRAME)?;
+
+        // erasing and redrawing a sprite changes the generation, not the picture
+        if state.display.generation() == last.generation() || state.display == last {
+            unchanged += 1;
+        } else {
+            unchanged = 0;
+            last.clone_from(&state.display);
+        }
+
+        let blank = state.display.words().iter().all(|word| *word == 0);
+        if unchanged >= STABLE_FRAMES && !blank {
+            break;
+        }
+    }
+    Ok(state.display)
+}
+
+pub fn render(display: &Display, palette: &Palette) -> RgbImage {
+    screenshot::render(display, palette, SCALE)
+}
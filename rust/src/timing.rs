@@ -0,0 +1,100 @@
+// frame pacing that doesn't depend on the os scheduler tick. windows rounds
+// sleeps up to 15.6 ms unless the timer resolution is raised, so the cpu thread
+// asks for 1 ms there and spins through the last bit of every wait elsewhere
+use std::thread;
+use std::time::{Duration, Instant};
+
+// sleeps end this early and the rest is spent yielding
+const SPIN_MARGIN: Duration = Duration::from_millis(2);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimerStrategy {
+    // timeBeginPeriod(1), then sleep and spin
+    HighResolution,
+    // the os timer is fine grained already, sleep and spin
+    SleepSpin,
+}
+
+impl TimerStrategy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TimerStrategy::HighResolution => "high resolution timer + spin",
+            TimerStrategy::SleepSpin => "sleep + spin",
+        }
+    }
+}
+
+// raises the timer resolution while alive where that is needed
+pub struct TimerResolution {
+    strategy: TimerStrategy,
+}
+
+impl TimerResolution {
+    pub fn acquire() -> Self {
+        TimerResolution {
+            strategy: platform::begin(),
+        }
+    }
+
+    pub fn strategy(&self) -> TimerStrategy {
+        self.strategy
+    }
+}
+
+impl Drop for TimerResolution {
+    fn drop(&mut self) {
+        if self.strategy == TimerStrategy::HighResolution {
+            platform::end();
+        }
+    }
+}
+
+pub fn sleep_until(deadline: Instant) {
+    let now = Instant::now();
+    if deadline <= now {
+        return;
+    }
+
+    let remaining = deadline - now;
+    if remaining > SPIN_MARGIN {
+        thread::sleep(remaining - SPIN_MARGIN);
+    }
+    while Instant::now() < deadline {
+        thread::yield_now();
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::TimerStrategy;
+
+    #[link(name = "winmm")]
+    unsafe extern "system" {
+        fn timeBeginPeriod(period: u32) -> u32;
+        fn timeEndPeriod(period: u32) -> u32;
+    }
+
+    pub fn begin() -> TimerStrategy {
+        // TIMERR_NOERROR is 0
+        if unsafe { timeBeginPeriod(1) } == 0 {
+            TimerStrategy::HighResolution
+        } else {
+            TimerStrategy::SleepSpin
+        }
+    }
+
+    pub fn end() {
+        unsafe { timeEndPeriod(1) };
+    }
+}
+
+#[cfg(not(windows))]
+mod platform {
+    use super::TimerStrategy;
+
+    pub fn begin() -> TimerStrategy {
+        TimerStrategy::SleepSpin
+    }
+
+    pub fn end() {}
+}
@@ -0,0 +1,240 @@
+// --trace: one line per executed instruction with its address, opcode and
+// mnemonic, the registers it reads and the ones it changed, e.g.
+//
+//     204  D015  DRW V0, V1, 5          V0=05 V1=00 I=22A VF 00>01
+//
+// --trace-format json writes json lines for tools instead, with events for
+// instructions, sprite draws, key changes, expired timers and a state
+// snapshot every emulated second:
+//
+//     {"event":"draw","cycle":3,"pc":516,"x":5,"y":0,"height":5,"collision":true}
+use crate::chip8::Instruction;
+#[cfg(feature = "std")]
+use crate::chip8::Keypad;
+use crate::disasm;
+#[cfg(feature = "std")]
+use crate::json;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use core::ops::RangeInclusive;
+#[cfg(feature = "std")]
+use std::io::Write;
+
+// emulated frames between json snapshots
+pub const SNAPSHOT_FRAMES: u64 = 60;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TraceFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl TraceFormat {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "text" => Some(TraceFormat::Text),
+            "json" => Some(TraceFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+// the machine state of a snapshot event
+pub struct StateSnapshot<'a> {
+    pub frame: u64,
+    pub pc: u16,
+    pub registers: Registers,
+    pub stack: &'a [u16],
+    pub delay_timer: u8,
+    pub sound_timer: u8,
+}
+
+// registers and I around one instruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Registers {
+    pub v: [u8; 16],
+    pub i: u16,
+}
+
+// writes need std, the rest of the module is what the core shares with it
+#[cfg(feature = "std")]
+pub struct Tracer {
+    out: Box<dyn Write + Send>,
+    // only instructions at these addresses are logged
+    range: Option<RangeInclusive<u16>>,
+    format: TraceFormat,
+    // the keypad as of the last key event
+    keypad: Keypad,
+}
+
+#[cfg(feature = "std")]
+impl Tracer {
+    pub fn new(out: Box<dyn Write + Send>) -> Self {
+        Tracer {
+            out,
+            range: None,
+            format: TraceFormat::Text,
+            keypad: [false; 16],
+        }
+    }
+
+    pub fn with_range(mut self, range: RangeInclusive<u16>) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    pub fn with_format(mut self, format: TraceFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn format(&self) -> TraceFormat {
+        self.format
+    }
+
+    pub fn wants(&self, pc: u16) -> bool {
+        self.range.as_ref().is_none_or(|range| range.contains(&pc))
+    }
+
+    // a log that can't be written isn't worth stopping the program for
+    pub fn record(
+        &mut self,
+        cycle: u64,
+        pc: u16,
+        inst: &Instruction,
+        before: &Registers,
+        after: &Registers,
+    ) {
+        if self.format == TraceFormat::Text {
+            let _ = writeln!(self.out, "{}", line(pc, inst, before, after));
+            return;
+        }
+        let _ = writeln!(
+            self.out,
+            r#"{{"event":"instruction","cycle":{cycle},"pc":{pc},"opcode":{},"mnemonic":{},"before":{},"after":{}}}"#,
+            inst.opcode(),
+            json::quote(&disasm::mnemonic(inst)),
+            registers_json(before),
+            registers_json(after),
+        );
+        if inst.indicator() == 0xD {
+            let _ = writeln!(
+                self.out,
+                r#"{{"event":"draw","cycle":{cycle},"pc":{pc},"x":{},"y":{},"height":{},"collision":{}}}"#,
+                before.v[inst.x() as usize],
+                before.v[inst.y() as usize],
+                inst.n(),
+                after.v[0xF] == 1,
+            );
+        }
+    }
+
+    // json only, call before every instruction with the keypad it sees
+    pub fn keypad(&mut self, cycle: u64, frame: u64, keypad: &Keypad) {
+        if self.format != TraceFormat::Json {
+            return;
+        }
+        for (key, (pressed, was)) in keypad.iter().zip(&self.keypad).enumerate() {
+            if pressed != was {
+                let _ = writeln!(
+                    self.out,
+                    r#"{{"event":"key","cycle":{cycle},"frame":{frame},"key":{key},"pressed":{pressed}}}"#
+                );
+            }
+        }
+        self.keypad = *keypad;
+    }
+
+    // json only, timer is "delay" or "sound"
+    pub fn timer_expired(&mut self, frame: u64, timer: &str) {
+        if self.format == TraceFormat::Json {
+            let _ = writeln!(
+                self.out,
+                r#"{{"event":"timer","frame":{frame},"timer":{}}}"#,
+                json::quote(timer)
+            );
+        }
+    }
+
+    // json only
+    pub fn snapshot(&mut self, state: &StateSnapshot) {
+        if self.format != TraceFormat::Json {
+            return;
+        }
+        let stack: Vec<String> = state.stack.iter().map(u16::to_string).collect();
+        let _ = writeln!(
+            self.out,
+            r#"{{"event":"snapshot","frame":{},"pc":{},"registers":{},"stack":[{}],"delay_timer":{},"sound_timer":{}}}"#,
+            state.frame,
+            state.pc,
+            registers_json(&state.registers),
+            stack.join(","),
+            state.delay_timer,
+            state.sound_timer,
+        );
+    }
+
+    pub fn flush(&mut self) {
+        let _ = self.out.flush();
+    }
+}
+
+// parses "200-2FF" or a single address "2A0", hex
+pub fn parse_range(text: &str) -> Option<RangeInclusive<u16>> {
+    let hex = |text: &str| {
+        let text = text.trim();
+        let digits = text
+            .strip_prefix("0x")
+            .or_else(|| text.strip_prefix("0X"))
+            .unwrap_or(text);
+        u16::from_str_radix(digits, 16).ok()
+    };
+    let (start, end) = match text.split_once('-') {
+        Some((start, end)) => (hex(start)?, hex(end)?),
+        None => (hex(text)?, hex(text)?),
+    };
+    (start <= end).then_some(start..=end)
+}
+
+pub fn line(pc: u16, inst: &Instruction, before: &Registers, after: &Registers) -> String {
+    let (x, y) = (inst.x() as usize, inst.y() as usize);
+    let (reads, reads_i): (&[usize], bool) = match inst.indicator() {
+        0x3 | 0x4 | 0x6 | 0x7 | 0xC | 0xE => (&[x], false),
+        0x5 | 0x8 | 0x9 => (&[x, y], false),
+        0xA => (&[], true),
+        0xB => (&[0], false),
+        0xD => (&[x, y], true),
+        0xF => (&[x], true),
+        _ => (&[], false),
+    };
+
+    let mut registers = Vec::new();
+    for (reg, (was, is)) in before.v.iter().zip(&after.v).enumerate() {
+        if was != is {
+            registers.push(format!("V{reg:X} {was:02X}>{is:02X}"));
+        } else if reads.contains(&reg) {
+            registers.push(format!("V{reg:X}={was:02X}"));
+        }
+    }
+    if before.i != after.i {
+        registers.push(format!("I {:03X}>{:03X}", before.i, after.i));
+    } else if reads_i {
+        registers.push(format!("I={:03X}", before.i));
+    }
+
+    let mnemonic = disasm::mnemonic(inst);
+    format!(
+        "{pc:03X}  {:04X}  {mnemonic:<24} {}",
+        inst.opcode(),
+        registers.join(" ")
+    )
+    .trim_end()
+    .to_owned()
+}
+
+#[cfg(feature = "std")]
+fn registers_json(registers: &Registers) -> String {
+    let v: Vec<String> = registers.v.iter().map(u8::to_string).collect();
+    format!(r#"{{"v":[{}],"i":{}}}"#, v.join(","), registers.i)
+}
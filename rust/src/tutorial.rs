@@ -0,0 +1,112 @@
+// --tutorial: a tiny rom and a walkthrough in the status line that explains
+// what the rom does and the emulator controls, one step at a time. each step
+// waits for the key press or hotkey it asks for
+use crate::backend::Hotkey;
+use crate::keymap::Keymap;
+
+// every key toggles a bar in its own column
+pub const TUTORIAL_ROM: [u8; 24] = [
+    0xF0, 0x0A, // 200: V0 = key
+    0x70, 0x18, // 202: V0 += 24, the column
+    0xA2, 0x10, // 204: I = sprite
+    0x61, 0x0C, // 206: V1 = 12
+    0xD0, 0x18, // 208: draw 8 rows at (V0, V1)
+    0x62, 0x03, // 20A: V2 = 3
+    0xF2, 0x18, // 20C: beep for 3 frames
+    0x12, 0x00, // 20E: next key
+    0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, // 210: sprite, a bar
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Goal {
+    AnyKey,
+    // the key that finished AnyKey
+    SameKey,
+    Hotkey(Hotkey),
+}
+
+const STEPS: &[Goal] = &[
+    Goal::AnyKey,
+    Goal::SameKey,
+    Goal::Hotkey(Hotkey::ToggleKeypadOverlay),
+    Goal::Hotkey(Hotkey::TogglePause),
+    Goal::Hotkey(Hotkey::StepFrame),
+    Goal::Hotkey(Hotkey::TogglePause),
+];
+
+#[derive(Clone, Debug, Default)]
+pub struct Walkthrough {
+    step: usize,
+    first_key: Option<u8>,
+    held: [bool; 16],
+}
+
+impl Walkthrough {
+    pub fn new() -> Self {
+        Walkthrough::default()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.step == STEPS.len()
+    }
+
+    // call with every keypad poll, a key counts when it goes down
+    pub fn keypad(&mut self, keypad: &[bool; 16]) {
+        for key in 0..16u8 {
+            let pressed = keypad[key as usize] && !self.held[key as usize];
+            self.held[key as usize] = keypad[key as usize];
+            if !pressed {
+                continue;
+            }
+            match STEPS.get(self.step) {
+                Some(Goal::AnyKey) => {
+                    self.first_key = Some(key);
+                    self.step += 1;
+                }
+                Some(Goal::SameKey) if self.first_key == Some(key) => self.step += 1,
+                _ => {}
+            }
+        }
+    }
+
+    pub fn hotkey(&mut self, hotkey: Hotkey) {
+        if STEPS.get(self.step) == Some(&Goal::Hotkey(hotkey)) {
+            self.step += 1;
+        }
+    }
+
+    // the current step for the status line, with the keys as keymap binds them
+    pub fn text(&self, keymap: &Keymap) -> Option<String> {
+        let goal = STEPS.get(self.step)?;
+        let bound = |hotkey: Hotkey| {
+            keymap
+                .hotkeys
+                .iter()
+                .find(|(_, bound)| *bound == hotkey)
+                .map_or("?", |(key, _)| key.as_str())
+                .to_owned()
+        };
+        let text = match goal {
+            Goal::AnyKey => "press a key: FX0A waits for it, DXYN draws a bar".to_owned(),
+            Goal::SameKey => {
+                let key = self.first_key.unwrap_or(0) as usize;
+                format!(
+                    "press {key:X} ({}) again: sprites are xored, it goes away",
+                    keymap.keypad[key]
+                )
+            }
+            Goal::Hotkey(Hotkey::ToggleKeypadOverlay) => format!(
+                "press {} to see where the 16 keys are",
+                bound(Hotkey::ToggleKeypadOverlay)
+            ),
+            Goal::Hotkey(Hotkey::StepFrame) => {
+                format!("press {} to run a single frame", bound(Hotkey::StepFrame))
+            }
+            Goal::Hotkey(hotkey) if self.step + 1 == STEPS.len() => {
+                format!("press {} to resume, that's all", bound(*hotkey))
+            }
+            Goal::Hotkey(hotkey) => format!("press {} to pause the cpu", bound(*hotkey)),
+        };
+        Some(format!("{}/{} {text}", self.step + 1, STEPS.len()))
+    }
+}
@@ -0,0 +1,97 @@
+// exports for the browser frontend in web/, the js side owns the frame loop
+use crate::chip8::{Chip8State, Event};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+static STATE: Mutex<Option<Chip8State>> = Mutex::new(None);
+static ROM: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+// what the last chip8_step queued, for chip8_next_event
+static EVENTS: Mutex<VecDeque<Event>> = Mutex::new(VecDeque::new());
+
+fn with_state<T>(f: impl FnOnce(&mut Chip8State) -> T) -> T {
+    let mut state = STATE.lock().unwrap();
+    f(state.get_or_insert_with(|| Chip8State::with_seed(0)))
+}
+
+// js copies the rom bytes into this buffer before calling chip8_load
+#[unsafe(no_mangle)]
+pub extern "C" fn chip8_rom_buffer(len: usize) -> *mut u8 {
+    let mut rom = ROM.lock().unwrap();
+    *rom = vec![0; len];
+    rom.as_mut_ptr()
+}
+
+// wasm32-unknown-unknown has no clock, so the seed comes from js
+#[unsafe(no_mangle)]
+pub extern "C" fn chip8_load(seed: u32) -> i32 {
+    let rom = ROM.lock().unwrap();
+    let mut state = Chip8State::with_seed(seed);
+    if state.load(&rom).is_err() {
+        return -1;
+    }
+    state.set_event_queue(true);
+    *STATE.lock().unwrap() = Some(state);
+    EVENTS.lock().unwrap().clear();
+    0
+}
+
+// called once per animation frame, so timers tick at the display rate.
+// returns -1 once the core stopped on an error
+#[unsafe(no_mangle)]
+pub extern "C" fn chip8_step(cycles: u32) -> i32 {
+    with_state(|state| {
+        let result = state.run_frame(cycles as usize);
+        EVENTS.lock().unwrap().extend(state.take_events());
+        match result {
+            Ok(()) => 0,
+            Err(_) => -1,
+        }
+    })
+}
+
+// the events of the steps so far one at a time, -1 once there are none:
+// 0 display updated, 1 sound on, 2 sound off, 3 halted, 4 breakpoint,
+// 5 state loaded
+#[unsafe(no_mangle)]
+pub extern "C" fn chip8_next_event() -> i32 {
+    match EVENTS.lock().unwrap().pop_front() {
+        None => -1,
+        Some(Event::DisplayUpdated) => 0,
+        Some(Event::Sound(true)) => 1,
+        Some(Event::Sound(false)) => 2,
+        Some(Event::Halted(_)) => 3,
+        Some(Event::Breakpoint(_)) => 4,
+        Some(Event::StateLoaded) => 5,
+    }
+}
+
+// packed rows of chip8_display_words_per_row u64 each, msb is the leftmost pixel
+#[unsafe(no_mangle)]
+pub extern "C" fn chip8_display() -> *const u64 {
+    with_state(|state| state.display.words().as_ptr())
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn chip8_display_width() -> u32 {
+    with_state(|state| state.display.width() as u32)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn chip8_display_height() -> u32 {
+    with_state(|state| state.display.height() as u32)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn chip8_display_words_per_row() -> u32 {
+    with_state(|state| state.display.words_per_row() as u32)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn chip8_key_down(key: u8) {
+    with_state(|state| state.keypad[(key & 0xF) as usize] = true);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn chip8_key_up(key: u8) {
+    with_state(|state| state.keypad[(key & 0xF) as usize] = false);
+}
@@ -0,0 +1,88 @@
+// --watch: follows a rom file, or a build directory and picks up the most
+// recently written rom in it, so every rebuild restarts the emulator on it
+use crate::error::Chip8Error;
+use crate::rom::{is_rom_file, load_rom_with_limit};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct RomWatcher {
+    // a rom file or a directory of them
+    target: PathBuf,
+    // the largest rom the machine has room for, see load_rom_with_limit
+    max_size: usize,
+    last_poll: Option<Instant>,
+    // path and modification time of the rom handed out last
+    current: Option<(PathBuf, SystemTime)>,
+}
+
+impl RomWatcher {
+    pub fn new(target: impl Into<PathBuf>, max_size: usize) -> Self {
+        RomWatcher {
+            target: target.into(),
+            max_size,
+            last_poll: None,
+            current: None,
+        }
+    }
+
+    // the rom when it was written since it was returned last, or the newest
+    // one in the directory when that differs, looked at most every
+    // POLL_INTERVAL
+    pub fn poll(&mut self) -> Option<(PathBuf, Result<Vec<u8>, Chip8Error>)> {
+        if self
+            .last_poll
+            .is_some_and(|last_poll| last_poll.elapsed() < POLL_INTERVAL)
+        {
+            return None;
+        }
+        self.last_poll = Some(Instant::now());
+
+        let newest = if self.target.is_dir() {
+            newest_rom(&self.target)
+        } else {
+            modified(&self.target).map(|time| Some((self.target.clone(), time)))
+        };
+        let newest = match newest {
+            Ok(newest) => newest?,
+            Err(err) => return Some((self.target.clone(), Err(err.into()))),
+        };
+        if self.current.as_ref() == Some(&newest) {
+            return None;
+        }
+
+        let path = newest.0.clone();
+        self.current = Some(newest);
+        let rom = load_rom_with_limit(&path, self.max_size);
+        Some((path, rom))
+    }
+}
+
+fn modified(path: &Path) -> io::Result<SystemTime> {
+    fs::metadata(path)?.modified()
+}
+
+// the .ch8 file in dir with the latest modification time
+pub fn newest_rom(dir: &Path) -> io::Result<Option<(PathBuf, SystemTime)>> {
+    let mut newest: Option<(PathBuf, SystemTime)> = None;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if !is_rom_file(&path) {
+            continue;
+        }
+
+        let metadata = entry.metadata()?;
+        if !metadata.is_file() {
+            continue;
+        }
+        let modified = metadata.modified()?;
+        if newest.as_ref().is_none_or(|(_, time)| modified > *time) {
+            newest = Some((path, modified));
+        }
+    }
+    Ok(newest)
+}
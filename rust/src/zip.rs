@@ -0,0 +1,407 @@
+// roms out of zip archives, "chip8 run collection.zip#pong.ch8". reads the
+// central directory, then inflates or copies the member in memory and
+// checks its crc. zip64, encryption and methods beyond stored and deflate
+// are errors
+use crate::png::crc32;
+use crate::rom::is_rom_file;
+use std::path::Path;
+
+const END_OF_DIRECTORY: u32 = 0x0605_4B50;
+const DIRECTORY_ENTRY: u32 = 0x0201_4B50;
+const LOCAL_HEADER: u32 = 0x0403_4B50;
+const END_OF_DIRECTORY_SIZE: usize = 22;
+const MAX_COMMENT_SIZE: usize = 0xFFFF;
+
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Member {
+    pub name: String,
+    pub size: usize,
+    method: u16,
+    encrypted: bool,
+    compressed_size: usize,
+    crc: u32,
+    offset: usize,
+}
+
+impl Member {
+    fn is_rom(&self) -> bool {
+        !self.name.ends_with('/') && is_rom_file(Path::new(&self.name))
+    }
+}
+
+pub fn is_zip(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+}
+
+// ("collection.zip", Some("pong.ch8")) for collection.zip#pong.ch8, None
+// for anything that isn't a zip
+pub fn split_member(arg: &str) -> Option<(&str, Option<&str>)> {
+    if is_zip(Path::new(arg)) {
+        return Some((arg, None));
+    }
+    let (path, member) = arg.rsplit_once('#')?;
+    is_zip(Path::new(path)).then_some((path, Some(member)))
+}
+
+pub struct ZipArchive<'a> {
+    bytes: &'a [u8],
+    members: Vec<Member>,
+}
+
+impl<'a> ZipArchive<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, String> {
+        let lowest = bytes
+            .len()
+            .saturating_sub(END_OF_DIRECTORY_SIZE + MAX_COMMENT_SIZE);
+        let end = (lowest..=bytes.len().saturating_sub(END_OF_DIRECTORY_SIZE))
+            .rev()
+            .find(|&at| u32_at(bytes, at) == Some(END_OF_DIRECTORY))
+            .ok_or("not a zip archive")?;
+        let count = u16_at(bytes, end + 10).ok_or("truncated zip")?;
+        let start = u32_at(bytes, end + 16).ok_or("truncated zip")?;
+        if count == 0xFFFF || start == 0xFFFF_FFFF {
+            return Err("zip64 archives aren't supported".to_owned());
+        }
+
+        let mut members = Vec::new();
+        let mut at = start as usize;
+        for _ in 0..count {
+            let field = |offset: usize| u32_at(bytes, at + offset).ok_or("truncated zip directory");
+            let short = |offset: usize| u16_at(bytes, at + offset).ok_or("truncated zip directory");
+            if field(0)? != DIRECTORY_ENTRY {
+                return Err("bad zip directory".to_owned());
+            }
+            let name_len = short(28)? as usize;
+            let name = bytes
+                .get(at + 46..at + 46 + name_len)
+                .ok_or("truncated zip directory")?;
+            members.push(Member {
+                name: String::from_utf8_lossy(name).into_owned(),
+                size: field(24)? as usize,
+                method: short(10)?,
+                encrypted: short(8)? & 1 != 0,
+                compressed_size: field(20)? as usize,
+                crc: field(16)?,
+                offset: field(42)? as usize,
+            });
+            at += 46 + name_len + short(30)? as usize + short(32)? as usize;
+        }
+        Ok(ZipArchive { bytes, members })
+    }
+
+    pub fn members(&self) -> &[Member] {
+        &self.members
+    }
+
+    // the member's bytes, at most max of them
+    pub fn extract(&self, member: &Member, max: usize) -> Result<Vec<u8>, String> {
+        let name = &member.name;
+        if member.encrypted {
+            return Err(format!("{name} is encrypted"));
+        }
+        if member.size > max {
+            return Err(format!(
+                "{name}: {} bytes, at most {max} fit in memory",
+                member.size
+            ));
+        }
+        let at = member.offset;
+        let header = |offset: usize| u16_at(self.bytes, at + offset).ok_or("truncated zip");
+        if u32_at(self.bytes, at) != Some(LOCAL_HEADER) {
+            return Err(format!("{name}: bad local header"));
+        }
+        let start = at + 30 + header(26)? as usize + header(28)? as usize;
+        let data = self
+            .bytes
+            .get(start..start + member.compressed_size)
+            .ok_or_else(|| format!("{name}: truncated"))?;
+        let bytes = match member.method {
+            STORED => data.to_vec(),
+            DEFLATED => inflate(data, max).map_err(|err| format!("{name}: {err}"))?,
+            method => {
+                return Err(format!(
+                    "{name}: compression method {method} isn't supported"
+                ));
+            }
+        };
+        if bytes.len() != member.size || crc32(&bytes) != member.crc {
+            return Err(format!("{name}: crc mismatch, the archive is damaged"));
+        }
+        Ok(bytes)
+    }
+
+    // the member named, by its path or its file name, or the only rom in
+    // the archive. several roms and no name is an error that lists them
+    pub fn rom(&self, name: Option<&str>, max: usize) -> Result<Vec<u8>, String> {
+        let member = match name {
+            Some(name) => self
+                .members
+                .iter()
+                .find(|member| member.name == name)
+                .or_else(|| {
+                    self.members
+                        .iter()
+                        .find(|member| Path::new(&member.name).file_name() == Some(name.as_ref()))
+                })
+                .ok_or_else(|| format!("no {name} in the archive"))?,
+            None => {
+                let roms: Vec<&Member> = self
+                    .members
+                    .iter()
+                    .filter(|member| member.is_rom())
+                    .collect();
+                match roms.as_slice() {
+                    [] => return Err("no .ch8 rom in the archive".to_owned()),
+                    [rom] => *rom,
+                    _ => {
+                        let mut err =
+                            format!("{} roms in the archive, pick one with #NAME:", roms.len());
+                        for rom in roms {
+                            err.push_str("\n  ");
+                            err.push_str(&rom.name);
+                        }
+                        return Err(err);
+                    }
+                }
+            }
+        };
+        self.extract(member, max)
+    }
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+// deflate, rfc 1951, decoding one bit at a time like zlib's puff
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+// the order code length code lengths come in
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+const MAX_BITS: usize = 15;
+
+struct Bits<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buffer: u32,
+    count: u32,
+}
+
+impl Bits<'_> {
+    fn take(&mut self, n: u32) -> Result<u32, String> {
+        let mut buffer = self.buffer;
+        while self.count < n {
+            let byte = *self.data.get(self.pos).ok_or("truncated deflate stream")?;
+            self.pos += 1;
+            buffer |= (byte as u32) << self.count;
+            self.count += 8;
+        }
+        self.buffer = buffer >> n;
+        self.count -= n;
+        Ok(buffer & ((1 << n) - 1))
+    }
+}
+
+// canonical codes as the count of each length and the symbols in code order
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0; MAX_BITS + 1];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        let mut offsets = [0; MAX_BITS + 1];
+        for len in 1..MAX_BITS {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Result<u16, String> {
+        let (mut code, mut first, mut index) = (0, 0, 0);
+        for &count in &self.counts[1..] {
+            code |= bits.take(1)? as i32;
+            let count = count as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("bad huffman code".to_owned())
+    }
+}
+
+fn inflate(data: &[u8], max: usize) -> Result<Vec<u8>, String> {
+    let mut bits = Bits {
+        data,
+        pos: 0,
+        buffer: 0,
+        count: 0,
+    };
+    let mut out = Vec::new();
+    loop {
+        let last = bits.take(1)? == 1;
+        match bits.take(2)? {
+            0 => stored(&mut bits, &mut out)?,
+            1 => {
+                let mut lengths = [0; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+                let literals = Huffman::new(&lengths);
+                let distances = Huffman::new(&[5; 30]);
+                codes(&mut bits, &mut out, &literals, &distances, max)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_tables(&mut bits)?;
+                codes(&mut bits, &mut out, &literals, &distances, max)?;
+            }
+            _ => return Err("bad deflate block".to_owned()),
+        }
+        if out.len() > max {
+            return Err(format!("more than {max} bytes"));
+        }
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+fn stored(bits: &mut Bits, out: &mut Vec<u8>) -> Result<(), String> {
+    // the rest of the current byte is padding
+    bits.buffer = 0;
+    bits.count = 0;
+    let header = bits
+        .data
+        .get(bits.pos..bits.pos + 4)
+        .ok_or("truncated deflate stream")?;
+    let len = u16::from_le_bytes([header[0], header[1]]);
+    if len != !u16::from_le_bytes([header[2], header[3]]) {
+        return Err("bad stored block".to_owned());
+    }
+    let start = bits.pos + 4;
+    let block = bits
+        .data
+        .get(start..start + len as usize)
+        .ok_or("truncated deflate stream")?;
+    out.extend_from_slice(block);
+    bits.pos = start + len as usize;
+    Ok(())
+}
+
+fn dynamic_tables(bits: &mut Bits) -> Result<(Huffman, Huffman), String> {
+    let literal_count = bits.take(5)? as usize + 257;
+    let distance_count = bits.take(5)? as usize + 1;
+    let code_count = bits.take(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return Err("bad dynamic block".to_owned());
+    }
+    let mut code_lengths = [0; 19];
+    for &symbol in &CODE_LENGTH_ORDER[..code_count] {
+        code_lengths[symbol] = bits.take(3)? as u8;
+    }
+    let code = Huffman::new(&code_lengths);
+
+    let mut lengths = vec![0; literal_count + distance_count];
+    let mut at = 0;
+    while at < lengths.len() {
+        let symbol = code.decode(bits)?;
+        let (len, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 if at > 0 => (lengths[at - 1], 3 + bits.take(2)? as usize),
+            17 => (0, 3 + bits.take(3)? as usize),
+            18 => (0, 11 + bits.take(7)? as usize),
+            _ => return Err("bad dynamic block".to_owned()),
+        };
+        if at + repeat > lengths.len() {
+            return Err("bad dynamic block".to_owned());
+        }
+        lengths[at..at + repeat].fill(len);
+        at += repeat;
+    }
+    if lengths[256] == 0 {
+        return Err("dynamic block without an end code".to_owned());
+    }
+    Ok((
+        Huffman::new(&lengths[..literal_count]),
+        Huffman::new(&lengths[literal_count..]),
+    ))
+}
+
+fn codes(
+    bits: &mut Bits,
+    out: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+    max: usize,
+) -> Result<(), String> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let index = symbol - 257;
+                if index >= LENGTH_BASE.len() {
+                    return Err("bad length code".to_owned());
+                }
+                let len =
+                    LENGTH_BASE[index] as usize + bits.take(LENGTH_EXTRA[index] as u32)? as usize;
+                let index = distances.decode(bits)? as usize;
+                if index >= DISTANCE_BASE.len() {
+                    return Err("bad distance code".to_owned());
+                }
+                let distance = DISTANCE_BASE[index] as usize
+                    + bits.take(DISTANCE_EXTRA[index] as u32)? as usize;
+                if distance > out.len() {
+                    return Err("distance before the start of the output".to_owned());
+                }
+                // overlapping copies repeat what they just wrote
+                for _ in 0..len {
+                    out.push(out[out.len() - distance]);
+                }
+            }
+        }
+        if out.len() > max {
+            return Err(format!("more than {max} bytes"));
+        }
+    }
+}
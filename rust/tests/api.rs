@@ -0,0 +1,21 @@
+// a frontend outside the crate gets by with the root exports
+use chip8::{Chip8State, Keypad, Quirks, RunState, Settings, UnknownOpcodePolicy};
+
+#[test]
+fn the_root_exports_run_a_program() {
+    let mut settings = Settings::default();
+    settings.unknown_opcode = UnknownOpcodePolicy::Skip;
+    let mut quirks = Quirks::default();
+    quirks.enable("cosmac-vip").unwrap();
+    settings.quirks = quirks;
+
+    let mut state = Chip8State::with_seed(1);
+    state.settings = settings;
+    state.load(&[0x60, 0x2A, 0x12, 0x02]).unwrap();
+    let keypad: Keypad = [false; 16];
+    state.keypad = keypad;
+    state.run_frame(10).unwrap();
+
+    assert_eq!(state.v[0], 0x2A);
+    assert_eq!(state.run_state(), RunState::Halted);
+}
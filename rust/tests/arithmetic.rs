@@ -0,0 +1,93 @@
+// every arithmetic opcode at the edges of a byte: results wrap, flags are
+// set explicitly and VF as the destination ends up holding the flag
+use chip8::{Chip8State, Quirks};
+
+// V0 and V1 set, then one opcode
+fn after(v0: u8, v1: u8, opcode: u16) -> Chip8State {
+    let [high, low] = opcode.to_be_bytes();
+    let mut state = Chip8State::with_seed(1);
+    state.load(&[0x60, v0, 0x61, v1, high, low]).unwrap();
+    for _ in 0..3 {
+        state.cycle().unwrap();
+    }
+    state
+}
+
+fn result(v0: u8, v1: u8, opcode: u16) -> (u8, u8) {
+    let state = after(v0, v1, opcode);
+    (state.v[0], state.v[0xF])
+}
+
+#[test]
+fn add_immediate_wraps_without_a_flag() {
+    assert_eq!(result(0xFF, 0, 0x7001), (0x00, 0));
+    assert_eq!(result(0x80, 0, 0x70FF), (0x7F, 0));
+    assert_eq!(result(0xFE, 0, 0x7001), (0xFF, 0));
+}
+
+#[test]
+fn add_carries() {
+    assert_eq!(result(0xFF, 0x01, 0x8014), (0x00, 1));
+    assert_eq!(result(0xFE, 0x01, 0x8014), (0xFF, 0));
+    assert_eq!(result(0xFF, 0xFF, 0x8014), (0xFE, 1));
+}
+
+#[test]
+fn subtractions_flag_no_borrow() {
+    assert_eq!(result(0x00, 0x01, 0x8015), (0xFF, 0));
+    assert_eq!(result(0x05, 0x05, 0x8015), (0x00, 1));
+    assert_eq!(result(0xFF, 0x00, 0x8015), (0xFF, 1));
+    assert_eq!(result(0x01, 0x00, 0x8017), (0xFF, 0));
+    assert_eq!(result(0x05, 0x05, 0x8017), (0x00, 1));
+}
+
+#[test]
+fn shifts_flag_the_bit_shifted_out() {
+    // VY is shifted by default, V1 here
+    assert_eq!(result(0, 0x01, 0x8016), (0x00, 1));
+    assert_eq!(result(0, 0xFE, 0x8016), (0x7F, 0));
+    assert_eq!(result(0, 0x80, 0x801E), (0x00, 1));
+    assert_eq!(result(0, 0x7F, 0x801E), (0xFE, 0));
+}
+
+#[test]
+fn vf_as_destination_holds_the_flag() {
+    // VF = 0xFF, VF += V1 with V1 = 1
+    let mut state = Chip8State::with_seed(1);
+    state.load(&[0x6F, 0xFF, 0x61, 0x01, 0x8F, 0x14]).unwrap();
+    for _ in 0..3 {
+        state.cycle().unwrap();
+    }
+    assert_eq!(state.v[0xF], 1);
+}
+
+#[test]
+fn logic_leaves_vf_unless_the_vip_quirk() {
+    assert_eq!(result(0xF0, 0x0F, 0x8011).0, 0xFF);
+    assert_eq!(result(0xF0, 0x3C, 0x8012).0, 0x30);
+    assert_eq!(result(0xFF, 0x0F, 0x8013).0, 0xF0);
+    let mut state = Chip8State::with_seed(1);
+    state.settings.quirks = Quirks::COSMAC_VIP;
+    state.load(&[0x6F, 0x05, 0x80, 0x13]).unwrap();
+    state.cycle().unwrap();
+    state.cycle().unwrap();
+    assert_eq!(state.v[0xF], 0);
+}
+
+#[test]
+fn i_wraps_and_vf_stays() {
+    assert_eq!(after(0x02, 0, 0xF01E).i, 0x002);
+    // I += V0 from 0xFFFF with VF = 7
+    let mut state = Chip8State::with_seed(1);
+    state.load(&[0x60, 0x02, 0x6F, 0x07, 0xF0, 0x1E]).unwrap();
+    state.i = 0xFFFF;
+    for _ in 0..3 {
+        state.cycle().unwrap();
+    }
+    assert_eq!((state.i, state.v[0xF]), (0x0001, 7));
+}
+
+#[test]
+fn jumps_with_offset_wrap() {
+    assert_eq!(after(0xFF, 0, 0xBFFF).pc, 0x10FE);
+}
@@ -0,0 +1,61 @@
+// asm takes what disasm prints, plus labels
+use chip8::asm::assemble;
+use chip8::disasm;
+use chip8::rom::{Platform, detect_platform};
+
+#[test]
+fn disassembly_assembles_back_into_the_rom() {
+    let rom = [
+        0x00, 0xE0, 0xA2, 0x2A, 0x60, 0x0C, 0xD0, 0x15, 0x8A, 0xB4, 0xF3, 0x33, 0xF2, 0x65, 0xE1,
+        0x9E, 0x12, 0x00, 0xF0, 0x00, 0x12, 0x34, 0x8F, 0xF9, 0x42,
+    ];
+    let listing: Vec<String> = disasm::disassemble(&rom, 0x200)
+        .into_iter()
+        .map(|line| line.mnemonic)
+        .collect();
+    assert_eq!(assemble(&listing.join("\n"), 0x200).unwrap(), rom);
+}
+
+#[test]
+fn labels_resolve_before_and_after_their_use() {
+    let source = "
+        start:  LD I, sprite   ; forward
+                DRW V0, V1, 5
+                jp start
+        sprite: DB 0xF0, 0b10010000, 144
+    ";
+    assert_eq!(
+        assemble(source, 0x200).unwrap(),
+        [0xA2, 0x06, 0xD0, 0x15, 0x12, 0x00, 0xF0, 0x90, 0x90]
+    );
+}
+
+#[test]
+fn errors_name_the_line() {
+    assert_eq!(
+        assemble("CLS\nJP nowhere", 0x200).unwrap_err(),
+        "line 2: unknown label nowhere"
+    );
+    assert_eq!(
+        assemble("LD V0, 0x100", 0x200).unwrap_err(),
+        "line 1: 0x100 doesn't fit in a byte"
+    );
+    assert!(
+        assemble("MOV V0, V1", 0x200)
+            .unwrap_err()
+            .starts_with("line 1: can't assemble")
+    );
+}
+
+#[test]
+fn platform_is_the_newest_any_opcode_needs() {
+    assert_eq!(detect_platform(&[0x00, 0xE0, 0x12, 0x00]), Platform::Chip8);
+    assert_eq!(
+        detect_platform(&[0x00, 0xFF, 0xD0, 0x10]),
+        Platform::SuperChip
+    );
+    assert_eq!(
+        detect_platform(&[0x00, 0xFF, 0xF0, 0x00, 0x12, 0x34]),
+        Platform::XoChip
+    );
+}
@@ -0,0 +1,33 @@
+// the assertion helpers downstream rom tests use
+mod common;
+
+use chip8::{RunState, assert_state};
+use common::run;
+
+#[test]
+fn passing_checks_are_silent() {
+    // V3 = 0x10, I = 0x20A, draw the 0xF0 below at 0,0, store V3, then halt
+    let state = run(
+        &[
+            0x63, 0x10, 0xA2, 0x0C, 0xD0, 0x01, 0xF3, 0x55, 0x12, 0x08, 0x00, 0x00, 0xF0,
+        ],
+        5,
+    );
+    assert_state!(
+        state,
+        v[3] == 0x10,
+        pc == 0x208,
+        i == 0x20C,
+        memory[0x20F] == 0x10,
+        pixel(3, 0) == on,
+        pixel(4, 0) == off,
+        run_state == RunState::Halted,
+    );
+}
+
+#[test]
+#[should_panic(expected = "v[0]: expected 0x02, got 0x01\n  pc: expected 0x300, got 0x202")]
+fn failures_are_all_reported() {
+    let state = run(&[0x60, 0x01], 1);
+    assert_state!(state, v[0] == 0x02, pc == 0x300, delay_timer == 0);
+}
@@ -0,0 +1,61 @@
+// what a breakpoint hit does depends on its action
+use chip8::breakpoint::{self, BreakAction};
+use chip8::{Chip8State, RunState};
+
+// V0 += 1 in a loop
+const ROM: [u8; 4] = [0x70, 0x01, 0x12, 0x00];
+
+fn looping(action: BreakAction) -> Chip8State {
+    let mut state = Chip8State::with_seed(1);
+    state.load(&ROM).unwrap();
+    state.breakpoints.add(0x202, action);
+    state
+}
+
+#[test]
+fn pause_stops_before_the_instruction_and_resumes_past_it() {
+    let mut state = looping(BreakAction::Pause);
+    state.run_frame(10).unwrap();
+    assert_eq!(state.run_state(), RunState::Paused);
+    assert_eq!((state.pc, state.v[0]), (0x202, 1));
+
+    state.set_paused(false);
+    state.run_frame(3).unwrap();
+    assert_eq!(state.run_state(), RunState::Paused);
+    assert_eq!((state.pc, state.v[0]), (0x202, 2));
+    assert_eq!(state.breakpoints.get(0x202).unwrap().hits, 2);
+}
+
+#[test]
+fn log_and_count_keep_running() {
+    let mut state = looping(BreakAction::Log);
+    state.run_frame(10).unwrap();
+    assert_eq!(state.run_state(), RunState::Running);
+    let log = state.breakpoints.take_log();
+    assert_eq!(log.len(), 5);
+    assert!(
+        log[0].starts_with("break 202 hit 1: V0=01 V1=00"),
+        "{}",
+        log[0]
+    );
+    assert!(state.breakpoints.take_log().is_empty());
+
+    let mut state = looping(BreakAction::Count);
+    state.run_frame(10).unwrap();
+    assert_eq!(state.breakpoints.get(0x202).unwrap().hits, 5);
+    assert!(state.breakpoints.take_log().is_empty());
+}
+
+#[test]
+fn breakpoints_parse_with_an_optional_action() {
+    assert_eq!(breakpoint::parse("2A0"), Some((0x2A0, BreakAction::Pause)));
+    assert_eq!(
+        breakpoint::parse("0x2a0:log"),
+        Some((0x2A0, BreakAction::Log))
+    );
+    assert_eq!(
+        breakpoint::parse("2A0:count"),
+        Some((0x2A0, BreakAction::Count))
+    );
+    assert_eq!(breakpoint::parse("2A0:stop"), None);
+}
@@ -0,0 +1,91 @@
+// the rom list shown when starting without a rom
+use chip8::backend::ui::{self, Menu};
+use chip8::browser::RomBrowser;
+use chip8::recent::{MAX_RECENT, RecentRoms};
+use std::fs;
+
+#[test]
+fn lists_roms_sorted_and_keeps_the_selection() {
+    let dir = std::env::temp_dir().join(format!("chip8-browser-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    for name in ["pong.ch8", "Brix.CH8", "notes.txt", "tetris.ch8"] {
+        fs::write(dir.join(name), [0x12, 0x00]).unwrap();
+    }
+
+    let mut browser = RomBrowser::open(&dir).unwrap();
+    assert_eq!(browser.names(), ["Brix.CH8", "pong.ch8", "tetris.ch8"]);
+    browser.move_selection(-1);
+    assert_eq!(browser.selected(), Some(dir.join("tetris.ch8").as_path()));
+
+    fs::write(dir.join("astro.ch8"), [0x12, 0x00]).unwrap();
+    browser.rescan().unwrap();
+    assert_eq!(browser.selected_index(), 3);
+    browser.move_selection(1);
+    assert_eq!(browser.selected_index(), 0);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn menu_scrolls_to_the_selected_item() {
+    let items: Vec<_> = (0..100).map(|n| format!("rom {n}")).collect();
+    let window = (320, 160);
+    let bars = |selected| {
+        let menu = Menu {
+            title: "roms".to_owned(),
+            items: items.clone(),
+            selected,
+            empty: String::new(),
+        };
+        // the selection bar is the only full width white rectangle
+        ui::menu(window, &menu)
+            .into_iter()
+            .filter(|(rect, gray)| *gray == 255 && rect.w > window.0 / 2)
+            .map(|(rect, _)| rect)
+            .collect::<Vec<_>>()
+    };
+    for selected in [0, 50, 99] {
+        let bars = bars(selected);
+        assert_eq!(bars.len(), 1);
+        assert!(bars[0].y >= 0 && bars[0].y + bars[0].h <= window.1);
+    }
+}
+
+#[test]
+fn recent_roms_persist_and_lead_the_list() {
+    let dir = std::env::temp_dir().join(format!("chip8-recent-{}", std::process::id()));
+    let roms = dir.join("roms");
+    fs::create_dir_all(&roms).unwrap();
+    for name in ["a.ch8", "b.ch8"] {
+        fs::write(roms.join(name), [0x12, 0x00]).unwrap();
+    }
+    let file = dir.join("state").join("recent");
+
+    let mut recent = RecentRoms::load(&file).unwrap();
+    assert!(recent.paths().is_empty());
+    for n in 0..MAX_RECENT + 2 {
+        recent.push(&dir.join(format!("gone-{n}.ch8")));
+    }
+    recent.push(&roms.join("b.ch8"));
+    recent.push(&roms.join("a.ch8"));
+    recent.push(&roms.join("b.ch8"));
+    recent.save().unwrap();
+
+    let recent = RecentRoms::load(&file).unwrap();
+    assert_eq!(recent.paths().len(), MAX_RECENT);
+    assert_eq!(
+        recent.paths()[..2],
+        [roms.join("b.ch8"), roms.join("a.ch8")]
+    );
+
+    // roms that no longer exist are left out of the browser
+    let mut browser = RomBrowser::open(&roms).unwrap();
+    browser.set_recent(recent.paths());
+    assert_eq!(
+        browser.names(),
+        ["recent: b.ch8", "recent: a.ch8", "a.ch8", "b.ch8"]
+    );
+    assert_eq!(browser.selected(), Some(roms.join("b.ch8").as_path()));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,61 @@
+// the roms bundled in the binary
+use chip8::Chip8State;
+use chip8::asm::assemble;
+use chip8::builtin::{BUILTIN_ROMS, builtin_rom};
+
+#[test]
+fn roms_match_their_sources() {
+    for rom in BUILTIN_ROMS {
+        let path = format!(
+            "{}/src/builtin/{}.asm",
+            env!("CARGO_MANIFEST_DIR"),
+            rom.name
+        );
+        let source = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(assemble(&source, 0x200).unwrap(), rom.bytes, "{}", rom.name);
+    }
+}
+
+#[test]
+fn roms_run_and_draw() {
+    for rom in BUILTIN_ROMS {
+        let mut state = Chip8State::with_seed(1);
+        state.load(rom.bytes).unwrap();
+        for _ in 0..60 {
+            state.run_frame(20).unwrap();
+        }
+        assert!(
+            state.display.words().iter().any(|&word| word != 0),
+            "{} drew nothing",
+            rom.name
+        );
+    }
+}
+
+#[test]
+fn opcode_test_ticks_every_check() {
+    const TICK: [u8; 5] = [0x01, 0x02, 0x84, 0x48, 0x30];
+    let mut state = Chip8State::with_seed(1);
+    state.load(builtin_rom("opcodes").unwrap().bytes).unwrap();
+    for _ in 0..60 {
+        state.run_frame(20).unwrap();
+    }
+    // eight marks a row, see src/builtin/opcodes.asm
+    for mark in 0..21 {
+        let (left, top) = (mark % 8 * 8, mark / 8 * 7);
+        let rows: Vec<u8> = (0..5)
+            .map(|row| {
+                (0..8).fold(0, |byte, bit| {
+                    byte << 1 | state.display.get(left + bit, top + row) as u8
+                })
+            })
+            .collect();
+        assert_eq!(rows, TICK, "check {mark}");
+    }
+}
+
+#[test]
+fn roms_are_found_by_name() {
+    assert_eq!(builtin_rom("ibm").unwrap().name, "ibm");
+    assert!(builtin_rom("pong").is_none());
+}
@@ -0,0 +1,101 @@
+use chip8::buzzer::{Buzzer, Waveform};
+use std::time::Duration;
+
+#[test]
+fn default_is_the_old_square_wave() {
+    let period = Buzzer::default().period(44100);
+    assert_eq!(period.len(), 100);
+    assert!(
+        period[..50]
+            .iter()
+            .all(|&sample| sample == 0xC0 - 1 || sample == 0xC0)
+    );
+    assert!(
+        period[50..]
+            .iter()
+            .all(|&sample| sample == 0x40 || sample == 0x41)
+    );
+}
+
+#[test]
+fn list_sets_every_setting() {
+    let mut buzzer = Buzzer::default();
+    buzzer
+        .set_list("wave=triangle, frequency=880,volume=30,duty=25,attack=0,release=40")
+        .unwrap();
+    assert_eq!(buzzer.waveform, Waveform::Triangle);
+    assert_eq!(buzzer.frequency, 880);
+    assert_eq!(buzzer.volume, 30);
+    assert_eq!(buzzer.duty, 25);
+    assert_eq!(buzzer.attack, Duration::ZERO);
+    assert_eq!(buzzer.release, Duration::from_millis(40));
+}
+
+#[test]
+fn bad_settings_are_errors() {
+    let mut buzzer = Buzzer::default();
+    assert!(
+        buzzer
+            .set_list("wave=sawtooth")
+            .unwrap_err()
+            .contains("square, sine, triangle")
+    );
+    assert!(buzzer.set_list("frequency=5").is_err());
+    assert!(buzzer.set_list("volume=101").is_err());
+    assert!(buzzer.set_list("duty=0").is_err());
+    assert!(
+        buzzer
+            .set_list("pitch=3")
+            .unwrap_err()
+            .contains("unknown buzzer setting")
+    );
+    assert!(buzzer.set_list("volume").is_err());
+    assert_eq!(buzzer, Buzzer::default());
+}
+
+#[test]
+fn duty_cycle_moves_the_edge() {
+    let mut buzzer = Buzzer::default();
+    buzzer.set("duty", "25").unwrap();
+    let period = buzzer.period(44100);
+    assert!(period[24] > 0x80);
+    assert!(period[26] < 0x80);
+}
+
+#[test]
+fn silent_at_zero_volume() {
+    let mut buzzer = Buzzer::default();
+    buzzer.set_list("wave=sine,volume=0").unwrap();
+    assert!(buzzer.period(44100).iter().all(|&sample| sample == 0x80));
+}
+
+#[test]
+fn attack_fades_in_and_release_out() {
+    let buzzer = Buzzer::default();
+    let attack = buzzer.attack(44100);
+    let release = buzzer.release(44100);
+    assert!(attack.len() >= 44100 * 5 / 1000);
+    assert!(release.len() >= 44100 * 10 / 1000);
+    assert_eq!(attack[0], 0x80);
+    assert!(release[release.len() - 1].abs_diff(0x80) <= 1);
+
+    assert_eq!(buzzer.envelope(Duration::ZERO, None), 0.0);
+    assert_eq!(buzzer.envelope(Duration::from_millis(5), None), 1.0);
+    assert_eq!(buzzer.envelope(Duration::from_millis(5), Some(0.5)), 0.25);
+    assert_eq!(buzzer.envelope(Duration::from_millis(20), Some(1.0)), 0.0);
+}
+
+#[test]
+fn config_reads_only_its_section() {
+    let mut buzzer = Buzzer::default();
+    buzzer
+        .load_config(
+            "[keys]\nvolume = 0\n\n[buzzer]\n# quieter\nvolume = 20\nwave = sine\n[overrides]\n",
+        )
+        .unwrap();
+    assert_eq!(buzzer.volume, 20);
+    assert_eq!(buzzer.waveform, Waveform::Sine);
+
+    let err = buzzer.load_config("[buzzer]\nvolume = loud\n").unwrap_err();
+    assert!(err.starts_with("line 2:"));
+}
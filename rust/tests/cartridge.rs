@@ -0,0 +1,71 @@
+// octo cartridges give up their source and options
+use chip8::cartridge::{self, OctoOptions};
+use chip8::gif::{self, GifFrame};
+use chip8::palette::{Palette, Rgb};
+use chip8::settings::Quirks;
+
+// a cartridge the way octo packs one, two bits per pixel over two frames
+fn cartridge(json: &str) -> Vec<u8> {
+    let mut payload = (json.len() as u32).to_be_bytes().to_vec();
+    payload.extend_from_slice(json.as_bytes());
+    let mut bits: Vec<u8> = payload
+        .iter()
+        .flat_map(|byte| [byte >> 6, byte >> 4 & 3, byte >> 2 & 3, byte & 3])
+        .collect();
+    bits.resize(2 * 32 * 32, 0);
+    let frames: Vec<GifFrame> = bits
+        .chunks(32 * 32)
+        .map(|pixels| GifFrame {
+            pixels: pixels.to_vec(),
+            delay: 10,
+        })
+        .collect();
+    gif::encode(32, 32, &Palette::default(), &frames)
+}
+
+#[test]
+fn source_and_options_come_out() {
+    let gif = cartridge(
+        r##"{"options":{"tickrate":20,"fillColor":"#FFCC00","backgroundColor":"#996600",
+        "vBlankQuirks":true,"clipQuirks":false,"shiftQuirks":true,"logicQuirks":false},
+        "program":": main\n\tloop again\n"}"##,
+    );
+    let cart = cartridge::parse(&gif).unwrap();
+    assert_eq!(cart.source, ": main\n\tloop again\n");
+    assert_eq!(
+        cart.options,
+        OctoOptions {
+            tickrate: Some(20),
+            colors: [
+                Some(Rgb(0x99, 0x66, 0x00)),
+                Some(Rgb(0xFF, 0xCC, 0x00)),
+                None,
+                None
+            ],
+            vblank: Some(true),
+            clip: Some(false),
+            shift: Some(true),
+            logic: Some(false),
+            ..OctoOptions::default()
+        }
+    );
+
+    let (mut quirks, mut palette, mut ips) = (Quirks::default(), Palette::CLASSIC, 700);
+    cart.options.apply(&mut quirks, &mut palette, &mut ips);
+    assert_eq!(ips, 20 * 60);
+    assert!(quirks.display_wait && quirks.wrap_sprites && quirks.shift_vx);
+    assert!(!quirks.vf_reset);
+    assert_eq!(palette.colors[1], Rgb(0xFF, 0xCC, 0x00));
+    assert_eq!(palette.colors[2], Palette::CLASSIC.colors[2]);
+}
+
+#[test]
+fn a_plain_gif_is_not_a_cartridge() {
+    let frame = GifFrame {
+        pixels: vec![0; 64],
+        delay: 0,
+    };
+    let gif = gif::encode(8, 8, &Palette::default(), &[frame]);
+    assert!(cartridge::parse(&gif).is_err());
+    assert!(cartridge::parse(b"not a gif").is_err());
+}
@@ -0,0 +1,36 @@
+// the machine the integration tests start from: seeded for repeatable
+// randoms, set up, loaded and run a few instructions in
+#![allow(dead_code)]
+use chip8::Chip8State;
+
+pub fn machine(rom: &[u8]) -> Chip8State {
+    machine_with(rom, |_| {})
+}
+
+// setup runs before the load, for settings load reads
+pub fn machine_with(rom: &[u8], setup: impl FnOnce(&mut Chip8State)) -> Chip8State {
+    let mut state = Chip8State::with_seed(1);
+    setup(&mut state);
+    state.load(rom).unwrap();
+    state
+}
+
+pub fn cycles(state: &mut Chip8State, instructions: usize) {
+    for _ in 0..instructions {
+        state.cycle().unwrap();
+    }
+}
+
+pub fn run(rom: &[u8], instructions: usize) -> Chip8State {
+    run_with(rom, instructions, |_| {})
+}
+
+pub fn run_with(
+    rom: &[u8],
+    instructions: usize,
+    setup: impl FnOnce(&mut Chip8State),
+) -> Chip8State {
+    let mut state = machine_with(rom, setup);
+    cycles(&mut state, instructions);
+    state
+}
@@ -0,0 +1,78 @@
+use chip8::compare::{Comparison, GAP, Side};
+use chip8::settings::{DEFAULT_INSTRUCTIONS_PER_SECOND, Quirks};
+
+// V0 = V1 >> 1, or V0 >> 1 with the shift quirk, then a dot at x = V0
+const SHIFT_DOT: &[u8] = &[
+    0x60, 0x08, 0x61, 0x04, 0x80, 0x16, 0xA2, 0x0C, 0xD0, 0x21, 0x12, 0x0A, 0x80,
+];
+
+fn sides(a: &str, b: &str) -> [Side; 2] {
+    [Side::parse(a).unwrap(), Side::parse(b).unwrap()]
+}
+
+#[test]
+fn side_is_quirks_and_a_speed() {
+    let side = Side::parse("cosmac-vip@1200").unwrap();
+    assert_eq!(side.quirks, Quirks::COSMAC_VIP);
+    assert_eq!(side.instructions_per_second, 1200);
+    assert_eq!(side.label, "cosmac-vip@1200");
+
+    let side = Side::parse("shift").unwrap();
+    assert!(side.quirks.shift_vx);
+    assert_eq!(
+        side.instructions_per_second,
+        DEFAULT_INSTRUCTIONS_PER_SECOND
+    );
+
+    assert!(Side::parse("warp").unwrap_err().contains("warp"));
+    assert!(Side::parse("shift@fast").is_err());
+    assert!(Side::parse("shift@0").is_err());
+}
+
+#[test]
+fn same_quirks_never_diverge() {
+    let mut comparison = Comparison::new(SHIFT_DOT, sides("modern", "modern")).unwrap();
+    for _ in 0..10 {
+        comparison.run_frame();
+    }
+    assert_eq!(comparison.frame(), 10);
+    assert_eq!(comparison.diverged(), None);
+    assert_eq!(comparison.status(), "frame 10, identical");
+}
+
+#[test]
+fn first_differing_frame_is_kept() {
+    let mut comparison = Comparison::new(SHIFT_DOT, sides("modern", "shift")).unwrap();
+    for _ in 0..5 {
+        comparison.run_frame();
+    }
+    assert_eq!(comparison.diverged(), Some(1));
+    assert_eq!(comparison.status(), "frame 5, diverged at frame 1");
+
+    let [a, b] = comparison.states();
+    assert!(a.display.get(2, 0) && !a.display.get(4, 0));
+    assert!(b.display.get(4, 0) && !b.display.get(2, 0));
+}
+
+#[test]
+fn screens_sit_side_by_side() {
+    let mut comparison = Comparison::new(SHIFT_DOT, sides("modern", "shift")).unwrap();
+    comparison.run_frame();
+    let screen = comparison.screen();
+    assert_eq!((screen.width(), screen.height()), (64 + GAP + 64, 32));
+    assert!(screen.get(2, 0));
+    assert!(screen.get(64 + GAP + 4, 0));
+    // the gap lights up once they diverged
+    assert!(screen.get(64, 31));
+}
+
+#[test]
+fn a_failing_side_diverges() {
+    // 5XY1 isn't an instruction
+    let rom = [0x51, 0x21, 0x12, 0x00];
+    let mut comparison = Comparison::new(&rom, sides("modern", "modern")).unwrap();
+    comparison.run_frame();
+    assert_eq!(comparison.diverged(), Some(1));
+    assert!(comparison.errors().iter().all(Option::is_some));
+    assert!(comparison.status().contains("modern stopped"));
+}
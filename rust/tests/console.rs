@@ -0,0 +1,128 @@
+// the debug console's peeks and pokes
+use chip8::Chip8State;
+use chip8::console::{DebugCommand, Register};
+use chip8::frame_queue::BackPressure;
+use chip8::handle::Chip8Handle;
+use std::time::{Duration, Instant};
+
+fn paused() -> Chip8State {
+    let mut state = Chip8State::with_seed(1);
+    state.load(&[0x12, 0x02, 0xAB, 0xCD]).unwrap();
+    state.set_paused(true);
+    state
+}
+
+fn run(state: &mut Chip8State, line: &str) -> Result<String, String> {
+    DebugCommand::parse(line)?.apply(state)
+}
+
+#[test]
+fn commands_parse_hex() {
+    assert_eq!(
+        DebugCommand::parse("peek 2A0 8"),
+        Ok(DebugCommand::Peek {
+            addr: 0x2A0,
+            len: 8
+        })
+    );
+    assert_eq!(
+        DebugCommand::parse("poke 0x300 ff 0"),
+        Ok(DebugCommand::Poke {
+            addr: 0x300,
+            bytes: vec![0xFF, 0]
+        })
+    );
+    assert_eq!(
+        DebugCommand::parse("set VA 10"),
+        Ok(DebugCommand::Set(Register::V(0xA), 0x10))
+    );
+    assert!(DebugCommand::parse("poke 300 100").is_err());
+    assert!(DebugCommand::parse("set v10 0").is_err());
+    assert!(DebugCommand::parse("jump").is_err());
+}
+
+#[test]
+fn peeks_show_memory_and_pokes_change_it() {
+    let mut state = paused();
+    assert_eq!(run(&mut state, "peek 200 4").unwrap(), "200  12 02 AB CD");
+    assert_eq!(run(&mut state, "poke 202 00 E0").unwrap(), "202  00 E0");
+    assert_eq!(state.memory[0x202..0x204], [0x00, 0xE0]);
+    assert!(run(&mut state, "peek FFF 2").is_err());
+    assert!(run(&mut state, "poke FFF 1 2").is_err());
+}
+
+#[test]
+fn registers_are_set_by_name() {
+    let mut state = paused();
+    run(&mut state, "set v3 2A").unwrap();
+    run(&mut state, "set i 300").unwrap();
+    run(&mut state, "set pc 202").unwrap();
+    run(&mut state, "set dt 3C").unwrap();
+    assert_eq!(state.v[3], 0x2A);
+    assert_eq!((state.i, state.pc, state.delay_timer), (0x300, 0x202, 0x3C));
+    let shown = run(&mut state, "regs").unwrap();
+    assert!(shown.contains("v3=2A"), "{shown}");
+    assert!(shown.contains("i=300 pc=202 dt=3C"), "{shown}");
+    assert!(run(&mut state, "set v0 100").is_err());
+}
+
+#[test]
+fn a_running_machine_is_only_read() {
+    let mut state = Chip8State::with_seed(1);
+    state.load(&[0x12, 0x02]).unwrap();
+    assert!(run(&mut state, "peek 200 2").is_ok());
+    assert!(run(&mut state, "poke 200 0").is_err());
+    assert_eq!(state.memory[0x200], 0x12);
+}
+
+#[test]
+fn memory_dumps_load_back() {
+    let path = std::env::temp_dir().join(format!("chip8-dump-{}.bin", std::process::id()));
+    let command = format!("dump {}", path.display());
+    assert_eq!(
+        DebugCommand::parse(&command),
+        Ok(DebugCommand::Dump(path.clone()))
+    );
+    assert!(DebugCommand::parse("load").is_err());
+
+    let mut state = paused();
+    run(&mut state, &command).unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), state.memory);
+    state.memory[0x200] = 0;
+    let shown = run(&mut state, &format!("load {}", path.display())).unwrap();
+    assert!(shown.starts_with("4096 bytes from"), "{shown}");
+    assert_eq!(state.memory[0x200], 0x12);
+
+    std::fs::write(&path, [0; 16]).unwrap();
+    assert!(run(&mut state, &format!("load {}", path.display())).is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn handle_hands_results_to_the_frontend() {
+    let handle = Chip8Handle::spawn(paused(), 2, BackPressure::DropOldest, |state, _| {
+        state.run_frame(10)
+    });
+    let peek = DebugCommand::parse("peek 202 2").unwrap();
+    let poke = DebugCommand::parse("poke 1000 1").unwrap();
+    handle.debug(peek);
+    handle.debug(poke);
+
+    let mut messages = Vec::new();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while messages.len() < 2 && Instant::now() < deadline {
+        messages.extend(handle.take_messages());
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    let mut state = paused();
+    assert_eq!(
+        messages,
+        [
+            run(&mut state, "peek 202 2"),
+            run(&mut state, "poke 1000 1")
+        ]
+    );
+    assert!(messages[1].is_err());
+    let (_, result) = handle.shutdown();
+    assert!(result.is_ok());
+}
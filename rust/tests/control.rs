@@ -0,0 +1,93 @@
+// the control socket's requests, by hand and over a unix socket
+#![cfg(all(feature = "network", unix))]
+use chip8::console::DebugCommand;
+use chip8::control::{ControlCommand, ControlServer};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn paths_are_the_rest_of_the_line() {
+    assert_eq!(
+        ControlCommand::parse("load roms/space invaders.ch8"),
+        Ok(ControlCommand::Load(PathBuf::from(
+            "roms/space invaders.ch8"
+        )))
+    );
+    assert_eq!(
+        ControlCommand::parse("  screenshot shot.png "),
+        Ok(ControlCommand::Screenshot(PathBuf::from("shot.png")))
+    );
+    assert_eq!(
+        ControlCommand::parse("save"),
+        Err("save expects a path".to_owned())
+    );
+    assert_eq!(ControlCommand::parse("resume"), Ok(ControlCommand::Resume));
+}
+
+#[test]
+fn pokes_are_the_consoles() {
+    assert_eq!(
+        ControlCommand::parse("poke 300 AB CD"),
+        Ok(ControlCommand::Poke(DebugCommand::Poke {
+            addr: 0x300,
+            bytes: vec![0xAB, 0xCD],
+        }))
+    );
+    assert!(ControlCommand::parse("poke 300").is_err());
+    assert_eq!(
+        ControlCommand::parse("regs"),
+        Err("unknown command regs".to_owned())
+    );
+}
+
+#[test]
+fn clients_get_replies_over_the_socket() {
+    let path = std::env::temp_dir().join(format!("chip8-control-{}.sock", std::process::id()));
+    let server = ControlServer::bind(&path).unwrap();
+    // one that still answers keeps its socket
+    assert!(ControlServer::bind(&path).is_err());
+    let mut stream = UnixStream::connect(&path).unwrap();
+    // the frontend's part
+    thread::spawn(move || {
+        loop {
+            for (command, reply_to) in server.requests() {
+                let reply = match command {
+                    ControlCommand::Pause => "ok\n".to_owned(),
+                    _ => "err not here\n".to_owned(),
+                };
+                let _ = reply_to.send(reply);
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    });
+
+    stream.write_all(b"pause\nbogus\nreset\n").unwrap();
+    let lines: Vec<String> = BufReader::new(stream)
+        .lines()
+        .take(3)
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(lines, ["ok", "err unknown command bogus", "err not here"]);
+}
+
+#[test]
+fn files_in_the_way_are_left_alone() {
+    let path = std::env::temp_dir().join(format!("chip8-control-{}.txt", std::process::id()));
+    std::fs::write(&path, "notes").unwrap();
+    assert!(ControlServer::bind(&path).is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "notes");
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn stale_sockets_are_replaced() {
+    let path =
+        std::env::temp_dir().join(format!("chip8-control-stale-{}.sock", std::process::id()));
+    // bound and dropped without connecting, what a crash leaves behind
+    drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+    let server = ControlServer::bind(&path).unwrap();
+    assert!(UnixStream::connect(server.path()).is_ok());
+}
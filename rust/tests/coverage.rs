@@ -0,0 +1,51 @@
+// coverage tells code from data and reached opcodes from missed ones
+use chip8::Chip8State;
+use chip8::coverage::{self, Region};
+
+#[rustfmt::skip]
+const ROM: [u8; 14] = [
+    0xA2, 0x0C, // 200: I = 20C
+    0xF1, 0x65, // 202: V0, V1 = 20C, 20D
+    0xF1, 0x55, // 204: and back
+    0xA3, 0x00, // 206: I = 300
+    0xF0, 0x33, // 208: bcd of V0 to 300
+    0x12, 0x0A, // 20A: done
+    0x07, 0x2A, // 20C: data
+];
+
+#[test]
+fn code_data_and_opcodes_are_told_apart() {
+    let mut state = Chip8State::with_seed(1);
+    state.load(&ROM).unwrap();
+    state.set_coverage(true);
+    for _ in 0..6 {
+        state.cycle().unwrap();
+    }
+    let coverage = state.coverage().unwrap();
+
+    assert_eq!(
+        coverage.regions(),
+        vec![
+            (0x200, 0x20B, Region::Executed),
+            (0x20C, 0x20D, Region::ReadWritten),
+            (0x300, 0x302, Region::Written),
+        ]
+    );
+    let ran: Vec<u16> = coverage.handlers().iter().copied().collect();
+    assert_eq!(ran, vec![0x1000, 0xA000, 0xF033, 0xF055, 0xF065]);
+    assert!(coverage.missed().any(|key| key == 0xD000));
+    assert_eq!(coverage.unimplemented().count(), 0);
+    assert!(coverage.to_string().contains("5 of"));
+}
+
+#[test]
+fn running_an_opcode_without_a_handler_shows_up() {
+    let mut state = Chip8State::with_seed(1);
+    // 8XY4, which decodes but has no handler of its own yet
+    state.load(&[0x80, 0x14, 0x12, 0x02]).unwrap();
+    state.set_coverage(true);
+    state.cycle().unwrap();
+    let coverage = state.coverage().unwrap();
+    assert_eq!(coverage.unimplemented().collect::<Vec<_>>(), vec![0x8004]);
+    assert!(!coverage::IMPLEMENTED.contains(&0x8004));
+}
@@ -0,0 +1,85 @@
+// crash dumps of programs that died on an error
+use chip8::Chip8State;
+use chip8::chip8::HISTORY_LEN;
+use chip8::crash;
+use chip8::error::Chip8Error;
+
+// LD V3, 0x2A; CALL 206; RET; RET
+const UNDERFLOW: [u8; 8] = [0x63, 0x2A, 0x22, 0x06, 0x00, 0xEE, 0x00, 0xEE];
+
+fn crash(rom: &[u8]) -> (Chip8State, Chip8Error) {
+    let mut state = Chip8State::with_seed(1);
+    state.load(rom).unwrap();
+    for _ in 0..100 {
+        if let Err(err) = state.cycle() {
+            return (state, err);
+        }
+    }
+    panic!("no crash");
+}
+
+#[test]
+fn recent_instructions_end_with_the_failing_one() {
+    let (state, _) = crash(&UNDERFLOW);
+    assert_eq!(
+        state.recent_instructions(),
+        [
+            (0x200, 0x632A),
+            (0x202, 0x2206),
+            (0x206, 0x00EE),
+            (0x204, 0x00EE)
+        ]
+    );
+}
+
+#[test]
+fn history_keeps_the_last_instructions_only() {
+    // LD V0, 1; JP 200
+    let mut state = Chip8State::with_seed(1);
+    state.load(&[0x60, 0x01, 0x12, 0x00]).unwrap();
+    for _ in 0..HISTORY_LEN * 3 + 1 {
+        state.cycle().unwrap();
+    }
+    let recent = state.recent_instructions();
+    assert_eq!(recent.len(), HISTORY_LEN);
+    assert_eq!(recent[0], (0x202, 0x1200));
+    assert_eq!(recent[HISTORY_LEN - 1], (0x200, 0x6001));
+}
+
+#[test]
+fn dump_has_registers_stack_history_and_memory() {
+    let (state, err) = crash(&UNDERFLOW);
+    assert!(crash::is_crash(&err));
+    let dump = crash::dump(&state, &err);
+    assert!(
+        dump.starts_with("error: return without subroutine at 204"),
+        "{dump}"
+    );
+    assert!(dump.contains("v3 2A"), "{dump}");
+    assert!(dump.contains("stack: empty"), "{dump}");
+    assert!(dump.contains("  204: 00EE  RET"), "{dump}");
+    assert!(dump.contains("200  63 2A 22 06 00 EE 00 EE"), "{dump}");
+    // rows of zeroes collapse into a star
+    assert!(dump.contains("\n*\n200  "), "{dump}");
+    assert!(!dump.contains("\n220  "), "{dump}");
+}
+
+#[test]
+fn dumps_are_written_to_new_files() {
+    let dir = std::env::temp_dir().join(format!("chip8-crash-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let (state, err) = crash(&UNDERFLOW);
+    let first = crash::write(&dir, &state, &err).unwrap();
+    let second = crash::write(&dir, &state, &err).unwrap();
+    assert_ne!(first, second);
+    assert_eq!(
+        std::fs::read_to_string(&first).unwrap(),
+        crash::dump(&state, &err)
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn rom_errors_are_no_crashes() {
+    assert!(!crash::is_crash(&Chip8Error::EmptyRom));
+}
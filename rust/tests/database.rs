@@ -0,0 +1,91 @@
+// rom identification against the archive's programs.json
+use chip8::database::{Database, RomInfo, sha1_hex};
+use chip8::settings::{DEFAULT_INSTRUCTIONS_PER_SECOND, Quirks, RomOverrides};
+use chip8::tutorial::TUTORIAL_ROM;
+
+#[test]
+fn sha1_matches_known_digests() {
+    assert_eq!(sha1_hex(b""), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+    assert_eq!(sha1_hex(b"abc"), "a9993e364706816aba3e25717850c26c9cd0d89d");
+    // two blocks of padding
+    assert_eq!(
+        sha1_hex(&[b'a'; 56]),
+        "c2db330f6083854c99d4b5bfb6e8f29f201be699"
+    );
+}
+
+#[test]
+fn the_bundled_copy_knows_the_built_in_roms() {
+    let database = Database::bundled();
+    let info = database.lookup(&TUTORIAL_ROM).unwrap();
+    assert_eq!(info.title, "CHIP-8 tutorial");
+    assert!(database.lookup(&[0x12, 0x00]).is_none());
+}
+
+#[test]
+fn archive_entries_bring_platform_tickrate_and_quirks() {
+    let json = r#"[{
+        "title": "Spacejam!",
+        "authors": ["William Donnelly"],
+        "release": "2015",
+        "roms": {
+            "A9993E364706816ABA3E25717850C26C9CD0D89D": {
+                "platforms": ["superchip", "xochip"],
+                "tickrate": 30,
+                "quirkyPlatforms": {"superchip": {"shift": true, "wrap": false}}
+            }
+        }
+    }]"#;
+    let database = Database::parse(json).unwrap();
+    let info = database.lookup(b"abc").unwrap();
+    assert_eq!(info.byline(), "Spacejam! by William Donnelly");
+    assert_eq!(info.release.as_deref(), Some("2015"));
+    assert_eq!(info.platform(), Some("superchip"));
+    assert_eq!(info.tickrate, Some(30));
+    assert_eq!(
+        info.quirks,
+        [("shift".to_owned(), true), ("wrap".to_owned(), false)]
+    );
+}
+
+#[test]
+fn known_roms_bring_their_platform_quirks_and_speed() {
+    let info = RomInfo {
+        platforms: vec!["originalChip8".to_owned()],
+        tickrate: Some(15),
+        quirks: vec![
+            ("vblank".to_owned(), false),
+            ("shift".to_owned(), true),
+            ("lores".to_owned(), true),
+        ],
+        ..RomInfo::default()
+    };
+    let mut quirks = Quirks::default();
+    let mut ips = DEFAULT_INSTRUCTIONS_PER_SECOND;
+    info.apply(&mut quirks, &mut ips);
+    // cosmac vip, but without the display wait the rom turns off
+    assert!(quirks.key_release);
+    assert!(!quirks.display_wait);
+    assert!(quirks.vf_reset && quirks.shift_vx);
+    assert_eq!(ips, 900);
+    assert_eq!(info.unsupported(), ["lores"]);
+}
+
+#[test]
+fn the_config_sets_quirks_and_speed_per_rom() {
+    let mut overrides = RomOverrides::new();
+    let config = "[keymap]\nreset = R\n\n[rom tetris.ch8]\nquirks = cosmac-vip\nips = 900\n";
+    overrides.load_config(config).unwrap();
+    let tetris = overrides.get("tetris.ch8").unwrap();
+    assert!(tetris.quirks.unwrap().display_wait);
+    assert_eq!(tetris.instructions_per_second, Some(900));
+    assert!(overrides.get("pong.ch8").is_none());
+
+    let err = RomOverrides::new()
+        .load_config("[rom pong.ch8]\nips = fast")
+        .unwrap_err();
+    assert_eq!(
+        err,
+        "line 2: ips expects a number of instructions per second"
+    );
+}
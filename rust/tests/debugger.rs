@@ -0,0 +1,90 @@
+// the debugger panel and its buttons
+#![cfg(feature = "debugger")]
+use chip8::backend::Hotkey;
+use chip8::backend::ui::{
+    debugger_button_at, debugger_buttons, debugger_panel, debugger_panel_lines,
+};
+use chip8::debugger::{DEBUGGER_CODE_ROWS, DebuggerView};
+use chip8::{Chip8State, RunState};
+
+// 200: LD I, 20A; 202: CALL 206; 204: JP 204; 206: LD V3, 7; 208: RET
+const ROM: [u8; 12] = [
+    0xA2, 0x0A, 0x22, 0x06, 0x12, 0x04, 0x63, 0x07, 0x00, 0xEE, 0xAB, 0xCD,
+];
+
+fn in_call() -> Chip8State {
+    let mut state = Chip8State::with_seed(1);
+    state.load(&ROM).unwrap();
+    state.run_frame(2).unwrap();
+    state.set_paused(true);
+    state
+}
+
+#[test]
+fn view_lists_code_around_pc_and_memory_around_i() {
+    let state = in_call();
+    let view = DebuggerView::new(&state);
+    assert_eq!(view.cpu.pc, 0x206);
+    assert!(view.paused);
+    assert_eq!(view.stack, [0x204]);
+    assert_eq!(view.code.len(), DEBUGGER_CODE_ROWS);
+    assert_eq!(view.code[0], (0x202, 0x2206));
+    assert_eq!(view.code[2], (0x206, 0x6307));
+    assert_eq!(view.memory_start, 0x208);
+    assert_eq!(view.memory[2..4], [0xAB, 0xCD]);
+}
+
+#[test]
+fn lines_show_registers_stack_and_disassembly() {
+    let lines = debugger_panel_lines(&DebuggerView::new(&in_call()));
+    assert_eq!(lines[0], "PC 206  I 20A  DT 00 ST 00");
+    assert!(lines[1].starts_with("V0 00 V1 00"));
+    assert_eq!(lines[5], "STACK 204");
+    assert!(lines.iter().any(|line| line.starts_with("206  6307  ")));
+    assert!(
+        lines
+            .iter()
+            .any(|line| line.starts_with("208  00 EE AB CD"))
+    );
+    for line in &lines {
+        assert!(line.len() <= 28, "{line}");
+    }
+}
+
+#[test]
+fn panel_stays_in_the_window() {
+    let layer = debugger_panel(640, &DebuggerView::new(&in_call()));
+    for (rect, _) in &layer {
+        assert!(rect.x >= 0 && rect.x + rect.w <= 640, "{rect:?}");
+        assert!(rect.y >= 0 && rect.y + rect.h <= 480, "{rect:?}");
+    }
+}
+
+#[test]
+fn clicks_on_buttons_are_hotkeys() {
+    let [(step, ..), (run, _, label), _] = debugger_buttons(640, true);
+    assert_eq!(label, "RUN");
+    let center = |rect: chip8::backend::ui::UiRect| (rect.x + rect.w / 2, rect.y + rect.h / 2);
+    let (x, y) = center(step);
+    assert_eq!(
+        debugger_button_at(640, true, x, y),
+        Some(Hotkey::StepInstruction)
+    );
+    let (x, y) = center(run);
+    assert_eq!(
+        debugger_button_at(640, true, x, y),
+        Some(Hotkey::TogglePause)
+    );
+    assert_eq!(debugger_button_at(640, true, 0, 0), None);
+}
+
+#[test]
+fn stepping_runs_one_instruction() {
+    let mut state = in_call();
+    state.step_instruction().unwrap();
+    assert_eq!(state.v[3], 7);
+    assert_eq!(state.run_state(), RunState::Paused);
+    state.step_instruction().unwrap();
+    assert_eq!(state.pc, 0x204);
+    assert!(DebuggerView::new(&state).stack.is_empty());
+}
@@ -0,0 +1,34 @@
+// the flat decoder, operands come out of the right nibbles
+use chip8::decode::{Alu, Op, decode};
+
+#[test]
+fn operands_are_pulled_out_once() {
+    assert_eq!(decode(0x00E0), Op::Clear);
+    assert_eq!(decode(0x00C3), Op::ScrollDown(3));
+    assert_eq!(decode(0x0A23), Op::Sys);
+    assert_eq!(decode(0x1ABC), Op::Jump(0xABC));
+    assert_eq!(decode(0x3A42), Op::SkipIfEqual { x: 0xA, nn: 0x42 });
+    assert_eq!(decode(0x5230), Op::SkipIfEqualReg { x: 2, y: 3 });
+    assert_eq!(decode(0x9230), Op::SkipIfNotEqualReg { x: 2, y: 3 });
+    assert_eq!(
+        decode(0x8AB6),
+        Op::Alu {
+            op: Alu::ShiftRight,
+            x: 0xA,
+            y: 0xB
+        }
+    );
+    assert_eq!(decode(0xB2F0), Op::JumpOffset { x: 2, nnn: 0x2F0 });
+    assert_eq!(decode(0xD12F), Op::Draw { x: 1, y: 2, n: 0xF });
+    assert_eq!(decode(0xE3A1), Op::SkipIfNotKey(3));
+    assert_eq!(decode(0xF000), Op::LoadLongI);
+    assert_eq!(decode(0xF765), Op::Restore(7));
+    assert_eq!(decode(0xF429), Op::Font(4));
+}
+
+#[test]
+fn unknown_opcodes_keep_their_bits() {
+    for opcode in [0x5231, 0x8008, 0x923F, 0xE000, 0xF100, 0xF0FF] {
+        assert_eq!(decode(opcode), Op::Invalid(opcode), "{opcode:04X}");
+    }
+}
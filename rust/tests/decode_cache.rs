@@ -0,0 +1,69 @@
+// the decode cache, self-modifying programs see their own writes
+use chip8::Chip8State;
+use chip8::savestate;
+
+// 200: LD V0, 1; 202: LD I, 200; 204: LD V0, 70; 206: LD V1, 01
+// 208: LD [I], V1; 20A: JP 200
+// the first pass runs LD V0, 1 and then writes ADD V0, 1 over it
+const SELF_MODIFYING: [u8; 12] = [
+    0x60, 0x01, 0xA2, 0x00, 0x60, 0x70, 0x61, 0x01, 0xF1, 0x55, 0x12, 0x00,
+];
+
+fn cached(rom: &[u8]) -> Chip8State {
+    let mut state = Chip8State::with_seed(1);
+    state.set_decode_cache(true);
+    state.load(rom).unwrap();
+    state
+}
+
+#[test]
+fn writes_over_code_drop_the_cached_instruction() {
+    let mut state = cached(&SELF_MODIFYING);
+    for _ in 0..7 {
+        state.cycle().unwrap();
+    }
+    // 7001 ran instead of the cached 6001
+    assert_eq!(state.pc, 0x202);
+    assert_eq!(state.v[0], 0x71);
+}
+
+#[test]
+fn writes_to_the_second_byte_count_too() {
+    // 200: LD V0, 0; 202: JP 200
+    let mut state = cached(&[0x60, 0x00, 0x12, 0x00]);
+    state.cycle().unwrap();
+    state.write_memory(0x201, 0x2A).unwrap();
+    state.pc = 0x200;
+    state.cycle().unwrap();
+    assert_eq!(state.v[0], 0x2A);
+}
+
+#[test]
+fn loads_and_restores_replace_the_cache() {
+    let mut state = cached(&[0x60, 0x01, 0x12, 0x00]);
+    let before = savestate::save(&state);
+    state.cycle().unwrap();
+    state.reset();
+    state.load(&[0x60, 0x02, 0x12, 0x00]).unwrap();
+    state.cycle().unwrap();
+    assert_eq!(state.v[0], 2);
+
+    savestate::load(&mut state, &before).unwrap();
+    state.cycle().unwrap();
+    assert_eq!(state.v[0], 1);
+}
+
+#[test]
+fn direct_memory_writes_need_an_invalidation() {
+    let mut state = cached(&[0x60, 0x01, 0x12, 0x00]);
+    state.cycle().unwrap();
+    state.memory[0x201] = 0x05;
+    state.pc = 0x200;
+    state.cycle().unwrap();
+    assert_eq!(state.v[0], 1);
+
+    state.invalidate_decode_cache();
+    state.pc = 0x200;
+    state.cycle().unwrap();
+    assert_eq!(state.v[0], 5);
+}
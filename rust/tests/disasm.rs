@@ -0,0 +1,114 @@
+// listings are structured data, the strings are only one way to show them
+use chip8::asm::assemble;
+use chip8::disasm::{self, Line};
+
+#[test]
+fn decodes_instructions_with_their_addresses() {
+    let lines = disasm::disassemble(&[0x00, 0xE0, 0xA2, 0x2A, 0xD0, 0x15, 0x8A, 0xB4], 0x200);
+    let mnemonics: Vec<_> = lines.iter().map(|line| line.mnemonic.as_str()).collect();
+    assert_eq!(
+        mnemonics,
+        ["CLS", "LD I, 0x22A", "DRW V0, V1, 5", "ADD VA, VB"]
+    );
+    assert_eq!(lines[2].address, 0x204);
+    assert_eq!(lines[2].bytes, [0xD0, 0x15]);
+}
+
+#[test]
+fn long_i_and_trailing_bytes_keep_their_size() {
+    let lines = disasm::disassemble(&[0xF0, 0x00, 0x12, 0x34, 0x8F, 0xF9, 0x42], 0x200);
+    assert_eq!(
+        lines,
+        [
+            Line {
+                address: 0x200,
+                bytes: vec![0xF0, 0x00, 0x12, 0x34],
+                mnemonic: "LD I, long 0x1234".to_owned(),
+            },
+            Line {
+                address: 0x204,
+                bytes: vec![0x8F, 0xF9],
+                mnemonic: "DW 0x8FF9".to_owned(),
+            },
+            Line {
+                address: 0x206,
+                bytes: vec![0x42],
+                mnemonic: "DB 0x42".to_owned(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn hexdump_rows_have_addresses_ascii_and_instructions() {
+    let mut rom = vec![0x00, 0xE0, 0x12, 0x00];
+    rom.extend_from_slice(b"HI!");
+    rom.resize(18, 0x41);
+    let lines = disasm::hexdump(&rom, 0x200);
+    let text: Vec<_> = lines.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        text[0],
+        "200  00 E0 12 00 48 49 21 41 41 41 41 41 41 41 41 41  |....HI!AAAAAAAAA|  \
+         CLS; JP 0x200; SNE V8, 0x49; CALL 0x141; SNE V1, 0x41; SNE V1, 0x41; SNE V1, 0x41; SNE V1, 0x41"
+    );
+    assert_eq!(lines[1].address, 0x210);
+    assert_eq!(lines[1].mnemonics, ["SNE V1, 0x41"]);
+    assert!(
+        text[1].starts_with(
+            "210  41 41                                            |AA              |"
+        )
+    );
+}
+
+// 200: LD I, 20A; 202: CALL 206; 204: JP 204; 206: DRW V0, V1, 5; 208: RET;
+// 20A: the sprite of a 0
+const PROGRAM: [u8; 15] = [
+    0xA2, 0x0A, 0x22, 0x06, 0x12, 0x04, 0xD0, 0x15, 0x00, 0xEE, 0xF0, 0x90, 0x90, 0x90, 0xF0,
+];
+
+#[test]
+fn analysis_follows_the_code_and_leaves_sprites_alone() {
+    let analysis = disasm::analyze(&PROGRAM, 0x200);
+    assert_eq!(
+        analysis.code.into_iter().collect::<Vec<_>>(),
+        [0x200, 0x202, 0x204, 0x206, 0x208]
+    );
+    assert_eq!(
+        analysis.labels.into_iter().collect::<Vec<_>>(),
+        [
+            (0x204, "label_204".to_owned()),
+            (0x206, "sub_206".to_owned()),
+            (0x20A, "data_20A".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn source_with_labels_assembles_back_into_the_rom() {
+    let source = disasm::source(&PROGRAM, 0x200);
+    assert_eq!(
+        source,
+        "    LD I, data_20A
+    CALL sub_206
+label_204:
+    JP label_204
+sub_206:
+    DRW V0, V1, 5
+    RET
+data_20A:
+    DB 0xF0, 0x90, 0x90, 0x90, 0xF0
+"
+    );
+    assert_eq!(assemble(&source, 0x200).unwrap(), PROGRAM);
+}
+
+#[test]
+fn skips_reach_both_sides_and_stray_jumps_keep_their_numbers() {
+    // 200: SE V0, 0; 202: JP 207, into the middle of 206; 204: RET;
+    // 206: LD V0, 0x12
+    let rom = [0x30, 0x00, 0x12, 0x07, 0x00, 0xEE, 0x60, 0x12];
+    let source = disasm::source(&rom, 0x200);
+    assert!(source.contains("    JP 0x207\n    RET\n"), "{source}");
+    assert!(source.ends_with("    DB 0x60, 0x12\n"), "{source}");
+    assert_eq!(assemble(&source, 0x200).unwrap(), rom);
+}
@@ -0,0 +1,96 @@
+// the display's own api, without the packing
+use chip8::{Chip8State, Display, Geometry};
+
+#[test]
+fn pixels_are_set_and_read_back() {
+    let mut display = Display::new(Geometry::SCHIP_HIRES);
+    display.set(0, 0, true);
+    display.set(127, 63, true);
+    display.set(64, 1, true);
+    display.set(64, 1, false);
+    assert!(display.get(0, 0) && display.get(127, 63));
+    assert!(!display.get(64, 1));
+    // setting a lit pixel is no collision, toggling is
+    display.set(5, 5, true);
+    display.set(5, 5, true);
+    assert!(display.toggle(5, 5));
+    assert!(!display.get(5, 5));
+}
+
+#[test]
+fn iteration_goes_row_by_row() {
+    let mut display = Display::new(Geometry::new(3, 2));
+    display.set(2, 0, true);
+    display.set(0, 1, true);
+    let pixels: Vec<(usize, usize, bool)> = display.iter_pixels().collect();
+    assert_eq!(
+        pixels,
+        [
+            (0, 0, false),
+            (1, 0, false),
+            (2, 0, true),
+            (0, 1, true),
+            (1, 1, false),
+            (2, 1, false)
+        ]
+    );
+}
+
+#[test]
+fn resizing_blanks_the_screen() {
+    let mut display = Display::new(Geometry::LORES);
+    display.set(3, 3, true);
+    let before = display.generation();
+    display.resize(Geometry::SCHIP_HIRES);
+    assert_eq!((display.width(), display.height()), (128, 64));
+    assert!(display.iter_pixels().all(|(_, _, on)| !on));
+    assert_ne!(display.generation(), before);
+    assert_eq!(display, Display::new(Geometry::SCHIP_HIRES));
+}
+
+#[test]
+fn cls_clears_the_screen() {
+    // draw the row of 8 at 206 to 0, 0, then clear
+    let mut state = Chip8State::with_seed(1);
+    state
+        .load(&[0xA2, 0x06, 0xD0, 0x01, 0x00, 0xE0, 0xFF])
+        .unwrap();
+    state.cycle().unwrap();
+    state.cycle().unwrap();
+    assert!(state.display.get(0, 0));
+    state.cycle().unwrap();
+    assert!(state.display.iter_pixels().all(|(_, _, on)| !on));
+}
+
+#[test]
+fn xo_chip_planes_draw_and_clear_apart() {
+    #[rustfmt::skip]
+    let program = [
+        0xA2, 0x0E, // 200: LD I, 20E
+        0xF3, 0x01, // 202: PLANE 3
+        0xD0, 0x01, // 204: DRW V0, V0, 1, a row for each plane
+        0xF2, 0x01, // 206: PLANE 2
+        0x00, 0xE0, // 208: CLS
+        0xF1, 0x01, // 20A: PLANE 1
+        0xD0, 0x01, // 20C: DRW V0, V0, 1, the first plane's row again
+        0xC0, 0x60, // 20E: the first plane's row, the second's
+    ];
+    let mut state = Chip8State::with_seed(1);
+    state.load(&program).unwrap();
+    let colors =
+        |state: &Chip8State| -> Vec<u8> { (0..4).map(|x| state.display.pixel(x, 0)).collect() };
+    for _ in 0..3 {
+        state.cycle().unwrap();
+    }
+    assert_eq!(colors(&state), [1, 3, 2, 0]);
+    assert_eq!(state.v[0xF], 0);
+    for _ in 0..2 {
+        state.cycle().unwrap();
+    }
+    assert_eq!(colors(&state), [1, 1, 0, 0]);
+    for _ in 0..2 {
+        state.cycle().unwrap();
+    }
+    assert_eq!(colors(&state), [0, 0, 0, 0]);
+    assert_eq!(state.v[0xF], 1);
+}
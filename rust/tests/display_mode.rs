@@ -0,0 +1,68 @@
+// 00FE and 00FF switch the resolution and tell frontends so by way of the
+// change counter
+use chip8::{Chip8State, Geometry};
+
+#[test]
+fn resolution_switches_count_once_each() {
+    let mut state = Chip8State::with_seed(1);
+    // high, high again, low
+    state.load(&[0x00, 0xFF, 0x00, 0xFF, 0x00, 0xFE]).unwrap();
+    let start = state.geometry_changes();
+
+    state.cycle().unwrap();
+    assert_eq!(state.display.geometry(), Geometry::SCHIP_HIRES);
+    assert_eq!(state.geometry_changes(), start + 1);
+    state.cycle().unwrap();
+    assert_eq!(state.geometry_changes(), start + 1);
+    state.cycle().unwrap();
+    assert_eq!(state.display.geometry(), Geometry::LORES);
+    assert_eq!(state.geometry_changes(), start + 2);
+}
+
+#[test]
+fn reset_goes_back_to_the_configured_geometry() {
+    let mut state = Chip8State::with_seed(1);
+    state.load(&[0x00, 0xFF]).unwrap();
+    state.cycle().unwrap();
+    assert_eq!(state.display.geometry(), Geometry::SCHIP_HIRES);
+    let switched = state.geometry_changes();
+
+    state.reset();
+    state.load(&[0x12, 0x00]).unwrap();
+    assert_eq!(state.display.geometry(), Geometry::LORES);
+    assert_eq!(state.geometry_changes(), switched + 1);
+
+    let custom = Geometry::new(64, 48);
+    state.set_geometry(custom);
+    state.reset();
+    state.load(&[0x00, 0xFF]).unwrap();
+    state.cycle().unwrap();
+    state.reset();
+    assert_eq!(state.display.geometry(), custom);
+}
+
+#[test]
+fn hires_roms_start_past_the_vip_patch_on_64x64() {
+    let mut rom = vec![0x12, 0x60];
+    rom.resize(0xC0, 0);
+    // 2C0: a dot at 0, 40, then the hires clear
+    rom.extend_from_slice(&[
+        0x61, 0x28, 0xA2, 0xCA, 0xD0, 0x11, 0x02, 0x30, 0x12, 0xC8, 0x80,
+    ]);
+    let mut state = Chip8State::with_seed(1);
+    state.load(&rom).unwrap();
+    assert_eq!(state.display.geometry(), Geometry::HIRES);
+
+    state.cycle().unwrap();
+    assert_eq!(state.pc, 0x2C0);
+    for _ in 0..3 {
+        state.cycle().unwrap();
+    }
+    assert!(state.display.get(0, 40));
+    state.cycle().unwrap();
+    assert!(!state.display.get(0, 40));
+
+    let mut state = Chip8State::with_seed(1);
+    state.load(&[0x12, 0x00]).unwrap();
+    assert_eq!(state.display.geometry(), Geometry::LORES);
+}
@@ -0,0 +1,20 @@
+// doctor's look at the rom directory
+use chip8::doctor::{Status, check_rom_dir};
+use std::fs;
+
+#[test]
+fn rom_dir_needs_to_exist_and_hold_roms() {
+    let dir = std::env::temp_dir().join(format!("chip8-doctor-{}", std::process::id()));
+    assert_eq!(check_rom_dir(&dir).status, Status::Fail);
+
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("notes.txt"), "").unwrap();
+    assert_eq!(check_rom_dir(&dir).status, Status::Warn);
+
+    fs::write(dir.join("pong.ch8"), [0x12, 0x00]).unwrap();
+    let check = check_rom_dir(&dir);
+    assert_eq!(check.status, Status::Ok);
+    assert!(check.message.ends_with("found 1 .ch8"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,88 @@
+// on_event observers, what they hear and when
+use chip8::breakpoint::BreakAction;
+use chip8::{Chip8State, Event, HaltReason};
+use std::sync::{Arc, Mutex};
+
+fn observed(rom: &[u8]) -> (Chip8State, Arc<Mutex<Vec<Event>>>) {
+    let mut state = Chip8State::with_seed(1);
+    state.load(rom).unwrap();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&events);
+    state.on_event(move |event| sink.lock().unwrap().push(event));
+    (state, events)
+}
+
+#[test]
+fn draws_sound_and_halting_are_announced_once() {
+    // 200: LD I, 20C; 202: DRW V0, V0, 1; 204: LD V1, 2; 206: LD ST, V1
+    // 208: JP 208; 20C: sprite
+    let (mut state, events) = observed(&[
+        0xA2, 0x0C, 0xD0, 0x01, 0x61, 0x02, 0xF1, 0x18, 0x12, 0x08, 0x00, 0x00, 0x80,
+    ]);
+    for _ in 0..8 {
+        state.cycle().unwrap();
+    }
+    state.tick_timers();
+    state.tick_timers();
+    state.tick_timers();
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            Event::DisplayUpdated,
+            Event::Sound(true),
+            Event::Halted(HaltReason::Finished),
+            Event::Sound(false),
+        ]
+    );
+}
+
+#[test]
+fn breakpoints_are_announced_with_their_address() {
+    // 200: LD V0, 1; 202: JP 200
+    let (mut state, events) = observed(&[0x60, 0x01, 0x12, 0x00]);
+    state.breakpoints.add(0x202, BreakAction::Pause);
+    state.run_frame(10).unwrap();
+    assert_eq!(*events.lock().unwrap(), [Event::Breakpoint(0x202)]);
+}
+
+#[test]
+fn observers_outlive_a_reset_until_cleared() {
+    // 200: CLS; 202: JP 200
+    let (mut state, events) = observed(&[0x00, 0xE0, 0x12, 0x00]);
+    state.reset();
+    state.load(&[0x00, 0xE0, 0x12, 0x00]).unwrap();
+    state.cycle().unwrap();
+    assert_eq!(*events.lock().unwrap(), [Event::DisplayUpdated]);
+    state.clear_observers();
+    state.run_frame(10).unwrap();
+    assert_eq!(events.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn the_queue_holds_events_until_taken() {
+    // 200: CLS; 202: CLS; 204: LD V1, 2; 206: LD ST, V1; 208: JP 208
+    let mut state = Chip8State::with_seed(1);
+    state
+        .load(&[0x00, 0xE0, 0x00, 0xE0, 0x61, 0x02, 0xF1, 0x18, 0x12, 0x08])
+        .unwrap();
+    assert!(state.take_events().is_empty());
+
+    state.set_event_queue(true);
+    for _ in 0..5 {
+        state.cycle().unwrap();
+    }
+    // the two clears come as one
+    assert_eq!(
+        state.take_events(),
+        [
+            Event::DisplayUpdated,
+            Event::Sound(true),
+            Event::Halted(HaltReason::Finished)
+        ]
+    );
+    assert!(state.take_events().is_empty());
+
+    let snapshot = state.snapshot();
+    state.restore(snapshot);
+    assert_eq!(state.take_events(), [Event::StateLoaded]);
+}
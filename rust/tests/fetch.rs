@@ -0,0 +1,85 @@
+// roms from stdin and over http
+#![cfg(feature = "network")]
+use chip8::Chip8Error;
+use chip8::fetch::fetch;
+use chip8::rom::read_rom;
+use std::io::{Cursor, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+const ROM: [u8; 4] = [0x60, 0x01, 0x12, 0x02];
+
+// answers each connection with the next response, returns the base url
+fn serve(responses: Vec<Vec<u8>>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for (response, stream) in responses.into_iter().zip(listener.incoming()) {
+            let mut stream = stream.unwrap();
+            let mut request = Vec::new();
+            let mut byte = [0];
+            while !request.ends_with(b"\r\n\r\n") && stream.read(&mut byte).unwrap() == 1 {
+                request.push(byte[0]);
+            }
+            stream.write_all(&response).unwrap();
+        }
+    });
+    url
+}
+
+fn ok(body: &[u8]) -> Vec<u8> {
+    let mut response =
+        format!("HTTP/1.0 200 OK\r\nContent-Length: {}\r\n\r\n", body.len()).into_bytes();
+    response.extend_from_slice(body);
+    response
+}
+
+#[test]
+fn stdin_roms_are_checked_like_files() {
+    assert_eq!(read_rom(Cursor::new(ROM), 4096).unwrap(), ROM);
+    assert!(matches!(
+        read_rom(Cursor::new([]), 4096),
+        Err(Chip8Error::EmptyRom)
+    ));
+    assert!(matches!(
+        read_rom(Cursor::new(vec![0; 100]), 10),
+        Err(Chip8Error::RomTooLarge { size: 11, max: 10 })
+    ));
+}
+
+#[test]
+fn fetches_a_rom() {
+    let url = serve(vec![ok(&ROM)]);
+    assert_eq!(fetch(&format!("{url}/game.ch8"), 4096).unwrap(), ROM);
+}
+
+#[test]
+fn follows_redirects() {
+    let url = serve(vec![
+        b"HTTP/1.1 302 Found\r\nLocation: /roms/game.ch8\r\n\r\n".to_vec(),
+        b"HTTP/1.1 301 Moved\r\nlocation: other.ch8\r\n\r\n".to_vec(),
+        ok(&ROM),
+    ]);
+    assert_eq!(fetch(&format!("{url}/game.ch8"), 4096).unwrap(), ROM);
+}
+
+#[test]
+fn http_errors_and_big_roms_are_errors() {
+    let url = serve(vec![
+        b"HTTP/1.0 404 Not Found\r\n\r\n".to_vec(),
+        ok(&[0; 100]),
+    ]);
+    let err = fetch(&format!("{url}/gone.ch8"), 4096).unwrap_err();
+    assert!(err.ends_with("http 404"), "{err}");
+    let err = fetch(&format!("{url}/big.ch8"), 10).unwrap_err();
+    assert!(
+        err.ends_with("100 bytes, at most 10 fit in memory"),
+        "{err}"
+    );
+}
+
+#[test]
+fn https_points_at_curl() {
+    let err = fetch("https://example.com/game.ch8", 4096).unwrap_err();
+    assert!(err.contains("curl -sL https://example.com/game.ch8 | chip8 run -"));
+}
@@ -0,0 +1,74 @@
+// the c api, called the way a c program would, so unsafe throughout
+#![cfg(feature = "ffi")]
+use chip8::ffi::*;
+use std::ptr;
+use std::slice;
+
+// 200: LD I, 20A; 202: DRW V0, V0, 1; 204: LD V1, 5; 206: LD ST, V1; 208: JP 208
+const ROM: [u8; 11] = [
+    0xA2, 0x0A, 0xD0, 0x01, 0x61, 0x05, 0xF1, 0x18, 0x12, 0x08, 0x80,
+];
+
+#[test]
+fn a_program_draws_into_the_framebuffer() {
+    unsafe {
+        let machine = chip8_new(1);
+        let buffer = chip8_rom_buffer(machine, ROM.len());
+        ptr::copy_nonoverlapping(ROM.as_ptr(), buffer, ROM.len());
+        assert_eq!(chip8_load(machine), 0);
+        // the vip's DXYN ends the frame
+        for _ in 0..2 {
+            assert_eq!(chip8_run_frame(machine, 10), 0);
+        }
+
+        let (width, height) = (chip8_width(machine), chip8_height(machine));
+        assert_eq!((width, height), (64, 32));
+        let pixels = slice::from_raw_parts(chip8_framebuffer(machine), (width * height) as usize);
+        assert_eq!(pixels[0], 1);
+        assert_eq!(pixels.iter().filter(|&&on| on == 1).count(), 1);
+        assert!(chip8_beeping(machine));
+        chip8_free(machine);
+    }
+}
+
+#[test]
+fn roms_too_big_and_null_machines_are_errors() {
+    unsafe {
+        let machine = chip8_new(1);
+        chip8_rom_buffer(machine, 0x1000);
+        assert_eq!(chip8_load(machine), -1);
+        chip8_set_key(machine, 0x1F, true);
+        assert_eq!(chip8_step(machine), 0);
+        chip8_free(machine);
+
+        assert_eq!(chip8_run_frame(ptr::null_mut(), 10), -1);
+        assert!(chip8_framebuffer(ptr::null_mut()).is_null());
+        chip8_free(ptr::null_mut());
+    }
+}
+
+#[test]
+fn loading_starts_a_cosmac_vip_over() {
+    unsafe {
+        // 200: LD VF, 5; 202: OR V0, V1; 204: LD I, 20E; 206: SNE VF, 0;
+        // 208: DRW V0, V0, 1; 20A: JP 20A; 20E: the sprite
+        #[rustfmt::skip]
+        let vf_reset = [
+            0x6F, 0x05, 0x80, 0x11, 0xA2, 0x0E, 0x4F, 0x00, 0xD0, 0x01, 0x12, 0x0A, 0x00, 0x00,
+            0x80,
+        ];
+        let machine = chip8_new(1);
+        for rom in [&[0x12, 0x00][..], &vf_reset] {
+            let buffer = chip8_rom_buffer(machine, rom.len());
+            ptr::copy_nonoverlapping(rom.as_ptr(), buffer, rom.len());
+            assert_eq!(chip8_load(machine), 0);
+            for _ in 0..2 {
+                assert_eq!(chip8_run_frame(machine, 10), 0);
+            }
+        }
+        // only drawn when OR cleared VF, as the vip's did
+        let pixels = slice::from_raw_parts(chip8_framebuffer(machine), 64 * 32);
+        assert_eq!(pixels[0], 1);
+        chip8_free(machine);
+    }
+}
@@ -0,0 +1,39 @@
+// frames broadcast to sinks next to the renderer's queue
+use chip8::Chip8State;
+use chip8::frame_queue::{BackPressure, Frame};
+use chip8::handle::Chip8Handle;
+use std::sync::mpsc;
+use std::time::Duration;
+
+// 200: ADD V0, 1; 202: JP 200
+const COUNTER: [u8; 4] = [0x70, 0x01, 0x12, 0x00];
+
+#[test]
+fn every_sink_sees_the_frames_until_it_goes_away() {
+    let mut state = Chip8State::with_seed(1);
+    state.load(&COUNTER).unwrap();
+    let handle = Chip8Handle::spawn(state, 2, BackPressure::DropOldest, |state, _| {
+        state.run_frame(10)
+    });
+    let (window, shown) = mpsc::sync_channel(64);
+    let (mirror, mirrored) = mpsc::sync_channel(64);
+    handle.add_sink(move |frame: &Frame| window.try_send(frame.display.clone()).is_ok());
+    handle.add_sink(move |frame: &Frame| {
+        !matches!(
+            mirror.try_send(frame.beep),
+            Err(mpsc::TrySendError::Disconnected(_))
+        )
+    });
+
+    let wait = Duration::from_secs(5);
+    shown.recv_timeout(wait).unwrap();
+    assert_eq!(mirrored.recv_timeout(wait), Ok(false));
+    shown.recv_timeout(wait).unwrap();
+    // the mirror hanging up doesn't stop the window's frames
+    drop(mirrored);
+    for _ in 0..3 {
+        shown.recv_timeout(wait).unwrap();
+    }
+    let (_, result) = handle.shutdown();
+    assert!(result.is_ok());
+}
@@ -0,0 +1,58 @@
+// the fuzzing harness finds new handlers, shrinks crashes, and the crashes
+// it found once stay fixed
+use chip8::fuzz::{self, FuzzConfig};
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn minimize_keeps_only_what_fails() {
+    let input: Vec<u8> = (0..200).map(|n| n as u8 | 0x80).collect();
+    // fails while 0x8A follows 0x89 somewhere
+    let fails = |rom: &[u8]| rom.windows(2).any(|pair| pair == [0x89, 0x8A]);
+    assert_eq!(fuzz::minimize(&input, fails), [0x89, 0x8A]);
+}
+
+#[test]
+fn handlers_ignore_operands() {
+    assert_eq!(fuzz::handler(0x8AB4), 0x8004);
+    assert_eq!(fuzz::handler(0xF633), 0xF033);
+    assert_eq!(fuzz::handler(0xD123), 0xD000);
+    assert_eq!(fuzz::handler(0x8ABF), 0x8FFF);
+    assert_eq!(fuzz::handler(0x00C4), 0x00C0);
+}
+
+#[test]
+fn mutations_reach_handlers_the_seed_doesnt() {
+    let seed = vec![0x60, 0x05, 0xA2, 0x00, 0xD0, 0x05, 0x12, 0x00];
+    let seed_coverage = fuzz::execute(&seed, 10).coverage;
+    let config = FuzzConfig {
+        threads: 2,
+        iterations: 2_000,
+        max_frames: 10,
+        seed: 7,
+    };
+    let report = fuzz::fuzz(&[seed], &config);
+    assert_eq!(report.executions, 2_000);
+    assert!(report.coverage.is_superset(&seed_coverage));
+    assert!(report.coverage.len() > seed_coverage.len() + 10);
+    assert!(report.corpus.len() > 1);
+    for crash in &report.crashes {
+        assert_eq!(
+            fuzz::execute(&crash.rom, 10).panic,
+            Some(crash.message.clone())
+        );
+    }
+}
+
+// reproducers saved by `fuzz SEEDS tests/fuzz` once the bug is fixed
+#[test]
+fn saved_crashes_stay_fixed() {
+    let Ok(entries) = fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fuzz")) else {
+        return;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let rom = fs::read(&path).unwrap();
+        let run = fuzz::execute(&rom, fuzz::DEFAULT_MAX_FRAMES);
+        assert_eq!(run.panic, None, "{}", path.display());
+    }
+}
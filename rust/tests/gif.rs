@@ -0,0 +1,199 @@
+// gif clips decode back to the frames they were made from
+use chip8::clip::ClipRecorder;
+use chip8::gif::{self, GifFrame};
+use chip8::palette::Palette;
+use chip8::recording::GifRecorder;
+use chip8::{Chip8State, Display, Geometry};
+
+// (delay, pixels) of every frame, a plain reading of the format
+fn decode(gif: &[u8]) -> (usize, usize, Vec<(u16, Vec<u8>)>) {
+    assert_eq!(&gif[..6], b"GIF89a");
+    let word = |at: usize| u16::from_le_bytes([gif[at], gif[at + 1]]);
+    let (width, height) = (word(6) as usize, word(8) as usize);
+    let mut at = 13 + 3 * (2 << (gif[10] & 7));
+    let mut frames = Vec::new();
+    let mut delay = 0;
+    loop {
+        match gif[at] {
+            0x3B => return (width, height, frames),
+            0x21 => {
+                if gif[at + 1] == 0xF9 {
+                    delay = word(at + 4);
+                }
+                at += 2;
+                while gif[at] != 0 {
+                    at += gif[at] as usize + 1;
+                }
+                at += 1;
+            }
+            0x2C => {
+                at += 10;
+                let min_code_size = gif[at];
+                at += 1;
+                let mut data = Vec::new();
+                while gif[at] != 0 {
+                    data.extend_from_slice(&gif[at + 1..at + 1 + gif[at] as usize]);
+                    at += gif[at] as usize + 1;
+                }
+                at += 1;
+                frames.push((delay, lzw_decode(&data, min_code_size)));
+            }
+            other => panic!("unexpected block {other:#x}"),
+        }
+    }
+}
+
+fn lzw_decode(data: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let mut table: Vec<Vec<u8>> = Vec::new();
+    let mut width = min_code_size + 1;
+    let mut previous: Option<Vec<u8>> = None;
+    let mut out = Vec::new();
+    let (mut bits, mut pending, mut bytes) = (0u32, 0u8, data.iter());
+    loop {
+        while pending < width {
+            bits |= (*bytes.next().expect("missing end code") as u32) << pending;
+            pending += 8;
+        }
+        let code = (bits & ((1 << width) - 1)) as u16;
+        bits >>= width;
+        pending -= width;
+
+        if code == clear {
+            table = (0..clear + 2).map(|c| vec![c as u8]).collect();
+            width = min_code_size + 1;
+            previous = None;
+            continue;
+        }
+        if code == clear + 1 {
+            return out;
+        }
+        let entry = match (table.get(code as usize), &previous) {
+            (Some(entry), _) => entry.clone(),
+            (None, Some(prev)) => [prev.clone(), vec![prev[0]]].concat(),
+            (None, None) => panic!("code {code} before any literal"),
+        };
+        if let Some(prev) = previous {
+            table.push([prev, vec![entry[0]]].concat());
+            if table.len() == 1 << width && width < 12 {
+                width += 1;
+            }
+        }
+        out.extend_from_slice(&entry);
+        previous = Some(entry);
+    }
+}
+
+#[test]
+fn frames_survive_encoding() {
+    // noise overflows the code table and forces clear codes
+    let mut seed = 1u32;
+    let noise: Vec<u8> = (0..256 * 128)
+        .map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            (seed & 3) as u8
+        })
+        .collect();
+    let frames = [
+        GifFrame {
+            pixels: vec![2; 256 * 128],
+            delay: 3,
+        },
+        GifFrame {
+            pixels: noise,
+            delay: 250,
+        },
+    ];
+    let (width, height, decoded) = decode(&gif::encode(256, 128, &Palette::OCTO, &frames));
+    assert_eq!((width, height), (256, 128));
+    let expected: Vec<_> = frames.iter().map(|f| (f.delay, f.pixels.clone())).collect();
+    assert_eq!(decoded, expected);
+}
+
+#[test]
+fn clip_merges_still_frames_and_lights_held_keys() {
+    let mut clip = ClipRecorder::new();
+    let mut display = Display::default();
+    let mut keypad = [false; 16];
+    for _ in 0..60 {
+        clip.push(&display, &keypad);
+    }
+    display.toggle(0, 0);
+    keypad[0x1] = true;
+    for _ in 0..60 {
+        clip.push(&display, &keypad);
+    }
+
+    let (width, height, frames) = decode(&clip.export(&Palette::CLASSIC, Some("pong")));
+    assert_eq!(width, 128);
+    // one still second each, 100 hundredths
+    assert_eq!(
+        frames.iter().map(|(delay, _)| *delay).collect::<Vec<_>>(),
+        [100, 100]
+    );
+    let (_, pixels) = &frames[1];
+    // the lit pixel sits below the caption, key 1 is the first cell of the strip
+    let strip = height - 9;
+    assert_eq!(pixels[(strip - 64) * width], 1);
+    assert_eq!(pixels[(strip + 1) * width + 1], 1);
+    assert_eq!(frames[0].1[(strip + 1) * width + 1], 3);
+}
+
+#[test]
+fn recordings_run_headless_at_native_resolution() {
+    let mut state = Chip8State::with_seed(1);
+    // draw the 0 glyph at 0,0, wait 30 frames, erase it and idle
+    #[rustfmt::skip]
+    state.load(&[
+        0xA2, 0x12, 0xD0, 0x05,
+        0x61, 0x1E, 0xF1, 0x15,
+        0xF2, 0x07, 0x32, 0x00, 0x12, 0x08,
+        0xD0, 0x05, 0x12, 0x10,
+        0xF0, 0x90, 0x90, 0x90, 0xF0,
+    ])
+    .unwrap();
+    let mut recording = GifRecorder::new();
+    for _ in 0..60 {
+        state.run_frame(10).unwrap();
+        recording.push(&state.display, state.emulated_time().frames);
+    }
+    // a resolution switch scales the earlier frames up
+    state.display = Display::new(Geometry::SCHIP_HIRES);
+    state.display.toggle(127, 63);
+    recording.push(&state.display, 61);
+
+    let (width, height, frames) = decode(&recording.encode(&Palette::CLASSIC));
+    assert_eq!((width, height), (128, 64));
+    let delays: Vec<_> = frames.iter().map(|(delay, _)| *delay).collect();
+    assert_eq!(delays.len(), 3);
+    assert_eq!(delays[0], 50);
+    let (_, drawn) = &frames[0];
+    // the top left glyph pixel covers two by two gif pixels
+    assert_eq!([drawn[0], drawn[1], drawn[width], drawn[width + 1]], [1; 4]);
+    assert_eq!(frames[2].1[width * height - 1], 1);
+}
+
+#[test]
+fn the_decoder_reads_back_what_encode_wrote() {
+    // enough noise to fill the code table and clear it
+    let pixels: Vec<u8> = (0u32..128 * 64)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8 & 3)
+        .collect();
+    let frames = [
+        GifFrame {
+            pixels: pixels.clone(),
+            delay: 5,
+        },
+        GifFrame {
+            pixels: vec![1; 128 * 64],
+            delay: 5,
+        },
+    ];
+    let images = gif::decode(&gif::encode(128, 64, &Palette::default(), &frames)).unwrap();
+    assert_eq!(images.len(), 2);
+    assert_eq!((images[0].width, images[0].height), (128, 64));
+    assert_eq!(images[0].pixels, pixels);
+    assert_eq!(images[1].pixels, vec![1; 128 * 64]);
+}
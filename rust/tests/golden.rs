@@ -0,0 +1,69 @@
+// golden screens: the bundled roms against theirs, and how differences and
+// blessing are reported
+use chip8::Settings;
+use chip8::builtin::BUILTIN_ROMS;
+use chip8::golden::{GoldenMode, GoldenRun, check_screen, screen_diff, screen_text};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("chip8-golden-{}", std::process::id()));
+    let _ = fs::remove_dir_all(dir.join(name));
+    dir.join(name)
+}
+
+#[test]
+fn builtin_roms_match_golden_screens() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/builtin");
+    let settings = Settings::default();
+    let run = GoldenRun {
+        instructions_per_frame: 20,
+        ..GoldenRun::default()
+    };
+    for rom in BUILTIN_ROMS {
+        let state = run.run(rom.bytes, &settings).unwrap();
+        let golden = dir.join(format!("{}.txt", rom.name));
+        if let Err(err) = check_screen(&golden, &state.display, GoldenMode::from_env()) {
+            panic!("{}: {err}", rom.name);
+        }
+    }
+}
+
+#[test]
+fn screens_are_rows_of_hashes_and_dots() {
+    let mut state = chip8::Chip8State::with_seed(1);
+    state.display.set(1, 0, true);
+    let text = screen_text(&state.display);
+    assert_eq!(text.lines().count(), 32);
+    assert!(text.starts_with(".#......"));
+    assert!(text.lines().all(|line| line.len() == 64));
+}
+
+#[test]
+fn diffs_name_the_rows_or_sizes_that_differ() {
+    assert_eq!(screen_diff("#.\n..\n", "#.\n..\n"), None);
+    assert_eq!(
+        screen_diff("#.\n..\n", "#.\n.#\n").unwrap(),
+        "row 1:\n  want ..\n  got  .#\n"
+    );
+    assert_eq!(
+        screen_diff("#.\n", "#..\n").unwrap(),
+        "golden screen is 2x1, the screen is 3x1"
+    );
+}
+
+#[test]
+fn blessing_writes_what_checking_then_expects() {
+    let path = scratch("bless").join("screen.txt");
+    let mut state = chip8::Chip8State::with_seed(1);
+    let err = check_screen(&path, &state.display, GoldenMode::Check).unwrap_err();
+    assert!(err.starts_with("missing golden screen"), "{err}");
+
+    check_screen(&path, &state.display, GoldenMode::Bless).unwrap();
+    check_screen(&path, &state.display, GoldenMode::Check).unwrap();
+
+    state.display.set(3, 2, true);
+    let err = check_screen(&path, &state.display, GoldenMode::Check).unwrap_err();
+    assert!(err.contains("CHIP8_BLESS=1"), "{err}");
+    assert!(err.contains("row 2:"), "{err}");
+}
@@ -0,0 +1,55 @@
+// a recorded session replays to the same screen, random numbers included
+use chip8::Chip8State;
+use chip8::input_log::{InputLog, InputReplay};
+
+// waits for a key and draws a random byte at a random spot
+#[rustfmt::skip]
+const ROM: [u8; 12] = [
+    0xF0, 0x0A, 0xC1, 0x3F, 0xC2, 0x1F, 0xA3, 0x00, 0xD1, 0x21, 0x12, 0x00,
+];
+
+fn run(keypad_for: &mut dyn FnMut(u64) -> [bool; 16], log: &mut InputLog) -> Chip8State {
+    let mut state = Chip8State::with_seed(42);
+    state.load(&ROM).unwrap();
+    for _ in 0..300 {
+        let frame = state.emulated_time().frames;
+        state.keypad = keypad_for(frame);
+        log.record(frame, &state.keypad);
+        state.run_frame(10).unwrap();
+    }
+    state
+}
+
+#[test]
+fn replay_reproduces_the_recorded_run() {
+    let mut recorded = InputLog::new();
+    // mashing keys every few frames, some held longer than others
+    let live = run(
+        &mut |frame| std::array::from_fn(|key| (frame + key as u64 * 7) % 23 < (key as u64 % 4)),
+        &mut recorded,
+    );
+    assert!(recorded.events().len() > 50);
+
+    let log = InputLog::parse(&recorded.to_text()).unwrap();
+    assert_eq!(log, recorded);
+    let mut replay = InputReplay::new(&log);
+    let replayed = run(&mut |frame| replay.keypad(frame), &mut InputLog::new());
+    assert_eq!(replayed.display, live.display);
+    assert_eq!(replayed.v, live.v);
+}
+
+#[test]
+fn rejects_broken_logs() {
+    assert!(
+        InputLog::parse("# nothing yet\n\n")
+            .unwrap()
+            .events()
+            .is_empty()
+    );
+    assert!(InputLog::parse("10 5 down\n12 G up").is_err());
+    assert!(InputLog::parse("10 5 sideways").is_err());
+    assert_eq!(
+        InputLog::parse("10 5 down\n4 5 up"),
+        Err("line 2: frame 4 is out of order".to_owned())
+    );
+}
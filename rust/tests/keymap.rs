@@ -0,0 +1,156 @@
+// rebinding keys from the config file and the command line
+use chip8::backend::Hotkey;
+use chip8::doctor::{self, Status};
+use chip8::keymap::{Keymap, auto_keys};
+
+#[test]
+fn config_rebinds_keypad_and_controls() {
+    let mut keymap = Keymap::default();
+    keymap
+        .load_config(
+            "# azerty\n[window]\nscale = 2\n[keymap]\n4 = a\n7 = q\nA = w\npause = Space\nspeed-up = =\n",
+        )
+        .unwrap();
+    assert_eq!(keymap.keypad[0x4], "a");
+    assert_eq!(keymap.keypad[0x7], "q");
+    assert_eq!(keymap.keypad[0xA], "w");
+    assert!(
+        keymap
+            .hotkeys
+            .contains(&("Space".to_owned(), Hotkey::TogglePause))
+    );
+    assert!(
+        !keymap
+            .hotkeys
+            .contains(&("P".to_owned(), Hotkey::TogglePause))
+    );
+    assert!(keymap.hotkeys.contains(&("=".to_owned(), Hotkey::SpeedUp)));
+}
+
+#[test]
+fn bad_config_lines_are_reported() {
+    let mut keymap = Keymap::default();
+    assert_eq!(
+        keymap.load_config("[keymap]\n\nrewind = R\n"),
+        Err("line 3: unknown action rewind".to_owned())
+    );
+    assert!(keymap.set_keypad_layout("1234").is_err());
+}
+
+#[test]
+fn doctor_catches_conflicting_bindings() {
+    assert_eq!(doctor::check_keymap(&Keymap::default()).status, Status::Ok);
+
+    let mut keymap = Keymap::default();
+    keymap.bind("pause", "x").unwrap();
+    assert_eq!(doctor::check_keymap(&keymap).status, Status::Fail);
+
+    let mut keymap = Keymap::default();
+    keymap.bind("reset", "P").unwrap();
+    assert_eq!(doctor::check_keymap(&keymap).status, Status::Fail);
+}
+
+#[test]
+fn gamepad_mapping_can_be_changed_per_rom() {
+    let mut keymap = Keymap::default();
+    keymap
+        .load_config("[gamepad]\na = 6\n[gamepad pong.ch8]\nup = 1\ndown = 4\n")
+        .unwrap();
+
+    let default = keymap.gamepad_for(Some("tetris.ch8"));
+    assert!(default.contains(&("up".to_owned(), 0x2)));
+    assert!(default.contains(&("a".to_owned(), 0x6)));
+
+    let pong = keymap.gamepad_for(Some("pong.ch8"));
+    assert!(pong.contains(&("up".to_owned(), 0x1)));
+    assert!(pong.contains(&("down".to_owned(), 0x4)));
+    assert!(pong.contains(&("a".to_owned(), 0x6)));
+    assert!(!pong.contains(&("up".to_owned(), 0x2)));
+
+    assert!(keymap.bind_button(None, "turbo", "1").is_err());
+    assert!(keymap.bind_button(None, "a", "10").is_err());
+}
+
+#[test]
+fn auto_keys_follow_the_keys_a_game_polls() {
+    let mapping = |polled: &[u8]| auto_keys(polled.iter().map(|key| 1 << key).sum());
+
+    // wasd with 6 as the action key
+    assert_eq!(
+        mapping(&[0x5, 0x7, 0x8, 0x9, 0x6]),
+        [
+            ("Up", 0x5),
+            ("Down", 0x8),
+            ("Left", 0x7),
+            ("Right", 0x9),
+            ("Space", 0x6)
+        ]
+        .map(|(name, key)| (name.to_owned(), key))
+    );
+    // a 4/6 cross without up and down, fire on F
+    assert_eq!(
+        mapping(&[0x4, 0x6, 0xF]),
+        [("Left", 0x4), ("Right", 0x6), ("Space", 0xF)].map(|(name, key)| (name.to_owned(), key))
+    );
+    // pong's left paddle
+    assert_eq!(
+        mapping(&[0x1, 0x4]),
+        [("Up", 0x1), ("Down", 0x4)].map(|(name, key)| (name.to_owned(), key))
+    );
+    // nothing that looks like directions
+    assert!(mapping(&[0xA]).is_empty());
+}
+
+#[test]
+fn modifiers_are_split_off_key_names() {
+    use chip8::backend::{Modifiers, split_modifiers};
+    assert_eq!(split_modifiers("F1"), (Modifiers::default(), "F1"));
+    assert_eq!(
+        split_modifiers("Shift+F1"),
+        (
+            Modifiers {
+                shift: true,
+                ctrl: false
+            },
+            "F1"
+        )
+    );
+    assert_eq!(
+        split_modifiers("Ctrl+Shift+="),
+        (
+            Modifiers {
+                shift: true,
+                ctrl: true
+            },
+            "="
+        )
+    );
+    // the plus key itself
+    assert_eq!(split_modifiers("+"), (Modifiers::default(), "+"));
+    assert_eq!(split_modifiers("Shift++").1, "+");
+}
+
+#[test]
+fn layouts_keep_the_block_and_relabel_it() {
+    let mut keymap = Keymap::default();
+    assert_eq!(keymap.legend()[0xA], 'Z');
+
+    keymap.load_config("[keymap]\nlayout = qwertz\n").unwrap();
+    assert_eq!(keymap.keypad, Keymap::default().keypad);
+    assert_eq!(keymap.legend()[0xA], 'Y');
+
+    keymap.set_layout("azerty").unwrap();
+    let legend = keymap.legend();
+    assert_eq!(
+        [legend[0x4], legend[0x5], legend[0x7], legend[0xA]],
+        ['A', 'Z', 'Q', 'W']
+    );
+    keymap.bind("0", "Space").unwrap();
+    assert_eq!(keymap.legend()[0x0], 'S');
+
+    keymap.set_layout("cosmac").unwrap();
+    assert_eq!(keymap.keypad[0xB], "b");
+    assert_eq!(doctor::check_keymap(&keymap).status, Status::Ok);
+
+    assert!(keymap.set_layout("dvorak").unwrap_err().contains("qwertz"));
+}
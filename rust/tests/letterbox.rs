@@ -0,0 +1,61 @@
+// scaling the display into windows of any shape
+use chip8::backend::{Viewport, dpi_scale, letterbox, letterbox_dpi};
+
+#[test]
+fn default_window_gets_bars_above_and_below() {
+    assert_eq!(
+        letterbox((640, 480), (64, 32)),
+        Viewport {
+            x: 0.0,
+            y: 80.0,
+            width: 640.0,
+            height: 320.0,
+            scale: 10.0
+        }
+    );
+}
+
+#[test]
+fn wide_window_uses_a_whole_scale_and_centers() {
+    let viewport = letterbox((1000, 300), (64, 32));
+    assert_eq!(viewport.scale, 9.0);
+    assert_eq!((viewport.x, viewport.y), (212.0, 6.0));
+    assert_eq!((viewport.width, viewport.height), (576.0, 288.0));
+}
+
+#[test]
+fn tiny_window_shrinks_the_display() {
+    let viewport = letterbox((32, 32), (64, 32));
+    assert_eq!(viewport.scale, 0.5);
+    assert_eq!((viewport.width, viewport.height), (32.0, 16.0));
+    assert_eq!(viewport.y, 8.0);
+}
+
+#[test]
+fn dpi_scale_is_framebuffer_over_window() {
+    assert_eq!(dpi_scale((640, 480), (1280, 960)), 2.0);
+    assert_eq!(dpi_scale((640, 480), (640, 480)), 1.0);
+    // minimized windows report nothing
+    assert_eq!(dpi_scale((0, 0), (0, 0)), 1.0);
+}
+
+#[test]
+fn hidpi_scale_is_whole_in_framebuffer_pixels() {
+    assert_eq!(
+        letterbox_dpi((640, 480), 1.0, (64, 32)),
+        letterbox((640, 480), (64, 32))
+    );
+
+    let viewport = letterbox_dpi((640, 480), 2.0, (64, 32));
+    assert_eq!(viewport.scale, 10.0);
+    assert_eq!((viewport.x, viewport.y), (0.0, 80.0));
+
+    // 150% makes the window 960 framebuffer pixels wide, 15 a chip8 pixel
+    let viewport = letterbox_dpi((640, 480), 1.5, (64, 32));
+    assert_eq!(viewport.scale * 1.5, 15.0);
+
+    // 1.25 over 100 logical pixels is 125 framebuffer pixels, 1 a chip8 pixel
+    let viewport = letterbox_dpi((100, 100), 1.25, (64, 32));
+    assert_eq!(viewport.scale * 1.25, 1.0);
+    assert_eq!(viewport.width * 1.25, 64.0);
+}
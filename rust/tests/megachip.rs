@@ -0,0 +1,171 @@
+use chip8::chip8::Chip8State;
+mod common;
+
+use chip8::decode::{MegaOp, Op, decode};
+use chip8::display::Geometry;
+use chip8::megachip::MEGACHIP_MEMORY_SIZE;
+use chip8::megachip::{Blend, MegaChip, Sample, WIDTH};
+use chip8::{rom, savestate};
+use common::{cycles, machine, machine_with};
+
+#[test]
+fn mega_opcodes_decode() {
+    assert_eq!(decode(0x0010), Op::Mega(MegaOp::Off));
+    assert_eq!(decode(0x0011), Op::Mega(MegaOp::On));
+    assert_eq!(decode(0x0112), Op::Mega(MegaOp::LoadHighI(0x12)));
+    assert_eq!(decode(0x0203), Op::Mega(MegaOp::LoadPalette(3)));
+    assert_eq!(
+        decode(0x0600),
+        Op::Mega(MegaOp::PlaySample { looped: true })
+    );
+    assert_eq!(
+        decode(0x0601),
+        Op::Mega(MegaOp::PlaySample { looped: false })
+    );
+    assert_eq!(decode(0x0700), Op::Mega(MegaOp::StopSample));
+    assert_eq!(decode(0x00B4), Op::Mega(MegaOp::ScrollUp(4)));
+    assert_eq!(Blend::from_mode(4), Blend::Multiply);
+    assert_eq!(Blend::from_mode(9), Blend::Normal);
+}
+
+#[test]
+fn megachip_roms_start_with_0011() {
+    assert!(rom::is_megachip(&[0x00, 0x11, 0x12, 0x00]));
+    assert!(!rom::is_megachip(&[0x00, 0xE0]));
+
+    let mut state = machine_with(&[0x00, 0x11, 0x00, 0x10], |state| state.set_megachip(true));
+    cycles(&mut state, 1);
+    assert_eq!(state.display.geometry(), Geometry::MEGACHIP);
+    assert!(state.megachip().unwrap().active);
+    cycles(&mut state, 1);
+    assert_eq!(state.display.geometry(), Geometry::LORES);
+}
+
+#[test]
+fn sprites_of_palette_indices_show_on_clear() {
+    #[rustfmt::skip]
+    let rom = [
+        0x00, 0x11,             // 200: on
+        0x01, 0x00, 0x02, 0x20, // 202: I = 0x000220
+        0x02, 0x02,             // 206: palette 1 and 2
+        0x03, 0x02,             // 208: sprites 2 wide
+        0x04, 0x01,             // 20A: and 1 high
+        0x09, 0x01,             // 20C: collide with color 1
+        0xA2, 0x28,             // 20E: I = 0x228
+        0xD0, 0x01,             // 210: draw at 0, 0
+        0xD0, 0x01,             // 212: draw again, over color 1
+        0x00, 0xE0,             // 214: show it
+        0x12, 0x16,             // 216: loop
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // 218: padding
+        0xFF, 0xFF, 0x00, 0x00, // 220: red
+        0xFF, 0x00, 0x00, 0xFF, // 224: blue
+        0x01, 0x02,             // 228: the sprite
+    ];
+    let mut state = machine_with(&rom, |state| state.set_megachip(true));
+    cycles(&mut state, 8);
+    assert_eq!(state.v[0xF], 0);
+    cycles(&mut state, 1);
+    assert_eq!(state.v[0xF], 1);
+
+    let megachip = state.megachip().unwrap();
+    assert_eq!(megachip.palette[1], 0xFFFF_0000);
+    assert_eq!(megachip.palette[2], 0xFF00_00FF);
+    // nothing shows before 00E0
+    assert_eq!(megachip.screen()[0], 0xFF00_0000);
+
+    cycles(&mut state, 1);
+    let screen = state.megachip().unwrap().screen();
+    assert_eq!(&screen[..3], &[0xFFFF_0000, 0xFF00_00FF, 0xFF00_0000]);
+    assert!(state.display.get(0, 0) && state.display.get(1, 0));
+    assert!(!state.display.get(2, 0));
+    assert_eq!(screen.len(), WIDTH * 192);
+}
+
+#[test]
+fn savestates_keep_the_megachip_state() {
+    #[rustfmt::skip]
+    let rom = [
+        0x00, 0x11,             // 200: on
+        0x01, 0x00, 0x02, 0x18, // 202: I = 0x000218
+        0x02, 0x02,             // 206: palette 1 and 2
+        0x03, 0x02,             // 208: sprites 2 wide
+        0x04, 0x01,             // 20A: and 1 high
+        0xA2, 0x20,             // 20C: I = 0x220
+        0xD0, 0x01,             // 20E: draw at 0, 0
+        0xA2, 0x22,             // 210: I = 0x222
+        0x06, 0x00,             // 212: loop the sample
+        0x00, 0xE0,             // 214: show it
+        0x12, 0x16,             // 216: loop
+        0xFF, 0xFF, 0x00, 0x00, // 218: red
+        0xFF, 0x00, 0x00, 0xFF, // 21C: blue
+        0x01, 0x02,             // 220: the sprite
+        0x1F, 0x40, 0x00, 0x00, 0x02, 0x00, // 222: 8000 Hz, 2 samples
+        0x80, 0xFF,
+    ];
+    let mut state = machine_with(&rom, |state| state.set_megachip(true));
+    cycles(&mut state, 9);
+    let bytes = savestate::save(&state);
+
+    let mut restored = Chip8State::with_seed(9);
+    savestate::load(&mut restored, &bytes).unwrap();
+    assert_eq!(restored.memory_size(), MEGACHIP_MEMORY_SIZE);
+    assert_eq!(restored.snapshot(), state.snapshot());
+    let megachip = restored.megachip().unwrap();
+    assert_eq!(megachip.palette[1], 0xFFFF_0000);
+    assert_eq!(megachip.sample().unwrap().data, [0x80, 0xFF]);
+    assert!(megachip.sample().unwrap().looped);
+    assert_eq!(megachip.screen()[0], 0xFF00_0000);
+
+    // what was drawn before the save shows after it
+    cycles(&mut restored, 1);
+    let screen = restored.megachip().unwrap().screen();
+    assert_eq!(&screen[..3], &[0xFFFF_0000, 0xFF00_00FF, 0xFF00_0000]);
+}
+
+#[test]
+fn blending_mixes_with_what_is_drawn() {
+    let mut megachip = MegaChip::default();
+    megachip.palette[1] = 0xFF80_8080;
+    megachip.palette[2] = 0xFF40_2000;
+    megachip.sprite_width = 1;
+    megachip.sprite_height = 1;
+    megachip.draw(0, 0, &[1]);
+    megachip.blend = Blend::Add;
+    megachip.draw(0, 0, &[2]);
+    let mut display = chip8::display::Display::new(Geometry::MEGACHIP);
+    megachip.present(&mut display);
+    assert_eq!(megachip.screen()[0], 0xFFC0_A080);
+}
+
+#[test]
+fn samples_are_read_from_the_long_i() {
+    #[rustfmt::skip]
+    let rom = [
+        0x00, 0x11,             // 200: on
+        0xA2, 0x08,             // 202: I = 0x208
+        0x06, 0x01,             // 204: play once
+        0x12, 0x06,             // 206: loop
+        0x1F, 0x40, 0x00, 0x00, 0x03, 0x00, // 208: 8000 Hz, 3 samples
+        0x80, 0xFF, 0x00,
+    ];
+    let mut state = machine_with(&rom, |state| state.set_megachip(true));
+    cycles(&mut state, 3);
+    let sample = state.megachip().unwrap().sample().unwrap();
+    assert_eq!(
+        **sample,
+        Sample {
+            rate: 8000,
+            data: vec![0x80, 0xFF, 0x00],
+            looped: false,
+        }
+    );
+}
+
+#[test]
+fn mega_opcodes_do_nothing_without_megachip() {
+    let mut state = machine(&[0x00, 0x11, 0x12, 0x02]);
+    cycles(&mut state, 1);
+    assert!(state.megachip().is_none());
+    assert_eq!(state.display.geometry(), Geometry::LORES);
+    assert_eq!(state.pc, 0x202);
+}
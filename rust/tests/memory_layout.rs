@@ -0,0 +1,104 @@
+// where roms load and how much memory there is, set_program_start and
+// set_memory_size
+use chip8::chip8::{ETI_660_PROGRAM_START, MEMORY_SIZE, PROGRAM_START};
+use chip8::frame_queue::BackPressure;
+use chip8::handle::Chip8Handle;
+use chip8::savestate;
+use chip8::settings::Machine;
+use chip8::{Chip8Error, Chip8State, WriteProtection};
+use std::time::{Duration, Instant};
+
+// 600: LD V0, 7; 602: LD I, 0x300; 604: LD [I], V0; 606: JP 606
+const ETI_ROM: [u8; 8] = [0x60, 0x07, 0xA3, 0x00, 0xF0, 0x55, 0x16, 0x06];
+
+#[test]
+fn eti_660_roms_load_and_start_at_600() {
+    let mut state = Chip8State::with_seed(1);
+    state.set_program_start(ETI_660_PROGRAM_START);
+    assert_eq!(state.pc, 0x600);
+    state.load(&ETI_ROM).unwrap();
+    assert_eq!(&state.memory[0x600..0x608], ETI_ROM);
+    assert_eq!(state.memory[PROGRAM_START], 0);
+    state.run_frame(3).unwrap();
+    assert_eq!((state.pc, state.v[0]), (0x606, 7));
+
+    // reset starts over where it was set
+    state.reset();
+    assert_eq!((state.pc, state.program_start()), (0x600, 0x600));
+    assert_eq!(
+        Machine::find("eti-660").unwrap().program_start,
+        ETI_660_PROGRAM_START
+    );
+}
+
+#[test]
+fn the_interpreter_area_grows_with_the_program_start() {
+    let mut state = Chip8State::with_seed(1);
+    state.set_program_start(ETI_660_PROGRAM_START);
+    state.settings.write_protection = WriteProtection::Halt;
+    state.load(&ETI_ROM).unwrap();
+    state.run_frame(2).unwrap();
+    assert!(matches!(
+        state.run_frame(1),
+        Err(Chip8Error::ProtectedWrite {
+            pc: 0x604,
+            addr: 0x300
+        })
+    ));
+}
+
+#[test]
+fn roms_fit_between_the_start_and_the_memory_size() {
+    let mut state = Chip8State::with_seed(1);
+    state.set_memory_size(0x800);
+    assert_eq!(state.memory_size(), 0x800);
+    assert!(matches!(
+        state.load(&[0; 0x601]),
+        Err(Chip8Error::RomTooLarge {
+            size: 0x601,
+            max: 0x600
+        })
+    ));
+    state.set_program_start(ETI_660_PROGRAM_START);
+    assert!(matches!(
+        state.load(&[0; 0x201]),
+        Err(Chip8Error::RomTooLarge { max: 0x200, .. })
+    ));
+    state.load(&[0x12, 0x00]).unwrap();
+    // the savestate keeps the size
+    let restored = savestate::decode(&savestate::save(&state)).unwrap();
+    let mut other = Chip8State::with_seed(1);
+    other.restore(restored);
+    assert_eq!(other.memory_size(), 0x800);
+    state.set_memory_size(MEMORY_SIZE);
+    assert!(!state.extended_memory());
+}
+
+#[test]
+fn handle_keeps_running_past_a_rom_that_does_not_fit() {
+    let mut state = Chip8State::with_seed(1);
+    state.set_program_start(ETI_660_PROGRAM_START);
+    state.load(&ETI_ROM).unwrap();
+    let handle = Chip8Handle::spawn(state, 2, BackPressure::DropOldest, |state, _| {
+        state.run_frame(10)
+    });
+    handle.load_rom(vec![0x12; 3000]);
+
+    let mut messages = Vec::new();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while messages.is_empty() && Instant::now() < deadline {
+        messages.extend(handle.take_messages());
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    let too_large = Chip8Error::RomTooLarge {
+        size: 3000,
+        max: MEMORY_SIZE - ETI_660_PROGRAM_START,
+    };
+    assert_eq!(messages, [Err(too_large.to_string())]);
+    assert!(handle.is_running());
+
+    // the program that was running still is
+    let (state, result) = handle.shutdown();
+    assert!(result.is_ok());
+    assert_eq!((state.pc, state.v[0]), (0x606, 7));
+}
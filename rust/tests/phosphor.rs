@@ -0,0 +1,48 @@
+// ghosting: dark pixels fading out over frames
+use chip8::palette::{Palette, Rgb};
+use chip8::phosphor::{Phosphor, blend};
+use chip8::{Display, Geometry};
+
+#[test]
+fn dark_pixels_fade_until_gone() {
+    let mut display = Display::default();
+    let mut phosphor = Phosphor::new(50);
+    display.set(3, 0, true);
+    assert!(phosphor.update(&display));
+    assert_eq!(phosphor.levels()[3], 255);
+    // the sprite erased to be drawn again, the flicker
+    display.set(3, 0, false);
+    let mut fades = Vec::new();
+    while phosphor.update(&display) {
+        fades.push(phosphor.levels()[3]);
+    }
+    assert_eq!(fades, [127, 63, 31, 15, 7, 3, 1, 0]);
+    // nothing left to redraw for
+    assert!(!phosphor.update(&display));
+}
+
+#[test]
+fn ghosts_blend_into_the_background() {
+    let palette = Palette::CLASSIC;
+    assert_eq!(blend(Rgb(0, 0, 0), Rgb(255, 255, 255), 0), Rgb(0, 0, 0));
+    assert_eq!(
+        blend(Rgb(0, 0, 0), Rgb(255, 255, 255), 255),
+        Rgb(255, 255, 255)
+    );
+    assert_eq!(blend(Rgb(200, 0, 0), Rgb(0, 100, 0), 51), Rgb(160, 20, 0));
+
+    let mut display = Display::new(Geometry::LORES);
+    let mut phosphor = Phosphor::new(100);
+    display.set(0, 0, true);
+    phosphor.update(&display);
+    display.set(0, 0, false);
+    phosphor.update(&display);
+    // at most 99 percent, a ghost never stays
+    assert_eq!(phosphor.persistence(), 99);
+    let colors: Vec<Rgb> = phosphor.colors(&palette).take(2).collect();
+    assert_eq!(colors, [Rgb(252, 252, 252), palette.background()]);
+    // a new size drops the ghosts
+    display.resize(Geometry::SCHIP_HIRES);
+    phosphor.update(&display);
+    assert!(phosphor.levels().iter().all(|level| *level == 0));
+}
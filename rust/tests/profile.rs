@@ -0,0 +1,47 @@
+// the profile counts executions per address and per opcode handler
+use chip8::Chip8State;
+use chip8::profile;
+
+// a three instruction loop behind a one time setup
+#[rustfmt::skip]
+const ROM: [u8; 8] = [
+    0x60, 0x03, // 200: V0 = 3
+    0x71, 0x01, // 202: V1 += 1
+    0x81, 0x02, // 204: V1 &= V0
+    0x12, 0x02, // 206: jump 202
+];
+
+#[test]
+fn the_loop_body_is_hot() {
+    let mut state = Chip8State::with_seed(1);
+    state.load(&ROM).unwrap();
+    state.set_profiling(true);
+    for _ in 0..31 {
+        state.cycle().unwrap();
+    }
+    let profile = state.profile().unwrap();
+
+    let addresses = profile.hot_addresses();
+    assert_eq!(addresses.len(), 4);
+    assert_eq!(&addresses[..3], &[(0x202, 10), (0x204, 10), (0x206, 10)]);
+    assert_eq!(addresses[3], (0x200, 1));
+
+    let opcodes = profile.opcode_counts();
+    assert_eq!(
+        opcodes,
+        vec![(0x1000, 10), (0x7000, 10), (0x8002, 10), (0x6000, 1)]
+    );
+    let report = profile.to_string();
+    assert!(report.contains("8XY2"), "{report}");
+    assert!(report.contains("202"), "{report}");
+}
+
+#[test]
+fn handler_keys_read_as_patterns() {
+    assert_eq!(profile::pattern(0x8004), "8XY4");
+    assert_eq!(profile::pattern(0xF033), "FX33");
+    assert_eq!(profile::pattern(0xD000), "DXYN");
+    assert_eq!(profile::pattern(0x00E0), "00E0");
+    assert_eq!(profile::pattern(0x00C0), "00CN");
+    assert_eq!(profile::pattern(0x8FFF), "8???");
+}
@@ -0,0 +1,97 @@
+// quirks and the machine profiles that bundle them
+mod common;
+
+use chip8::settings::Machine;
+use chip8::{MemoryIncrement, Quirks};
+use common::run_with;
+
+#[test]
+fn logic_ops_clear_vf_on_the_vip() {
+    // VF = 1, V0 |= V1
+    let rom = [0x6F, 0x01, 0x80, 0x11];
+    assert_eq!(
+        run_with(&rom, 2, |state| state.settings.quirks = Quirks::MODERN).v[0xF],
+        1
+    );
+    assert_eq!(
+        run_with(&rom, 2, |state| state.settings.quirks = Quirks::COSMAC_VIP).v[0xF],
+        0
+    );
+}
+
+#[test]
+fn shifts_take_vy_unless_the_quirk_says_vx() {
+    // V0 = 1, V1 = 0x80, V0 <<= V1
+    let rom = [0x60, 0x01, 0x61, 0x80, 0x80, 0x1E];
+    let state = run_with(&rom, 3, |state| state.settings.quirks = Quirks::MODERN);
+    assert_eq!((state.v[0], state.v[0xF]), (0x00, 1));
+    let state = run_with(&rom, 3, |state| {
+        state.settings.quirks = Quirks::SUPERCHIP_MODERN
+    });
+    assert_eq!((state.v[0], state.v[0xF]), (0x02, 0));
+}
+
+#[test]
+fn bnnn_jumps_by_v0_or_vx() {
+    // V0 = 2, V3 = 8, B300
+    let rom = [0x60, 0x02, 0x63, 0x08, 0xB3, 0x00];
+    assert_eq!(
+        run_with(&rom, 3, |state| state.settings.quirks = Quirks::MODERN).pc,
+        0x302
+    );
+    assert_eq!(
+        run_with(&rom, 3, |state| state.settings.quirks = Quirks::CHIP_48).pc,
+        0x308
+    );
+}
+
+#[test]
+fn loads_and_stores_move_i_by_the_machine() {
+    // I = 300, store V0..V2
+    let rom = [0xA3, 0x00, 0xF2, 0x55];
+    assert_eq!(
+        run_with(&rom, 2, |state| state.settings.quirks = Quirks::MODERN).i,
+        0x300
+    );
+    assert_eq!(
+        run_with(&rom, 2, |state| state.settings.quirks = Quirks::CHIP_48).i,
+        0x302
+    );
+    assert_eq!(
+        run_with(&rom, 2, |state| state.settings.quirks = Quirks::COSMAC_VIP).i,
+        0x303
+    );
+}
+
+#[test]
+fn presets_and_names_enable_quirks() {
+    let mut quirks = Quirks::default();
+    quirks.enable("chip-48,vf-reset").unwrap();
+    assert_eq!(quirks.memory, MemoryIncrement::ByX);
+    assert!(quirks.shift_vx && quirks.jump_vx && quirks.vf_reset);
+    assert_eq!(quirks.enable("superchip-2"), Err("superchip-2".to_owned()));
+}
+
+#[test]
+fn machines_match_the_quirks_table() {
+    let vip = Machine::find("cosmac-vip").unwrap();
+    assert!(vip.quirks.vf_reset && vip.quirks.display_wait && !vip.quirks.wrap_sprites);
+    let xo = Machine::find("xo-chip").unwrap();
+    assert!(xo.quirks.wrap_sprites && xo.extended_memory);
+    assert_eq!(xo.quirks.memory, MemoryIncrement::ByXPlusOne);
+    let legacy = Machine::find("superchip-legacy").unwrap();
+    assert!(legacy.quirks.display_wait && legacy.quirks.shift_vx);
+    assert!(
+        !Machine::find("superchip-modern")
+            .unwrap()
+            .quirks
+            .display_wait
+    );
+    for machine in Machine::ALL {
+        assert!(
+            Quirks::PRESETS
+                .iter()
+                .any(|(name, quirks)| *name == machine.name && *quirks == machine.quirks)
+        );
+    }
+}
@@ -0,0 +1,104 @@
+// the remote debug protocol, by hand and over a socket
+#![cfg(feature = "network")]
+mod common;
+
+use chip8::Chip8State;
+use chip8::remote::{RemoteCommand, RemoteServer};
+use common::machine;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+
+// 200: LD V0, 1; 202: ADD V0, 1; 204: JP 202
+const ROM: [u8; 6] = [0x60, 0x01, 0x70, 0x01, 0x12, 0x02];
+
+fn run(state: &mut Chip8State, line: &str) -> String {
+    match RemoteCommand::parse(line) {
+        Ok(command) => command.apply(state),
+        Err(err) => format!("err {err}\n"),
+    }
+}
+
+#[test]
+fn steps_run_one_instruction_of_a_paused_machine() {
+    let mut state = machine(&ROM);
+    assert_eq!(run(&mut state, "step"), "err pause the machine first\n");
+    assert_eq!(run(&mut state, "pause"), "ok\n");
+    assert_eq!(
+        run(&mut state, "step"),
+        "paused pc=202 frame=0 cycle=1\nok\n"
+    );
+    assert_eq!(
+        run(&mut state, "step 3"),
+        "paused pc=204 frame=0 cycle=4\nok\n"
+    );
+    assert_eq!(state.v[0], 3);
+}
+
+#[test]
+fn next_runs_to_a_draw() {
+    // 200: ADD V0, 1; 202: SE V0, 9; 204: JP 200; 206: DRW V0, V0, 1; 208: JP 208
+    let mut state = machine(&[0x70, 0x01, 0x30, 0x09, 0x12, 0x00, 0xD0, 0x01, 0x12, 0x08]);
+    assert_eq!(
+        run(&mut state, "next draw"),
+        "err pause the machine first\n"
+    );
+    run(&mut state, "pause");
+    assert!(run(&mut state, "next draw").starts_with("paused pc=208 "));
+    assert_eq!(state.v[0], 9);
+    assert!(
+        run(&mut state, "next sound").starts_with("err stopped before a sound, paused pc=208 ")
+    );
+}
+
+#[test]
+fn breakpoints_pause_and_can_be_stepped_over() {
+    let mut state = machine(&ROM);
+    assert_eq!(run(&mut state, "break 204"), "ok\n");
+    state.run_frame(10).unwrap();
+    assert_eq!(state.pc, 0x204);
+    assert!(run(&mut state, "status").starts_with("paused pc=204"));
+    assert!(run(&mut state, "step").starts_with("paused pc=202"));
+    assert_eq!(run(&mut state, "breaks"), "204 pause 1 hits\nok\n");
+    assert_eq!(run(&mut state, "delete 204"), "ok\n");
+    assert_eq!(run(&mut state, "delete 204"), "err no breakpoint at 204\n");
+}
+
+#[test]
+fn console_commands_go_through() {
+    let mut state = machine(&ROM);
+    run(&mut state, "pause");
+    assert_eq!(run(&mut state, "poke 300 AB"), "300  AB\nok\n");
+    assert_eq!(run(&mut state, "set v5 7"), "v5 = 7\nok\n");
+    assert_eq!(state.v[5], 7);
+    assert!(run(&mut state, "jump").starts_with("err "));
+}
+
+#[test]
+fn clients_get_replies_over_tcp() {
+    let server = RemoteServer::bind("127.0.0.1:0").unwrap();
+    let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+    // the cpu thread's part
+    thread::spawn(move || {
+        let mut state = machine(&ROM);
+        loop {
+            for (command, reply_to) in server.requests() {
+                let _ = reply_to.send(command.apply(&mut state));
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    });
+
+    stream.write_all(b"pause\nbogus\nregs\n").unwrap();
+    let lines: Vec<String> = BufReader::new(stream)
+        .lines()
+        .take(5)
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(lines[0], "ok");
+    assert!(lines[1].starts_with("err "));
+    assert!(lines[2].starts_with("v0=00"));
+    assert!(lines[3].starts_with("i=000 pc=200"));
+    assert_eq!(lines[4], "ok");
+}
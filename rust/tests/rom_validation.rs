@@ -0,0 +1,100 @@
+// load time rom checks and the memory bounds the cpu enforces at runtime
+use chip8::rom::{MAX_EXTENDED_ROM_SIZE, MAX_ROM_SIZE};
+use chip8::{Chip8Error, Chip8State};
+
+#[test]
+fn rejects_empty_rom() {
+    let mut state = Chip8State::with_seed(1);
+    assert!(matches!(state.load(&[]), Err(Chip8Error::EmptyRom)));
+}
+
+#[test]
+fn rejects_oversized_rom() {
+    let mut state = Chip8State::with_seed(1);
+    let rom = vec![0; MAX_ROM_SIZE + 1];
+    assert!(matches!(
+        state.load(&rom),
+        Err(Chip8Error::RomTooLarge {
+            max: MAX_ROM_SIZE,
+            ..
+        })
+    ));
+    assert!(state.load(&rom[..MAX_ROM_SIZE]).is_ok());
+}
+
+#[test]
+fn pc_running_off_the_end_of_memory_is_an_error() {
+    let mut state = Chip8State::with_seed(1);
+    // jump to the last byte, the second half of the opcode is past the end
+    state.load(&[0x1F, 0xFF]).unwrap();
+    state.cycle().unwrap();
+    assert!(matches!(
+        state.cycle(),
+        Err(Chip8Error::MemoryOutOfBounds {
+            pc: 0xFFF,
+            addr: 0x1000
+        })
+    ));
+}
+
+#[test]
+fn i_past_the_end_of_memory_is_an_error() {
+    let mut state = Chip8State::with_seed(1);
+    // I = 0xFFF, then store V0 and V1
+    state.load(&[0xAF, 0xFF, 0xF1, 0x55]).unwrap();
+    state.cycle().unwrap();
+    assert!(matches!(
+        state.cycle(),
+        Err(Chip8Error::MemoryOutOfBounds {
+            pc: 0x202,
+            addr: 0x1000
+        })
+    ));
+}
+
+#[test]
+fn extended_memory_fits_a_64k_rom() {
+    let mut state = Chip8State::with_seed(1);
+    let rom = vec![0; MAX_EXTENDED_ROM_SIZE];
+    assert!(state.load(&rom).is_err());
+    state.set_extended_memory(true);
+    assert!(state.load(&rom).is_ok());
+    state.reset();
+    assert!(state.extended_memory());
+}
+
+#[test]
+fn long_i_reaches_past_4k() {
+    let mut state = Chip8State::with_seed(1);
+    state.set_extended_memory(true);
+    // V0 = 0x2A, I = long 0xFFF0, store V0, then skip over another long I
+    #[rustfmt::skip]
+    let rom = [
+        0x60, 0x2A,
+        0xF0, 0x00, 0xFF, 0xF0,
+        0xF0, 0x55,
+        0x30, 0x2A,
+        0xF0, 0x00, 0x12, 0x34,
+    ];
+    state.load(&rom).unwrap();
+    for _ in 0..4 {
+        state.cycle().unwrap();
+    }
+    assert_eq!(state.memory[0xFFF0], 0x2A);
+    assert_eq!(state.i, 0xFFF0);
+    assert_eq!(state.pc, 0x20E);
+    assert!(state.is_page_dirty(0xFFF0 / chip8::chip8::PAGE_SIZE));
+}
+
+#[test]
+fn long_i_is_invalid_without_extended_memory() {
+    let mut state = Chip8State::with_seed(1);
+    state.load(&[0xF0, 0x00, 0xFF, 0xF0]).unwrap();
+    assert!(matches!(
+        state.cycle(),
+        Err(Chip8Error::InvalidOpcode {
+            pc: 0x200,
+            opcode: 0xF000
+        })
+    ));
+}
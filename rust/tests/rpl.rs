@@ -0,0 +1,62 @@
+// FX75 and FX85 keep their flags per rom across runs
+mod common;
+
+use chip8::rpl::RplStore;
+use common::{machine_with, run_with};
+use std::path::PathBuf;
+
+// LD V0, 42; LD V1, 7; LD R, V1; JP 206
+const SAVES: [u8; 8] = [0x60, 0x2A, 0x61, 0x07, 0xF1, 0x75, 0x12, 0x06];
+// LD V1, R; JP 202
+const LOADS: [u8; 4] = [0xF1, 0x85, 0x12, 0x02];
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("chip8-rpl-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn flags_outlive_the_machine_in_a_file_per_rom() {
+    let dir = temp_dir("file");
+    let state = run_with(&SAVES, 3, |state| {
+        state.set_rpl_store(RplStore::in_dir(&dir))
+    });
+    assert_eq!(state.rpl_flags()[..3], [42, 7, 0]);
+    assert_eq!(
+        std::fs::read(dir.join(RplStore::key(&SAVES))).unwrap()[..2],
+        [42, 7]
+    );
+
+    // the same rom again reads them back, another one starts from zero
+    let state = machine_with(&SAVES, |state| state.set_rpl_store(RplStore::in_dir(&dir)));
+    assert_eq!(state.rpl_flags()[..2], [42, 7]);
+    let state = run_with(&LOADS, 1, |state| {
+        state.set_rpl_store(RplStore::in_dir(&dir))
+    });
+    assert_eq!(state.v[..2], [0, 0]);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn without_a_directory_they_last_through_resets() {
+    let mut state = run_with(&SAVES, 3, |state| state.set_rpl_store(RplStore::new()));
+    state.reset();
+    state.load(&SAVES).unwrap();
+    assert_eq!(state.rpl_flags()[..2], [42, 7]);
+    assert!(state.rpl_store_mut().dir().is_none());
+}
+
+#[test]
+fn an_unwritable_directory_falls_back_to_memory() {
+    let file = temp_dir("blocked");
+    std::fs::write(&file, b"not a directory").unwrap();
+    let mut state = run_with(&SAVES, 3, |state| {
+        state.set_rpl_store(RplStore::in_dir(&file))
+    });
+    let store = state.rpl_store_mut();
+    assert!(store.take_error().is_some());
+    assert!(store.dir().is_none());
+    assert_eq!(store.load(&RplStore::key(&SAVES))[..2], [42, 7]);
+    let _ = std::fs::remove_file(&file);
+}
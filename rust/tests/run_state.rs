@@ -0,0 +1,112 @@
+// pausing freezes the cpu and the timers and resumes where it left off
+use chip8::frame_queue::{BackPressure, Frame};
+use chip8::handle::Chip8Handle;
+use chip8::{Chip8State, RunState};
+use std::sync::mpsc;
+use std::time::Duration;
+
+#[test]
+fn paused_machine_stands_still() {
+    let mut state = Chip8State::with_seed(1);
+    // V0 = 5, delay = V0, then loop forever on the next instruction
+    state.load(&[0x60, 0x05, 0xF0, 0x15, 0x12, 0x04]).unwrap();
+    state.run_frame(2).unwrap();
+    let (pc, delay) = (state.pc, state.delay_timer);
+
+    state.set_paused(true);
+    assert_eq!(state.run_state(), RunState::Paused);
+    state.run_for(Duration::from_millis(50)).unwrap();
+    state.run_frame(10).unwrap();
+    assert_eq!((state.pc, state.delay_timer), (pc, delay));
+
+    state.set_paused(false);
+    assert_eq!(state.run_state(), RunState::Running);
+    state.run_frame(1).unwrap();
+    assert_eq!(state.delay_timer, delay - 1);
+}
+
+#[test]
+fn resume_goes_back_to_waiting_for_a_key() {
+    let mut state = Chip8State::with_seed(1);
+    state.load(&[0xF3, 0x0A]).unwrap();
+    state.cycle().unwrap();
+    state.set_paused(true);
+    state.set_paused(true);
+    state.set_paused(false);
+    assert_eq!(state.run_state(), RunState::WaitingForKey { register: 3 });
+}
+
+#[test]
+fn step_frame_runs_one_frame_and_pauses_again() {
+    let mut state = Chip8State::with_seed(1);
+    state.settings.instructions_per_second = 600;
+    // count up V0 forever
+    state.load(&[0x70, 0x01, 0x12, 0x00]).unwrap();
+    state.set_paused(true);
+
+    state.step_frame().unwrap();
+    assert_eq!(state.run_state(), RunState::Paused);
+    assert_eq!(state.emulated_time().cycles, 10);
+    assert_eq!(state.emulated_time().frames, 1);
+    assert_eq!(state.v[0], 5);
+}
+
+#[test]
+fn wait_for_key_takes_a_held_key_only_once() {
+    let mut state = Chip8State::with_seed(1);
+    // V0 = key twice
+    state.load(&[0xF0, 0x0A, 0xF1, 0x0A]).unwrap();
+    state.cycle().unwrap();
+    state.keypad[0x4] = true;
+    state.run_frame(2).unwrap();
+    assert_eq!(state.v[0], 4);
+
+    // 4 still down, like an autorepeating host key
+    state.run_frame(10).unwrap();
+    assert_eq!(state.run_state(), RunState::WaitingForKey { register: 1 });
+    state.keypad[0x4] = false;
+    state.run_frame(1).unwrap();
+    state.keypad[0x4] = true;
+    state.run_frame(1).unwrap();
+    assert_eq!((state.run_state(), state.v[1]), (RunState::Running, 4));
+}
+
+#[test]
+fn key_release_quirk_waits_for_the_key_to_go_up() {
+    let mut state = Chip8State::with_seed(1);
+    state.settings.quirks.key_release = true;
+    state.load(&[0xF0, 0x0A]).unwrap();
+    state.cycle().unwrap();
+
+    state.keypad[0x7] = true;
+    state.run_frame(10).unwrap();
+    assert_eq!(state.run_state(), RunState::WaitingForKey { register: 0 });
+    // other keys don't matter once 7 went down
+    state.keypad[0x2] = true;
+    state.keypad[0x7] = false;
+    state.cycle().unwrap();
+    assert_eq!((state.run_state(), state.v[0]), (RunState::Running, 7));
+}
+
+#[test]
+fn halted_machine_runs_its_timers_out() {
+    let mut state = Chip8State::with_seed(1);
+    // V0 = 16, sound = V0, then jump to itself
+    state.load(&[0x60, 0x10, 0xF0, 0x18, 0x12, 0x04]).unwrap();
+    let handle = Chip8Handle::spawn(state, 2, BackPressure::DropOldest, |state, _| {
+        state.run_frame(10)
+    });
+    let (sink, frames) = mpsc::channel();
+    handle.add_sink(move |frame: &Frame| sink.send((frame.run_state, frame.beep)).is_ok());
+
+    let wait = Duration::from_secs(5);
+    loop {
+        match frames.recv_timeout(wait).unwrap() {
+            (RunState::Halted, false) => break,
+            (_, beep) => assert!(beep),
+        }
+    }
+    let (state, result) = handle.shutdown();
+    assert!(result.is_ok());
+    assert_eq!((state.sound_timer, state.delay_timer), (0, 0));
+}
@@ -0,0 +1,96 @@
+// savestates round trip, and every format that ever shipped still loads.
+// tests/savestates/vN.c8s is written by version N and never regenerated
+use chip8::rng::Rng;
+use chip8::savestate::{self, VERSION};
+use chip8::{Chip8Error, Chip8State, RunState};
+
+// V0 = 5, V1 = 10, draws a 4x3 box there, calls a subroutine that takes a
+// random number and waits for a key into V3
+const ROM: [u8; 19] = [
+    0x60, 0x05, 0x61, 0x0A, 0xA2, 0x10, 0xD0, 0x13, 0x22, 0x0C, 0x12, 0x0A, 0xC2, 0xFF, 0xF3, 0x0A,
+    0xF0, 0x90, 0xF0,
+];
+
+fn waiting_machine() -> Chip8State {
+    let mut state = Chip8State::with_seed(1);
+    state.load(&ROM).unwrap();
+    state.delay_timer = 30;
+    state.run_frame(10).unwrap();
+    state
+}
+
+fn assert_waiting_machine(state: &Chip8State) {
+    assert_eq!(state.run_state(), RunState::WaitingForKey { register: 3 });
+    assert_eq!(&state.v[..2], [5, 10]);
+    assert_eq!(
+        (state.pc, state.i, state.stack.as_slice()),
+        (0x210, 0x210, &[0x20A][..])
+    );
+    assert_eq!(state.delay_timer, 29);
+    assert!(state.display.get(5, 10) && state.display.get(8, 12) && !state.display.get(6, 11));
+    assert_eq!(&state.memory[0x200..0x213], ROM);
+    let mut rng = Rng::new(1);
+    rng.next_u8();
+    assert_eq!(state.rng, rng);
+    assert_eq!(state.emulated_time().cycles, 7);
+}
+
+#[test]
+fn state_round_trips_and_keeps_running() {
+    let state = waiting_machine();
+    let mut restored = Chip8State::with_seed(99);
+    savestate::load(&mut restored, &savestate::save(&state)).unwrap();
+    assert_eq!(restored.snapshot(), state.snapshot());
+
+    restored.keypad[0xB] = true;
+    restored.cycle().unwrap();
+    assert_eq!(restored.v[3], 0xB);
+}
+
+#[test]
+fn states_of_every_release_load() {
+    let fixtures: [&[u8]; 3] = [
+        include_bytes!("savestates/v1.c8s"),
+        include_bytes!("savestates/v2.c8s"),
+        include_bytes!("savestates/v3.c8s"),
+    ];
+    for (version, bytes) in (1..).zip(fixtures) {
+        let mut state = Chip8State::with_seed(99);
+        savestate::load(&mut state, bytes).unwrap();
+        assert_waiting_machine(&state);
+        assert!(state.megachip().is_none(), "v{version}");
+    }
+}
+
+#[test]
+fn old_states_migrate_to_the_current_version() {
+    let v1 = include_bytes!("savestates/v1.c8s");
+    let migrated = savestate::decode(v1).unwrap();
+    assert_eq!(migrated.megachip, None);
+    let saved = savestate::encode(&migrated);
+    assert_eq!(saved[4..6], VERSION.to_le_bytes());
+    // the v1 body, then no megachip from 2 and from 3 the first plane
+    // selected and an empty second one
+    let (body, added) = saved[6..].split_at(v1.len() - 6);
+    assert_eq!(body, &v1[6..]);
+    assert_eq!(added[..2], [0, 1]);
+    assert_eq!(added[2..], [0; 64 / 8 * 32]);
+    assert_eq!(savestate::decode(&saved).unwrap(), migrated);
+}
+
+#[test]
+fn states_from_newer_releases_are_refused() {
+    let mut bytes = savestate::save(&waiting_machine());
+    bytes[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+    let mut state = Chip8State::with_seed(1);
+    assert!(matches!(
+        savestate::load(&mut state, &bytes),
+        Err(Chip8Error::UnsupportedSaveState { .. })
+    ));
+    bytes.truncate(100);
+    bytes[4..6].copy_from_slice(&VERSION.to_le_bytes());
+    assert!(matches!(
+        savestate::load(&mut state, &bytes),
+        Err(Chip8Error::InvalidSaveState { .. })
+    ));
+}
@@ -0,0 +1,69 @@
+// hook scripts on frames, breakpoints and writes
+#![cfg(feature = "scripting")]
+mod common;
+
+use chip8::script::Script;
+use chip8::{Chip8State, RunState};
+use common::machine_with;
+
+// 200: LD V3, 9; 202: LD I, 300; 204: ADD V0, 1; 206: LD [I], V0; 208: JP 204
+const ROM: [u8; 10] = [0x63, 0x09, 0xA3, 0x00, 0x70, 0x01, 0xF0, 0x55, 0x12, 0x04];
+
+// a step of the machine and the script after it, like the cpu thread does
+fn step(state: &mut Chip8State, script: &mut Script) -> Vec<Result<String, String>> {
+    state.run_frame(4).unwrap();
+    script.run(state)
+}
+
+#[test]
+fn frame_hooks_fire_every_n_frames() {
+    let mut script = Script::parse("on frame 2\n  print tick\n").unwrap();
+    let mut state = machine_with(&ROM, |state| script.install(state));
+    let printed: Vec<usize> = (0..6)
+        .map(|_| step(&mut state, &mut script).len())
+        .collect();
+    assert_eq!(printed, [0, 1, 0, 1, 0, 1]);
+}
+
+#[test]
+fn break_hooks_run_before_the_instruction_and_go_on() {
+    let mut script =
+        Script::parse("on break 206\n  set v0 7 if v3 > 5\n  set v1 1 if v3 < 5\n").unwrap();
+    let mut state = machine_with(&ROM, |state| script.install(state));
+    for _ in 0..3 {
+        step(&mut state, &mut script);
+    }
+    assert_eq!(state.memory[0x300], 7);
+    assert_eq!(state.v[1], 0);
+    assert_ne!(state.run_state(), RunState::Paused);
+}
+
+#[test]
+fn write_hooks_see_changed_bytes_and_can_pause() {
+    let mut script = Script::parse("on write 300\n  print wrote\n  pause if [300] == 2\n").unwrap();
+    let mut state = machine_with(&ROM, |state| script.install(state));
+    assert!(step(&mut state, &mut script).is_empty());
+    let shown = step(&mut state, &mut script);
+    assert_eq!(shown, [Ok("wrote".to_owned())]);
+    assert_eq!(state.run_state(), RunState::Paused);
+}
+
+#[test]
+fn scripts_press_keys() {
+    let mut script = Script::parse("on frame\n  press 5\n  release 5 if v0 == 0\n").unwrap();
+    let mut state = machine_with(&ROM, |state| script.install(state));
+    step(&mut state, &mut script);
+    assert!(state.keypad[5]);
+}
+
+#[test]
+fn errors_name_the_line() {
+    assert_eq!(
+        Script::parse("print early"),
+        Err("line 1: actions go under an on frame, on break or on write".to_owned())
+    );
+    let err = Script::parse("on frame\n\n  jump 200\n").unwrap_err();
+    assert!(err.starts_with("line 3: "), "{err}");
+    assert!(Script::parse("on click").is_err());
+    assert!(Script::parse("on frame\n  set v0 1 if v0 ~ 2").is_err());
+}
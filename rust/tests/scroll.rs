@@ -0,0 +1,117 @@
+// scrolling moves every pixel by exactly the scrolled amount at any size
+use chip8::{Chip8State, Display, Geometry};
+
+// a pseudo random pattern, every row different
+fn noise(geometry: Geometry) -> Display {
+    let mut display = Display::new(geometry);
+    let mut seed = 7u32;
+    for y in 0..geometry.height {
+        for x in 0..geometry.width {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            if seed & 1 != 0 {
+                display.toggle(x, y);
+            }
+        }
+    }
+    display
+}
+
+// the scroll done one pixel at a time, (dx, dy) in pixels
+fn shifted(display: &Display, dx: isize, dy: isize) -> Display {
+    let mut out = Display::new(display.geometry());
+    for y in 0..display.height() {
+        for x in 0..display.width() {
+            let (from_x, from_y) = (x as isize - dx, y as isize - dy);
+            if (0..display.width() as isize).contains(&from_x)
+                && (0..display.height() as isize).contains(&from_y)
+                && display.get(from_x as usize, from_y as usize)
+            {
+                out.toggle(x, y);
+            }
+        }
+    }
+    out
+}
+
+#[test]
+fn scrolls_match_pixel_by_pixel_moves() {
+    // 100 wide leaves unused bits in the last word of a row
+    for geometry in [
+        Geometry::LORES,
+        Geometry::SCHIP_HIRES,
+        Geometry::new(100, 20),
+    ] {
+        let display = noise(geometry);
+        for amount in [0, 1, 4, 15, 63, 64, 65, 200] {
+            let by = amount as isize;
+            let mut down = display.clone();
+            down.scroll_down(amount);
+            assert_eq!(down, shifted(&display, 0, by), "{geometry:?} down {amount}");
+            let mut up = display.clone();
+            up.scroll_up(amount);
+            assert_eq!(up, shifted(&display, 0, -by), "{geometry:?} up {amount}");
+            let mut right = display.clone();
+            right.scroll_right(amount);
+            assert_eq!(
+                right,
+                shifted(&display, by, 0),
+                "{geometry:?} right {amount}"
+            );
+            let mut left = display.clone();
+            left.scroll_left(amount);
+            assert_eq!(
+                left,
+                shifted(&display, -by, 0),
+                "{geometry:?} left {amount}"
+            );
+        }
+    }
+}
+
+#[test]
+fn scroll_opcodes_work_in_both_resolutions() {
+    // down 3, up 1, right 4, left 4 and right 4 again
+    let program = [0x00, 0xC3, 0x00, 0xD1, 0x00, 0xFB, 0x00, 0xFC, 0x00, 0xFB];
+    for geometry in [Geometry::LORES, Geometry::SCHIP_HIRES] {
+        let mut state = Chip8State::with_seed(1);
+        state.set_geometry(geometry);
+        state.load(&program).unwrap();
+        state.display = noise(geometry);
+        let start = state.display.clone();
+        for _ in 0..program.len() / 2 {
+            state.cycle().unwrap();
+        }
+        let expected = shifted(&shifted(&shifted(&start, 0, 3), 0, -1), 4, 0);
+        let expected = shifted(&shifted(&expected, -4, 0), 4, 0);
+        assert_eq!(state.display, expected, "{geometry:?}");
+    }
+}
+
+#[test]
+fn scrolls_move_only_the_selected_planes() {
+    // PLANE 2, down 3, PLANE 3, left 4
+    let program = [0xF2, 0x01, 0x00, 0xC3, 0xF3, 0x01, 0x00, 0xFC];
+    let mut state = Chip8State::with_seed(1);
+    state.load(&program).unwrap();
+    let (first, second) = (
+        noise(Geometry::LORES),
+        shifted(&noise(Geometry::LORES), 5, 0),
+    );
+    state.display = Display::from_planes(
+        Geometry::LORES,
+        [first.words().to_vec(), second.words().to_vec()],
+        1,
+    )
+    .unwrap();
+    for _ in 0..program.len() / 2 {
+        state.cycle().unwrap();
+    }
+    assert_eq!(state.display.selected_planes(), 3);
+    assert_eq!(state.display.plane_words(0), shifted(&first, -4, 0).words());
+    assert_eq!(
+        state.display.plane_words(1),
+        shifted(&shifted(&second, 0, 3), -4, 0).words()
+    );
+}
@@ -0,0 +1,63 @@
+// numbered save states per rom on disk
+mod common;
+
+use chip8::RunState;
+use chip8::slots::{self, StateSlots};
+use common::run;
+use std::path::PathBuf;
+
+// LD V0, 1; ADD V0, 1; JP 202
+const COUNTER: [u8; 6] = [0x60, 0x01, 0x70, 0x01, 0x12, 0x02];
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("chip8-slots-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn slots_are_named_by_rom_and_number() {
+    let slots = StateSlots::new("states");
+    let path = slots.path(&COUNTER, 2);
+    assert!(path.starts_with("states"));
+    assert!(path.to_string_lossy().ends_with("-2.state"));
+    assert_ne!(path, slots.path(&COUNTER, 3));
+    assert_ne!(path, slots.path(&[0x12, 0x00], 2));
+}
+
+#[test]
+fn a_saved_slot_resumes_the_machine() {
+    let dir = temp_dir("resume");
+    let slots = StateSlots::new(&dir);
+    let path = slots.path(&COUNTER, 1);
+    let saved = run(&COUNTER, 5);
+    slots::save(&path, &saved).unwrap();
+
+    let mut state = run(&COUNTER, 1);
+    slots::load(&path, &mut state).unwrap();
+    assert_eq!(state.v[0], saved.v[0]);
+    assert_eq!(state.pc, saved.pc);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn loading_keeps_a_paused_machine_paused() {
+    let dir = temp_dir("paused");
+    let path = StateSlots::new(&dir).path(&COUNTER, 1);
+    slots::save(&path, &run(&COUNTER, 5)).unwrap();
+
+    let mut state = run(&COUNTER, 1);
+    state.set_paused(true);
+    slots::load(&path, &mut state).unwrap();
+    assert_eq!(state.run_state(), RunState::Paused);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn an_empty_slot_is_an_error_with_its_path() {
+    let dir = temp_dir("empty");
+    let path = StateSlots::new(&dir).path(&COUNTER, 4);
+    let mut state = run(&COUNTER, 1);
+    let err = slots::load(&path, &mut state).unwrap_err();
+    assert!(err.contains("-4.state"), "{err}");
+}
@@ -0,0 +1,62 @@
+use chip8::Chip8State;
+use chip8::compare::{Comparison, Side};
+use chip8::state_diff::diff;
+
+// V0 = V1 >> 1, or V0 >> 1 with the shift quirk, then a dot at x = V0
+const SHIFT_DOT: &[u8] = &[
+    0x60, 0x08, 0x61, 0x04, 0x80, 0x16, 0xA2, 0x0C, 0xD0, 0x21, 0x12, 0x0A, 0x80,
+];
+
+#[test]
+fn identical_machines_have_no_diff() {
+    let mut state = Chip8State::with_seed(1);
+    state.load(SHIFT_DOT).unwrap();
+    let report = diff(&state.snapshot(), &state.snapshot());
+    assert!(report.is_empty());
+    assert_eq!(report.to_string(), "identical\n");
+}
+
+#[test]
+fn registers_memory_and_pixels_are_reported() {
+    let mut a = Chip8State::with_seed(1);
+    a.load(SHIFT_DOT).unwrap();
+    let mut b = Chip8State::with_seed(1);
+    b.load(SHIFT_DOT).unwrap();
+    b.v[3] = 0x42;
+    b.write_memory(0x300, 1).unwrap();
+    b.write_memory(0x301, 1).unwrap();
+    b.write_memory(0x305, 1).unwrap();
+    b.display.set(5, 6, true);
+
+    let report = diff(&a.snapshot(), &b.snapshot());
+    assert_eq!(report.registers.len(), 1);
+    assert_eq!(report.registers[0].name, "V3");
+    assert_eq!(
+        (&*report.registers[0].a, &*report.registers[0].b),
+        ("00", "42")
+    );
+    assert_eq!(report.memory, vec![0x300..0x302, 0x305..0x306]);
+    assert_eq!(report.pixels, vec![(5, 6)]);
+
+    let text = report.to_string();
+    assert!(text.contains("V3       00 -> 42"));
+    assert!(text.contains("memory   300-301 (2 bytes)"));
+    assert!(text.contains("memory   305\n"));
+    assert!(text.contains("1 pixel differs, at 5,6"));
+}
+
+#[test]
+fn two_runs_part_at_the_quirk() {
+    let sides = [Side::parse("").unwrap(), Side::parse("shift").unwrap()];
+    let mut comparison = Comparison::new(SHIFT_DOT, sides).unwrap();
+    assert!(comparison.state_diff().is_empty());
+    comparison.run_frame();
+    let report = comparison.state_diff();
+    let v0 = report
+        .registers
+        .iter()
+        .find(|change| change.name == "V0")
+        .unwrap();
+    assert_eq!((&*v0.a, &*v0.b), ("02", "04"));
+    assert!(!report.pixels.is_empty());
+}
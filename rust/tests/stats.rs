@@ -0,0 +1,37 @@
+// the counts of a run
+use chip8::Chip8State;
+use chip8::stats::RunStats;
+
+#[test]
+fn draws_sounds_and_key_waits_are_counted() {
+    // 200: DRW V0, V0, 1; 202: LD ST, V1 with V1 = 0, silent;
+    // 204: LD V1, 5; 206: LD ST, V1; 208: LD ST, V1 again; 20A: LD V2, K
+    let rom = [
+        0xD0, 0x01, 0xF1, 0x18, 0x61, 0x05, 0xF1, 0x18, 0xF1, 0x18, 0xF2, 0x0A,
+    ];
+    let mut state = Chip8State::with_seed(1);
+    state.load(&rom).unwrap();
+    for _ in 0..3 {
+        state.run_frame(10).unwrap();
+    }
+    let stats = state.stats();
+    assert_eq!(
+        stats,
+        RunStats {
+            instructions: 6,
+            frames: 3,
+            draws: 1,
+            sounds: 1,
+            key_wait_frames: 3,
+        }
+    );
+    assert_eq!(stats.instructions_per_second(), 120);
+    assert!(
+        stats
+            .to_string()
+            .ends_with("key waits     3 frames, 0.0 s in FX0A")
+    );
+
+    state.reset();
+    assert_eq!(state.stats(), RunStats::default());
+}
@@ -0,0 +1,52 @@
+// cycle() says what the instruction did
+mod common;
+
+use chip8::settings::UnknownOpcodePolicy;
+use chip8::{HaltReason, StepOutcome};
+use common::machine;
+
+#[test]
+fn each_instruction_reports_what_changed() {
+    #[rustfmt::skip]
+    let mut state = machine(&[
+        0x60, 0x05, // 200: V0 = 5
+        0xA2, 0x00, // 202: I = 200, the program makes a fine sprite
+        0xD0, 0x05, // 204: draw
+        0xF0, 0x18, // 206: beep
+        0xF1, 0x0A, // 208: wait for a key
+        0x12, 0x0A, // 20A: jump to itself
+    ]);
+    assert_eq!(state.cycle().unwrap(), StepOutcome::Executed);
+    assert_eq!(state.cycle().unwrap(), StepOutcome::Executed);
+    assert_eq!(state.cycle().unwrap(), StepOutcome::DisplayUpdated);
+    assert_eq!(state.cycle().unwrap(), StepOutcome::Beep(true));
+    assert_eq!(state.cycle().unwrap(), StepOutcome::WaitingForKey);
+    assert_eq!(state.cycle().unwrap(), StepOutcome::WaitingForKey);
+    state.keypad[3] = true;
+    assert_eq!(state.cycle().unwrap(), StepOutcome::Executed);
+    assert_eq!(state.v[1], 3);
+    assert_eq!(
+        state.cycle().unwrap(),
+        StepOutcome::Halted(HaltReason::Finished)
+    );
+    assert!(!state.cycle().unwrap().is_running());
+}
+
+#[test]
+fn traps_and_pauses_are_outcomes_too() {
+    let mut state = machine(&[0xFF, 0xFF]);
+    state.settings.unknown_opcode = UnknownOpcodePolicy::Trap;
+    let trapped = StepOutcome::Halted(HaltReason::Trap {
+        pc: 0x200,
+        opcode: 0xFFFF,
+    });
+    assert_eq!(state.cycle().unwrap(), trapped);
+    assert_eq!(state.cycle().unwrap(), trapped);
+
+    let mut state = machine(&[0x12, 0x00]);
+    state.set_paused(true);
+    assert_eq!(state.cycle().unwrap(), StepOutcome::Paused);
+    state.set_paused(false);
+    state.breakpoints.add(0x200, Default::default());
+    assert_eq!(state.cycle().unwrap(), StepOutcome::Breakpoint(0x200));
+}
@@ -0,0 +1,100 @@
+// step, frame, run_until_draw and run_to keep the timing an embedder would
+// otherwise have to copy
+mod common;
+
+use chip8::{RunState, RunTarget, StepOutcome};
+use common::machine_with;
+
+// 200: V0 = 60, DT = V0; 204: ADD V1, 1; 206: JP 204
+const COUNTER: [u8; 8] = [0x60, 0x3C, 0xF0, 0x15, 0x71, 0x01, 0x12, 0x04];
+
+#[test]
+fn steps_tick_the_timers_once_a_frame() {
+    // 10 instructions a frame
+    let mut state = machine_with(&COUNTER, |state| {
+        state.settings.instructions_per_second = 600
+    });
+    for _ in 0..9 {
+        state.step().unwrap();
+    }
+    assert_eq!(state.delay_timer, 60);
+    state.step().unwrap();
+    assert_eq!(state.delay_timer, 59);
+    assert_eq!(state.emulated_time().frames, 1);
+    for _ in 0..100 {
+        state.step().unwrap();
+    }
+    assert_eq!(state.delay_timer, 49);
+}
+
+#[test]
+fn a_frame_runs_at_the_set_speed() {
+    let mut state = machine_with(&COUNTER, |state| {
+        state.settings.instructions_per_second = 600
+    });
+    state.frame().unwrap();
+    assert_eq!(state.emulated_time().cycles, 10);
+    assert_eq!(state.delay_timer, 59);
+    assert_eq!(state.instructions_per_frame(), 10);
+}
+
+#[test]
+fn runs_until_the_screen_changes() {
+    // 200: V0 = 0; 202: ADD V0, 1; 204: SE V0, 50; 206: JP 202
+    // 208: LD I, 20E; 20A: DRW V1, V1, 1; 20C: JP 20C; 20E: a pixel
+    let rom = [
+        0x60, 0x00, 0x70, 0x01, 0x30, 0x32, 0x12, 0x02, 0xA2, 0x0E, 0xD1, 0x11, 0x12, 0x0C, 0x80,
+    ];
+    let mut state = machine_with(&rom, |state| state.settings.instructions_per_second = 600);
+    assert!(state.run_until_draw(60).unwrap());
+    assert_eq!(state.pc, 0x20C);
+    assert_eq!(state.v[0], 50);
+    // halted, nothing more to show
+    assert!(!state.run_until_draw(2).unwrap());
+    assert_eq!(
+        state.step().unwrap(),
+        StepOutcome::Halted(chip8::HaltReason::Finished)
+    );
+}
+
+#[test]
+fn paused_machines_dont_step() {
+    let mut state = machine_with(&COUNTER, |state| {
+        state.settings.instructions_per_second = 600
+    });
+    state.set_paused(true);
+    assert_eq!(state.step().unwrap(), StepOutcome::Paused);
+    assert!(!state.run_until_draw(10).unwrap());
+    assert_eq!(state.emulated_time().cycles, 0);
+}
+
+// 200: ADD V1, 1; 202: SE V1, 50; 204: JP 200; 206: DRW V0, V0, 5;
+// 208: V2 = 5; 20A: ST = V2; 20C: JP 20C
+const DRAW_THEN_BEEP: [u8; 14] = [
+    0x71, 0x01, 0x31, 0x32, 0x12, 0x00, 0xD0, 0x05, 0x62, 0x05, 0xF2, 0x18, 0x12, 0x0C,
+];
+
+#[test]
+fn run_to_pauses_after_the_next_draw_or_sound() {
+    let mut state = machine_with(&DRAW_THEN_BEEP, |state| {
+        state.settings.instructions_per_second = 600
+    });
+    // only a paused machine
+    assert!(!state.run_to(RunTarget::Draw, 10).unwrap());
+    state.set_paused(true);
+    assert!(state.run_to(RunTarget::Sound, 60).unwrap());
+    assert_eq!((state.pc, state.v[1], state.sound_timer), (0x20C, 50, 5));
+    assert_eq!(state.run_state(), RunState::Paused);
+
+    let mut state = machine_with(&DRAW_THEN_BEEP, |state| {
+        state.settings.instructions_per_second = 600
+    });
+    state.set_paused(true);
+    assert!(state.run_to(RunTarget::Draw, 60).unwrap());
+    assert_eq!((state.pc, state.v[1]), (0x208, 50));
+    assert_eq!(state.run_state(), RunState::Paused);
+    // the jump to itself halts before anything draws again
+    assert!(!state.run_to(RunTarget::Draw, 60).unwrap());
+    assert_eq!(state.pc, 0x20C);
+    assert_eq!(state.run_state(), RunState::Paused);
+}
@@ -0,0 +1,61 @@
+// runs the community test roms headlessly and compares the final screen
+// against a golden text bitmap in tests/golden/<name>.txt, see chip8::golden.
+//
+// the roms are not checked in yet, drop them into tests/roms/ from
+// https://github.com/Timendus/chip8-test-suite and run
+//
+//     CHIP8_BLESS=1 cargo test --test test_roms -- --ignored
+//
+// once to write their golden screens, then without CHIP8_BLESS to check
+// them. a missing rom or golden screen fails.
+use chip8::Settings;
+use chip8::golden::{GoldenMode, GoldenRun, check_screen};
+use std::fs;
+use std::path::Path;
+
+struct TestRom {
+    file: &'static str,
+    run: GoldenRun<'static>,
+}
+
+const fn test_rom(file: &'static str, frames: usize, pokes: &'static [(usize, u8)]) -> TestRom {
+    TestRom {
+        file,
+        run: GoldenRun {
+            frames,
+            instructions_per_frame: 10,
+            seed: 1,
+            pokes,
+        },
+    }
+}
+
+const TEST_ROMS: &[TestRom] = &[
+    test_rom("1-chip8-logo.ch8", 60, &[]),
+    test_rom("2-ibm-logo.ch8", 60, &[]),
+    test_rom("3-corax+.ch8", 60, &[]),
+    test_rom("4-flags.ch8", 120, &[]),
+    // the quirks rom reads its platform from 0x1FF
+    test_rom("5-quirks.ch8", 300, &[(0x1FF, 1)]),
+];
+
+#[test]
+#[ignore = "needs tests/roms"]
+fn test_roms_match_golden_screens() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+
+    for test in TEST_ROMS {
+        let rom = fs::read(dir.join("roms").join(test.file))
+            .unwrap_or_else(|err| panic!("tests/roms/{}: {err}", test.file));
+
+        let name = test.file.trim_end_matches(".ch8");
+        let state = test
+            .run
+            .run(&rom, &Settings::default())
+            .unwrap_or_else(|err| panic!("{} stopped: {err}", test.file));
+        let golden = dir.join("golden").join(format!("{name}.txt"));
+        if let Err(err) = check_screen(&golden, &state.display, GoldenMode::from_env()) {
+            panic!("{} after {} frames: {err}", test.file, test.run.frames);
+        }
+    }
+}
@@ -0,0 +1,52 @@
+// title screen capture waits for the screen to settle, screenshots show it
+// as is
+use chip8::palette::{Palette, Rgb};
+use chip8::screenshot;
+use chip8::settings::Quirks;
+use chip8::thumbnail;
+use chip8::{Display, Geometry};
+
+#[test]
+fn waits_past_a_blank_start_for_the_title() {
+    #[rustfmt::skip]
+    let rom = [
+        // wait 48 frames, longer than the screen has to be stable
+        0x61, 0x30, 0xF1, 0x15,
+        0xF2, 0x07, 0x32, 0x00, 0x12, 0x04,
+        // draw a line at 0,0 and idle
+        0xA2, 0x10, 0xD0, 0x01, 0x12, 0x0E,
+        0xFF,
+    ];
+    let display = thumbnail::capture_title_screen(&rom, Quirks::default(), 600).unwrap();
+    assert!(display.get(0, 0));
+}
+
+#[test]
+fn captures_the_title_before_the_program_moves_on() {
+    #[rustfmt::skip]
+    let rom = [
+        // draw a line at 0,0
+        0xA2, 0x14, 0xD0, 0x01,
+        // wait 40 frames, then erase it again and idle
+        0x61, 0x28, 0xF1, 0x15,
+        0xF2, 0x07, 0x32, 0x00, 0x12, 0x08,
+        0xD0, 0x01, 0x12, 0x10,
+        0x00, 0x00, 0xFF,
+    ];
+    let display = thumbnail::capture_title_screen(&rom, Quirks::default(), 600).unwrap();
+    assert!(display.get(7, 0));
+}
+
+#[test]
+fn screenshots_scale_pixels_in_palette_colors() {
+    let mut display = Display::new(Geometry::SCHIP_HIRES);
+    display.toggle(127, 63);
+    let image = screenshot::render(&display, &Palette::AMBER, 3);
+    assert_eq!((image.width, image.height), (384, 192));
+    let pixel = |x: usize, y: usize| {
+        let i = (y * image.width + x) * 3;
+        Rgb(image.pixels[i], image.pixels[i + 1], image.pixels[i + 2])
+    };
+    assert_eq!(pixel(381, 189), Palette::AMBER.foreground());
+    assert_eq!(pixel(380, 191), Palette::AMBER.background());
+}
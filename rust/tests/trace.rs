@@ -0,0 +1,113 @@
+// --trace lines and the address filter
+use chip8::Chip8State;
+use chip8::json;
+use chip8::trace::{self, TraceFormat, Tracer};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Default)]
+struct Shared(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Shared {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[rustfmt::skip]
+const ROM: [u8; 10] = [
+    0x60, 0x05, // 200: V0 = 5
+    0x61, 0x03, // 202: V1 = 3
+    0x70, 0x01, // 204: V0 += 1
+    0xA2, 0x20, // 206: I = 220
+    0x12, 0x08, // 208: loop
+];
+
+fn traced(tracer: impl FnOnce(Tracer) -> Tracer, cycles: usize) -> Vec<String> {
+    let out = Shared::default();
+    let mut state = Chip8State::with_seed(1);
+    state.load(&ROM).unwrap();
+    state.set_trace(Some(tracer(Tracer::new(Box::new(out.clone())))));
+    for _ in 0..cycles {
+        state.cycle().unwrap();
+    }
+    state.set_trace(None);
+    let text = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+    text.lines().map(str::to_owned).collect()
+}
+
+#[test]
+fn every_instruction_is_logged_with_its_registers() {
+    let lines = traced(|tracer| tracer, 5);
+    assert_eq!(lines.len(), 5);
+    assert!(
+        lines[0].starts_with("200  6005  LD V0, 0x05"),
+        "{}",
+        lines[0]
+    );
+    assert!(lines[0].ends_with("V0 00>05"), "{}", lines[0]);
+    assert!(lines[2].ends_with("V0 05>06"), "{}", lines[2]);
+    assert!(lines[3].ends_with("I 000>220"), "{}", lines[3]);
+    assert!(lines[4].starts_with("208  1208  JP 0x208"), "{}", lines[4]);
+}
+
+#[test]
+fn the_range_filters_by_address() {
+    let lines = traced(|tracer| tracer.with_range(0x204..=0x206), 8);
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("204"));
+    assert!(lines[1].starts_with("206"));
+}
+
+#[test]
+fn ranges_are_hex() {
+    assert_eq!(trace::parse_range("200-2FF"), Some(0x200..=0x2FF));
+    assert_eq!(trace::parse_range("0x2a0"), Some(0x2A0..=0x2A0));
+    assert_eq!(trace::parse_range("300-200"), None);
+    assert_eq!(trace::parse_range("zz"), None);
+}
+
+#[test]
+fn json_lines_have_events_for_tools() {
+    // V0 = 1, delay = V0, draw the font 0 at (1, 1), loop
+    let rom = [0x60, 0x01, 0xF0, 0x15, 0xD0, 0x05, 0x12, 0x06];
+    let out = Shared::default();
+    let mut state = Chip8State::with_seed(1);
+    state.load(&rom).unwrap();
+    state.set_trace(Some(
+        Tracer::new(Box::new(out.clone())).with_format(TraceFormat::Json),
+    ));
+    state.keypad[5] = true;
+    for _ in 0..trace::SNAPSHOT_FRAMES {
+        state.run_frame(4).unwrap();
+    }
+    state.set_trace(None);
+
+    let text = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+    let events: Vec<json::Value> = text
+        .lines()
+        .map(|line| json::parse(line).unwrap())
+        .collect();
+    let event = |name: &str| {
+        events
+            .iter()
+            .find(|e| e.get("event").and_then(json::Value::as_str) == Some(name))
+            .unwrap_or_else(|| panic!("no {name} event"))
+    };
+    let first = event("instruction");
+    assert_eq!(first.get("mnemonic").unwrap().as_str(), Some("LD V0, 0x01"));
+    assert_eq!(first.get("pc").unwrap().as_f64(), Some(512.0));
+    assert_eq!(event("draw").get("height").unwrap().as_f64(), Some(5.0));
+    let key = event("key");
+    assert_eq!(key.get("key").unwrap().as_f64(), Some(5.0));
+    assert_eq!(key.get("pressed").unwrap().as_bool(), Some(true));
+    assert_eq!(event("timer").get("timer").unwrap().as_str(), Some("delay"));
+    let snapshot = event("snapshot");
+    assert_eq!(snapshot.get("frame").unwrap().as_f64(), Some(60.0));
+    assert_eq!(snapshot.get("pc").unwrap().as_f64(), Some(0x206 as f64));
+}
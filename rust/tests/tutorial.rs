@@ -0,0 +1,60 @@
+// the tutorial rom does what its walkthrough says and the walkthrough moves
+// on with the presses it asks for
+use chip8::Chip8State;
+use chip8::backend::Hotkey;
+use chip8::keymap::Keymap;
+use chip8::tutorial::{TUTORIAL_ROM, Walkthrough};
+
+#[test]
+fn keys_toggle_their_bar() {
+    let mut state = Chip8State::with_seed(1);
+    state.load(&TUTORIAL_ROM).unwrap();
+    let mut press = |key: usize| {
+        state.run_frame(10).unwrap();
+        state.keypad[key] = true;
+        state.run_frame(10).unwrap();
+        state.keypad[key] = false;
+        state.run_frame(10).unwrap();
+        (0..32).filter(|y| state.display.get(24 + key, *y)).count()
+    };
+    assert_eq!(press(5), 8);
+    assert_eq!(press(5), 0);
+    assert_eq!(press(0xF), 8);
+}
+
+#[test]
+fn walkthrough_follows_the_controls() {
+    let keymap = Keymap::default();
+    let mut tutorial = Walkthrough::new();
+    let mut keypad = [false; 16];
+    assert!(tutorial.text(&keymap).unwrap().starts_with("1/6"));
+
+    keypad[7] = true;
+    tutorial.keypad(&keypad);
+    // holding the key is no second press, another key isn't the same one
+    tutorial.keypad(&keypad);
+    keypad = [false; 16];
+    keypad[3] = true;
+    tutorial.keypad(&keypad);
+    let text = tutorial.text(&keymap).unwrap();
+    assert!(text.starts_with("2/6 press 7"), "{text}");
+    keypad = [false; 16];
+    tutorial.keypad(&keypad);
+    keypad[7] = true;
+    tutorial.keypad(&keypad);
+
+    // hotkeys only count in their turn
+    tutorial.hotkey(Hotkey::TogglePause);
+    assert!(tutorial.text(&keymap).unwrap().contains("F1"));
+    for hotkey in [
+        Hotkey::ToggleKeypadOverlay,
+        Hotkey::TogglePause,
+        Hotkey::StepFrame,
+        Hotkey::TogglePause,
+    ] {
+        assert!(!tutorial.is_done());
+        tutorial.hotkey(hotkey);
+    }
+    assert!(tutorial.is_done());
+    assert_eq!(tutorial.text(&keymap), None);
+}
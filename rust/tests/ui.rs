@@ -0,0 +1,225 @@
+// the keypad overlay every backend paints
+use chip8::backend::letterbox;
+use chip8::backend::ui::{
+    DebugHud, KEY_HISTORY_LEN, KeyHistory, UiLayer, UiRect, debug_hud, debug_hud_lines,
+    keypad_overlay, memory_view, pixel_grid, virtual_key_at, virtual_keypad, virtual_keypad_split,
+    window_title,
+};
+use chip8::memory_view::{MEMORY_VIEW_COLUMNS, MEMORY_VIEW_ROWS, MemoryCenter, MemoryView};
+use chip8::{Chip8State, CpuSnapshot, RunState, SpriteBox};
+
+#[test]
+fn history_counts_presses_not_held_keys() {
+    let mut history = KeyHistory::new();
+    let mut keypad = [false; 16];
+    keypad[0x5] = true;
+    history.update(&keypad);
+    history.update(&keypad);
+    keypad[0x5] = false;
+    keypad[0xA] = true;
+    history.update(&keypad);
+    assert_eq!(history.presses(), [0x5, 0xA]);
+
+    for _ in 0..KEY_HISTORY_LEN {
+        history.update(&[false; 16]);
+        history.update(&[true; 16]);
+    }
+    assert_eq!(history.presses().len(), KEY_HISTORY_LEN);
+    assert_eq!(history.total(), 2 + 16 * KEY_HISTORY_LEN as u64);
+}
+
+#[test]
+fn overlay_stays_in_the_window_and_grows_a_ticker() {
+    let keypad = [false; 16];
+    let labels = ['x'; 16];
+    let plain = keypad_overlay(320, &keypad, &labels, None);
+    let ticker = keypad_overlay(320, &keypad, &labels, Some(&[0x1, 0x2]));
+    assert!(ticker.len() > plain.len());
+    for (rect, _) in &ticker {
+        assert!(rect.x >= 0 && rect.x + rect.w <= 320, "{rect:?}");
+    }
+    let bottom = |layer: &UiLayer| layer.iter().map(|(rect, _)| rect.y + rect.h).max();
+    assert!(bottom(&ticker) > bottom(&plain));
+}
+
+#[test]
+fn debug_hud_shows_registers_and_the_current_instruction() {
+    let mut cpu = CpuSnapshot {
+        pc: 0x204,
+        i: 0x22A,
+        opcode: 0xD015,
+        delay_timer: 0x3C,
+        stack_depth: 2,
+        ..CpuSnapshot::default()
+    };
+    cpu.v[0xF] = 1;
+    let hud = DebugHud {
+        cpu,
+        instructions_per_second: 700.4,
+        frames_per_second: 59.9,
+    };
+    let lines = debug_hud_lines(&hud);
+    assert!(
+        lines[0].starts_with("PC 204  D015 DRW V0, V1, 5"),
+        "{}",
+        lines[0]
+    );
+    assert_eq!(lines[1], "I 22A  STACK 2");
+    assert!(lines[5].ends_with("VF 01"));
+    assert_eq!(lines[6], "DT 3C  ST 00");
+    assert_eq!(lines[7], "IPS 700  FPS 60");
+    assert!(!debug_hud(&hud).is_empty());
+}
+
+#[test]
+fn memory_view_centers_on_i_and_shows_recent_writes() {
+    let mut state = Chip8State::new();
+    // LD I, 0x300; LD V0, 0x7B; LD B, V0
+    state.load(&[0xA3, 0x00, 0x60, 0x7B, 0xF0, 0x33]).unwrap();
+    state.set_heatmap_enabled(true);
+    for _ in 0..3 {
+        state.cycle().unwrap();
+    }
+
+    let view = MemoryView::new(&state, MemoryCenter::I);
+    assert_eq!(view.addr, 0x300);
+    assert_eq!(
+        view.start,
+        0x300 - (MEMORY_VIEW_ROWS / 2 * MEMORY_VIEW_COLUMNS) as u16
+    );
+    assert_eq!(view.bytes.len(), MEMORY_VIEW_ROWS * MEMORY_VIEW_COLUMNS);
+    let at = (view.addr - view.start) as usize;
+    assert_eq!(view.bytes[at..at + 3], [1, 2, 3]);
+    assert!(view.written[at..at + 3].iter().all(|heat| *heat > 0));
+    assert_eq!(view.written[at + 3], 0);
+    assert!(!memory_view(640, &view).is_empty());
+
+    // the end of memory doesn't run the view short
+    state.i = 0xFFF;
+    let view = MemoryView::new(&state, MemoryCenter::I);
+    assert_eq!(view.bytes.len(), MEMORY_VIEW_ROWS * MEMORY_VIEW_COLUMNS);
+    assert_eq!(view.start as usize + view.bytes.len(), 0x1000);
+}
+
+#[test]
+fn titles_name_the_rom_its_state_and_the_rates() {
+    assert_eq!(
+        window_title(None, RunState::Running, None),
+        "CHIP-8 - running"
+    );
+    assert_eq!(
+        window_title(
+            Some("Tetris by Fran Dachille"),
+            RunState::Paused,
+            Some((59.6, 700.2))
+        ),
+        "CHIP-8 - Tetris by Fran Dachille - paused - 60 fps 700 ips"
+    );
+    assert_eq!(
+        window_title(
+            Some("pong.ch8"),
+            RunState::WaitingForKey { register: 0 },
+            None
+        ),
+        "CHIP-8 - pong.ch8 - waiting for key"
+    );
+}
+
+#[test]
+fn virtual_keypad_goes_beside_or_below_the_display() {
+    let (display, pad) = virtual_keypad_split((1200, 600));
+    assert_eq!((display.w, display.h), (800, 600));
+    assert_eq!((pad.x, pad.w, pad.h), (800, 400, 400));
+
+    let (display, pad) = virtual_keypad_split((600, 1000));
+    assert_eq!((display.w, display.h), (600, 500));
+    assert_eq!((pad.y, pad.w), (500, 500));
+
+    // the top left button is 1, the bottom right F, the display nothing
+    assert_eq!(virtual_key_at((1200, 600), 850, 150), Some(0x1));
+    assert_eq!(virtual_key_at((1200, 600), 1150, 450), Some(0xF));
+    assert_eq!(virtual_key_at((1200, 600), 400, 300), None);
+
+    let mut keypad = [false; 16];
+    keypad[0x1] = true;
+    let layer = virtual_keypad((1200, 600), &keypad);
+    assert!(layer.contains(&(
+        UiRect {
+            x: 802,
+            y: 102,
+            w: 96,
+            h: 96
+        },
+        255
+    )));
+}
+
+#[test]
+fn the_grid_lines_up_with_the_pixels_and_outlines_the_last_sprite() {
+    // 200: LD I, 206; 202: DRW V0, V0, 2; 204: the same again; 206: a bar
+    let mut state = Chip8State::with_seed(1);
+    state
+        .load(&[0xA2, 0x06, 0xD0, 0x02, 0xD0, 0x02, 0xFF, 0xFF])
+        .unwrap();
+    state.run_frame(2).unwrap();
+    let first = state.last_sprite().unwrap();
+    assert_eq!(
+        first,
+        SpriteBox {
+            x: 0,
+            y: 0,
+            width: 8,
+            height: 2,
+            collided: false,
+        }
+    );
+    state.run_frame(1).unwrap();
+    assert!(state.last_sprite().unwrap().collided);
+
+    // 10 window pixels a chip8 pixel
+    let viewport = letterbox((640, 320), (64, 32));
+    let grid = pixel_grid(&viewport, (64, 32), None);
+    assert_eq!(grid.len(), 63 + 31);
+    assert!(grid.contains(&(
+        UiRect {
+            x: 10,
+            y: 0,
+            w: 1,
+            h: 320
+        },
+        64
+    )));
+    assert!(grid.contains(&(
+        UiRect {
+            x: 0,
+            y: 310,
+            w: 640,
+            h: 1
+        },
+        64
+    )));
+
+    let outlined = pixel_grid(&viewport, (64, 32), Some(&first));
+    assert!(outlined.contains(&(
+        UiRect {
+            x: 0,
+            y: 0,
+            w: 80,
+            h: 1
+        },
+        160
+    )));
+    assert!(outlined.contains(&(
+        UiRect {
+            x: 79,
+            y: 0,
+            w: 1,
+            h: 20
+        },
+        160
+    )));
+
+    // too small for lines, the outline stays
+    let small = letterbox((128, 64), (64, 32));
+    assert_eq!(pixel_grid(&small, (64, 32), Some(&first)).len(), 4);
+}
@@ -0,0 +1,75 @@
+// Timing::CosmacVip, instructions cost what they cost on the vip
+use chip8::chip8::TIMER_PERIOD;
+use chip8::decode::{VIP_FRAME_CYCLES, decode};
+use chip8::settings::Machine;
+use chip8::{Chip8State, Timing};
+
+// 200: LD V0, 1; 202: JP 200, 29 machine cycles a round
+const LOAD_LOOP: [u8; 4] = [0x60, 0x01, 0x12, 0x00];
+// 200: LD I, 206; 202: DRW V0, V0, 5; 204: JP 202; 206: sprite
+const DRAW_LOOP: [u8; 11] = [
+    0xA2, 0x06, 0xD0, 0x05, 0x12, 0x02, 0xF0, 0x90, 0x90, 0x90, 0xF0,
+];
+
+fn vip(rom: &[u8]) -> Chip8State {
+    let mut state = Chip8State::with_seed(1);
+    state.settings.timing = Timing::CosmacVip;
+    state.load(rom).unwrap();
+    state
+}
+
+#[test]
+fn a_frame_holds_a_vip_frame_of_machine_cycles() {
+    let mut state = vip(&LOAD_LOOP);
+    state.run_frame(1).unwrap();
+    // 87 rounds leave 21 cycles, the 6001 and 1200 after them overrun by 8,
+    // which the next frame starts with
+    assert_eq!(state.emulated_time().cycles, 176);
+    assert_eq!(VIP_FRAME_CYCLES, 2544);
+    state.run_frame(1).unwrap();
+    assert_eq!(state.emulated_time().cycles, 176 + 176);
+}
+
+#[test]
+fn sprites_take_longer_than_loads() {
+    let mut load = vip(&LOAD_LOOP);
+    let mut draw = vip(&DRAW_LOOP);
+    load.run_frame(0).unwrap();
+    draw.run_frame(0).unwrap();
+    assert!(draw.emulated_time().cycles * 4 < load.emulated_time().cycles);
+    assert!(decode(0xD005).vip_cycles(false) > 10 * decode(0x6001).vip_cycles(false));
+    assert!(decode(0x3000).vip_cycles(true) > decode(0x3000).vip_cycles(false));
+}
+
+#[test]
+fn stepping_and_real_time_follow_the_same_frames() {
+    let mut state = vip(&LOAD_LOOP);
+    while state.emulated_time().frames == 0 {
+        state.step().unwrap();
+    }
+    assert_eq!(state.emulated_time().cycles, 176);
+
+    let mut state = vip(&LOAD_LOOP);
+    state.run_for(TIMER_PERIOD * 2).unwrap();
+    assert_eq!(state.emulated_time().frames, 2);
+    assert_eq!(state.emulated_time().cycles, 176 + 176);
+}
+
+#[test]
+fn flat_timing_keeps_the_instruction_budget() {
+    let mut state = Chip8State::with_seed(1);
+    state.load(&LOAD_LOOP).unwrap();
+    state.run_frame(10).unwrap();
+    assert_eq!(state.emulated_time().cycles, 10);
+}
+
+#[test]
+fn the_vip_machine_brings_its_timing() {
+    assert_eq!(
+        Machine::find("cosmac-vip").unwrap().timing,
+        Timing::CosmacVip
+    );
+    assert_eq!(Machine::find("xo-chip").unwrap().timing, Timing::Flat);
+    assert_eq!(Timing::find("cosmac-vip"), Some(Timing::CosmacVip));
+    assert_eq!(Timing::find("slow"), None);
+}
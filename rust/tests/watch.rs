@@ -0,0 +1,34 @@
+// --watch hands a rom out again whenever it is written
+use chip8::rom::MAX_ROM_SIZE;
+use chip8::watch::RomWatcher;
+use std::fs::{self, File};
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn watched_rom_reloads_when_written() {
+    let dir = std::env::temp_dir().join(format!("chip8-watch-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let rom = dir.join("game.ch8");
+    fs::write(&rom, [0x12, 0x00]).unwrap();
+
+    let mut watcher = RomWatcher::new(&rom, MAX_ROM_SIZE);
+    let (path, bytes) = watcher.poll().unwrap();
+    assert_eq!((path, bytes.unwrap()), (rom.clone(), vec![0x12, 0x00]));
+    std::thread::sleep(Duration::from_millis(600));
+    assert!(watcher.poll().is_none());
+
+    fs::write(&rom, [0x12, 0x02]).unwrap();
+    // file systems with coarse timestamps might not see the write otherwise
+    let later = SystemTime::now() + Duration::from_secs(5);
+    File::options()
+        .write(true)
+        .open(&rom)
+        .unwrap()
+        .set_modified(later)
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(600));
+    let (_, bytes) = watcher.poll().unwrap();
+    assert_eq!(bytes.unwrap(), [0x12, 0x02]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,69 @@
+// writes below 0x200 under WriteProtection
+mod common;
+
+use chip8::{Chip8Error, RunState, StepOutcome, WriteProtection};
+use common::run_with;
+
+// LD V0, 7; LD I, 0x050; LD [I], V0; JP 204
+const CLOBBERS_FONT: [u8; 8] = [0x60, 0x07, 0xA0, 0x50, 0xF0, 0x55, 0x12, 0x04];
+
+#[test]
+fn unprotected_writes_go_through() {
+    let mut state = run_with(&CLOBBERS_FONT, 2, |state| {
+        state.settings.write_protection = WriteProtection::Off
+    });
+    assert_eq!(state.cycle().unwrap(), StepOutcome::Executed);
+    assert_eq!(state.memory[0x50], 7);
+    assert_eq!(state.take_protected_write(), None);
+}
+
+#[test]
+fn halt_stops_before_the_write() {
+    let mut state = run_with(&CLOBBERS_FONT, 2, |state| {
+        state.settings.write_protection = WriteProtection::Halt
+    });
+    match state.cycle() {
+        Err(Chip8Error::ProtectedWrite {
+            pc: 0x204,
+            addr: 0x50,
+        }) => {}
+        other => panic!("{other:?}"),
+    }
+    assert_eq!(state.memory[0x50], 0xF0);
+    assert_eq!(state.pc, 0x204);
+}
+
+#[test]
+fn pause_stops_after_the_instruction_once() {
+    let mut state = run_with(&CLOBBERS_FONT, 2, |state| {
+        state.settings.write_protection = WriteProtection::Pause
+    });
+    assert_eq!(
+        state.cycle().unwrap(),
+        StepOutcome::ProtectedWrite {
+            pc: 0x204,
+            addr: 0x50
+        }
+    );
+    assert_eq!(state.memory[0x50], 7);
+    assert_eq!(state.run_state(), RunState::Paused);
+    assert_eq!(state.take_protected_write(), Some((0x204, 0x50)));
+    assert_eq!(state.take_protected_write(), None);
+
+    // going on doesn't pause again until the next bad write
+    state.set_paused(false);
+    assert_eq!(state.cycle().unwrap(), StepOutcome::Executed);
+    assert!(matches!(
+        state.cycle().unwrap(),
+        StepOutcome::ProtectedWrite { pc: 0x204, .. }
+    ));
+}
+
+#[test]
+fn program_memory_is_fair_game() {
+    // LD V0, 7; LD I, 0x300; LD [I], V0
+    let state = run_with(&[0x60, 0x07, 0xA3, 0x00, 0xF0, 0x55], 3, |state| {
+        state.settings.write_protection = WriteProtection::Halt
+    });
+    assert_eq!(state.memory[0x300], 7);
+}
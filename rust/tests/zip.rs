@@ -0,0 +1,90 @@
+// roms out of zip archives
+use chip8::builtin::builtin_rom;
+use chip8::zip::{ZipArchive, split_member};
+use std::path::Path;
+
+fn archive(name: &str) -> Vec<u8> {
+    std::fs::read(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/zips")
+            .join(name),
+    )
+    .unwrap()
+}
+
+fn rom(name: &str) -> &'static [u8] {
+    builtin_rom(name).unwrap().bytes
+}
+
+#[test]
+fn members_are_split_off_zip_paths() {
+    assert_eq!(split_member("roms.zip"), Some(("roms.zip", None)));
+    assert_eq!(
+        split_member("a/ROMS.ZIP#pong.ch8"),
+        Some(("a/ROMS.ZIP", Some("pong.ch8")))
+    );
+    assert_eq!(split_member("pong.ch8"), None);
+    assert_eq!(split_member("notes#1.ch8"), None);
+}
+
+#[test]
+fn lists_every_member() {
+    let bytes = archive("collection.zip");
+    let zip = ZipArchive::parse(&bytes).unwrap();
+    let names: Vec<&str> = zip.members().iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(
+        names,
+        ["README.txt", "roms/", "roms/ibm.ch8", "roms/catch.ch8"]
+    );
+}
+
+#[test]
+fn inflates_members_by_path_or_file_name() {
+    let bytes = archive("collection.zip");
+    let zip = ZipArchive::parse(&bytes).unwrap();
+    assert_eq!(zip.rom(Some("roms/ibm.ch8"), 4096).unwrap(), rom("ibm"));
+    assert_eq!(zip.rom(Some("catch.ch8"), 4096).unwrap(), rom("catch"));
+    let readme = zip.rom(Some("README.txt"), 4096).unwrap();
+    assert_eq!(readme.len(), 1620);
+    assert!(readme.starts_with(b"a collection of small roms"));
+    assert_eq!(
+        zip.rom(Some("pong.ch8"), 4096).unwrap_err(),
+        "no pong.ch8 in the archive"
+    );
+}
+
+#[test]
+fn several_roms_need_a_name() {
+    let bytes = archive("collection.zip");
+    let zip = ZipArchive::parse(&bytes).unwrap();
+    assert_eq!(
+        zip.rom(None, 4096).unwrap_err(),
+        "2 roms in the archive, pick one with #NAME:\n  roms/ibm.ch8\n  roms/catch.ch8"
+    );
+}
+
+#[test]
+fn the_only_rom_needs_no_name() {
+    let bytes = archive("single.zip");
+    let zip = ZipArchive::parse(&bytes).unwrap();
+    assert_eq!(zip.rom(None, 4096).unwrap(), rom("opcodes"));
+}
+
+#[test]
+fn damage_and_size_are_errors() {
+    let mut bytes = archive("single.zip");
+    let zip = ZipArchive::parse(&bytes).unwrap();
+    assert!(
+        zip.rom(None, 100)
+            .unwrap_err()
+            .ends_with("at most 100 fit in memory")
+    );
+    // a byte of the stored rom, behind its 30 byte header and name
+    bytes[30 + "opcodes.ch8".len() + 5] ^= 0xFF;
+    let zip = ZipArchive::parse(&bytes).unwrap();
+    assert!(zip.rom(None, 4096).unwrap_err().contains("crc mismatch"));
+    assert_eq!(
+        ZipArchive::parse(b"not a zip").err().unwrap(),
+        "not a zip archive"
+    );
+}
@@ -0,0 +1,116 @@
+// build the core with
+//   cargo build --lib --release --no-default-features --features std --target wasm32-unknown-unknown
+// copy target/wasm32-unknown-unknown/release/chip8.wasm next to this file
+// and serve the directory over http (e.g. python3 -m http.server)
+
+const CYCLES_PER_FRAME = 10;
+
+// classic 1234/qwer/asdf/zxcv layout
+const KEYMAP = {
+    "1": 0x1, "2": 0x2, "3": 0x3, "4": 0xC,
+    "q": 0x4, "w": 0x5, "e": 0x6, "r": 0xD,
+    "a": 0x7, "s": 0x8, "d": 0x9, "f": 0xE,
+    "z": 0xA, "x": 0x0, "c": 0xB, "v": 0xF,
+};
+
+const canvas = document.getElementById("screen");
+const ctx = canvas.getContext("2d");
+const scaled = document.createElement("canvas");
+
+let chip8 = null;
+let running = false;
+
+WebAssembly.instantiateStreaming(fetch("chip8.wasm")).then(({ instance }) => {
+    chip8 = instance.exports;
+});
+
+document.getElementById("rom").addEventListener("change", async (e) => {
+    const file = e.target.files[0];
+    if (!file || !chip8) {
+        return;
+    }
+    const bytes = new Uint8Array(await file.arrayBuffer());
+    const ptr = chip8.chip8_rom_buffer(bytes.length);
+    new Uint8Array(chip8.memory.buffer, ptr, bytes.length).set(bytes);
+    if (chip8.chip8_load((Math.random() * 0x100000000) >>> 0) !== 0) {
+        console.error("rom does not fit into memory");
+        return;
+    }
+    drawn = false;
+
+    if (!running) {
+        running = true;
+        requestAnimationFrame(frame);
+    }
+});
+
+document.addEventListener("keydown", (e) => {
+    const key = KEYMAP[e.key.toLowerCase()];
+    if (chip8 && key !== undefined) {
+        chip8.chip8_key_down(key);
+    }
+});
+
+document.addEventListener("keyup", (e) => {
+    const key = KEYMAP[e.key.toLowerCase()];
+    if (chip8 && key !== undefined) {
+        chip8.chip8_key_up(key);
+    }
+});
+
+// chip8_next_event codes
+const EVENT_DISPLAY_UPDATED = 0;
+const EVENT_HALTED = 3;
+
+let drawn = false;
+
+function frame() {
+    if (chip8.chip8_step(CYCLES_PER_FRAME) !== 0) {
+        console.error("chip8 stopped on an error");
+        running = false;
+        return;
+    }
+    let redraw = !drawn;
+    for (let event = chip8.chip8_next_event(); event !== -1; event = chip8.chip8_next_event()) {
+        if (event === EVENT_DISPLAY_UPDATED) {
+            redraw = true;
+        } else if (event === EVENT_HALTED) {
+            console.log("program finished");
+        }
+    }
+    if (redraw) {
+        draw();
+        drawn = true;
+    }
+    requestAnimationFrame(frame);
+}
+
+function draw() {
+    const width = chip8.chip8_display_width();
+    const height = chip8.chip8_display_height();
+    const wordsPerRow = chip8.chip8_display_words_per_row();
+
+    if (scaled.width !== width || scaled.height !== height) {
+        scaled.width = width;
+        scaled.height = height;
+    }
+    const image = new ImageData(width, height);
+
+    // memory.buffer can be replaced when the wasm heap grows, so view it fresh each frame
+    const words = new BigUint64Array(chip8.memory.buffer, chip8.chip8_display(), wordsPerRow * height);
+    for (let y = 0; y < height; y++) {
+        for (let x = 0; x < width; x++) {
+            const word = words[y * wordsPerRow + Math.floor(x / 64)];
+            const on = (word >> BigInt(63 - (x % 64))) & 1n;
+            const offset = (y * width + x) * 4;
+            const c = on ? 255 : 0;
+            image.data[offset] = c;
+            image.data[offset + 1] = c;
+            image.data[offset + 2] = c;
+            image.data[offset + 3] = 255;
+        }
+    }
+    scaled.getContext("2d").putImageData(image, 0, 0);
+    ctx.imageSmoothingEnabled = false;
+    ctx.drawImage(scaled, 0, 0, canvas.width, canvas.height);
+}